@@ -56,6 +56,8 @@ bitfield! {
     pub hit_left_lower_half, set_hit_left_lower_half: 17; // 0x20000
     pub hit_right_lower_half, set_hit_right_lower_half: 18; // 0x40000
     pub hit_right_higher_half, set_hit_right_higher_half: 19; // 0x80000
+    /// Set if player overlaps a ladder tile, d-rs extension. (corresponds to flag & 0x100000)
+    pub on_ladder, set_on_ladder: 20; // 0x100000
 }
 
 impl Flag {
@@ -133,7 +135,7 @@ bitfield! {
 }
 
 bitfield! {
-    #[derive(Clone, Copy)]
+    #[derive(Clone, Copy, Serialize, Deserialize)]
     #[repr(C)]
     pub struct BulletFlag(u8);
     impl Debug;
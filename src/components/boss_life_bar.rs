@@ -1,7 +1,9 @@
 use crate::common::Rect;
+use crate::components::draw_common::HudPlacement;
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
+use crate::framework::graphics::screen_insets_scaled;
 use crate::game::frame::Frame;
 use crate::game::shared_game_state::SharedGameState;
 use crate::game::npc::boss::BossNPC;
@@ -44,7 +46,7 @@ impl BossLifeBar {
         self.prev_life = self.life;
     }
 
-    fn draw_regular(&self, state: &mut SharedGameState, ctx: &mut Context, _frame: &Frame) -> GameResult {
+    fn draw_regular(&self, state: &mut SharedGameState, ctx: &mut Context, placement: HudPlacement) -> GameResult {
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
 
         let box_length = 256;
@@ -59,43 +61,19 @@ impl BossLifeBar {
         rect_prev_bar.right = ((self.prev_life as u32 * bar_length) / self.max_life as u32).min(bar_length) as u16;
         rect_life_bar.right = ((self.life as u32 * bar_length) / self.max_life as u32).min(bar_length) as u16;
 
-        batch.add_rect(
-            ((state.canvas_size.0 - box_length as f32) / 2.0).floor(),
-            state.canvas_size.1 - 20.0,
-            &box_rect1,
-        );
-        batch.add_rect(
-            ((state.canvas_size.0 - box_length as f32) / 2.0).floor(),
-            state.canvas_size.1 - 12.0,
-            &box_rect2,
-        );
-        batch.add_rect(
-            ((state.canvas_size.0 - box_length as f32) / 2.0).floor(),
-            state.canvas_size.1 - 20.0,
-            &box_rect1,
-        );
-        batch.add_rect(
-            ((state.canvas_size.0 - box_length as f32) / 2.0 + 40.0).floor(),
-            state.canvas_size.1 - 16.0,
-            &rect_prev_bar,
-        );
-        batch.add_rect(
-            ((state.canvas_size.0 - box_length as f32) / 2.0 + 40.0).floor(),
-            state.canvas_size.1 - 16.0,
-            &rect_life_bar,
-        );
-        batch.add_rect(
-            ((state.canvas_size.0 - box_length as f32) / 2.0 + 8.0).floor(),
-            state.canvas_size.1 - 16.0,
-            &text_rect,
-        );
+        placement.add_rect(batch, 0.0, 0.0, &box_rect1);
+        placement.add_rect(batch, 0.0, 8.0, &box_rect2);
+        placement.add_rect(batch, 0.0, 0.0, &box_rect1);
+        placement.add_rect(batch, 40.0, 4.0, &rect_prev_bar);
+        placement.add_rect(batch, 40.0, 4.0, &rect_life_bar);
+        placement.add_rect(batch, 8.0, 4.0, &text_rect);
 
         batch.draw(ctx)?;
 
         Ok(())
     }
 
-    fn draw_nx(&self, state: &mut SharedGameState, ctx: &mut Context, _frame: &Frame) -> GameResult {
+    fn draw_nx(&self, state: &mut SharedGameState, ctx: &mut Context, placement: HudPlacement) -> GameResult {
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
 
         let box_length = 148;
@@ -112,17 +90,15 @@ impl BossLifeBar {
         rect_prev_bar.right = ((self.prev_life as u32 * bar_length) / self.max_life as u32).min(bar_length) as u16;
         rect_life_bar.right = ((self.life as u32 * bar_length) / self.max_life as u32).min(bar_length) as u16;
 
-        let base_x = state.canvas_size.0 - box_length as f32;
-
-        batch.add_rect((base_x - 6.0).floor(), state.canvas_size.1 - 20.0, &box_rect1);
-        batch.add_rect((base_x - 6.0).floor(), state.canvas_size.1 - 12.0, &box_rect2);
-        batch.add_rect((base_x - 6.0).floor(), state.canvas_size.1 - 20.0, &box_rect1);
-        batch.add_rect((state.canvas_size.0 - 18.0).floor(), state.canvas_size.1 - 20.0, &box_rect3);
-        batch.add_rect((state.canvas_size.0 - 18.0).floor(), state.canvas_size.1 - 12.0, &box_rect4);
-        batch.add_rect((state.canvas_size.0 - 18.0).floor(), state.canvas_size.1 - 20.0, &box_rect3);
-        batch.add_rect((base_x + 34.0).floor(), state.canvas_size.1 - 16.0, &rect_prev_bar);
-        batch.add_rect((base_x + 34.0).floor(), state.canvas_size.1 - 16.0, &rect_life_bar);
-        batch.add_rect((base_x + 2.0).floor(), state.canvas_size.1 - 16.0, &text_rect);
+        placement.add_rect(batch, 0.0, 0.0, &box_rect1);
+        placement.add_rect(batch, 0.0, 8.0, &box_rect2);
+        placement.add_rect(batch, 0.0, 0.0, &box_rect1);
+        placement.add_rect(batch, 136.0, 0.0, &box_rect3);
+        placement.add_rect(batch, 136.0, 8.0, &box_rect4);
+        placement.add_rect(batch, 136.0, 0.0, &box_rect3);
+        placement.add_rect(batch, 40.0, 4.0, &rect_prev_bar);
+        placement.add_rect(batch, 40.0, 4.0, &rect_life_bar);
+        placement.add_rect(batch, 8.0, 4.0, &text_rect);
 
         batch.draw(ctx)?;
 
@@ -165,9 +141,24 @@ impl GameEntity<(&NPCList, &BossNPC)> for BossLifeBar {
             return Ok(());
         }
 
-        match state.constants.is_switch {
-            true => self.draw_nx(state, ctx, _frame),
-            false => self.draw_regular(state, ctx, _frame),
+        let layout = &state.constants.hud_layout;
+        let is_switch = state.constants.is_switch;
+        let insets = screen_insets_scaled(ctx, state.scale);
+        let width = if is_switch { 142.0 } else { 256.0 };
+
+        let bar = HudPlacement::new(&layout.boss_life(is_switch), width, 16.0, state.canvas_size, insets);
+        let numbers = HudPlacement::new(&layout.boss_life_numbers(), 64.0, 8.0, state.canvas_size, insets);
+
+        match (bar, is_switch) {
+            (Some(bar), true) => self.draw_nx(state, ctx, bar)?,
+            (Some(bar), false) => self.draw_regular(state, ctx, bar)?,
+            (None, _) => {}
         }
+
+        if let Some(numbers) = numbers {
+            numbers.draw_fraction(32.0, 0.0, self.life as usize, self.max_life as usize, state, ctx)?;
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,250 @@
+use std::collections::VecDeque;
+
+use crate::common::{Color, Rect};
+use crate::components::text_boxes::draw_rich_line;
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::graphics;
+use crate::game::player::TargetPlayer;
+use crate::game::scripting::tsc::text_script::DialogueHistoryEntry;
+use crate::game::shared_game_state::{PlayerCount, SharedGameState};
+use crate::graphics::font::Font;
+use crate::input::combined_menu_controller::CombinedMenuController;
+use crate::sound::mixer::MixerBus;
+
+const ROW_HEIGHT: f32 = 16.0;
+const LIST_TOP: f32 = 32.0;
+const LIST_WIDTH: f32 = 288.0;
+const FACE_COLUMN_WIDTH: f32 = 56.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BacklogRow {
+    /// First line said by someone, the face is drawn next to it.
+    Speaker(usize),
+    Line(usize),
+    Blank,
+}
+
+fn layout_rows(history: &VecDeque<DialogueHistoryEntry>) -> Vec<BacklogRow> {
+    let mut rows = Vec::with_capacity(history.len() * 2);
+    let mut group_start = 0;
+
+    let pad_group = |rows: &mut Vec<BacklogRow>, group_start: usize, face: u16| {
+        // faces are 3 rows tall
+        while face != 0 && rows.len() - group_start < 3 {
+            rows.push(BacklogRow::Blank);
+        }
+    };
+
+    for (idx, entry) in history.iter().enumerate() {
+        let prev = if idx > 0 { history.get(idx - 1) } else { None };
+
+        match prev {
+            Some(prev) if prev.face == entry.face && prev.player == entry.player => {
+                rows.push(BacklogRow::Line(idx));
+            }
+            _ => {
+                if let Some(prev) = prev {
+                    pad_group(&mut rows, group_start, prev.face);
+                    rows.push(BacklogRow::Blank);
+                }
+
+                group_start = rows.len();
+                rows.push(BacklogRow::Speaker(idx));
+            }
+        }
+    }
+
+    if let Some(last) = history.back() {
+        pad_group(&mut rows, group_start, last.face);
+    }
+
+    rows
+}
+
+/// Scrollable list of dialogue lines recorded by the script VM.
+pub struct DialogueBacklog {
+    open: bool,
+    /// Number of rows scrolled up from the most recent line.
+    scroll: usize,
+    controller: CombinedMenuController,
+}
+
+impl DialogueBacklog {
+    pub fn new() -> DialogueBacklog {
+        DialogueBacklog { open: false, scroll: 0, controller: CombinedMenuController::new() }
+    }
+
+    pub fn init(&mut self, state: &mut SharedGameState) {
+        self.controller.add(state.settings.create_player1_controller());
+        self.controller.add(state.settings.create_player2_controller());
+    }
+
+    pub fn open(&mut self, state: &mut SharedGameState) {
+        self.open = true;
+        self.scroll = 0;
+        state.sound_manager.play_sfx_on(5, MixerBus::UI);
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn visible_rows(state: &SharedGameState) -> usize {
+        ((state.canvas_size.1 - LIST_TOP - ROW_HEIGHT) / ROW_HEIGHT).floor().max(1.0) as usize
+    }
+
+    /// Updates the controller, has to be called every tick so buttons held while opening the backlog
+    /// aren't treated as pressed.
+    pub fn tick(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        self.controller.update(state, ctx)?;
+        self.controller.update_trigger();
+
+        if !self.open {
+            return Ok(());
+        }
+
+        let visible_rows = Self::visible_rows(state);
+        let max_scroll = layout_rows(&state.textscript_vm.history).len().saturating_sub(visible_rows);
+        let prev_scroll = self.scroll;
+
+        if self.controller.trigger_up() {
+            self.scroll += 1;
+        } else if self.controller.trigger_down() {
+            self.scroll = self.scroll.saturating_sub(1);
+        } else if self.controller.trigger_left() {
+            self.scroll += visible_rows;
+        } else if self.controller.trigger_right() {
+            self.scroll = self.scroll.saturating_sub(visible_rows);
+        }
+
+        self.scroll = self.scroll.min(max_scroll);
+        if self.scroll != prev_scroll {
+            state.sound_manager.play_sfx_on(1, MixerBus::UI);
+        }
+
+        if self.controller.trigger_back() || self.controller.trigger_ok() {
+            self.open = false;
+            state.sound_manager.play_sfx_on(5, MixerBus::UI);
+        }
+
+        Ok(())
+    }
+
+    pub fn draw(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        if !self.open {
+            return Ok(());
+        }
+
+        let rect = Rect::new(0, 0, state.screen_size.0 as isize + 1, state.screen_size.1 as isize + 1);
+        graphics::draw_rect(ctx, rect, Color::from_rgba(0, 0, 0, 200))?;
+
+        let title = state.loc.t("menus.pause_menu.backlog");
+        state.font.builder().center(state.canvas_size.0).y(12.0).shadow(true).draw(
+            title,
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+
+        if state.textscript_vm.history.is_empty() {
+            let text = state.loc.t("menus.pause_menu.backlog_empty");
+            state.font.builder().center(state.canvas_size.0).y(LIST_TOP).shadow(true).draw(
+                text,
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+
+            return Ok(());
+        }
+
+        let rows = layout_rows(&state.textscript_vm.history);
+        let visible_rows = Self::visible_rows(state);
+        let first_row = rows.len().saturating_sub(visible_rows + self.scroll);
+        let left = ((state.canvas_size.0 - LIST_WIDTH) / 2.0).floor();
+        let show_player = state.player_count == PlayerCount::Two;
+
+        let clip_rect = Rect::new_size(
+            0,
+            (LIST_TOP * state.scale) as isize,
+            state.screen_size.0 as isize,
+            (visible_rows as f32 * ROW_HEIGHT * state.scale) as isize,
+        );
+        graphics::set_clip_rect(ctx, Some(clip_rect))?;
+
+        // faces of speakers whose lines start above the visible area can still be partially visible
+        for (i, row) in rows.iter().enumerate().skip(first_row.saturating_sub(2)).take(visible_rows + 2) {
+            let idx = match *row {
+                BacklogRow::Speaker(idx) => idx,
+                _ => continue,
+            };
+            let y = LIST_TOP + (i as f32 - first_row as f32) * ROW_HEIGHT;
+            let entry = &state.textscript_vm.history[idx];
+
+            if entry.face != 0 {
+                let face_num = entry.face % 100;
+                let rect = Rect::new_size((face_num % 6) * 48, (face_num / 6) * 48, 48, 48);
+
+                let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "Face")?;
+                batch.add_rect(left, y, &rect);
+                batch.draw(ctx)?;
+            } else if show_player {
+                let label = if entry.player == TargetPlayer::Player1 { "1P" } else { "2P" };
+                state.font.builder().position(left, y).shadow(true).draw(
+                    label,
+                    ctx,
+                    &state.constants,
+                    &mut state.texture_set,
+                )?;
+            }
+        }
+
+        for (i, row) in rows.iter().enumerate().skip(first_row).take(visible_rows) {
+            let idx = match *row {
+                BacklogRow::Speaker(idx) | BacklogRow::Line(idx) => idx,
+                BacklogRow::Blank => continue,
+            };
+            let y = LIST_TOP + (i - first_row) as f32 * ROW_HEIGHT;
+
+            draw_rich_line(
+                &state.font,
+                &state.textscript_vm,
+                &state.constants,
+                &mut state.texture_set,
+                ctx,
+                &state.textscript_vm.history[idx].text,
+                (left + FACE_COLUMN_WIDTH, y),
+                true,
+                0,
+            )?;
+        }
+
+        graphics::set_clip_rect(ctx, None)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_layout_rows() {
+    use BacklogRow::{Blank, Line, Speaker};
+
+    let entry = |face: u16, player: TargetPlayer| DialogueHistoryEntry { face, player, text: vec!['a'] };
+    let history: VecDeque<DialogueHistoryEntry> = [
+        entry(1, TargetPlayer::Player1),
+        entry(1, TargetPlayer::Player1),
+        entry(0, TargetPlayer::Player1),
+        entry(0, TargetPlayer::Player2),
+        entry(2, TargetPlayer::Player1),
+    ]
+    .into_iter()
+    .collect();
+
+    // groups with a face are padded to its height, groups are separated with a blank row
+    assert_eq!(
+        layout_rows(&history),
+        vec![Speaker(0), Line(1), Blank, Blank, Speaker(2), Blank, Speaker(3), Blank, Speaker(4), Blank, Blank]
+    );
+    assert!(layout_rows(&VecDeque::new()).is_empty());
+}
@@ -1,7 +1,9 @@
 use crate::common::Rect;
+use crate::engine_constants::hud_layout::HudElement;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::game::shared_game_state::SharedGameState;
+use crate::graphics::texture_set::SpriteBatch;
 
 #[derive(Debug, EnumIter, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum Alignment {
@@ -10,31 +12,102 @@ pub enum Alignment {
 }
 
 pub fn draw_number(x: f32, y: f32, val: usize, align: Alignment, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+    draw_number_scaled(x, y, 1.0, val, align, state, ctx)
+}
+
+pub fn draw_number_scaled(
+    x: f32,
+    y: f32,
+    scale: f32,
+    val: usize,
+    align: Alignment,
+    state: &mut SharedGameState,
+    ctx: &mut Context,
+) -> GameResult {
     let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
 
     let n = val.to_string();
-    let align_offset = if align == Alignment::Right { n.len() as f32 * 8.0 } else { 0.0 };
+    let align_offset = if align == Alignment::Right { n.len() as f32 * 8.0 * scale } else { 0.0 };
 
     for (offset, chr) in n.chars().enumerate() {
         let idx = chr as u16 - '0' as u16;
-        batch.add_rect(x - align_offset + offset as f32 * 8.0, y, &Rect::new_size(idx * 8, 56, 8, 8));
+        batch.add_rect_scaled(
+            x - align_offset + offset as f32 * 8.0 * scale,
+            y,
+            scale,
+            scale,
+            &Rect::new_size(idx * 8, 56, 8, 8),
+        );
     }
 
     batch.draw(ctx)?;
     Ok(())
 }
 
-pub fn draw_number_zeros(x: f32, y: f32, val: usize, align: Alignment, zeros: usize, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
-    let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
+/// Screen position and scale of a HUD element, coordinates within the element are unscaled.
+#[derive(Debug, Clone, Copy)]
+pub struct HudPlacement {
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32,
+}
 
-    let n = format!("{:01$}", val, zeros);
-    let align_offset = if align == Alignment::Right { n.len() as f32 * 8.0 } else { 0.0 };
+impl HudPlacement {
+    /// Places an element with given unscaled size, returns None if the element is hidden.
+    pub fn new(
+        element: &HudElement,
+        width: f32,
+        height: f32,
+        canvas_size: (f32, f32),
+        insets: (f32, f32, f32, f32),
+    ) -> Option<HudPlacement> {
+        if !element.visible {
+            return None;
+        }
 
-    for (offset, chr) in n.chars().enumerate() {
-        let idx = chr as u16 - '0' as u16;
-        batch.add_rect(x - align_offset + offset as f32 * 8.0, y, &Rect::new_size(idx * 8, 56, 8, 8));
+        let (x, y) = element.place(width, height, canvas_size, insets);
+        Some(HudPlacement { x, y, scale: element.scale })
     }
 
-    batch.draw(ctx)?;
-    Ok(())
+    pub fn x(&self, x: f32) -> f32 {
+        self.x + x * self.scale
+    }
+
+    pub fn y(&self, y: f32) -> f32 {
+        self.y + y * self.scale
+    }
+
+    pub fn add_rect(&self, batch: &mut Box<dyn SpriteBatch>, x: f32, y: f32, rect: &Rect<u16>) {
+        batch.add_rect_scaled(self.x(x), self.y(y), self.scale, self.scale, rect);
+    }
+
+    pub fn draw_number(
+        &self,
+        x: f32,
+        y: f32,
+        val: usize,
+        align: Alignment,
+        state: &mut SharedGameState,
+        ctx: &mut Context,
+    ) -> GameResult {
+        draw_number_scaled(self.x(x), self.y(y), self.scale, val, align, state, ctx)
+    }
+
+    /// Draws a `value/max` pair, with the slash at given position.
+    pub fn draw_fraction(
+        &self,
+        x: f32,
+        y: f32,
+        value: usize,
+        max: usize,
+        state: &mut SharedGameState,
+        ctx: &mut Context,
+    ) -> GameResult {
+        let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
+        self.add_rect(batch, x, y, &Rect::new_size(72, 48, 8, 8));
+        batch.draw(ctx)?;
+
+        self.draw_number(x, y, value, Alignment::Right, state, ctx)?;
+        self.draw_number(x + 8.0, y, max, Alignment::Left, state, ctx)
+    }
 }
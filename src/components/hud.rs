@@ -1,5 +1,5 @@
 use crate::common::Rect;
-use crate::components::draw_common::{Alignment, draw_number};
+use crate::components::draw_common::{Alignment, HudPlacement};
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
@@ -27,6 +27,8 @@ pub struct HUD {
     life_bar_counter: u16,
     air: u16,
     air_counter: u16,
+    has_booster: bool,
+    booster_fuel: u32,
     current_level: usize,
     weapon_count: usize,
     current_weapon: isize,
@@ -53,6 +55,8 @@ impl HUD {
             life_bar_counter: 0,
             air: 0,
             air_counter: 0,
+            has_booster: false,
+            booster_fuel: 0,
             current_level: 0,
             weapon_count: 0,
             current_weapon: 0,
@@ -62,6 +66,32 @@ impl HUD {
     }
 }
 
+impl HUD {
+    /// Returns the position of a weapon icon within the weapons element.
+    fn weapon_icon_x(&self, idx: usize, first_frame_offset: isize) -> f32 {
+        let mut pos_x =
+            ((idx as isize - self.current_weapon + first_frame_offset) as f32 * 16.0) + self.weapon_x_pos as f32;
+
+        if pos_x < 8.0 {
+            pos_x += 48.0 + self.weapon_count as f32 * 16.0;
+        } else if pos_x >= 24.0 {
+            pos_x += 48.0;
+        }
+
+        if pos_x >= 72.0 + ((self.weapon_count - 1) as f32 * 16.0) {
+            pos_x -= 48.0 + self.weapon_count as f32 * 16.0;
+        } else if pos_x < 72.0 && pos_x >= 24.0 {
+            pos_x -= 48.0;
+        }
+
+        if self.alignment == Alignment::Right && pos_x > 32.0 {
+            pos_x -= 96.0 + self.weapon_count as f32 * 16.0;
+        }
+
+        pos_x
+    }
+}
+
 impl GameEntity<(&Player, &mut Inventory)> for HUD {
     fn tick(&mut self, state: &mut SharedGameState, (player, inventory): (&Player, &mut Inventory)) -> GameResult {
         let (ammo, max_ammo) = inventory.get_current_ammo();
@@ -78,6 +108,9 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
         self.max_life = player.max_life;
         self.air = player.air;
         self.air_counter = player.air_counter;
+        self.has_booster =
+            (player.equip.has_booster_0_8() || player.equip.has_booster_2_0()) && !state.settings.infinite_booster;
+        self.booster_fuel = player.booster_fuel;
         self.shock = player.shock_counter / 2 % 2 != 0;
         self.weapon_count = inventory.get_weapon_count();
         self.current_weapon = inventory.get_current_weapon_idx() as isize;
@@ -85,7 +118,7 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
         self.current_level = inventory.get_current_level() as usize;
 
         for (a, slot) in self.weapon_types.iter_mut().enumerate() {
-            *slot = if let Some(weapon) = inventory.get_weapon(a) { weapon.wtype as u8 } else { 0 };
+            *slot = if let Some(weapon) = inventory.get_weapon(a) { weapon.wtype.id() } else { 0 };
         }
 
         // update health bar
@@ -138,35 +171,30 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
 
         // touch handler
         if state.settings.touch_controls && self.weapon_count != 0 {
-            let mut rect;
-            let weapon_offset = match self.alignment {
-                Alignment::Left => 0,
-                Alignment::Right => (state.canvas_size.0 - 104.0) as isize,
-            };
-
-            for a in 0..self.weapon_count {
-                let mut pos_x = ((a as isize - self.current_weapon) * 16) + self.weapon_x_pos as isize;
-
-                if pos_x < 8 {
-                    pos_x += 48 + self.weapon_count as isize * 16;
-                } else if pos_x >= 24 {
-                    pos_x += 48;
-                }
-
-                if pos_x >= 72 + ((self.weapon_count as isize - 1) * 16) {
-                    pos_x -= 48 + self.weapon_count as isize * 16;
-                } else if pos_x < 72 && pos_x >= 24 {
-                    pos_x -= 48;
-                }
-
-                let wtype = self.weapon_types[a];
-                if wtype != 0 {
-                    rect = Rect::new_size(pos_x + weapon_offset - 4, 16 + (4 * state.scale as isize), 24, 24);
+            let weapons = HudPlacement::new(
+                &state.constants.hud_layout.weapons(self.alignment),
+                40.0,
+                16.0,
+                state.canvas_size,
+                (0.0, 0.0, 0.0, 0.0),
+            );
 
-                    if state.touch_controls.consume_click_in(rect) {
-                        state.sound_manager.play_sfx(4);
-                        inventory.current_weapon = a as u16;
-                        self.weapon_x_pos = 32;
+            if let Some(weapons) = weapons {
+                for a in 0..self.weapon_count {
+                    let wtype = self.weapon_types[a];
+                    if wtype != 0 {
+                        let rect = Rect::new_size(
+                            weapons.x(self.weapon_icon_x(a, 0)) as isize - 4,
+                            weapons.y as isize + (4 * state.scale as isize),
+                            (24.0 * weapons.scale) as isize,
+                            (24.0 * weapons.scale) as isize,
+                        );
+
+                        if state.touch_controls.consume_click_in(rect) {
+                            state.sound_manager.play_sfx(4);
+                            inventory.current_weapon = a as u16;
+                            self.weapon_x_pos = 32;
+                        }
                     }
                 }
             }
@@ -180,86 +208,97 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
             return Ok(());
         }
 
-        let (left, top, right, bottom) = screen_insets_scaled(ctx, state.scale);
+        let insets = screen_insets_scaled(ctx, state.scale);
+        let layout = &state.constants.hud_layout;
+        let canvas_size = state.canvas_size;
+
+        let life = HudPlacement::new(&layout.life(self.alignment), 64.0, 8.0, canvas_size, insets);
+        let weapons = HudPlacement::new(&layout.weapons(self.alignment), 40.0, 16.0, canvas_size, insets);
+        let ammo = HudPlacement::new(&layout.ammo(self.alignment), 32.0, 16.0, canvas_size, insets);
+        let experience = HudPlacement::new(&layout.experience(self.alignment), 64.0, 8.0, canvas_size, insets);
+        let mut air = HudPlacement::new(&layout.air(self.alignment), 80.0, 8.0, canvas_size, insets);
+        let xp_numbers = HudPlacement::new(&layout.xp_numbers(self.alignment), 64.0, 8.0, canvas_size, insets);
+        let booster_fuel = HudPlacement::new(&layout.booster_fuel(self.alignment), 64.0, 8.0, canvas_size, insets);
+        let max_booster_fuel = state.constants.booster.fuel;
+
+        // weapon info slides along with the weapon icons when switching
+        let weap_x = self.weapon_x_pos as f32 - 16.0;
+        let right_aligned = self.alignment == Alignment::Right;
+
+        // the right side HUD has the bars in front of the heart and level boxes
+        let (heart_x, life_bar_x) = if right_aligned { (40.0, 0.0) } else { (0.0, 24.0) };
+        let (lv_x, xp_bar_x) = if right_aligned { (40.0 + weap_x, weap_x) } else { (weap_x, 24.0 + weap_x) };
+
+        if let Some(air) = air.as_mut().filter(|_| self.has_player2) {
+            air.x += if right_aligned { 50.0 } else { -50.0 };
+        }
 
-        // none
-        let weap_x = self.weapon_x_pos as f32;
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
 
-        let (bar_offset, num_offset, weapon_offset) = match self.alignment {
-            Alignment::Left => (left, left, left),
-            Alignment::Right => (
-                state.canvas_size.0 - 112.0 - right,
-                state.canvas_size.0 - 48.0 - right,
-                state.canvas_size.0 - 40.0 - right,
-            ),
-        };
-        let air_offset = if self.has_player2 {
-            50.0 * match self.alignment {
-                Alignment::Left => -1.0,
-                Alignment::Right => 1.0,
-            }
-        } else {
-            0.0
-        };
-
-        if self.max_ammo == 0 {
-            batch.add_rect(bar_offset + weap_x + 48.0, 16.0 + top, &Rect::new_size(80, 48, 16, 8));
-            batch.add_rect(bar_offset + weap_x + 48.0, 24.0 + top, &Rect::new_size(80, 48, 16, 8));
+        if let Some(ammo) = ammo.filter(|_| self.max_ammo == 0) {
+            ammo.add_rect(batch, weap_x + 16.0, 0.0, &Rect::new_size(80, 48, 16, 8));
+            ammo.add_rect(batch, weap_x + 16.0, 8.0, &Rect::new_size(80, 48, 16, 8));
         }
 
         if !self.shock {
-            // per
-            batch.add_rect(bar_offset + weap_x + 32.0, 24.0 + top, &Rect::new_size(72, 48, 8, 8));
-            // lv
-            batch.add_rect(num_offset + weap_x, 32.0 + top, &Rect::new_size(80, 80, 16, 8));
-            // xp box
-            batch.add_rect(bar_offset + weap_x + 24.0, 32.0 + top, &Rect::new_size(0, 72, 40, 8));
-
-            if self.max_level {
-                batch.add_rect(bar_offset + weap_x + 24.0, 32.0 + top, &Rect::new_size(40, 72, 40, 8));
-            } else if self.max_xp > 0 {
-                // xp bar
-                let bar_width = (self.xp as f32 / self.max_xp as f32 * 40.0) as u16;
-
-                batch.add_rect(bar_offset + weap_x + 24.0, 32.0 + top, &Rect::new_size(0, 80, bar_width, 8));
+            if let Some(ammo) = ammo {
+                // per
+                ammo.add_rect(batch, weap_x, 8.0, &Rect::new_size(72, 48, 8, 8));
             }
 
-            if (self.xp_bar_counter & 0x02) != 0 {
-                batch.add_rect(bar_offset + weap_x + 24.0, 32.0 + top, &Rect::new_size(40, 80, 40, 8));
+            if let Some(experience) = experience {
+                // lv
+                experience.add_rect(batch, lv_x, 0.0, &Rect::new_size(80, 80, 16, 8));
+                // xp box
+                experience.add_rect(batch, xp_bar_x, 0.0, &Rect::new_size(0, 72, 40, 8));
+
+                if self.max_level {
+                    experience.add_rect(batch, xp_bar_x, 0.0, &Rect::new_size(40, 72, 40, 8));
+                } else if self.max_xp > 0 {
+                    // xp bar
+                    let bar_width = (self.xp as f32 / self.max_xp as f32 * 40.0) as u16;
+
+                    experience.add_rect(batch, xp_bar_x, 0.0, &Rect::new_size(0, 80, bar_width, 8));
+                }
+
+                if (self.xp_bar_counter & 0x02) != 0 {
+                    experience.add_rect(batch, xp_bar_x, 0.0, &Rect::new_size(40, 80, 40, 8));
+                }
             }
 
-            if self.max_life != 0 {
+            if let Some(life) = life.filter(|_| self.max_life != 0) {
                 let yellow_bar_width = (self.life_bar as f32 / self.max_life as f32 * 39.0) as u16;
                 let bar_width = (self.life as f32 / self.max_life as f32 * 39.0) as u16;
 
                 // heart/hp number box
-                batch.add_rect(num_offset + 16.0, 40.0 + top, &Rect::new_size(0, 40, 24, 8));
+                life.add_rect(batch, heart_x, 0.0, &Rect::new_size(0, 40, 24, 8));
                 // life box
-                batch.add_rect(bar_offset + 40.0, 40.0 + top, &Rect::new_size(24, 40, 40, 8));
+                life.add_rect(batch, life_bar_x, 0.0, &Rect::new_size(24, 40, 40, 8));
                 // yellow bar
-                batch.add_rect(bar_offset + 40.0, 40.0 + top, &Rect::new_size(0, 32, yellow_bar_width, 8));
+                life.add_rect(batch, life_bar_x, 0.0, &Rect::new_size(0, 32, yellow_bar_width, 8));
                 // life
-                batch.add_rect(bar_offset + 40.0, 40.0 + top, &Rect::new_size(0, 24, bar_width, 8));
+                life.add_rect(batch, life_bar_x, 0.0, &Rect::new_size(0, 24, bar_width, 8));
+            }
+
+            if let Some(booster_fuel) = booster_fuel.filter(|_| self.has_booster && max_booster_fuel > 0) {
+                let bar_width =
+                    (self.booster_fuel.min(max_booster_fuel) as f32 / max_booster_fuel as f32 * 39.0) as u16;
+
+                booster_fuel.add_rect(batch, life_bar_x, 0.0, &Rect::new_size(24, 40, 40, 8));
+                booster_fuel.add_rect(batch, life_bar_x, 0.0, &Rect::new_size(0, 32, bar_width, 8));
             }
         }
 
-        if self.air_counter > 0 {
+        if let Some(air) = air.filter(|_| self.air_counter > 0) {
             let rect = if self.air % 30 > 10 { Rect::new_size(112, 72, 32, 8) } else { Rect::new_size(112, 80, 32, 8) };
 
-            batch.add_rect(
-                left + ((state.canvas_size.0 - left - right) / 2.0).floor() - 40.0 + air_offset,
-                top + ((state.canvas_size.1 - top - bottom) / 2.0).floor(),
-                &rect,
-            );
+            air.add_rect(batch, 0.0, 0.0, &rect);
         }
 
         batch.draw(ctx)?;
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "ArmsImage")?;
 
-        if self.weapon_count != 0 {
-            let mut rect = Rect::new(0, 0, 0, 16);
-
+        if let Some(weapons) = weapons.filter(|_| self.weapon_count != 0) {
             // First frame of animation is off by one weapon
             // There's probably a more elegant solution than this
             let first_frame_offset = if self.weapon_x_pos == 32 {
@@ -271,53 +310,38 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
             };
 
             for a in 0..self.weapon_count {
-                let mut pos_x = ((a as isize - self.current_weapon + first_frame_offset) as f32 * 16.0) + weap_x;
-
-                if pos_x < 8.0 {
-                    pos_x += 48.0 + self.weapon_count as f32 * 16.0;
-                } else if pos_x >= 24.0 {
-                    pos_x += 48.0;
-                }
-
-                if pos_x >= 72.0 + ((self.weapon_count - 1) as f32 * 16.0) {
-                    pos_x -= 48.0 + self.weapon_count as f32 * 16.0;
-                } else if pos_x < 72.0 && pos_x >= 24.0 {
-                    pos_x -= 48.0;
-                }
-
-                if self.alignment == Alignment::Right && pos_x > 32.0 {
-                    pos_x -= 96.0 + self.weapon_count as f32 * 16.0;
-                }
-
                 let wtype = self.weapon_types[a];
                 if wtype != 0 {
-                    rect.left = wtype as u16 * 16;
-                    rect.right = rect.left + 16;
-                    batch.add_rect(pos_x + weapon_offset, 16.0 + top, &rect);
+                    let rect = state.constants.weapon.get_arms_icon_rect(wtype as u16);
+                    weapons.add_rect(batch, self.weapon_icon_x(a, first_frame_offset), 0.0, &rect);
                 }
             }
         }
 
         batch.draw(ctx)?;
 
-        if self.air_counter > 0 && self.air_counter % 6 < 4 {
-            draw_number(
-                left + ((state.canvas_size.0 - left - right) / 2.0).floor() + 8.0 + air_offset,
-                top + ((state.canvas_size.1 - top - bottom) / 2.0).floor(),
-                (self.air / 10) as usize,
-                Alignment::Left,
-                state,
-                ctx,
-            )?;
+        if let Some(air) = air.filter(|_| self.air_counter > 0 && self.air_counter % 6 < 4) {
+            air.draw_number(48.0, 0.0, (self.air / 10) as usize, Alignment::Left, state, ctx)?;
         }
 
-        if self.max_ammo != 0 {
-            draw_number(bar_offset + weap_x + 64.0, 16.0 + top, self.ammo as usize, Alignment::Right, state, ctx)?;
-            draw_number(bar_offset + weap_x + 64.0, 24.0 + top, self.max_ammo as usize, Alignment::Right, state, ctx)?;
+        if let Some(ammo) = ammo.filter(|_| self.max_ammo != 0) {
+            ammo.draw_number(weap_x + 32.0, 0.0, self.ammo as usize, Alignment::Right, state, ctx)?;
+            ammo.draw_number(weap_x + 32.0, 8.0, self.max_ammo as usize, Alignment::Right, state, ctx)?;
         }
         if !self.shock {
-            draw_number(num_offset + weap_x + 24.0, 32.0 + top, self.current_level, Alignment::Right, state, ctx)?;
-            draw_number(num_offset + 40.0, 40.0 + top, self.life_bar as usize, Alignment::Right, state, ctx)?;
+            if let Some(experience) = experience {
+                experience.draw_number(lv_x + 24.0, 0.0, self.current_level, Alignment::Right, state, ctx)?;
+            }
+            if let Some(life) = life {
+                life.draw_number(heart_x + 24.0, 0.0, self.life_bar as usize, Alignment::Right, state, ctx)?;
+            }
+            if let Some(xp_numbers) = xp_numbers.filter(|_| !self.max_level && self.max_xp != 0) {
+                xp_numbers.draw_fraction(24.0, 0.0, self.xp as usize, self.max_xp as usize, state, ctx)?;
+            }
+            if let Some(booster_fuel) = booster_fuel.filter(|_| self.has_booster && max_booster_fuel > 0) {
+                let percent = self.booster_fuel.min(max_booster_fuel) as usize * 100 / max_booster_fuel as usize;
+                booster_fuel.draw_number(heart_x + 24.0, 0.0, percent, Alignment::Right, state, ctx)?;
+            }
         }
 
         Ok(())
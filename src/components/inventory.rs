@@ -11,6 +11,7 @@ use crate::input::touch_controls::TouchControlType;
 use crate::game::player::Player;
 use crate::game::scripting::tsc::text_script::{ScriptMode, TextScriptExecutionState};
 use crate::game::weapon::{WeaponLevel, WeaponType};
+use crate::sound::mixer::MixerBus;
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 #[repr(u8)]
@@ -64,7 +65,7 @@ impl InventoryUI {
     }
 
     fn get_weapon_event_number(&self, inventory: &Inventory) -> u16 {
-        inventory.get_current_weapon().map(|w| w.wtype as u16 + 1000).unwrap_or(1000)
+        inventory.get_current_weapon().map(|w| w.wtype.id() as u16 + 1000).unwrap_or(1000)
     }
 
     fn exit(&mut self, state: &mut SharedGameState, _player: &mut Player, inventory: &mut Inventory, hud: &mut HUD) {
@@ -154,14 +155,14 @@ impl GameEntity<(&mut Context, &mut Player, &mut Inventory, &mut HUD)> for Inven
                 if self.weapon_count > 0
                 {
                     if player.controller.trigger_left() {
-                        state.sound_manager.play_sfx(4);
+                        state.sound_manager.play_sfx_on(4, MixerBus::UI);
                         inventory.prev_weapon();
                         state.control_flags.set_ok_button_disabled(false);
                         state.textscript_vm.start_script(self.get_weapon_event_number(inventory));
                     }
     
                     if player.controller.trigger_right() {
-                        state.sound_manager.play_sfx(4);
+                        state.sound_manager.play_sfx_on(4, MixerBus::UI);
                         inventory.next_weapon();
                         state.control_flags.set_ok_button_disabled(false);
                         state.textscript_vm.start_script(self.get_weapon_event_number(inventory));
@@ -179,7 +180,7 @@ impl GameEntity<(&mut Context, &mut Player, &mut Inventory, &mut HUD)> for Inven
                 let mut moved_cursor = false;
 
                 if player.controller.trigger_left() {
-                    state.sound_manager.play_sfx(1);
+                    state.sound_manager.play_sfx_on(1, MixerBus::UI);
 
                     if (self.selected_item % count_x) != 0 {
                         self.selected_item -= 1;
@@ -202,7 +203,7 @@ impl GameEntity<(&mut Context, &mut Player, &mut Inventory, &mut HUD)> for Inven
                         _ => self.selected_item += 1,
                     }
 
-                    state.sound_manager.play_sfx(1);
+                    state.sound_manager.play_sfx_on(1, MixerBus::UI);
                     state.control_flags.set_ok_button_disabled(false);
                     moved_cursor = true;
                 }
@@ -211,13 +212,13 @@ impl GameEntity<(&mut Context, &mut Player, &mut Inventory, &mut HUD)> for Inven
                     if self.selected_item < count_x {
                         self.focus = InventoryFocus::Weapons;
 
-                        state.sound_manager.play_sfx(4);
+                        state.sound_manager.play_sfx_on(4, MixerBus::UI);
                         state.control_flags.set_ok_button_disabled(false);
                         state.textscript_vm.start_script(self.get_weapon_event_number(inventory));
                     } else {
                         self.selected_item -= count_x;
 
-                        state.sound_manager.play_sfx(1);
+                        state.sound_manager.play_sfx_on(1, MixerBus::UI);
                         state.control_flags.set_ok_button_disabled(false);
                         moved_cursor = true;
                     }
@@ -227,13 +228,13 @@ impl GameEntity<(&mut Context, &mut Player, &mut Inventory, &mut HUD)> for Inven
                     if self.selected_item / 6 == self.item_count.saturating_sub(1) / 6 {
                         self.focus = InventoryFocus::Weapons;
 
-                        state.sound_manager.play_sfx(4);
+                        state.sound_manager.play_sfx_on(4, MixerBus::UI);
                         state.control_flags.set_ok_button_disabled(false);
                         moved_cursor = true;
                     } else {
                         self.selected_item += count_x;
 
-                        state.sound_manager.play_sfx(1);
+                        state.sound_manager.play_sfx_on(1, MixerBus::UI);
                         state.control_flags.set_ok_button_disabled(false);
                         moved_cursor = true;
                     }
@@ -263,7 +264,7 @@ impl GameEntity<(&mut Context, &mut Player, &mut Inventory, &mut HUD)> for Inven
 
                 if state.touch_controls.consume_click_in(slot_rect) {
                     self.focus = InventoryFocus::Weapons;
-                    state.sound_manager.play_sfx(4);
+                    state.sound_manager.play_sfx_on(4, MixerBus::UI);
                     self.selected_weapon = i;
                     inventory.current_weapon = i;
                     state.textscript_vm.start_script(self.get_weapon_event_number(inventory));
@@ -276,7 +277,7 @@ impl GameEntity<(&mut Context, &mut Player, &mut Inventory, &mut HUD)> for Inven
                     Rect::new_size(x + 12 + (i % count_x) as isize * 32, y + 68 + (i / count_x) as isize * 16, 32, 16);
 
                 if state.touch_controls.consume_click_in(slot_rect) {
-                    state.sound_manager.play_sfx(1);
+                    state.sound_manager.play_sfx_on(1, MixerBus::UI);
 
                     if self.focus == InventoryFocus::Items && inventory.current_item == i {
                         state.textscript_vm.start_script(self.get_item_event_number_action(inventory));
@@ -363,10 +364,7 @@ impl GameEntity<(&mut Context, &mut Player, &mut Inventory, &mut HUD)> for Inven
                 break;
             }
 
-            tmp_rect.left = (weapon.wtype as u16 % 16) * 16;
-            tmp_rect.top = (weapon.wtype as u16 / 16) * 16;
-            tmp_rect.right = tmp_rect.left + 16;
-            tmp_rect.bottom = tmp_rect.top + 16;
+            tmp_rect = state.constants.weapon.get_arms_icon_rect(weapon.wtype.id() as u16);
 
             batch.add_rect(x + 12.0 + idx as f32 * 40.0, y + 16.0, &tmp_rect);
         }
@@ -21,8 +21,28 @@ pub enum MapSystemState {
     FadeOutBox(u16),
 }
 
+/// Returns the shade used to draw a tile with given attribute on maps, 0 is empty space.
+pub fn tile_shade(attr: u8) -> usize {
+    match attr {
+        0 => 0,
+        0x01 | 0x02 | 0x40 | 0x44 | 0x51 | 0x52 | 0x55 | 0x56 | 0x60 | 0x71 | 0x72 | 0x75 | 0x76 | 0x80 | 0x81
+        | 0x82 | 0x83 | 0xA0 | 0xA1 | 0xA2 | 0xA3 => 1,
+        0x43 | 0x50 | 0x53 | 0x54 | 0x57 | 0x63 | 0x70 | 0x73 | 0x74 | 0x77 => 2,
+        _ => 3,
+    }
+}
+
+pub const TILE_SHADE_RECTS: [Rect<u16>; 4] = [
+    Rect { left: 240, top: 24, right: 241, bottom: 25 },
+    Rect { left: 241, top: 24, right: 242, bottom: 25 },
+    Rect { left: 242, top: 24, right: 243, bottom: 25 },
+    Rect { left: 243, top: 24, right: 244, bottom: 25 },
+];
+
 pub struct MapSystem {
     texture: RefCell<Option<Box<dyn BackendTexture>>>,
+    /// Another visited stage shown instead of the current one, and its ID.
+    browsed: Option<(usize, Stage)>,
     has_map_data: RefCell<bool>,
     last_size: (u16, u16),
     tick: u16,
@@ -33,6 +53,7 @@ impl MapSystem {
     pub fn new() -> MapSystem {
         MapSystem {
             texture: RefCell::new(None),
+            browsed: None,
             has_map_data: RefCell::new(false),
             last_size: (0, 0),
             tick: 0,
@@ -40,7 +61,7 @@ impl MapSystem {
         }
     }
 
-    fn render_map(&self, state: &mut SharedGameState, ctx: &mut Context, stage: &Stage) -> GameResult {
+    fn render_map(&self, state: &mut SharedGameState, ctx: &mut Context, stage: &Stage, stage_id: usize) -> GameResult {
         if self.texture.borrow().is_none() {
             *self.has_map_data.borrow_mut() = false;
             return Ok(());
@@ -51,28 +72,19 @@ impl MapSystem {
         graphics::set_render_target(ctx, self.texture.borrow().as_ref())?;
         graphics::clear(ctx, Color::new(0.0, 0.0, 0.0, 1.0));
 
+        let fog_of_war = state.settings.fog_of_war;
+        let explored = state.exploration.get(stage_id as u16);
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
 
         for y in 0..stage.map.height {
             for x in 0..stage.map.width {
-                const RECTS: [Rect<u16>; 4] = [
-                    Rect { left: 240, top: 24, right: 241, bottom: 25 },
-                    Rect { left: 241, top: 24, right: 242, bottom: 25 },
-                    Rect { left: 242, top: 24, right: 243, bottom: 25 },
-                    Rect { left: 243, top: 24, right: 244, bottom: 25 },
-                ];
-
-                let attr = stage.map.get_attribute(x as _, y as _);
-
-                let layer = match attr {
-                    0 => 0,
-                    0x01 | 0x02 | 0x40 | 0x44 | 0x51 | 0x52 | 0x55 | 0x56 | 0x60 | 0x71 | 0x72 | 0x75 | 0x76 | 0x80
-                    | 0x81 | 0x82 | 0x83 | 0xA0 | 0xA1 | 0xA2 | 0xA3 => 1,
-                    0x43 | 0x50 | 0x53 | 0x54 | 0x57 | 0x63 | 0x70 | 0x73 | 0x74 | 0x77 => 2,
-                    _ => 3,
+                let shade = if fog_of_war && !explored.map_or(false, |e| e.is_explored(x as _, y as _)) {
+                    0
+                } else {
+                    tile_shade(stage.map.get_attribute(x as _, y as _))
                 };
 
-                batch.add_rect(x as _, y as _, &RECTS[layer]);
+                batch.add_rect(x as _, y as _, &TILE_SHADE_RECTS[shade]);
             }
         }
 
@@ -82,11 +94,49 @@ impl MapSystem {
         Ok(())
     }
 
+    /// Switches to the previous or next visited stage, wrapping around.
+    fn browse(&mut self, state: &mut SharedGameState, ctx: &mut Context, stage_id: usize, offset: isize) {
+        let visited: Vec<usize> = state.exploration.visited_stages().map(|id| id as usize).collect();
+        let shown_id = self.browsed.as_ref().map_or(stage_id, |(id, _)| *id);
+
+        let idx = match visited.iter().position(|&id| id == shown_id) {
+            Some(idx) => idx as isize,
+            None => return,
+        };
+        let next_id = visited[(idx + offset).rem_euclid(visited.len() as isize) as usize];
+
+        if next_id == shown_id {
+            return;
+        }
+
+        if next_id == stage_id {
+            self.browsed = None;
+        } else {
+            let data = match state.stages.get(next_id) {
+                Some(data) => data,
+                None => return,
+            };
+
+            match Stage::load(&state.constants.base_paths, data, ctx) {
+                Ok(stage) => self.browsed = Some((next_id, stage)),
+                Err(err) => {
+                    log::warn!("Failed to load stage {} for the map: {}", next_id, err);
+                    return;
+                }
+            }
+        }
+
+        state.sound_manager.play_sfx(1);
+        *self.has_map_data.borrow_mut() = false;
+        self.state = MapSystemState::FadeInLine(0);
+    }
+
     pub fn tick(
         &mut self,
         state: &mut SharedGameState,
         ctx: &mut Context,
         stage: &Stage,
+        stage_id: usize,
         players: [&Player; 2],
     ) -> GameResult {
         let touch_rect = Rect::new_size(0, 0, state.canvas_size.0 as isize, state.canvas_size.1 as isize);
@@ -103,10 +153,28 @@ impl MapSystem {
 
         if self.state == MapSystemState::Hidden {
             self.tick = 0;
+            self.browsed = None;
             *self.has_map_data.borrow_mut() = false;
             return Ok(());
         }
 
+        if self.state == MapSystemState::Visible {
+            for player in &players {
+                if player.controller.trigger_left() {
+                    self.browse(state, ctx, stage_id, -1);
+                    break;
+                } else if player.controller.trigger_right() {
+                    self.browse(state, ctx, stage_id, 1);
+                    break;
+                }
+            }
+        }
+
+        let stage = match &self.browsed {
+            Some((_, browsed)) => browsed,
+            None => stage,
+        };
+
         self.tick = self.tick.wrapping_add(1);
 
         let width = (stage.map.width as f32 * state.scale) as u16;
@@ -175,14 +243,20 @@ impl MapSystem {
         state: &mut SharedGameState,
         ctx: &mut Context,
         stage: &Stage,
+        stage_id: usize,
         players: [&Player; 2],
     ) -> GameResult {
         if self.state == MapSystemState::Hidden {
             return Ok(());
         }
 
+        let (stage, shown_id) = match &self.browsed {
+            Some((id, browsed)) => (browsed, *id),
+            None => (stage, stage_id),
+        };
+
         if !*self.has_map_data.borrow() {
-            self.render_map(state, ctx, stage)?;
+            self.render_map(state, ctx, stage, shown_id)?;
         }
 
         let (scr_w, scr_h) = (state.canvas_size.0 * state.scale, state.canvas_size.1 * state.scale);
@@ -204,8 +278,15 @@ impl MapSystem {
             stage.data.name.as_str()
         };
 
+        // other visited stages can be browsed with left and right
+        let map_name = if state.exploration.visited_stages().nth(1).is_some() {
+            format!("< {} >", map_name)
+        } else {
+            map_name.to_owned()
+        };
+
         state.font.builder().center(state.canvas_size.0).y(9.0).draw(
-            map_name,
+            &map_name,
             ctx,
             &state.constants,
             &mut state.texture_set,
@@ -259,7 +340,7 @@ impl MapSystem {
             tex.draw()?;
         }
 
-        if (self.tick & 8) != 0 {
+        if (self.tick & 8) != 0 && self.browsed.is_none() {
             const PLAYER_RECT: Rect<u16> = Rect { left: 0, top: 57, right: 1, bottom: 58 };
 
             let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
@@ -0,0 +1,99 @@
+use crate::common::{Color, Rect};
+use crate::components::draw_common::HudPlacement;
+use crate::components::map_system::{tile_shade, TILE_SHADE_RECTS};
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::graphics;
+use crate::framework::graphics::screen_insets_scaled;
+use crate::game::player::Player;
+use crate::game::shared_game_state::SharedGameState;
+use crate::game::stage::Stage;
+
+/// Maximum size of the area shown on the minimap, in tiles.
+const MINIMAP_SIZE: (u16, u16) = (64, 40);
+
+/// Explored surroundings of player 1, drawn in a corner of the screen when enabled in settings.
+pub struct Minimap {
+    tick: u16,
+}
+
+impl Minimap {
+    pub fn new() -> Minimap {
+        Minimap { tick: 0 }
+    }
+
+    pub fn tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+    }
+
+    pub fn draw(
+        &self,
+        state: &mut SharedGameState,
+        ctx: &mut Context,
+        stage: &Stage,
+        stage_id: usize,
+        players: [&Player; 2],
+    ) -> GameResult {
+        if !state.settings.minimap {
+            return Ok(());
+        }
+
+        let width = MINIMAP_SIZE.0.min(stage.map.width);
+        let height = MINIMAP_SIZE.1.min(stage.map.height);
+        let insets = screen_insets_scaled(ctx, state.scale);
+        let element = state.constants.hud_layout.minimap();
+        let placement = match HudPlacement::new(&element, width as f32, height as f32, state.canvas_size, insets) {
+            Some(placement) => placement,
+            None => return Ok(()),
+        };
+
+        // keep player 1 in the middle, unless it's close to the edge of the stage
+        let tile_div = stage.map.tile_size.as_int() * 0x200;
+        let left = (players[0].x / tile_div - width as i32 / 2).clamp(0, (stage.map.width - width) as i32) as usize;
+        let top = (players[0].y / tile_div - height as i32 / 2).clamp(0, (stage.map.height - height) as i32) as usize;
+
+        let background = Rect::new_size(
+            (placement.x * state.scale) as isize,
+            (placement.y * state.scale) as isize,
+            (width as f32 * placement.scale * state.scale) as isize,
+            (height as f32 * placement.scale * state.scale) as isize,
+        );
+        graphics::draw_rect(ctx, background, Color::from_rgba(0, 0, 32, 120))?;
+
+        let explored = match state.exploration.get(stage_id as u16) {
+            Some(explored) => explored,
+            None => return Ok(()),
+        };
+        let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
+
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                if explored.is_explored(left + x, top + y) {
+                    let shade = tile_shade(stage.map.get_attribute(left + x, top + y));
+                    placement.add_rect(batch, x as f32, y as f32, &TILE_SHADE_RECTS[shade]);
+                }
+            }
+        }
+
+        if (self.tick & 8) != 0 {
+            const PLAYER_RECT: Rect<u16> = Rect { left: 0, top: 57, right: 1, bottom: 58 };
+
+            for player in players.iter() {
+                if !player.cond.alive() || player.cond.hidden() {
+                    continue;
+                }
+
+                let x = (player.x / tile_div) as isize - left as isize;
+                let y = (player.y / tile_div) as isize - top as isize;
+
+                if (0..width as isize).contains(&x) && (0..height as isize).contains(&y) {
+                    placement.add_rect(batch, x as f32, y as f32, &PLAYER_RECT);
+                }
+            }
+        }
+
+        batch.draw(ctx)?;
+
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ pub mod background;
 pub mod boss_life_bar;
 pub mod compact_jukebox;
 pub mod credits;
+pub mod dialogue_backlog;
 pub mod draw_common;
 pub mod fade;
 pub mod falling_island;
@@ -9,6 +10,7 @@ pub mod flash;
 pub mod hud;
 pub mod inventory;
 pub mod map_system;
+pub mod minimap;
 pub mod nikumaru;
 pub mod number_popup;
 pub mod replay;
@@ -1,11 +1,12 @@
 use byteorder::{LE, ReadBytesExt, WriteBytesExt};
 
 use crate::common::Rect;
-use crate::components::draw_common::{Alignment, draw_number, draw_number_zeros};
+use crate::components::draw_common::{Alignment, HudPlacement};
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::filesystem;
+use crate::framework::graphics::screen_insets_scaled;
 use crate::framework::vfs::OpenOptions;
 use crate::game::frame::Frame;
 use crate::game::shared_game_state::{SharedGameState, TimingMode};
@@ -132,10 +133,14 @@ impl GameEntity<&Player> for NikumaruCounter {
             return Ok(());
         }
 
-        let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
+        let element = state.constants.hud_layout.nikumaru();
+        let insets = screen_insets_scaled(ctx, state.scale);
+        let placement = match HudPlacement::new(&element, 64.0, 8.0, state.canvas_size, insets) {
+            Some(placement) => placement,
+            None => return Ok(()),
+        };
 
-        let x = 16.0;
-        let y = 8.0;
+        let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
 
         const CLOCK_RECTS: [Rect<u16>; 2] = [
             Rect { left: 112, top: 104, right: 120, bottom: 112 },
@@ -149,17 +154,19 @@ impl GameEntity<&Player> for NikumaruCounter {
         };
 
         if self.tick % 30 <= 10 {
-            batch.add_rect(x, y, &CLOCK_RECTS[1]);
+            placement.add_rect(batch, 0.0, 0.0, &CLOCK_RECTS[1]);
         } else {
-            batch.add_rect(x, y, &CLOCK_RECTS[0]);
+            placement.add_rect(batch, 0.0, 0.0, &CLOCK_RECTS[0]);
         }
-        batch.add_rect(x + 30.0, y, &PRIME);
+        placement.add_rect(batch, 30.0, 0.0, &PRIME);
 
         batch.draw(ctx)?;
 
-        draw_number(x + 32.0, y, self.tick / minute, Alignment::Right, state, ctx)?;
-        draw_number_zeros(x + 52.0, y, (self.tick / second) % 60, Alignment::Right, 2, state, ctx)?;
-        draw_number(x + 64.0, y, (self.tick / one_tenth) % 10, Alignment::Right, state, ctx)?;
+        let seconds = (self.tick / second) % 60;
+        placement.draw_number(32.0, 0.0, self.tick / minute, Alignment::Right, state, ctx)?;
+        placement.draw_number(44.0, 0.0, seconds / 10, Alignment::Right, state, ctx)?;
+        placement.draw_number(52.0, 0.0, seconds % 10, Alignment::Right, state, ctx)?;
+        placement.draw_number(64.0, 0.0, (self.tick / one_tenth) % 10, Alignment::Right, state, ctx)?;
 
         Ok(())
     }
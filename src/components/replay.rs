@@ -1,7 +1,8 @@
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Write};
 
 use byteorder::{LE, ReadBytesExt, WriteBytesExt};
 
+use crate::common::Direction;
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
@@ -10,16 +11,24 @@ use crate::framework::keyboard::ScanCode;
 use crate::framework::vfs::OpenOptions;
 use crate::game::frame::Frame;
 use crate::game::shared_game_state::{ReplayKind, ReplayState, SharedGameState};
+use crate::input::player_controller::PlayerController;
 use crate::input::replay_player_controller::{KeyState, ReplayController};
 use crate::game::player::Player;
+use crate::game::settings::{AssistFlags, AssistSettings};
 use crate::graphics::font::Font;
 
+/// Menu buttons aren't recorded, version 2 replays use their bits for mouse aiming.
+const AIM_LEFT: u16 = 1 << 14;
+const AIM_RIGHT: u16 = 1 << 15;
+
 #[derive(Clone)]
 pub struct Replay {
     replay_version: u16,
     keylist: Vec<u16>,
     last_input: KeyState,
     rng_seed: u64,
+    assist: AssistSettings,
+    assist_flags: AssistFlags,
     pub controller: ReplayController,
     tick: usize,
     resume_tick: usize,
@@ -33,6 +42,8 @@ impl Replay {
             keylist: Vec::new(),
             last_input: KeyState(0),
             rng_seed: 0,
+            assist: AssistSettings::default(),
+            assist_flags: AssistFlags(0),
             controller: ReplayController::new(),
             tick: 0,
             resume_tick: 0,
@@ -43,6 +54,10 @@ impl Replay {
     pub fn initialize_recording(&mut self, state: &mut SharedGameState) {
         if !self.is_active {
             self.rng_seed = state.game_rng.dump_state();
+            self.assist = state.settings.assist;
+            // the game speed can be changed during the run, store the flags it started with
+            self.assist_flags = self.assist.flags(state.settings.speed);
+            state.replay_assist = Some(self.assist);
             self.is_active = true;
         }
     }
@@ -54,6 +69,7 @@ impl Replay {
         is_new_record: bool,
     ) -> GameResult {
         state.replay_state = ReplayState::None;
+        state.replay_assist = None;
 
         self.write_replay(state, ctx, ReplayKind::Last)?;
 
@@ -74,6 +90,7 @@ impl Replay {
             state.replay_state = ReplayState::Playback(replay_kind);
             self.read_replay(state, ctx, replay_kind)?;
             state.game_rng.load_state(self.rng_seed);
+            state.replay_assist = Some(self.assist);
             self.is_active = true;
         }
         Ok(())
@@ -85,11 +102,18 @@ impl Replay {
             [state.get_rec_filename(), replay_kind.get_suffix()].join(""),
             OpenOptions::new().write(true).create(true),
         ) {
-            file.write_u16::<LE>(0)?; // Space for versioning replay files
-            file.write_u64::<LE>(self.rng_seed)?;
-            for input in &self.keylist {
-                file.write_u16::<LE>(*input)?;
-            }
+            self.write_to(&mut file)?;
+        }
+        Ok(())
+    }
+
+    fn write_to(&self, file: &mut impl Write) -> GameResult {
+        file.write_u16::<LE>(2)?; // Space for versioning replay files
+        file.write_u64::<LE>(self.rng_seed)?;
+        file.write_u8(self.assist.damage_taken)?;
+        file.write_u8(self.assist_flags.0)?;
+        for input in &self.keylist {
+            file.write_u16::<LE>(*input)?;
         }
         Ok(())
     }
@@ -97,44 +121,81 @@ impl Replay {
     fn read_replay(&mut self, state: &mut SharedGameState, ctx: &mut Context, replay_kind: ReplayKind) -> GameResult {
         if let Ok(mut file) = filesystem::user_open(ctx, [state.get_rec_filename(), replay_kind.get_suffix()].join(""))
         {
-            self.replay_version = file.read_u16::<LE>()?;
-            self.rng_seed = file.read_u64::<LE>()?;
+            self.read_from(&mut file, state.settings.assist.high_contrast_text)?;
+        }
+        Ok(())
+    }
 
-            let mut data = Vec::new();
-            file.read_to_end(&mut data)?;
+    /// Reads a replay, visual assists aren't recorded so `high_contrast_text` is kept from the current settings.
+    fn read_from(&mut self, file: &mut impl Read, high_contrast_text: bool) -> GameResult {
+        self.replay_version = file.read_u16::<LE>()?;
+        self.rng_seed = file.read_u64::<LE>()?;
 
-            let count = data.len() / 2;
-            let mut inputs = Vec::new();
-            let mut f = Cursor::new(data);
+        // version 0 replays were recorded before assist mode existed
+        self.assist = AssistSettings { high_contrast_text, ..Default::default() };
+        if self.replay_version >= 1 {
+            let damage_taken = file.read_u8()?;
+            let flags = AssistFlags(file.read_u8()?);
 
-            for _ in 0..count {
-                inputs.push(f.read_u16::<LE>()?);
-            }
+            self.assist.damage_taken = damage_taken;
+            self.assist.infinite_air = flags.infinite_air();
+            self.assist.auto_fire = flags.auto_fire();
+            self.assist.pit_protection = flags.pit_protection();
+        }
 
-            self.keylist = inputs;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+
+        let count = data.len() / 2;
+        let mut inputs = Vec::new();
+        let mut f = Cursor::new(data);
+
+        for _ in 0..count {
+            inputs.push(f.read_u16::<LE>()?);
         }
+
+        self.keylist = inputs;
         Ok(())
     }
 }
 
+fn encode_input(controller: &dyn PlayerController) -> u16 {
+    // This mimics the KeyState bitfield
+    controller.move_left() as u16
+        + ((controller.move_right() as u16) << 1)
+        + ((controller.move_up() as u16) << 2)
+        + ((controller.move_down() as u16) << 3)
+        + ((controller.trigger_map() as u16) << 4)
+        + ((controller.trigger_inventory() as u16) << 5)
+        + (((controller.jump() || controller.trigger_menu_ok()) as u16) << 6)
+        + (((controller.shoot() || controller.trigger_menu_back()) as u16) << 7)
+        + ((controller.next_weapon() as u16) << 8)
+        + ((controller.prev_weapon() as u16) << 9)
+        + ((controller.trigger_menu_ok() as u16) << 11)
+        + ((controller.skip() as u16) << 12)
+        + ((controller.strafe() as u16) << 13)
+        + match controller.aim_direction() {
+            Some(Direction::Left) => AIM_LEFT,
+            Some(Direction::Right) => AIM_RIGHT,
+            _ => 0,
+        }
+}
+
+fn decode_input(input: u16, replay_version: u16) -> (KeyState, Option<Direction>) {
+    let aim = match input & (AIM_LEFT | AIM_RIGHT) {
+        AIM_LEFT if replay_version >= 2 => Some(Direction::Left),
+        AIM_RIGHT if replay_version >= 2 => Some(Direction::Right),
+        _ => None,
+    };
+
+    (KeyState(input & !(AIM_LEFT | AIM_RIGHT)), aim)
+}
+
 impl GameEntity<(&mut Context, &mut Player)> for Replay {
     fn tick(&mut self, state: &mut SharedGameState, (ctx, player): (&mut Context, &mut Player)) -> GameResult {
         match state.replay_state {
             ReplayState::Recording => {
-                // This mimics the KeyState bitfield
-                let inputs = player.controller.move_left() as u16
-                    + ((player.controller.move_right() as u16) << 1)
-                    + ((player.controller.move_up() as u16) << 2)
-                    + ((player.controller.move_down() as u16) << 3)
-                    + ((player.controller.trigger_map() as u16) << 4)
-                    + ((player.controller.trigger_inventory() as u16) << 5)
-                    + (((player.controller.jump() || player.controller.trigger_menu_ok()) as u16) << 6)
-                    + (((player.controller.shoot() || player.controller.trigger_menu_back()) as u16) << 7)
-                    + ((player.controller.next_weapon() as u16) << 8)
-                    + ((player.controller.prev_weapon() as u16) << 9)
-                    + ((player.controller.trigger_menu_ok() as u16) << 11)
-                    + ((player.controller.skip() as u16) << 12)
-                    + ((player.controller.strafe() as u16) << 13);
+                let inputs = encode_input(player.controller.as_ref());
 
                 self.keylist.push(inputs);
             }
@@ -142,13 +203,15 @@ impl GameEntity<(&mut Context, &mut Player)> for Replay {
                 let pause = ctx.keyboard_context.is_key_pressed(ScanCode::Escape) && (self.tick - self.resume_tick > 3);
 
                 let next_input = if pause { 1 << 10 } else { *self.keylist.get(self.tick).unwrap_or(&0) };
+                let (next_state, aim) = decode_input(next_input, self.replay_version);
 
-                self.controller.state = KeyState(next_input);
+                self.controller.aim = aim;
+                self.controller.state = next_state;
                 self.controller.old_state = self.last_input;
                 player.controller = Box::new(self.controller);
 
                 if !pause {
-                    self.last_input = KeyState(next_input);
+                    self.last_input = next_state;
                     self.tick += 1;
                 } else {
                     self.resume_tick = self.tick;
@@ -156,7 +219,8 @@ impl GameEntity<(&mut Context, &mut Player)> for Replay {
 
                 if self.tick >= self.keylist.len() {
                     state.replay_state = ReplayState::None;
-                    player.controller = state.settings.create_player1_controller();
+                    state.replay_assist = None;
+                    player.controller = state.settings.create_player1_game_controller();
                 }
             }
             ReplayState::None => {}
@@ -186,3 +250,42 @@ impl GameEntity<(&mut Context, &mut Player)> for Replay {
         Ok(())
     }
 }
+
+#[test]
+fn test_replay_aim_round_trip() {
+    let mut controller = ReplayController::new();
+    controller.state.set_left(true);
+    controller.state.set_shoot(true);
+    controller.aim = Some(Direction::Right);
+
+    let mut replay = Replay::new();
+    replay.rng_seed = 0x1234_5678_9abc;
+    replay.assist.damage_taken = 50;
+    replay.assist_flags.set_pit_protection(true);
+    replay.keylist = vec![encode_input(&controller), 0];
+    controller.aim = Some(Direction::Left);
+    replay.keylist.push(encode_input(&controller));
+
+    let mut data = Vec::new();
+    replay.write_to(&mut data).unwrap();
+
+    let mut loaded = Replay::new();
+    loaded.read_from(&mut Cursor::new(&data), true).unwrap();
+    assert_eq!(loaded.replay_version, 2);
+    assert_eq!(loaded.rng_seed, replay.rng_seed);
+    assert_eq!(loaded.keylist, replay.keylist);
+    assert_eq!(loaded.assist.damage_taken, 50);
+    assert!(loaded.assist.pit_protection && loaded.assist.high_contrast_text && !loaded.assist.auto_fire);
+
+    let decoded: Vec<(u16, Option<Direction>)> = loaded
+        .keylist
+        .iter()
+        .map(|&input| decode_input(input, loaded.replay_version))
+        .map(|(state, aim)| (state.0, aim))
+        .collect();
+    assert_eq!(decoded, vec![(0b1000_0001, Some(Direction::Right)), (0, None), (0b1000_0001, Some(Direction::Left))]);
+
+    // aiming is ignored in replays recorded before it existed
+    assert_eq!(decode_input(AIM_LEFT | 1, 1).1, None);
+    assert_eq!(decode_input(AIM_LEFT | 1, 1).0 .0, 1);
+}
@@ -7,6 +7,7 @@ use crate::game::shared_game_state::SharedGameState;
 use crate::input::touch_controls::TouchControlType;
 use crate::game::player::Player;
 use crate::game::scripting::tsc::text_script::ScriptMode;
+use crate::sound::mixer::MixerBus;
 
 pub struct StageSelect {
     pub current_teleport_slot: u8,
@@ -69,7 +70,7 @@ impl GameEntity<(&mut Context, &Player, &Player)> for StageSelect {
 
         if self.prev_teleport_slot != self.current_teleport_slot {
             self.prev_teleport_slot = self.current_teleport_slot;
-            state.sound_manager.play_sfx(1);
+            state.sound_manager.play_sfx_on(1, MixerBus::UI);
             if let Some(&(index, _event_num)) = state.teleporter_slots.get(self.current_teleport_slot as usize) {
                 state.textscript_vm.start_script(1000 + index);
             } else {
@@ -88,7 +89,7 @@ impl GameEntity<(&mut Context, &Player, &Player)> for StageSelect {
                     if self.current_teleport_slot as usize == i {
                         ok_pressed = true;
                     } else {
-                        state.sound_manager.play_sfx(1);
+                        state.sound_manager.play_sfx_on(1, MixerBus::UI);
                         self.current_teleport_slot = i as u8;
                     }
 
@@ -100,7 +101,7 @@ impl GameEntity<(&mut Context, &Player, &Player)> for StageSelect {
             slot_rect = Rect::new_size(state.canvas_size.0 as isize - 34 - off_right as isize, 8 + off_top as isize, 26, 26);
 
             if state.touch_controls.consume_click_in(slot_rect) {
-                state.sound_manager.play_sfx(5);
+                state.sound_manager.play_sfx_on(5, MixerBus::UI);
                 cancel_pressed = true;
             }
         }
@@ -1,18 +1,25 @@
 use crate::common::{Color, Rect};
-use crate::engine_constants::AnimatedFace;
+use crate::engine_constants::{AnimatedFace, EngineConstants};
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::graphics;
 use crate::game::frame::Frame;
-use crate::game::scripting::tsc::text_script::{ConfirmSelection, TextScriptExecutionState, TextScriptLine};
+use crate::game::scripting::tsc::rich_text;
+use crate::game::scripting::tsc::rich_text::{RichChar, TextEffect, TextStyle};
+use crate::game::scripting::tsc::text_script::{
+    ConfirmSelection, TextScriptExecutionState, TextScriptLine, TextScriptVM,
+};
 use crate::game::shared_game_state::SharedGameState;
+use crate::graphics::bmfont::BMFont;
 use crate::graphics::font::{Font, Symbols};
+use crate::graphics::texture_set::TextureSet;
 
 pub struct TextBoxes {
     pub slide_in: u8,
     pub anim_counter: usize,
     animated_face: AnimatedFace,
+    effect_counter: u16,
 }
 
 const FACE_TEX: &str = "Face";
@@ -24,12 +31,105 @@ impl TextBoxes {
             slide_in: 7,
             anim_counter: 0,
             animated_face: AnimatedFace { face_id: 0, anim_id: 0, anim_frames: vec![(0, 0)] },
+            effect_counter: 0,
         }
     }
 }
 
+fn effect_offset(effect: TextEffect, counter: u16, idx: usize) -> (f32, f32) {
+    match effect {
+        TextEffect::None => (0.0, 0.0),
+        TextEffect::Shake => {
+            let seed = (counter / 2) as usize * 31 + idx * 17;
+            ((seed % 3) as f32 - 1.0, (seed / 3 % 3) as f32 - 1.0)
+        }
+        TextEffect::Wave => (0.0, ((counter as f32 + idx as f32 * 5.0) / 8.0).sin() * 2.0),
+    }
+}
+
+/// Draws a text box line containing style markers or inline icons left by rich text tags.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_rich_line(
+    font: &BMFont,
+    vm: &TextScriptVM,
+    constants: &EngineConstants,
+    texture_set: &mut TextureSet,
+    ctx: &mut Context,
+    line: &[char],
+    (mut x, y): (f32, f32),
+    shadow: bool,
+    counter: u16,
+) -> GameResult {
+    let symbols = Symbols { symbols: &vm.substitution_rect_map, texture: "TextBox" };
+    let rich_text_enabled = constants.textscript.extensions.rich_text;
+    let icon_y = (y + (font.line_height() - 16.0) / 2.0).floor();
+    let mut style = TextStyle::new();
+    let mut idx = 0;
+
+    while idx < line.len() {
+        match RichChar::parse(line[idx], rich_text_enabled) {
+            RichChar::Style(style_idx) => {
+                style = vm.text_styles.get(style_idx).copied().unwrap_or_default();
+                idx += 1;
+            }
+            RichChar::ItemIcon(item_id) => {
+                let rect = Rect::new_size((item_id % 8) * 32, (item_id / 8) * 16, 32, 16);
+
+                let batch = texture_set.get_or_load_batch(ctx, constants, "ItemImage")?;
+                batch.add_rect(x, icon_y, &rect);
+                batch.draw(ctx)?;
+
+                x += rich_text::ITEM_ICON_WIDTH;
+                idx += 1;
+            }
+            RichChar::WeaponIcon(weapon_id) => {
+                let rect = constants.weapon.get_arms_icon_rect(weapon_id);
+
+                let batch = texture_set.get_or_load_batch(ctx, constants, "ArmsImage")?;
+                batch.add_rect(x, icon_y, &rect);
+                batch.draw(ctx)?;
+
+                x += rich_text::WEAPON_ICON_WIDTH;
+                idx += 1;
+            }
+            RichChar::Char(_) => {
+                let end = line[idx..]
+                    .iter()
+                    .position(|&chr| !matches!(RichChar::parse(chr, rich_text_enabled), RichChar::Char(_)))
+                    .map_or(line.len(), |len| idx + len);
+                let (r, g, b) = style.color.unwrap_or((255, 255, 255));
+
+                if style.effect == TextEffect::None {
+                    let builder =
+                        font.builder().position(x, y).shadow(shadow).color((r, g, b, 255)).with_symbols(Some(symbols));
+                    x += builder.compute_width_iter(line[idx..end].iter().copied());
+                    builder.draw_iter(line[idx..end].iter().copied(), ctx, constants, texture_set)?;
+                } else {
+                    for (i, &chr) in line[idx..end].iter().enumerate() {
+                        let (off_x, off_y) = effect_offset(style.effect, counter, idx + i);
+                        let builder = font
+                            .builder()
+                            .position(x + off_x, y + off_y)
+                            .shadow(shadow)
+                            .color((r, g, b, 255))
+                            .with_symbols(Some(symbols));
+                        x += builder.compute_width_iter(std::iter::once(chr));
+                        builder.draw_iter(std::iter::once(chr), ctx, constants, texture_set)?;
+                    }
+                }
+
+                idx = end;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl GameEntity<()> for TextBoxes {
     fn tick(&mut self, state: &mut SharedGameState, _custom: ()) -> GameResult {
+        self.effect_counter = self.effect_counter.wrapping_add(1);
+
         if state.textscript_vm.face != 0 {
             self.slide_in = self.slide_in.saturating_sub(1);
             self.anim_counter = self.anim_counter.wrapping_add(1);
@@ -73,10 +173,21 @@ impl GameEntity<()> for TextBoxes {
             state.canvas_size.1 as f32 - off_bottom - 66.0
         };
         let left_pos = off_left + center - 122.0;
+        let high_contrast = state.settings.assist.high_contrast_text;
 
         {
             let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
-            if state.textscript_vm.flags.background_visible() {
+            if state.textscript_vm.flags.background_visible() && high_contrast {
+                let rect = Rect::new_size(
+                    (left_pos * state.scale) as isize,
+                    (top_pos * state.scale) as isize,
+                    (244.0 * state.scale) as isize,
+                    (64.0 * state.scale) as isize,
+                );
+
+                graphics::draw_rect(ctx, rect, Color::from_rgb(0, 0, 0))?;
+                graphics::draw_outline_rect(ctx, rect, state.scale as usize, Color::from_rgb(255, 255, 255))?;
+            } else if state.textscript_vm.flags.background_visible() {
                 batch.add_rect(left_pos, top_pos, &state.constants.textscript.textbox_rect_top);
                 for i in 1..7 {
                     batch.add_rect(left_pos, top_pos + i as f32 * 8.0, &state.constants.textscript.textbox_rect_middle);
@@ -185,10 +296,7 @@ impl GameEntity<()> for TextBoxes {
             if state.textscript_vm.item < 1000 {
                 let item_id = state.textscript_vm.item as u16;
 
-                rect.left = (item_id % 16) * 16;
-                rect.right = rect.left + 16;
-                rect.top = (item_id / 16) * 16;
-                rect.bottom = rect.top + 16;
+                rect = state.constants.weapon.get_arms_icon_rect(item_id);
 
                 let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "ArmsImage")?;
                 batch.add_rect((center - 12.0).floor(), state.canvas_size.1 - off_bottom - 104.0, &rect);
@@ -208,6 +316,7 @@ impl GameEntity<()> for TextBoxes {
         }
 
         let text_offset = if state.textscript_vm.face == 0 { 0.0 } else { 56.0 };
+        let rich_text_enabled = state.constants.textscript.extensions.rich_text;
 
         let y_offset = if let TextScriptExecutionState::MsgNewLine(_, _, _, _, counter) = state.textscript_vm.state {
             16.0 - counter as f32 * 4.0
@@ -227,15 +336,32 @@ impl GameEntity<()> for TextBoxes {
         graphics::set_clip_rect(ctx, Some(clip_rect))?;
         for (idx, line) in lines.iter().enumerate() {
             if !line.is_empty() {
-                let symbols = Symbols { symbols: &state.textscript_vm.substitution_rect_map, texture: "TextBox" };
-
-                state
-                    .font
-                    .builder()
-                    .position(left_pos + text_offset + 14.0, top_pos + 10.0 + idx as f32 * 16.0 - y_offset)
-                    .shadow(state.constants.textscript.text_shadow)
-                    .with_symbols(Some(symbols))
-                    .draw_iter(line.iter().copied(), ctx, &state.constants, &mut state.texture_set)?;
+                let x = left_pos + text_offset + 14.0;
+                let y = top_pos + 10.0 + idx as f32 * 16.0 - y_offset;
+                let shadow = state.constants.textscript.text_shadow || high_contrast;
+
+                if !rich_text_enabled || line.iter().all(|&chr| matches!(RichChar::from_char(chr), RichChar::Char(_))) {
+                    let symbols = Symbols { symbols: &state.textscript_vm.substitution_rect_map, texture: "TextBox" };
+
+                    state.font.builder().position(x, y).shadow(shadow).with_symbols(Some(symbols)).draw_iter(
+                        line.iter().copied(),
+                        ctx,
+                        &state.constants,
+                        &mut state.texture_set,
+                    )?;
+                } else {
+                    draw_rich_line(
+                        &state.font,
+                        &state.textscript_vm,
+                        &state.constants,
+                        &mut state.texture_set,
+                        ctx,
+                        line,
+                        (x, y),
+                        shadow,
+                        self.effect_counter,
+                    )?;
+                }
             }
         }
         graphics::set_clip_rect(ctx, None)?;
@@ -247,17 +373,12 @@ impl GameEntity<()> for TextBoxes {
                     .builder()
                     .with_symbols(Some(Symbols { symbols: &state.textscript_vm.substitution_rect_map, texture: "" }));
 
-                let (mut x, y) = match state.textscript_vm.current_line {
-                    TextScriptLine::Line1 => {
-                        (builder.compute_width_iter(state.textscript_vm.line_1.iter().copied()), top_pos + 10.0)
-                    }
-                    TextScriptLine::Line2 => {
-                        (builder.compute_width_iter(state.textscript_vm.line_2.iter().copied()), top_pos + 10.0 + 16.0)
-                    }
-                    TextScriptLine::Line3 => {
-                        (builder.compute_width_iter(state.textscript_vm.line_3.iter().copied()), top_pos + 10.0 + 32.0)
-                    }
+                let (line, y) = match state.textscript_vm.current_line {
+                    TextScriptLine::Line1 => (&state.textscript_vm.line_1, top_pos + 10.0),
+                    TextScriptLine::Line2 => (&state.textscript_vm.line_2, top_pos + 10.0 + 16.0),
+                    TextScriptLine::Line3 => (&state.textscript_vm.line_3, top_pos + 10.0 + 32.0),
                 };
+                let mut x = rich_text::compute_width(&builder, line, rich_text_enabled);
                 x += left_pos + text_offset + 14.0;
 
                 graphics::draw_rect(
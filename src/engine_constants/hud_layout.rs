@@ -0,0 +1,225 @@
+use crate::components::draw_common::Alignment;
+
+/// Screen point an element is attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum HudAnchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct HudElement {
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    #[serde(default)]
+    pub anchor: HudAnchor,
+    /// Distance from the anchored screen edge, or an offset from the center of the screen for centered anchors.
+    #[serde(default)]
+    pub x: f32,
+    #[serde(default)]
+    pub y: f32,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Keeps the element out of the screen areas covered by notches and rounded corners.
+    #[serde(default = "default_true")]
+    pub safe_area: bool,
+}
+
+impl HudElement {
+    pub const fn new(anchor: HudAnchor, x: f32, y: f32) -> HudElement {
+        HudElement { visible: true, anchor, x, y, scale: 1.0, safe_area: true }
+    }
+
+    pub const fn hidden(anchor: HudAnchor, x: f32, y: f32) -> HudElement {
+        HudElement { visible: false, anchor, x, y, scale: 1.0, safe_area: true }
+    }
+
+    /// Returns the position of the top left corner of an element with given unscaled size.
+    pub fn place(&self, width: f32, height: f32, canvas_size: (f32, f32), insets: (f32, f32, f32, f32)) -> (f32, f32) {
+        let (left, top, right, bottom) = if self.safe_area { insets } else { (0.0, 0.0, 0.0, 0.0) };
+        let (width, height) = (width * self.scale, height * self.scale);
+
+        let x = match self.anchor {
+            HudAnchor::TopLeft | HudAnchor::Left | HudAnchor::BottomLeft => left + self.x,
+            HudAnchor::Top | HudAnchor::Center | HudAnchor::Bottom => {
+                left + ((canvas_size.0 - left - right - width) / 2.0).floor() + self.x
+            }
+            HudAnchor::TopRight | HudAnchor::Right | HudAnchor::BottomRight => canvas_size.0 - right - width - self.x,
+        };
+
+        let y = match self.anchor {
+            HudAnchor::TopLeft | HudAnchor::Top | HudAnchor::TopRight => top + self.y,
+            HudAnchor::Left | HudAnchor::Center | HudAnchor::Right => {
+                top + ((canvas_size.1 - top - bottom - height) / 2.0).floor() + self.y
+            }
+            HudAnchor::BottomLeft | HudAnchor::Bottom | HudAnchor::BottomRight => {
+                canvas_size.1 - bottom - height - self.y
+            }
+        };
+
+        (x.floor(), y.floor())
+    }
+}
+
+/// Elements of a player's HUD, unset ones keep their default placement for the player's side of the screen.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HudPlayerLayout {
+    pub life: Option<HudElement>,
+    pub weapons: Option<HudElement>,
+    pub ammo: Option<HudElement>,
+    pub experience: Option<HudElement>,
+    pub air: Option<HudElement>,
+    /// Exact experience of the current weapon, hidden by default.
+    pub xp_numbers: Option<HudElement>,
+    /// Remaining Booster fuel in percent, hidden by default.
+    pub booster_fuel: Option<HudElement>,
+}
+
+/// Placement of HUD elements, can be changed by mods (or per language) through `hud_layout.json`,
+/// and by users through `hud_layout.json` in the user directory.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HudLayout {
+    pub player1: HudPlayerLayout,
+    pub player2: HudPlayerLayout,
+    pub nikumaru: Option<HudElement>,
+    /// Defaults to the bottom center of the screen, or the bottom right corner on Switch.
+    pub boss_life: Option<HudElement>,
+    /// Exact health of the boss, hidden by default.
+    pub boss_life_numbers: Option<HudElement>,
+    /// Shown if enabled in settings.
+    pub minimap: Option<HudElement>,
+}
+
+impl HudLayout {
+    fn player_element(
+        &self,
+        alignment: Alignment,
+        element: fn(&HudPlayerLayout) -> Option<HudElement>,
+        left: HudElement,
+        right: HudElement,
+    ) -> HudElement {
+        match alignment {
+            Alignment::Left => element(&self.player1).unwrap_or(left),
+            Alignment::Right => element(&self.player2).unwrap_or(right),
+        }
+    }
+
+    pub fn life(&self, alignment: Alignment) -> HudElement {
+        self.player_element(
+            alignment,
+            |p| p.life,
+            HudElement::new(HudAnchor::TopLeft, 16.0, 40.0),
+            HudElement::new(HudAnchor::TopRight, 8.0, 40.0),
+        )
+    }
+
+    pub fn weapons(&self, alignment: Alignment) -> HudElement {
+        self.player_element(
+            alignment,
+            |p| p.weapons,
+            HudElement::new(HudAnchor::TopLeft, 0.0, 16.0),
+            HudElement::new(HudAnchor::TopRight, 0.0, 16.0),
+        )
+    }
+
+    pub fn ammo(&self, alignment: Alignment) -> HudElement {
+        self.player_element(
+            alignment,
+            |p| p.ammo,
+            HudElement::new(HudAnchor::TopLeft, 48.0, 16.0),
+            HudElement::new(HudAnchor::TopRight, 32.0, 16.0),
+        )
+    }
+
+    pub fn experience(&self, alignment: Alignment) -> HudElement {
+        self.player_element(
+            alignment,
+            |p| p.experience,
+            HudElement::new(HudAnchor::TopLeft, 16.0, 32.0),
+            HudElement::new(HudAnchor::TopRight, 8.0, 32.0),
+        )
+    }
+
+    pub fn air(&self, alignment: Alignment) -> HudElement {
+        self.player_element(
+            alignment,
+            |p| p.air,
+            HudElement::new(HudAnchor::Center, 0.0, 4.0),
+            HudElement::new(HudAnchor::Center, 0.0, 4.0),
+        )
+    }
+
+    pub fn xp_numbers(&self, alignment: Alignment) -> HudElement {
+        self.player_element(
+            alignment,
+            |p| p.xp_numbers,
+            HudElement::hidden(HudAnchor::TopLeft, 16.0, 48.0),
+            HudElement::hidden(HudAnchor::TopRight, 8.0, 48.0),
+        )
+    }
+
+    pub fn booster_fuel(&self, alignment: Alignment) -> HudElement {
+        self.player_element(
+            alignment,
+            |p| p.booster_fuel,
+            HudElement::hidden(HudAnchor::TopLeft, 16.0, 56.0),
+            HudElement::hidden(HudAnchor::TopRight, 8.0, 56.0),
+        )
+    }
+
+    pub fn nikumaru(&self) -> HudElement {
+        self.nikumaru.unwrap_or(HudElement::new(HudAnchor::TopLeft, 16.0, 8.0))
+    }
+
+    pub fn boss_life(&self, is_switch: bool) -> HudElement {
+        self.boss_life.unwrap_or(if is_switch {
+            HudElement::new(HudAnchor::BottomRight, 12.0, 4.0)
+        } else {
+            HudElement::new(HudAnchor::Bottom, 0.0, 4.0)
+        })
+    }
+
+    pub fn boss_life_numbers(&self) -> HudElement {
+        self.boss_life_numbers.unwrap_or(HudElement::hidden(HudAnchor::Bottom, 0.0, 24.0))
+    }
+
+    pub fn minimap(&self) -> HudElement {
+        self.minimap.unwrap_or(HudElement::new(HudAnchor::TopRight, 8.0, 8.0))
+    }
+}
+
+#[test]
+fn test_hud_element_place() {
+    let canvas_size = (320.0, 240.0);
+    let insets = (10.0, 0.0, 20.0, 0.0);
+
+    let life = HudElement::new(HudAnchor::TopLeft, 16.0, 40.0);
+    assert_eq!(life.place(64.0, 8.0, canvas_size, insets), (26.0, 40.0));
+
+    let life = HudElement::new(HudAnchor::TopRight, 8.0, 40.0);
+    assert_eq!(life.place(64.0, 8.0, canvas_size, insets), (228.0, 40.0));
+
+    let boss_life = HudElement { safe_area: false, ..HudElement::new(HudAnchor::Bottom, 0.0, 4.0) };
+    assert_eq!(boss_life.place(256.0, 16.0, canvas_size, insets), (32.0, 220.0));
+
+    let air = HudElement { scale: 2.0, ..HudElement::new(HudAnchor::Center, 0.0, 4.0) };
+    assert_eq!(air.place(80.0, 8.0, canvas_size, insets), (75.0, 116.0));
+}
@@ -1,28 +1,36 @@
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Cursor, Read};
+use std::str::FromStr;
 
 use byteorder::{ReadBytesExt, LE};
 use case_insensitive_hashmap::CaseInsensitiveHashMap;
 use xmltree::Element;
 
 use crate::case_insensitive_hashmap;
-use crate::common::{BulletFlag, Color, Rect};
+use crate::common::{BulletFlag, Color, Direction, Rect};
+use crate::engine_constants::hud_layout::HudLayout;
 use crate::engine_constants::npcs::NPCConsts;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::filesystem;
 use crate::framework::gamepad::{Axis, Button};
+use crate::game::npc::list::NPC_LIST_DEFAULT_MAX_CAP;
+use crate::game::player::skin::{create_skin, load_skin_metadata, PlayerAnimationState};
 use crate::game::player::ControlMode;
+use crate::game::scripting::tsc::opcodes::TSCOpCode;
 use crate::game::scripting::tsc::text_script::TextScriptEncoding;
+use crate::game::scripting::tsc::translation::TranslationCatalog;
 use crate::game::settings::Settings;
 use crate::game::shared_game_state::{FontData, Season};
+use crate::game::weapon::WeaponType;
 use crate::i18n::Locale;
 use crate::sound::pixtone::{Channel, Envelope, PixToneParameters, Waveform};
 use crate::sound::SoundManager;
 
+pub mod hud_layout;
 mod npcs;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, serde::Deserialize)]
 pub struct PhysicsConsts {
     pub max_dash: i32,
     pub max_move: i32,
@@ -34,6 +42,47 @@ pub struct PhysicsConsts {
     pub jump: i32,
 }
 
+/// Movement of the player in `ControlMode::Jetpack`.
+#[derive(Debug, Copy, Clone, serde::Deserialize)]
+pub struct JetpackConsts {
+    pub accel: i32,
+    pub max_speed: i32,
+    pub resist: i32,
+}
+
+/// Velocity applied when the player jumps off a wall in mid-air.
+#[derive(Debug, Copy, Clone, serde::Deserialize)]
+pub struct WallKickConsts {
+    pub vel_x: i32,
+    pub vel_y: i32,
+}
+
+/// Named set of player physics, selected with `<UNI` using its `id` instead of a built-in control mode.
+///
+/// Physics that are not set fall back to the values from `PlayerConsts`.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct PhysicsPreset {
+    pub id: u8,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub control_mode: Option<ControlMode>,
+    #[serde(default)]
+    pub air_physics: Option<PhysicsConsts>,
+    #[serde(default)]
+    pub water_physics: Option<PhysicsConsts>,
+    #[serde(default)]
+    pub jetpack: Option<JetpackConsts>,
+    #[serde(default)]
+    pub wall_kick: Option<WallKickConsts>,
+    /// Climbing speed on ladder tiles (attribute 0x0c), ladders are ignored if zero.
+    #[serde(default)]
+    pub ladder_speed: i32,
+    /// Upward velocity of a swim stroke, allows jumping repeatedly in water if non-zero.
+    #[serde(default)]
+    pub swim_stroke: i32,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct BoosterConsts {
     pub fuel: u32,
@@ -51,6 +100,7 @@ pub struct PlayerConsts {
     pub control_mode: ControlMode,
     pub air_physics: PhysicsConsts,
     pub water_physics: PhysicsConsts,
+    pub jetpack: JetpackConsts,
     pub frames_left: [Rect<u16>; 12],
     pub frames_right: [Rect<u16>; 12],
     pub frames_bubble: [Rect<u16>; 2],
@@ -125,7 +175,7 @@ impl Clone for CaretConsts {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, serde::Deserialize)]
 pub struct BulletData {
     pub damage: u8,
     pub life: u8,
@@ -175,11 +225,107 @@ pub struct BulletRects {
     pub b042_spur_trail_l3: [Rect<u16>; 6],
 }
 
+/// Pattern used by custom weapons to fire their bullets.
+#[derive(Debug, Copy, Clone, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FiringPattern {
+    /// Fires a single bullet.
+    Single,
+    /// Fires `count` bullets fanned out evenly over `angle` degrees.
+    Spread { count: u8, angle: u16 },
+    /// Fires `count` bullets in a row, `interval` ticks apart.
+    Burst { count: u8, interval: u16 },
+    /// Fires when shoot is released, using `charged_bullet` if it was held for at least `ticks`.
+    Charge { ticks: u16, charged_bullet: u16 },
+}
+
+impl Default for FiringPattern {
+    fn default() -> Self {
+        FiringPattern::Single
+    }
+}
+
+#[derive(Debug, Copy, Clone, serde::Deserialize)]
+pub struct CustomWeaponLevel {
+    pub bullet: u16,
+    #[serde(default)]
+    pub pattern: FiringPattern,
+    /// Maximum amount of bullets of this level a player can have on screen, unlimited if zero.
+    #[serde(default)]
+    pub max_bullets: u16,
+    #[serde(default = "custom_weapon_default_ammo_cost")]
+    pub ammo_cost: u16,
+    /// If set, holding shoot fires every `refire_ticks` ticks.
+    #[serde(default)]
+    pub auto_fire: bool,
+    #[serde(default)]
+    pub refire_ticks: u16,
+    /// Sound effect played when firing, none if zero.
+    #[serde(default)]
+    pub sound: u8,
+}
+
+const fn custom_weapon_default_ammo_cost() -> u16 {
+    1
+}
+
+/// Weapon defined in `weapons.json`, its `id` must not be used by any of the vanilla weapons.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct CustomWeapon {
+    pub id: u8,
+    #[serde(default)]
+    pub name: String,
+    /// Experience required to level up, same as `arms_level.tbl` entries.
+    pub level_table: [u16; 3],
+    pub levels: [CustomWeaponLevel; 3],
+    /// Icon in `ArmsImage`, defaults to the one at the weapon id.
+    #[serde(default)]
+    pub arms_icon: Option<Rect<u16>>,
+}
+
+/// Bullet defined in `weapons.json`, its `id` must be past the end of the bullet table.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct CustomBullet {
+    pub id: u16,
+    pub data: BulletData,
+    pub speed: i32,
+    #[serde(default)]
+    pub gravity: i32,
+    /// Acceleration towards the closest shootable NPC, homing is disabled if zero.
+    #[serde(default)]
+    pub homing: i32,
+    /// Maximum distance to NPCs followed by homing bullets.
+    #[serde(default = "custom_bullet_default_homing_range")]
+    pub homing_range: i32,
+    /// Animation frames for left, up, right and down facing bullets.
+    pub rects: [Vec<Rect<u16>>; 4],
+    #[serde(default = "custom_bullet_default_anim_ticks")]
+    pub anim_ticks: u16,
+}
+
+const fn custom_bullet_default_homing_range() -> i32 {
+    0x10000
+}
+
+const fn custom_bullet_default_anim_ticks() -> u16 {
+    1
+}
+
+#[derive(serde::Deserialize)]
+struct WeaponDefinitions {
+    #[serde(default)]
+    weapons: Vec<CustomWeapon>,
+    #[serde(default)]
+    bullets: Vec<CustomBullet>,
+}
+
 #[derive(Debug)]
 pub struct WeaponConsts {
     pub bullet_table: Vec<BulletData>,
     pub bullet_rects: BulletRects,
     pub level_table: [[u16; 3]; 14],
+    pub custom_weapons: Vec<CustomWeapon>,
+    pub custom_bullets: Vec<CustomBullet>,
 }
 
 impl Clone for WeaponConsts {
@@ -188,10 +334,42 @@ impl Clone for WeaponConsts {
             bullet_table: self.bullet_table.clone(),
             bullet_rects: self.bullet_rects,
             level_table: self.level_table,
+            custom_weapons: self.custom_weapons.clone(),
+            custom_bullets: self.custom_bullets.clone(),
         }
     }
 }
 
+impl WeaponConsts {
+    pub fn get_custom_weapon(&self, id: u8) -> Option<&CustomWeapon> {
+        self.custom_weapons.iter().find(|weapon| weapon.id == id)
+    }
+
+    pub fn get_custom_bullet(&self, id: u16) -> Option<&CustomBullet> {
+        self.custom_bullets.iter().find(|bullet| bullet.id == id)
+    }
+
+    pub fn get_bullet_data(&self, id: u16) -> Option<&BulletData> {
+        self.bullet_table.get(id as usize).or_else(|| self.get_custom_bullet(id).map(|bullet| &bullet.data))
+    }
+
+    pub fn get_level_table(&self, weapon_id: u8) -> [u16; 3] {
+        match self.get_custom_weapon(weapon_id) {
+            Some(weapon) => weapon.level_table,
+            None => self.level_table.get(weapon_id as usize).copied().unwrap_or([0; 3]),
+        }
+    }
+
+    /// Returns the icon of given weapon in `ArmsImage`.
+    pub fn get_arms_icon_rect(&self, weapon_id: u16) -> Rect<u16> {
+        if let Some(rect) = u8::try_from(weapon_id).ok().and_then(|id| self.get_custom_weapon(id)?.arms_icon) {
+            return rect;
+        }
+
+        Rect::new_size((weapon_id % 16) * 16, (weapon_id / 16) * 16, 16, 16)
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct WorldConsts {
     pub snack_rect: Rect<u16>,
@@ -212,7 +390,7 @@ pub struct ExtraSoundtrack {
     pub available: bool,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct TextScriptConsts {
     pub encoding: TextScriptEncoding,
     pub encrypted: bool,
@@ -243,6 +421,42 @@ pub struct TextScriptConsts {
     pub text_speed_normal: u8,
     pub text_speed_fast: u8,
     pub fade_ticks: i8,
+    /// Events the scripts jump to when the player dies or falls into a pit, the player is rescued from them
+    /// instead with pit protection enabled.
+    pub pit_death_events: Vec<u16>,
+    pub extensions: TextScriptExtensions,
+    /// Translated message strings, loaded from `tsc_translation.po` or `tsc_translation.json`.
+    pub translation: TranslationCatalog,
+}
+
+/// Opt-in additions to the TSC language, enabled by mods through `textscript.json`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct TextScriptExtensions {
+    /// Enables integer variable opcodes (`<VA=`, `<VA+`, `<VAJ`, `<RNJ`, `<VNM` and so on).
+    #[serde(default)]
+    pub variables: bool,
+    /// Opcodes handled by Lua scripts through `doukutsu.setTSCHandler`.
+    #[serde(default)]
+    pub opcodes: Vec<CustomOpcode>,
+    /// Enables `{...}` markup in message text (colors, effects, text speed and inline icons)
+    /// and word wrapping at the edge of the text box.
+    #[serde(default)]
+    pub rich_text: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CustomOpcode {
+    /// Three character name, can't be the same as any of the built-in opcodes.
+    pub name: String,
+    /// Number of 4 digit operands, separated with `:` like in the built-in opcodes.
+    #[serde(default)]
+    pub operands: u8,
+}
+
+impl TextScriptExtensions {
+    pub fn get_opcode(&self, name: &str) -> Option<&CustomOpcode> {
+        self.opcodes.iter().find(|opcode| opcode.name == name)
+    }
 }
 
 #[derive(Debug)]
@@ -290,6 +504,39 @@ impl Clone for TitleConsts {
     }
 }
 
+/// Limits of the entity lists, can be raised by mods through `capacities.json`.
+#[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CapacityConsts {
+    #[serde(default = "default_npc_capacity")]
+    pub npc: u16,
+    #[serde(default = "default_bullet_capacity")]
+    pub bullet: usize,
+    #[serde(default = "default_caret_capacity")]
+    pub caret: usize,
+}
+
+fn default_npc_capacity() -> u16 {
+    NPC_LIST_DEFAULT_MAX_CAP
+}
+
+fn default_bullet_capacity() -> usize {
+    256
+}
+
+fn default_caret_capacity() -> usize {
+    512
+}
+
+impl Default for CapacityConsts {
+    fn default() -> Self {
+        CapacityConsts {
+            npc: default_npc_capacity(),
+            bullet: default_bullet_capacity(),
+            caret: default_caret_capacity(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GamepadConsts {
     pub button_rects: HashMap<Button, [Rect<u16>; 4]>,
@@ -340,11 +587,16 @@ pub struct EngineConstants {
     pub organya_paths: Vec<String>,
     pub credit_illustration_paths: Vec<String>,
     pub player_skin_paths: Vec<String>,
+    /// Preview frames of skin packs, keyed by their spritesheet name.
+    pub player_skin_previews: HashMap<String, Rect<u16>>,
     pub animated_face_table: Vec<AnimatedFace>,
     pub string_table: HashMap<String, String>,
     pub missile_flags: Vec<u16>,
     pub locales: Vec<Locale>,
     pub gamepad: GamepadConsts,
+    pub capacity: CapacityConsts,
+    pub physics_presets: Vec<PhysicsPreset>,
+    pub hud_layout: HudLayout,
 }
 
 impl Clone for EngineConstants {
@@ -365,7 +617,7 @@ impl Clone for EngineConstants {
             npc: self.npc,
             weapon: self.weapon.clone(),
             tex_sizes: self.tex_sizes.clone(),
-            textscript: self.textscript,
+            textscript: self.textscript.clone(),
             title: self.title.clone(),
             inventory_dim_color: self.inventory_dim_color,
             font_path: self.font_path.clone(),
@@ -375,11 +627,15 @@ impl Clone for EngineConstants {
             organya_paths: self.organya_paths.clone(),
             credit_illustration_paths: self.credit_illustration_paths.clone(),
             player_skin_paths: self.player_skin_paths.clone(),
+            player_skin_previews: self.player_skin_previews.clone(),
             animated_face_table: self.animated_face_table.clone(),
             string_table: self.string_table.clone(),
             missile_flags: self.missile_flags.clone(),
             locales: self.locales.clone(),
             gamepad: self.gamepad.clone(),
+            capacity: self.capacity,
+            physics_presets: self.physics_presets.clone(),
+            hud_layout: self.hud_layout.clone(),
         }
     }
 }
@@ -427,6 +683,7 @@ impl EngineConstants {
                     resist: 0x19,
                     jump: 0x280,
                 },
+                jetpack: JetpackConsts { accel: 0x40, max_speed: 0x400, resist: 0x20 },
                 frames_left: [
                     Rect { left: 0, top: 0, right: 16, bottom: 16 },
                     Rect { left: 16, top: 0, right: 32, bottom: 16 },
@@ -1371,6 +1628,8 @@ impl EngineConstants {
                     [1, 1, 1],
                     [40, 60, 200],
                 ],
+                custom_weapons: Vec::new(),
+                custom_bullets: Vec::new(),
             },
             tex_sizes: case_insensitive_hashmap! {
                 "ArmsImage" => (256, 16),
@@ -1564,6 +1823,9 @@ impl EngineConstants {
                 text_speed_normal: 4,
                 text_speed_fast: 1,
                 fade_ticks: 15,
+                pit_death_events: vec![40, 42],
+                extensions: TextScriptExtensions::default(),
+                translation: TranslationCatalog::default(),
             },
             title: TitleConsts {
                 intro_text: "Studio Pixel presents".to_owned(),
@@ -1675,6 +1937,7 @@ impl EngineConstants {
                 "endpic/".to_owned(),          // NXEngine
             ],
             player_skin_paths: vec!["MyChar".to_owned()],
+            player_skin_previews: HashMap::new(),
             animated_face_table: vec![AnimatedFace { face_id: 0, anim_id: 0, anim_frames: vec![(0, 0)] }],
             string_table: HashMap::new(),
             missile_flags: vec![200, 201, 202, 218, 550, 766, 880, 920, 1551],
@@ -1705,6 +1968,9 @@ impl EngineConstants {
                     (Axis::TriggerRight, GamepadConsts::rects(Rect::new(32, 80, 64, 96))),
                 ]),
             },
+            capacity: CapacityConsts::default(),
+            physics_presets: Vec::new(),
+            hud_layout: HudLayout::default(),
         }
     }
 
@@ -1930,6 +2196,165 @@ impl EngineConstants {
         Ok(())
     }
 
+    pub fn load_capacities(&mut self, ctx: &mut Context) -> GameResult {
+        self.capacity = CapacityConsts::default();
+
+        if let Ok(file) = filesystem::open_find(ctx, &self.base_paths, "capacities.json") {
+            match serde_json::from_reader::<_, CapacityConsts>(file) {
+                Ok(capacity) => self.capacity = capacity,
+                Err(err) => log::warn!("Failed to deserialize capacities: {}", err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds skin packs from `skins/<name>/`, each one containing a `skin` spritesheet and its metadata.
+    pub fn load_skin_packs(&mut self, ctx: &mut Context) -> GameResult {
+        self.player_skin_paths.retain(|path| !path.starts_with("skins/"));
+        self.player_skin_previews.clear();
+
+        let mut names: Vec<String> = filesystem::read_dir_find(ctx, &self.base_paths, "skins/")?
+            .filter_map(|path| path.file_name().map(|name| name.to_string_lossy().to_string()))
+            .collect();
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            let texture_name = format!("skins/{}/skin", name);
+
+            if let Some(meta) = load_skin_metadata(&texture_name, &self.base_paths, ctx) {
+                log::info!("Loaded skin pack {} ({})", name, meta.base.name);
+
+                let skin = create_skin(texture_name.clone(), Some(meta), 0);
+                let preview = skin.animation_frame_for(PlayerAnimationState::Idle, Direction::Left, 0);
+
+                self.player_skin_previews.insert(texture_name.clone(), preview);
+                self.player_skin_paths.push(texture_name);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn load_script_extensions(&mut self, ctx: &mut Context) -> GameResult {
+        self.textscript.extensions = TextScriptExtensions::default();
+
+        if let Ok(file) = filesystem::open_find(ctx, &self.base_paths, "textscript.json") {
+            match serde_json::from_reader::<_, TextScriptExtensions>(file) {
+                Ok(mut extensions) => {
+                    extensions.opcodes.retain(|opcode| {
+                        let valid = opcode.name.len() == 3
+                            && opcode.name.is_ascii()
+                            && TSCOpCode::from_str(&opcode.name).is_err();
+                        if !valid {
+                            log::warn!("Invalid or already existing custom opcode name: {}", opcode.name);
+                        }
+
+                        valid
+                    });
+
+                    self.textscript.extensions = extensions;
+                }
+                Err(err) => log::warn!("Failed to deserialize text script extensions: {}", err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the text script translation, the user's catalog of current game or mod (`user_filename` without
+    /// the extension) takes priority over the game data so catalogs written there by `/extract_text` can be edited
+    /// and applied with `/reload_translation`.
+    pub fn load_script_translation(&mut self, ctx: &mut Context, user_filename: &str) -> GameResult {
+        self.textscript.translation = TranslationCatalog::default();
+
+        let po_path = format!("{}.po", user_filename);
+        let json_path = format!("{}.json", user_filename);
+
+        let file = if let Ok(file) = filesystem::user_open(ctx, &po_path) {
+            Some((file, true))
+        } else if let Ok(file) = filesystem::user_open(ctx, &json_path) {
+            Some((file, false))
+        } else if let Ok(file) = filesystem::open_find(ctx, &self.base_paths, "tsc_translation.po") {
+            Some((file, true))
+        } else if let Ok(file) = filesystem::open_find(ctx, &self.base_paths, "tsc_translation.json") {
+            Some((file, false))
+        } else {
+            None
+        };
+
+        match file {
+            Some((file, true)) => match TranslationCatalog::read_po(BufReader::new(file)) {
+                Ok(translation) => self.textscript.translation = translation,
+                Err(err) => log::warn!("Failed to parse text script translation: {}", err),
+            },
+            Some((file, false)) => match serde_json::from_reader::<_, TranslationCatalog>(file) {
+                Ok(translation) => self.textscript.translation = translation,
+                Err(err) => log::warn!("Failed to deserialize text script translation: {}", err),
+            },
+            None => (),
+        }
+        Ok(())
+    }
+
+    pub fn load_hud_layout(&mut self, ctx: &mut Context) -> GameResult {
+        self.hud_layout = HudLayout::default();
+
+        // user's own layout takes priority over the one provided by the game or mod
+        let file = filesystem::user_open(ctx, "/hud_layout.json")
+            .or_else(|_| filesystem::open_find(ctx, &self.base_paths, "hud_layout.json"));
+
+        if let Ok(file) = file {
+            match serde_json::from_reader::<_, HudLayout>(file) {
+                Ok(layout) => self.hud_layout = layout,
+                Err(err) => log::warn!("Failed to deserialize HUD layout: {}", err),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn load_physics_presets(&mut self, ctx: &mut Context) -> GameResult {
+        self.physics_presets.clear();
+
+        if let Ok(file) = filesystem::open_find(ctx, &self.base_paths, "physics_presets.json") {
+            match serde_json::from_reader::<_, Vec<PhysicsPreset>>(file) {
+                Ok(presets) => self.physics_presets = presets,
+                Err(err) => log::warn!("Failed to deserialize physics presets: {}", err),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn load_weapon_definitions(&mut self, ctx: &mut Context) -> GameResult {
+        self.weapon.custom_weapons.clear();
+        self.weapon.custom_bullets.clear();
+
+        if let Ok(file) = filesystem::open_find(ctx, &self.base_paths, "weapons.json") {
+            match serde_json::from_reader::<_, WeaponDefinitions>(file) {
+                Ok(definitions) => {
+                    for weapon in definitions.weapons {
+                        if WeaponType::from_vanilla_id(weapon.id).is_some() {
+                            log::warn!("Custom weapon {} conflicts with a vanilla weapon, skipping.", weapon.id);
+                            continue;
+                        }
+
+                        self.weapon.custom_weapons.push(weapon);
+                    }
+
+                    for bullet in definitions.bullets {
+                        if (bullet.id as usize) < self.weapon.bullet_table.len() {
+                            log::warn!("Custom bullet {} conflicts with the bullet table, skipping.", bullet.id);
+                            continue;
+                        }
+
+                        self.weapon.custom_bullets.push(bullet);
+                    }
+                }
+                Err(err) => log::warn!("Failed to deserialize weapon definitions: {}", err),
+            }
+        }
+        Ok(())
+    }
+
     /// Loads bullet.tbl and arms_level.tbl from CS+ files,
     /// even though they match vanilla 1:1, we should load them for completeness
     /// or if any crazy person uses it for a CS+ mod...
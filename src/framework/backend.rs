@@ -4,8 +4,8 @@ use imgui::DrawData;
 
 use crate::common::{Color, Rect};
 use crate::framework::context::Context;
-use crate::framework::error::GameResult;
-use crate::framework::graphics::{BlendMode, VSyncMode};
+use crate::framework::error::{GameError, GameResult};
+use crate::framework::graphics::{BlendMode, PostProcessConfig, VSyncMode};
 use crate::game::Game;
 
 #[repr(C)]
@@ -53,6 +53,15 @@ pub trait BackendRenderer {
         Ok(())
     }
 
+    fn supports_post_process(&self) -> bool {
+        false
+    }
+
+    /// Sets up the post-processing chain applied in `present`, ignored by renderers which don't support it.
+    fn set_post_process(&mut self, _config: PostProcessConfig) -> GameResult {
+        Ok(())
+    }
+
     fn create_texture_mutable(&mut self, width: u16, height: u16) -> GameResult<Box<dyn BackendTexture>>;
 
     fn create_texture(&mut self, width: u16, height: u16, data: &[u8]) -> GameResult<Box<dyn BackendTexture>>;
@@ -86,6 +95,11 @@ pub trait BackendRenderer {
         shader: BackendShader,
     ) -> GameResult;
 
+    /// Reads back the final canvas as RGBA8 pixels, with rows ordered from top to bottom.
+    fn read_framebuffer(&mut self) -> GameResult<(u16, u16, Vec<u8>)> {
+        Err(GameError::RenderError("Framebuffer readback is not supported by this renderer.".to_string()))
+    }
+
     fn as_any(&self) -> &dyn Any;
 }
 
@@ -108,11 +122,15 @@ pub trait BackendGamepad {
 }
 
 #[allow(unreachable_code)]
-pub fn init_backend(headless: bool, size_hint: (u16, u16)) -> GameResult<Box<dyn Backend>> {
+pub fn init_backend(headless: bool, headless_render: bool, size_hint: (u16, u16)) -> GameResult<Box<dyn Backend>> {
     if headless {
         return crate::framework::backend_null::NullBackend::new();
     }
 
+    if headless_render {
+        return crate::framework::backend_null::NullBackend::new_software();
+    }
+
     #[cfg(all(feature = "backend-horizon"))]
     {
         return crate::framework::backend_horizon::HorizonBackend::new();
@@ -7,7 +7,7 @@ use std::rc::Rc;
 use std::sync::Arc;
 use std::vec::Vec;
 
-use glutin::event::{ElementState, Event, TouchPhase, VirtualKeyCode, WindowEvent};
+use glutin::event::{ElementState, Event, MouseScrollDelta, TouchPhase, VirtualKeyCode, WindowEvent};
 use glutin::event_loop::{ControlFlow, EventLoop};
 use glutin::window::WindowBuilder;
 use glutin::{Api, ContextBuilder, GlProfile, GlRequest, PossiblyCurrent, WindowedContext};
@@ -21,6 +21,7 @@ use crate::framework::error::GameResult;
 use crate::framework::filesystem;
 use crate::framework::gl;
 use crate::framework::keyboard::ScanCode;
+use crate::framework::mouse::MouseButton;
 use crate::framework::render_opengl::{GLContext, OpenGLRenderer};
 use crate::game::Game;
 use crate::game::GAME_SUSPENDED;
@@ -242,10 +243,11 @@ impl BackendEventLoop for GlutinEventLoop {
                 Event::WindowEvent { event: WindowEvent::Touch(touch), window_id }
                     if window_id == window.window().id() =>
                 {
+                    let loc_x = touch.location.x * ctx.screen_size.0 as f64 / ctx.real_screen_size.0 as f64;
+                    let loc_y = touch.location.y * ctx.screen_size.1 as f64 / ctx.real_screen_size.1 as f64;
+                    let (loc_x, loc_y) = state_ref.screen_to_canvas(ctx, (loc_x as f32, loc_y as f32));
+                    let (loc_x, loc_y) = (loc_x as f64, loc_y as f64);
                     let mut controls = &mut state_ref.touch_controls;
-                    let scale = state_ref.scale as f64;
-                    let loc_x = (touch.location.x * ctx.screen_size.0 as f64 / ctx.real_screen_size.0 as f64) / scale;
-                    let loc_y = (touch.location.y * ctx.screen_size.1 as f64 / ctx.real_screen_size.1 as f64) / scale;
 
                     match touch.phase {
                         TouchPhase::Started | TouchPhase::Moved => {
@@ -288,6 +290,29 @@ impl BackendEventLoop for GlutinEventLoop {
                         }
                     }
                 }
+                Event::WindowEvent { event: WindowEvent::CursorMoved { position, .. }, window_id }
+                    if window_id == window.window().id() =>
+                {
+                    let x = position.x as f32 * ctx.screen_size.0 / ctx.real_screen_size.0.max(1) as f32;
+                    let y = position.y as f32 * ctx.screen_size.1 / ctx.real_screen_size.1.max(1) as f32;
+                    ctx.mouse_context.set_position(x, y);
+                }
+                Event::WindowEvent { event: WindowEvent::MouseInput { state, button, .. }, window_id }
+                    if window_id == window.window().id() =>
+                {
+                    if let Some(drs_button) = conv_mouse_button(button) {
+                        ctx.mouse_context.set_button(drs_button, state == ElementState::Pressed);
+                    }
+                }
+                Event::WindowEvent { event: WindowEvent::MouseWheel { delta, .. }, window_id }
+                    if window_id == window.window().id() =>
+                {
+                    let lines = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => y.round() as i32,
+                        MouseScrollDelta::PixelDelta(position) => (position.y / 16.0).round() as i32,
+                    };
+                    ctx.mouse_context.scroll(lines);
+                }
                 Event::RedrawRequested(id) if id == window.window().id() => {
                     {
                         let mutex = GAME_SUSPENDED.lock().unwrap();
@@ -413,6 +438,15 @@ impl BackendEventLoop for GlutinEventLoop {
     }
 }
 
+fn conv_mouse_button(button: glutin::event::MouseButton) -> Option<MouseButton> {
+    match button {
+        glutin::event::MouseButton::Left => Some(MouseButton::Left),
+        glutin::event::MouseButton::Right => Some(MouseButton::Right),
+        glutin::event::MouseButton::Middle => Some(MouseButton::Middle),
+        glutin::event::MouseButton::Other(_) => None,
+    }
+}
+
 fn conv_keycode(code: VirtualKeyCode) -> Option<ScanCode> {
     match code {
         VirtualKeyCode::Key1 => Some(ScanCode::Key1),
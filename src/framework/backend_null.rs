@@ -11,19 +11,27 @@ use crate::framework::backend::{
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::graphics::BlendMode;
+use crate::framework::render_software::SoftwareRenderer;
 use crate::game::Game;
 
-pub struct NullBackend;
+pub struct NullBackend {
+    software_render: bool,
+}
 
 impl NullBackend {
     pub fn new() -> GameResult<Box<dyn Backend>> {
-        Ok(Box::new(NullBackend))
+        Ok(Box::new(NullBackend { software_render: false }))
+    }
+
+    /// Windowless backend that still draws every frame, using the software renderer.
+    pub fn new_software() -> GameResult<Box<dyn Backend>> {
+        Ok(Box::new(NullBackend { software_render: true }))
     }
 }
 
 impl Backend for NullBackend {
     fn create_event_loop(&self, _ctx: &Context) -> GameResult<Box<dyn BackendEventLoop>> {
-        Ok(Box::new(NullEventLoop))
+        Ok(Box::new(NullEventLoop { software_render: self.software_render }))
     }
     
     fn as_any(&self) -> &dyn Any {
@@ -31,7 +39,9 @@ impl Backend for NullBackend {
     }
 }
 
-pub struct NullEventLoop;
+pub struct NullEventLoop {
+    software_render: bool,
+}
 
 impl BackendEventLoop for NullEventLoop {
     fn run(&mut self, game: &mut Game, ctx: &mut Context) {
@@ -62,6 +72,10 @@ impl BackendEventLoop for NullEventLoop {
     }
 
     fn new_renderer(&self, _ctx: *mut Context) -> GameResult<Box<dyn BackendRenderer>> {
+        if self.software_render {
+            return Ok(Box::new(SoftwareRenderer::new(640, 480)));
+        }
+
         let mut imgui = imgui::Context::create();
         imgui.io_mut().display_size = [640.0, 480.0];
         imgui.fonts().build_alpha8_texture();
@@ -36,6 +36,7 @@ use crate::framework::filesystem;
 use crate::framework::gamepad::{Axis, Button, GamepadType};
 use crate::framework::graphics::BlendMode;
 use crate::framework::keyboard::ScanCode;
+use crate::framework::mouse::MouseButton;
 #[cfg(feature = "render-opengl")]
 use crate::framework::render_opengl::{GLContext, OpenGLRenderer};
 use crate::framework::ui::init_imgui;
@@ -332,6 +333,22 @@ impl BackendEventLoop for SDL2EventLoop {
                             ctx.keyboard_context.set_key(drs_scan, false);
                         }
                     }
+                    Event::MouseMotion { x, y, .. } => {
+                        ctx.mouse_context.set_position(x as f32, y as f32);
+                    }
+                    Event::MouseButtonDown { mouse_btn, .. } => {
+                        if let Some(drs_button) = conv_mouse_button(mouse_btn) {
+                            ctx.mouse_context.set_button(drs_button, true);
+                        }
+                    }
+                    Event::MouseButtonUp { mouse_btn, .. } => {
+                        if let Some(drs_button) = conv_mouse_button(mouse_btn) {
+                            ctx.mouse_context.set_button(drs_button, false);
+                        }
+                    }
+                    Event::MouseWheel { y, .. } => {
+                        ctx.mouse_context.scroll(y);
+                    }
                     Event::ControllerDeviceAdded { which, .. } => {
                         let game_controller = &self.refs.borrow().game_controller;
 
@@ -1110,6 +1127,17 @@ impl Drop for SDL2Texture {
     }
 }
 
+fn conv_mouse_button(button: sdl2::mouse::MouseButton) -> Option<MouseButton> {
+    match button {
+        sdl2::mouse::MouseButton::Left => Some(MouseButton::Left),
+        sdl2::mouse::MouseButton::Right => Some(MouseButton::Right),
+        sdl2::mouse::MouseButton::Middle => Some(MouseButton::Middle),
+        sdl2::mouse::MouseButton::X1 => Some(MouseButton::X1),
+        sdl2::mouse::MouseButton::X2 => Some(MouseButton::X2),
+        sdl2::mouse::MouseButton::Unknown => None,
+    }
+}
+
 fn conv_scancode(code: keyboard::Scancode) -> Option<ScanCode> {
     match code {
         Scancode::A => Some(ScanCode::A),
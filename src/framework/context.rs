@@ -4,15 +4,19 @@ use crate::framework::filesystem::Filesystem;
 use crate::framework::gamepad::GamepadContext;
 use crate::framework::graphics::VSyncMode;
 use crate::framework::keyboard::KeyboardContext;
+use crate::framework::mouse::MouseContext;
 use crate::game::Game;
 
 pub struct Context {
     pub headless: bool,
+    /// Runs without a window like `headless`, but the game is still drawn, using the software renderer.
+    pub headless_render: bool,
     pub size_hint: (u16, u16),
     pub(crate) filesystem: Filesystem,
     pub(crate) renderer: Option<Box<dyn BackendRenderer>>,
     pub(crate) gamepad_context: GamepadContext,
     pub(crate) keyboard_context: KeyboardContext,
+    pub(crate) mouse_context: MouseContext,
     pub(crate) real_screen_size: (u32, u32),
     pub(crate) screen_size: (f32, f32),
     pub(crate) screen_insets: (f32, f32, f32, f32),
@@ -23,11 +27,13 @@ impl Context {
     pub fn new() -> Context {
         Context {
             headless: false,
+            headless_render: false,
             size_hint: (640, 480),
             filesystem: Filesystem::new(),
             renderer: None,
             gamepad_context: GamepadContext::new(),
             keyboard_context: KeyboardContext::new(),
+            mouse_context: MouseContext::new(),
             real_screen_size: (320, 240),
             screen_size: (320.0, 240.0),
             screen_insets: (0.0, 0.0, 0.0, 0.0),
@@ -36,7 +42,7 @@ impl Context {
     }
 
     pub fn run(&mut self, game: &mut Game) -> GameResult {
-        let backend = init_backend(self.headless, self.size_hint)?;
+        let backend = init_backend(self.headless, self.headless_render, self.size_hint)?;
         let mut event_loop = backend.create_event_loop(self)?;
         self.renderer = Some(event_loop.new_renderer(self as *mut Context)?);
 
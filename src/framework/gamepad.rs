@@ -319,6 +319,21 @@ impl GamepadContext {
         false
     }
 
+    pub(crate) fn axis_value(&self, gamepad_index: u32, axis: Axis) -> f64 {
+        if let Some(gamepad) = self.get_gamepad_by_index(gamepad_index as usize) {
+            return match axis {
+                Axis::LeftX => gamepad.left_x,
+                Axis::LeftY => gamepad.left_y,
+                Axis::RightX => gamepad.right_x,
+                Axis::RightY => gamepad.right_y,
+                Axis::TriggerLeft => gamepad.trigger_left,
+                Axis::TriggerRight => gamepad.trigger_right,
+            };
+        }
+
+        0.0
+    }
+
     pub(crate) fn update_axes(&mut self, gamepad_id: u32) {
         if let Some(gamepad) = self.get_gamepad_mut(gamepad_id) {
             let mut axes = [
@@ -424,6 +439,11 @@ pub fn is_axis_active(ctx: &Context, gamepad_index: u32, axis: Axis, direction:
     ctx.gamepad_context.is_axis_active(gamepad_index, axis, direction)
 }
 
+/// Returns the current value of an axis, with the hardware deadzone already applied.
+pub fn axis_value(ctx: &Context, gamepad_index: u32, axis: Axis) -> f64 {
+    ctx.gamepad_context.axis_value(gamepad_index, axis)
+}
+
 pub fn get_gamepads(ctx: &Context) -> &Vec<GamepadData> {
     ctx.gamepad_context.get_gamepads()
 }
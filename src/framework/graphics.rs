@@ -37,6 +37,60 @@ pub enum VSyncMode {
     VRRTickSync3x,
 }
 
+/// Controls how the game canvas gets scaled to the window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum UpscaleMode {
+    /// The game is drawn at window resolution, scaled by the largest integer that fits.
+    Native,
+    /// The game is drawn at its base resolution and scaled up by the largest integer that fits, letterboxed.
+    Integer,
+    /// The game is drawn at its base resolution and stretched to fit the window with sharp bilinear filtering.
+    SharpBilinear,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum ColorblindFilter {
+    Off,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+/// A post-processing pass supplied by the user, the source is a fragment shader body without the `#version` line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserShaderPass {
+    pub name: String,
+    pub source: String,
+}
+
+/// Describes the chain of post-processing passes applied to the final canvas when presenting it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostProcessConfig {
+    pub upscale_mode: UpscaleMode,
+    /// Size of the canvas the game is drawn into, `None` means the window size.
+    pub render_size: Option<(u32, u32)>,
+    /// Size of a single game pixel in canvas pixels.
+    pub pixel_scale: f32,
+    pub colorblind_filter: ColorblindFilter,
+    pub user_passes: Vec<UserShaderPass>,
+    pub scanlines: bool,
+    pub crt: bool,
+}
+
+impl Default for PostProcessConfig {
+    fn default() -> Self {
+        PostProcessConfig {
+            upscale_mode: UpscaleMode::Native,
+            render_size: None,
+            pixel_scale: 1.0,
+            colorblind_filter: ColorblindFilter::Off,
+            user_passes: Vec::new(),
+            scanlines: false,
+            crt: false,
+        }
+    }
+}
+
 pub fn clear(ctx: &mut Context, color: Color) {
     if let Some(renderer) = &mut ctx.renderer {
         renderer.clear(color)
@@ -60,6 +114,22 @@ pub fn set_vsync_mode(ctx: &mut Context, mode: VSyncMode) -> GameResult {
     Ok(())
 }
 
+pub fn supports_post_process(ctx: &Context) -> bool {
+    if let Some(renderer) = ctx.renderer.as_ref() {
+        return renderer.supports_post_process();
+    }
+
+    false
+}
+
+pub fn set_post_process(ctx: &mut Context, config: PostProcessConfig) -> GameResult {
+    if let Some(renderer) = &mut ctx.renderer {
+        return renderer.set_post_process(config);
+    }
+
+    Err(GameError::RenderError("Rendering backend hasn't been initialized yet.".to_string()))
+}
+
 #[allow(unused)]
 pub fn renderer_initialized(ctx: &mut Context) -> bool {
     ctx.renderer.is_some()
@@ -196,3 +266,11 @@ pub fn draw_triangle_list(
 
     Err(GameError::RenderError("Rendering backend hasn't been initialized yet.".to_string()))
 }
+
+pub fn read_framebuffer(ctx: &mut Context) -> GameResult<(u16, u16, Vec<u8>)> {
+    if let Some(renderer) = &mut ctx.renderer {
+        return renderer.read_framebuffer();
+    }
+
+    Err(GameError::RenderError("Rendering backend hasn't been initialized yet.".to_string()))
+}
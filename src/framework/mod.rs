@@ -16,8 +16,10 @@ pub mod gamepad;
 mod gl;
 pub mod graphics;
 pub mod keyboard;
+pub mod mouse;
 #[cfg(feature = "render-opengl")]
 pub mod render_opengl;
+pub mod render_software;
 pub mod ui;
 pub mod util;
 pub mod vfs;
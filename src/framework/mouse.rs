@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::framework::context::Context;
+
+#[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    X1,
+    X2,
+}
+
+pub struct MouseContext {
+    /// Cursor position in the same units as the screen size.
+    position: (f32, f32),
+    pressed_buttons_set: HashSet<MouseButton>,
+    /// Sum of all wheel movements, positive values mean scrolling up.
+    wheel_position: i32,
+}
+
+impl MouseContext {
+    pub(crate) fn new() -> Self {
+        Self { position: (0.0, 0.0), pressed_buttons_set: HashSet::with_capacity(5), wheel_position: 0 }
+    }
+
+    pub(crate) fn set_position(&mut self, x: f32, y: f32) {
+        self.position = (x, y);
+    }
+
+    pub(crate) fn set_button(&mut self, button: MouseButton, pressed: bool) {
+        if pressed {
+            let _ = self.pressed_buttons_set.insert(button);
+        } else {
+            let _ = self.pressed_buttons_set.remove(&button);
+        }
+    }
+
+    pub(crate) fn scroll(&mut self, delta: i32) {
+        self.wheel_position = self.wheel_position.wrapping_add(delta);
+    }
+
+    pub(crate) fn position(&self) -> (f32, f32) {
+        self.position
+    }
+
+    pub(crate) fn is_button_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_buttons_set.contains(&button)
+    }
+
+    pub(crate) fn wheel_position(&self) -> i32 {
+        self.wheel_position
+    }
+}
+
+impl Default for MouseContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the cursor position in the same units as `graphics::screen_size`.
+pub fn position(ctx: &Context) -> (f32, f32) {
+    ctx.mouse_context.position()
+}
+
+/// Checks if a mouse button is currently pressed down.
+pub fn is_button_pressed(ctx: &Context, button: MouseButton) -> bool {
+    ctx.mouse_context.is_button_pressed(button)
+}
+
+/// Returns the sum of all mouse wheel movements, compare it with a previous value to get the scrolled distance.
+pub fn wheel_position(ctx: &Context) -> i32 {
+    ctx.mouse_context.wheel_position()
+}
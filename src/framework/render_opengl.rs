@@ -17,7 +17,7 @@ use crate::framework::error::GameError::RenderError;
 use crate::framework::error::GameResult;
 use crate::framework::gl;
 use crate::framework::gl::types::*;
-use crate::framework::graphics::{BlendMode, VSyncMode};
+use crate::framework::graphics::{BlendMode, ColorblindFilter, PostProcessConfig, UpscaleMode, VSyncMode};
 use crate::framework::util::{field_offset, return_param};
 use crate::game::GAME_SUSPENDED;
 
@@ -310,6 +310,13 @@ const VERTEX_SHADER_BASIC_GLES: &str = include_str!("shaders/opengles/vertex_bas
 const FRAGMENT_SHADER_TEXTURED_GLES: &str = include_str!("shaders/opengles/fragment_textured_100.glsl");
 const FRAGMENT_SHADER_COLOR_GLES: &str = include_str!("shaders/opengles/fragment_color_100.glsl");
 
+const POST_SHADER_COMMON: &str = include_str!("shaders/postprocess/common.glsl");
+const POST_SHADER_NEAREST: &str = include_str!("shaders/postprocess/nearest.glsl");
+const POST_SHADER_SHARP_BILINEAR: &str = include_str!("shaders/postprocess/sharp_bilinear.glsl");
+const POST_SHADER_COLORBLIND: &str = include_str!("shaders/postprocess/colorblind.glsl");
+const POST_SHADER_SCANLINES: &str = include_str!("shaders/postprocess/scanlines.glsl");
+const POST_SHADER_CRT: &str = include_str!("shaders/postprocess/crt.glsl");
+
 #[derive(Copy, Clone)]
 struct RenderShader {
     program_id: GLuint,
@@ -318,6 +325,9 @@ struct RenderShader {
     scale: GLint,
     time: GLint,
     frame_offset: GLint,
+    texture_size: GLint,
+    output_size: GLint,
+    source_size: GLint,
     position: GLuint,
     uv: GLuint,
     color: GLuint,
@@ -332,6 +342,9 @@ impl Default for RenderShader {
             scale: 0,
             time: 0,
             frame_offset: 0,
+            texture_size: 0,
+            output_size: 0,
+            source_size: 0,
             position: 0,
             uv: 0,
             color: 0,
@@ -395,6 +408,9 @@ impl RenderShader {
             shader.scale = gl.gl.GetUniformLocation(shader.program_id, b"Scale\0".as_ptr() as _) as _;
             shader.time = gl.gl.GetUniformLocation(shader.program_id, b"Time\0".as_ptr() as _) as _;
             shader.frame_offset = gl.gl.GetUniformLocation(shader.program_id, b"FrameOffset\0".as_ptr() as _) as _;
+            shader.texture_size = gl.gl.GetUniformLocation(shader.program_id, b"TextureSize\0".as_ptr() as _) as _;
+            shader.output_size = gl.gl.GetUniformLocation(shader.program_id, b"OutputSize\0".as_ptr() as _) as _;
+            shader.source_size = gl.gl.GetUniformLocation(shader.program_id, b"SourceSize\0".as_ptr() as _) as _;
             shader.position = gl.gl.GetAttribLocation(shader.program_id, b"Position\0".as_ptr() as _) as _;
             shader.uv = gl.gl.GetAttribLocation(shader.program_id, b"UV\0".as_ptr() as _) as _;
             shader.color = gl.gl.GetAttribLocation(shader.program_id, b"Color\0".as_ptr() as _) as _;
@@ -591,6 +607,8 @@ pub struct OpenGLRenderer {
     context_active: Arc<RefCell<bool>>,
     def_matrix: [[f32; 4]; 4],
     curr_matrix: [[f32; 4]; 4],
+    post_process: PostProcessChain,
+    window_size: (u32, u32),
 }
 
 impl OpenGLRenderer {
@@ -602,6 +620,8 @@ impl OpenGLRenderer {
             context_active: Arc::new(RefCell::new(true)),
             def_matrix: [[0.0; 4]; 4],
             curr_matrix: [[0.0; 4]; 4],
+            post_process: PostProcessChain::new(),
+            window_size: (320, 240),
         }
     }
 
@@ -651,28 +671,23 @@ impl BackendRenderer for OpenGLRenderer {
                 gl.gl.ClearColor(0.0, 0.0, 0.0, 1.0);
                 gl.gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
-                let matrix =
-                    [[2.0f32, 0.0, 0.0, 0.0], [0.0, -2.0, 0.0, 0.0], [0.0, 0.0, -1.0, 0.0], [-1.0, 1.0, 0.0, 1.0]];
-
-                self.render_data.tex_shader.bind_attrib_pointer(gl, self.render_data.vbo);
-                gl.gl.UniformMatrix4fv(self.render_data.tex_shader.proj_mtx, 1, gl::FALSE, matrix.as_ptr() as _);
-
-                let color = (255, 255, 255, 255);
-                let vertices = [
-                    VertexData { position: (0.0, 1.0), uv: (0.0, 0.0), color },
-                    VertexData { position: (0.0, 0.0), uv: (0.0, 1.0), color },
-                    VertexData { position: (1.0, 0.0), uv: (1.0, 1.0), color },
-                    VertexData { position: (0.0, 1.0), uv: (0.0, 0.0), color },
-                    VertexData { position: (1.0, 0.0), uv: (1.0, 1.0), color },
-                    VertexData { position: (1.0, 1.0), uv: (1.0, 0.0), color },
-                ];
+                if self.post_process.dirty {
+                    self.post_process.rebuild(gl, self.refs.gles2_mode);
+                }
 
-                self.draw_arrays_tex_id(
-                    gl::TRIANGLES,
-                    &vertices,
-                    self.render_data.surf_texture,
-                    BackendShader::Texture,
-                )?;
+                if self.post_process.passes.is_empty() {
+                    self.draw_surface()?;
+                } else {
+                    self.post_process.frame = self.post_process.frame.wrapping_add(1);
+                    self.post_process.run(
+                        gl,
+                        self.render_data.vbo,
+                        self.render_data.surf_texture,
+                        self.render_data.last_size,
+                        self.window_size,
+                        0,
+                    )?;
+                }
 
                 gl.gl.Finish();
             }
@@ -714,6 +729,12 @@ impl BackendRenderer for OpenGLRenderer {
     }
 
     fn prepare_draw(&mut self, width: f32, height: f32) -> GameResult {
+        self.window_size = (width as u32, height as u32);
+        let (width, height) = match self.post_process.config.render_size {
+            Some((width, height)) => (width as f32, height as f32),
+            None => (width, height),
+        };
+
         if let Some((_, gl)) = self.get_context() {
             unsafe {
                 let (width_u, height_u) = (width as u32, height as u32);
@@ -788,6 +809,19 @@ impl BackendRenderer for OpenGLRenderer {
         }
     }
 
+    fn supports_post_process(&self) -> bool {
+        true
+    }
+
+    fn set_post_process(&mut self, config: PostProcessConfig) -> GameResult {
+        if self.post_process.config != config {
+            self.post_process.config = config;
+            self.post_process.dirty = true;
+        }
+
+        Ok(())
+    }
+
     fn create_texture_mutable(&mut self, width: u16, height: u16) -> GameResult<Box<dyn BackendTexture>> {
         if let Some((_, gl)) = self.get_context() {
             unsafe {
@@ -1208,12 +1242,79 @@ impl BackendRenderer for OpenGLRenderer {
         self.draw_arrays(gl::TRIANGLES, vertices, texture, shader)
     }
 
+    fn read_framebuffer(&mut self) -> GameResult<(u16, u16, Vec<u8>)> {
+        if let Some((_, gl)) = self.get_context() {
+            unsafe {
+                if self.post_process.dirty {
+                    self.post_process.rebuild(gl, self.refs.gles2_mode);
+                }
+
+                // always capture at the window size, either through the post-processing chain or by stretching the
+                // canvas the same way it's presented, without the debug UI drawn on top of the canvas afterwards
+                let size = self.window_size;
+                let framebuffer = self.post_process.ensure_capture_target(gl, size);
+
+                gl.gl.BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+                gl.gl.Viewport(0, 0, size.0 as _, size.1 as _);
+                gl.gl.ClearColor(0.0, 0.0, 0.0, 1.0);
+                gl.gl.Clear(gl::COLOR_BUFFER_BIT);
+
+                if self.post_process.passes.is_empty() {
+                    self.draw_surface()?;
+                } else {
+                    self.post_process.run(
+                        gl,
+                        self.render_data.vbo,
+                        self.render_data.surf_texture,
+                        self.render_data.last_size,
+                        size,
+                        framebuffer,
+                    )?;
+                }
+
+                let pixels = read_pixels(gl, framebuffer, size);
+
+                let (width, height) = self.render_data.last_size;
+                gl.gl.BindFramebuffer(gl::FRAMEBUFFER, self.render_data.surf_framebuffer);
+                gl.gl.Viewport(0, 0, width as _, height as _);
+
+                Ok((size.0 as u16, size.1 as u16, pixels))
+            }
+        } else {
+            Err(RenderError("No OpenGL context available!".to_string()))
+        }
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
 impl OpenGLRenderer {
+    /// Draws the canvas stretched over the whole viewport of currently bound framebuffer.
+    unsafe fn draw_surface(&mut self) -> GameResult {
+        if let Some((_, gl)) = self.get_context() {
+            let matrix = [[2.0f32, 0.0, 0.0, 0.0], [0.0, -2.0, 0.0, 0.0], [0.0, 0.0, -1.0, 0.0], [-1.0, 1.0, 0.0, 1.0]];
+
+            self.render_data.tex_shader.bind_attrib_pointer(gl, self.render_data.vbo);
+            gl.gl.UniformMatrix4fv(self.render_data.tex_shader.proj_mtx, 1, gl::FALSE, matrix.as_ptr() as _);
+
+            let color = (255, 255, 255, 255);
+            let vertices = [
+                VertexData { position: (0.0, 1.0), uv: (0.0, 0.0), color },
+                VertexData { position: (0.0, 0.0), uv: (0.0, 1.0), color },
+                VertexData { position: (1.0, 0.0), uv: (1.0, 1.0), color },
+                VertexData { position: (0.0, 1.0), uv: (0.0, 0.0), color },
+                VertexData { position: (1.0, 0.0), uv: (1.0, 1.0), color },
+                VertexData { position: (1.0, 1.0), uv: (1.0, 0.0), color },
+            ];
+
+            self.draw_arrays_tex_id(gl::TRIANGLES, &vertices, self.render_data.surf_texture, BackendShader::Texture)?;
+        }
+
+        Ok(())
+    }
+
     fn draw_arrays(
         &mut self,
         vert_type: GLenum,
@@ -1283,6 +1384,302 @@ impl OpenGLRenderer {
     }
 }
 
+/// Post-processing passes applied to the canvas when presenting it, the first pass always upscales the canvas
+/// to the output size and the last one draws into the window.
+struct PostProcessChain {
+    config: PostProcessConfig,
+    dirty: bool,
+    passes: Vec<RenderShader>,
+    framebuffers: [GLuint; 2],
+    textures: [GLuint; 2],
+    target_size: (u32, u32),
+    /// Offscreen copy of the final output, used by frame capture.
+    capture_framebuffer: GLuint,
+    capture_texture: GLuint,
+    capture_size: (u32, u32),
+    frame: u32,
+}
+
+impl PostProcessChain {
+    fn new() -> PostProcessChain {
+        PostProcessChain {
+            config: PostProcessConfig::default(),
+            dirty: true,
+            passes: Vec::new(),
+            framebuffers: [0; 2],
+            textures: [0; 2],
+            target_size: (0, 0),
+            capture_framebuffer: 0,
+            capture_texture: 0,
+            capture_size: (0, 0),
+            frame: 0,
+        }
+    }
+
+    fn compile_pass(gl: &Gl, gles2_mode: bool, name: &str, defines: &str, body: &str) -> Option<RenderShader> {
+        let (vertex_shader, header) = if gles2_mode {
+            (VERTEX_SHADER_BASIC_GLES, "#version 100\nprecision mediump float;\n")
+        } else {
+            (VERTEX_SHADER_BASIC, "#version 110\n")
+        };
+        let fragment_shader = format!("{}{}{}\n{}\n", header, defines, POST_SHADER_COMMON, body);
+
+        match RenderShader::compile(gl, vertex_shader, &fragment_shader) {
+            Ok(shader) => Some(shader),
+            Err(e) => {
+                log::error!("Failed to compile post-processing pass {}: {}", name, e);
+                None
+            }
+        }
+    }
+
+    fn rebuild(&mut self, gl: &Gl, gles2_mode: bool) {
+        self.dirty = false;
+
+        for pass in self.passes.drain(..) {
+            unsafe {
+                gl.gl.DeleteProgram(pass.program_id);
+            }
+        }
+
+        let config = &self.config;
+        let has_effects = config.colorblind_filter != ColorblindFilter::Off
+            || !config.user_passes.is_empty()
+            || config.scanlines
+            || config.crt;
+
+        // plain blit is done by the regular present path
+        if config.upscale_mode == UpscaleMode::Native && !has_effects {
+            return;
+        }
+
+        let (name, upscale_shader) = match config.upscale_mode {
+            UpscaleMode::SharpBilinear => ("sharp_bilinear", POST_SHADER_SHARP_BILINEAR),
+            UpscaleMode::Native | UpscaleMode::Integer => ("nearest", POST_SHADER_NEAREST),
+        };
+
+        let mut passes = Vec::new();
+        match PostProcessChain::compile_pass(gl, gles2_mode, name, "", upscale_shader) {
+            Some(shader) => passes.push(shader),
+            None => return,
+        }
+
+        let colorblind_filter = match config.colorblind_filter {
+            ColorblindFilter::Off => None,
+            ColorblindFilter::Protanopia => Some(1),
+            ColorblindFilter::Deuteranopia => Some(2),
+            ColorblindFilter::Tritanopia => Some(3),
+        };
+
+        if let Some(filter) = colorblind_filter {
+            let defines = format!("#define FILTER {}\n", filter);
+            passes.extend(PostProcessChain::compile_pass(gl, gles2_mode, "colorblind", &defines, POST_SHADER_COLORBLIND));
+        }
+
+        for pass in &config.user_passes {
+            passes.extend(PostProcessChain::compile_pass(gl, gles2_mode, &pass.name, "", &pass.source));
+        }
+
+        if config.scanlines {
+            passes.extend(PostProcessChain::compile_pass(gl, gles2_mode, "scanlines", "", POST_SHADER_SCANLINES));
+        }
+
+        if config.crt {
+            passes.extend(PostProcessChain::compile_pass(gl, gles2_mode, "crt", "", POST_SHADER_CRT));
+        }
+
+        self.passes = passes;
+    }
+
+    unsafe fn ensure_targets(&mut self, gl: &Gl, size: (u32, u32)) {
+        if self.target_size == size && self.textures[0] != 0 {
+            return;
+        }
+
+        self.target_size = size;
+
+        for i in 0..2 {
+            if self.textures[i] == 0 {
+                self.textures[i] = return_param(|x| gl.gl.GenTextures(1, x));
+                gl.gl.BindTexture(gl::TEXTURE_2D, self.textures[i]);
+                gl.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+                gl.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+                gl.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+                gl.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+            }
+
+            gl.gl.BindTexture(gl::TEXTURE_2D, self.textures[i]);
+            gl.gl.TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as _,
+                size.0 as _,
+                size.1 as _,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                null() as _,
+            );
+            gl.gl.BindTexture(gl::TEXTURE_2D, 0);
+
+            if self.framebuffers[i] == 0 {
+                self.framebuffers[i] = return_param(|x| gl.gl.GenFramebuffers(1, x));
+                gl.gl.BindFramebuffer(gl::FRAMEBUFFER, self.framebuffers[i]);
+                gl.gl.FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::TEXTURE_2D,
+                    self.textures[i],
+                    0,
+                );
+            }
+        }
+
+        gl.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+    }
+
+    unsafe fn ensure_capture_target(&mut self, gl: &Gl, size: (u32, u32)) -> GLuint {
+        if self.capture_size == size && self.capture_texture != 0 {
+            return self.capture_framebuffer;
+        }
+
+        self.capture_size = size;
+
+        if self.capture_texture == 0 {
+            self.capture_texture = return_param(|x| gl.gl.GenTextures(1, x));
+            gl.gl.BindTexture(gl::TEXTURE_2D, self.capture_texture);
+            gl.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+            gl.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+        }
+
+        gl.gl.BindTexture(gl::TEXTURE_2D, self.capture_texture);
+        gl.gl.TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA as _,
+            size.0 as _,
+            size.1 as _,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            null() as _,
+        );
+        gl.gl.BindTexture(gl::TEXTURE_2D, 0);
+
+        if self.capture_framebuffer == 0 {
+            self.capture_framebuffer = return_param(|x| gl.gl.GenFramebuffers(1, x));
+            gl.gl.BindFramebuffer(gl::FRAMEBUFFER, self.capture_framebuffer);
+            gl.gl.FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                self.capture_texture,
+                0,
+            );
+            gl.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        self.capture_framebuffer
+    }
+
+    /// Draws the source texture through all passes, the last one renders into given framebuffer.
+    unsafe fn run(
+        &mut self,
+        gl: &Gl,
+        vbo: GLuint,
+        source_texture: GLuint,
+        source_size: (u32, u32),
+        window_size: (u32, u32),
+        target_framebuffer: GLuint,
+    ) -> GameResult {
+        let (src_w, src_h) = (source_size.0.max(1) as f32, source_size.1.max(1) as f32);
+        let (win_w, win_h) = (window_size.0.max(1) as f32, window_size.1.max(1) as f32);
+
+        let (out_w, out_h) = match self.config.upscale_mode {
+            UpscaleMode::Native => (win_w, win_h),
+            UpscaleMode::Integer => {
+                let scale = (win_w / src_w).min(win_h / src_h).floor().max(1.0);
+                (src_w * scale, src_h * scale)
+            }
+            UpscaleMode::SharpBilinear => {
+                let scale = (win_w / src_w).min(win_h / src_h);
+                ((src_w * scale).round(), (src_h * scale).round())
+            }
+        };
+        let (out_x, out_y) = (((win_w - out_w) / 2.0).floor(), ((win_h - out_h) / 2.0).floor());
+        let game_size = (src_w / self.config.pixel_scale, src_h / self.config.pixel_scale);
+
+        if self.passes.len() > 1 {
+            self.ensure_targets(gl, (out_w as u32, out_h as u32));
+        }
+
+        let matrix = [[2.0f32, 0.0, 0.0, 0.0], [0.0, 2.0, 0.0, 0.0], [0.0, 0.0, -1.0, 0.0], [-1.0, -1.0, 0.0, 1.0]];
+        let color = (255, 255, 255, 255);
+        let vertices = [
+            VertexData { position: (0.0, 0.0), uv: (0.0, 0.0), color },
+            VertexData { position: (0.0, 1.0), uv: (0.0, 1.0), color },
+            VertexData { position: (1.0, 1.0), uv: (1.0, 1.0), color },
+            VertexData { position: (0.0, 0.0), uv: (0.0, 0.0), color },
+            VertexData { position: (1.0, 1.0), uv: (1.0, 1.0), color },
+            VertexData { position: (1.0, 0.0), uv: (1.0, 0.0), color },
+        ];
+
+        let count = self.passes.len();
+        for (i, pass) in self.passes.iter().enumerate() {
+            let (input, input_size) =
+                if i == 0 { (source_texture, (src_w, src_h)) } else { (self.textures[(i - 1) % 2], (out_w, out_h)) };
+
+            if i == count - 1 {
+                gl.gl.BindFramebuffer(gl::FRAMEBUFFER, target_framebuffer);
+                gl.gl.Viewport(out_x as _, out_y as _, out_w as _, out_h as _);
+            } else {
+                gl.gl.BindFramebuffer(gl::FRAMEBUFFER, self.framebuffers[i % 2]);
+                gl.gl.Viewport(0, 0, out_w as _, out_h as _);
+            }
+
+            pass.bind_attrib_pointer(gl, vbo)?;
+            gl.gl.UniformMatrix4fv(pass.proj_mtx, 1, gl::FALSE, matrix.as_ptr() as _);
+            gl.gl.Uniform1i(pass.texture, 0);
+            gl.gl.Uniform2f(pass.texture_size, input_size.0, input_size.1);
+            gl.gl.Uniform2f(pass.output_size, out_w, out_h);
+            gl.gl.Uniform2f(pass.source_size, game_size.0, game_size.1);
+            gl.gl.Uniform1f(pass.time, self.frame as f32);
+
+            gl.gl.BindTexture(gl::TEXTURE_2D, input);
+            gl.gl.BufferData(
+                gl::ARRAY_BUFFER,
+                (vertices.len() * mem::size_of::<VertexData>()) as _,
+                vertices.as_ptr() as _,
+                gl::STREAM_DRAW,
+            );
+            gl.gl.DrawArrays(gl::TRIANGLES, 0, vertices.len() as _);
+        }
+
+        gl.gl.BindTexture(gl::TEXTURE_2D, 0);
+        gl.gl.BindBuffer(gl::ARRAY_BUFFER, 0);
+
+        Ok(())
+    }
+}
+
+/// Reads RGBA8 pixels of given framebuffer, with rows ordered from top to bottom.
+unsafe fn read_pixels(gl: &Gl, framebuffer: GLuint, (width, height): (u32, u32)) -> Vec<u8> {
+    let stride = width as usize * 4;
+    let mut pixels = vec![0u8; stride * height as usize];
+
+    gl.gl.BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+    gl.gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+    gl.gl.ReadPixels(0, 0, width as _, height as _, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as _);
+
+    // OpenGL returns the rows bottom to top
+    let mut flipped = Vec::with_capacity(pixels.len());
+    for row in pixels.chunks_exact(stride).rev() {
+        flipped.extend_from_slice(row);
+    }
+
+    flipped
+}
+
 impl Drop for OpenGLRenderer {
     fn drop(&mut self) {
         *self.context_active.as_ref().borrow_mut() = false;
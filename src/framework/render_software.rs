@@ -0,0 +1,643 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use imgui::{DrawData, TextureId, Ui};
+
+use crate::common::{Color, Rect};
+use crate::framework::backend::{BackendRenderer, BackendShader, BackendTexture, SpriteBatchCommand, VertexData};
+use crate::framework::error::GameError::RenderError;
+use crate::framework::error::GameResult;
+use crate::framework::graphics::BlendMode;
+
+/// RGBA8 pixel storage, rows ordered from top to bottom.
+#[derive(Clone)]
+struct Surface {
+    width: u16,
+    height: u16,
+    pixels: Vec<u8>,
+}
+
+impl Surface {
+    fn new(width: u16, height: u16) -> Surface {
+        Surface { width, height, pixels: vec![0; width as usize * height as usize * 4] }
+    }
+
+    fn sample(&self, x: f32, y: f32) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [0.0; 4];
+        }
+
+        let x = (x.floor() as isize).clamp(0, self.width as isize - 1) as usize;
+        let y = (y.floor() as isize).clamp(0, self.height as isize - 1) as usize;
+        let idx = (y * self.width as usize + x) * 4;
+        let px = &self.pixels[idx..idx + 4];
+
+        [px[0] as f32 / 255.0, px[1] as f32 / 255.0, px[2] as f32 / 255.0, px[3] as f32 / 255.0]
+    }
+
+    /// Samples the surface using normalized coordinates.
+    fn sample_uv(&self, u: f32, v: f32) -> [f32; 4] {
+        self.sample(u * self.width as f32, v * self.height as f32)
+    }
+}
+
+type SurfaceRef = Rc<RefCell<Surface>>;
+
+/// State shared between the renderer and its textures, mirroring what OpenGL keeps in its context.
+struct RasterState {
+    screen: SurfaceRef,
+    target: SurfaceRef,
+    blend_enabled: bool,
+    /// Last blend function that was set, it stays in effect when blending is enabled again by a sprite batch.
+    blend_func: BlendMode,
+    clip: Option<Rect>,
+}
+
+impl RasterState {
+    fn blend(&self) -> Option<BlendMode> {
+        if self.blend_enabled {
+            Some(self.blend_func)
+        } else {
+            None
+        }
+    }
+}
+
+enum Sampler<'a> {
+    Fill,
+    Texture(&'a Surface),
+    /// (surface, scale, t, (frame_x, frame_y))
+    Water(&'a Surface, f32, f32, (f32, f32)),
+}
+
+impl Sampler<'_> {
+    fn shade(&self, x: f32, y: f32, uv: (f32, f32), color: [f32; 4]) -> [f32; 4] {
+        match self {
+            Sampler::Fill => color,
+            Sampler::Texture(surface) => {
+                let texel = surface.sample_uv(uv.0, uv.1);
+
+                [color[0] * texel[0], color[1] * texel[1], color[2] * texel[2], color[3] * texel[3]]
+            }
+            Sampler::Water(surface, scale, t, frame_pos) => {
+                // port of fragment_water_110.glsl, which works in bottom-up screen coordinates
+                let (width, height) = (surface.width as f32, surface.height as f32);
+                let (inv_x, inv_y) = (1.0 / width, 1.0 / height);
+                let (u, v) = (x * inv_x, (height - y) * inv_y + 1.0);
+
+                let wave_x = u + ((-frame_pos.1 * inv_y + u * 16.0) + t / 20.0).sin() * scale * inv_x;
+                let wave_y = v - ((-frame_pos.0 * inv_x + v * 16.0) + t / 5.0).cos() * scale * inv_y;
+                let off = 0.35 * scale * inv_y;
+                let off2 = 2.0 * off;
+
+                let tex = |dx: f32, dy: f32| -> [f32; 4] {
+                    surface.sample_uv(wave_x + dx, 1.0 - (wave_y + dy).rem_euclid(1.0))
+                };
+
+                let mut out = [0.0f32; 3];
+                for (weight, dy) in [(0.25, 0.0), (0.125, off), (0.125, -off)] {
+                    let c = tex(0.0, dy);
+                    out[0] += c[0] * weight;
+                    out[1] += c[1] * weight;
+                    out[2] += c[2] * weight;
+                }
+
+                for (weight, dy) in [(0.0625, -off), (0.125, 0.0), (0.0625, off)] {
+                    let c = tex(-off, dy);
+                    out[0] += c[0] * weight;
+                    out[1] += c[1] * weight;
+                    out[2] += tex(-off2, dy)[2] * weight;
+
+                    let c = tex(off, -dy);
+                    out[0] += c[1] * weight;
+                    out[1] += c[2] * weight;
+                    out[2] += tex(off2, -dy)[0] * weight;
+                }
+
+                let a = color[3];
+                [
+                    out[0] * (1.0 - a) + color[0] * a,
+                    out[1] * (1.0 - a) + color[1] * a,
+                    out[2] * (1.0 - a) + color[2] * a,
+                    1.0,
+                ]
+            }
+        }
+    }
+}
+
+fn blend_pixel(dst: &mut [u8], src: [f32; 4], blend: Option<BlendMode>) {
+    let d = [dst[0] as f32 / 255.0, dst[1] as f32 / 255.0, dst[2] as f32 / 255.0, dst[3] as f32 / 255.0];
+
+    let out = match blend {
+        None | Some(BlendMode::None) => src,
+        Some(BlendMode::Alpha) => {
+            let a = src[3];
+            [
+                src[0] * a + d[0] * (1.0 - a),
+                src[1] * a + d[1] * (1.0 - a),
+                src[2] * a + d[2] * (1.0 - a),
+                src[3] * a + d[3] * (1.0 - a),
+            ]
+        }
+        Some(BlendMode::Add) => [src[0] + d[0], src[1] + d[1], src[2] + d[2], src[3] + d[3]],
+        Some(BlendMode::Multiply) => [d[0] * src[0], d[1] * src[1], d[2] * src[2], d[3] * src[3]],
+    };
+
+    for (dst, value) in dst.iter_mut().zip(out.iter()) {
+        *dst = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+}
+
+#[inline(always)]
+fn edge(a: (f32, f32), b: (f32, f32), p: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+/// Whether pixels lying exactly on the edge belong to the triangle, so shared edges aren't drawn twice.
+#[inline(always)]
+fn is_top_left(a: (f32, f32), b: (f32, f32)) -> bool {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+
+    (dy == 0.0 && dx > 0.0) || dy < 0.0
+}
+
+fn rasterize(
+    target: &mut Surface,
+    clip: Option<Rect>,
+    blend: Option<BlendMode>,
+    vertices: &[VertexData],
+    sampler: &Sampler,
+) {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (0isize, 0isize, target.width as isize, target.height as isize);
+    if let Some(clip) = clip {
+        min_x = min_x.max(clip.left);
+        min_y = min_y.max(clip.top);
+        max_x = max_x.min(clip.right);
+        max_y = max_y.min(clip.bottom);
+    }
+
+    for tri in vertices.chunks_exact(3) {
+        let (v0, mut v1, mut v2) = (&tri[0], &tri[1], &tri[2]);
+        let mut area = edge(v0.position, v1.position, v2.position);
+        if area == 0.0 {
+            continue;
+        }
+
+        if area < 0.0 {
+            std::mem::swap(&mut v1, &mut v2);
+            area = -area;
+        }
+
+        let (p0, p1, p2) = (v0.position, v1.position, v2.position);
+        let left = (p0.0.min(p1.0).min(p2.0).floor() as isize).max(min_x);
+        let top = (p0.1.min(p1.1).min(p2.1).floor() as isize).max(min_y);
+        let right = (p0.0.max(p1.0).max(p2.0).ceil() as isize).min(max_x);
+        let bottom = (p0.1.max(p1.1).max(p2.1).ceil() as isize).min(max_y);
+
+        let (tl0, tl1, tl2) = (is_top_left(p1, p2), is_top_left(p2, p0), is_top_left(p0, p1));
+        let color = |v: &VertexData| {
+            [v.color.0 as f32 / 255.0, v.color.1 as f32 / 255.0, v.color.2 as f32 / 255.0, v.color.3 as f32 / 255.0]
+        };
+        let (c0, c1, c2) = (color(v0), color(v1), color(v2));
+
+        for y in top..bottom {
+            for x in left..right {
+                let p = (x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = edge(p1, p2, p);
+                let w1 = edge(p2, p0, p);
+                let w2 = edge(p0, p1, p);
+
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+
+                if (w0 == 0.0 && !tl0) || (w1 == 0.0 && !tl1) || (w2 == 0.0 && !tl2) {
+                    continue;
+                }
+
+                let (l0, l1, l2) = (w0 / area, w1 / area, w2 / area);
+                let uv = (v0.uv.0 * l0 + v1.uv.0 * l1 + v2.uv.0 * l2, v0.uv.1 * l0 + v1.uv.1 * l1 + v2.uv.1 * l2);
+                let color = [
+                    c0[0] * l0 + c1[0] * l1 + c2[0] * l2,
+                    c0[1] * l0 + c1[1] * l1 + c2[1] * l2,
+                    c0[2] * l0 + c1[2] * l1 + c2[2] * l2,
+                    c0[3] * l0 + c1[3] * l1 + c2[3] * l2,
+                ];
+
+                let src = sampler.shade(p.0, p.1, uv, color);
+                let idx = (y as usize * target.width as usize + x as usize) * 4;
+                blend_pixel(&mut target.pixels[idx..idx + 4], src, blend);
+            }
+        }
+    }
+}
+
+/// Rasterizes vertices into the current target using a sampler reading from `source`. The source is only copied
+/// if it's also the render target, since a surface can't be read while it's being drawn into.
+fn rasterize_from<F>(state: &RasterState, vertices: &[VertexData], source: &SurfaceRef, sampler: F)
+where
+    F: for<'a> Fn(&'a Surface) -> Sampler<'a>,
+{
+    if Rc::ptr_eq(source, &state.target) {
+        let snapshot = source.borrow().clone();
+        rasterize(&mut state.target.borrow_mut(), state.clip, state.blend(), vertices, &sampler(&snapshot));
+    } else {
+        let source = source.borrow();
+        rasterize(&mut state.target.borrow_mut(), state.clip, state.blend(), vertices, &sampler(&source));
+    }
+}
+
+fn push_quad(vertices: &mut Vec<VertexData>, src: Rect<f32>, dest: Rect<f32>, color: (u8, u8, u8, u8)) {
+    vertices.extend_from_slice(&[
+        VertexData { position: (dest.left, dest.bottom), uv: (src.left, src.bottom), color },
+        VertexData { position: (dest.left, dest.top), uv: (src.left, src.top), color },
+        VertexData { position: (dest.right, dest.top), uv: (src.right, src.top), color },
+        VertexData { position: (dest.left, dest.bottom), uv: (src.left, src.bottom), color },
+        VertexData { position: (dest.right, dest.top), uv: (src.right, src.top), color },
+        VertexData { position: (dest.right, dest.bottom), uv: (src.right, src.bottom), color },
+    ]);
+}
+
+pub struct SoftwareTexture {
+    width: u16,
+    height: u16,
+    surface: SurfaceRef,
+    vertices: Vec<VertexData>,
+    state: Rc<RefCell<RasterState>>,
+}
+
+impl BackendTexture for SoftwareTexture {
+    fn dimensions(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    fn add(&mut self, command: SpriteBatchCommand) {
+        let (tex_scale_x, tex_scale_y) = (1.0 / self.width as f32, 1.0 / self.height as f32);
+
+        let (mut src, dest, flip_x, flip_y, color) = match command {
+            SpriteBatchCommand::DrawRect(src, dest) => (src, dest, false, false, (255, 255, 255, 255)),
+            SpriteBatchCommand::DrawRectFlip(src, dest, flip_x, flip_y) => {
+                (src, dest, flip_x, flip_y, (255, 255, 255, 255))
+            }
+            SpriteBatchCommand::DrawRectTinted(src, dest, color) => (src, dest, false, false, color.to_rgba()),
+            SpriteBatchCommand::DrawRectFlipTinted(src, dest, flip_x, flip_y, color) => {
+                (src, dest, flip_x, flip_y, color.to_rgba())
+            }
+        };
+
+        if flip_x {
+            std::mem::swap(&mut src.left, &mut src.right);
+        }
+
+        if flip_y {
+            std::mem::swap(&mut src.top, &mut src.bottom);
+        }
+
+        let src = Rect {
+            left: src.left * tex_scale_x,
+            top: src.top * tex_scale_y,
+            right: src.right * tex_scale_x,
+            bottom: src.bottom * tex_scale_y,
+        };
+
+        push_quad(&mut self.vertices, src, dest, color);
+    }
+
+    fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    fn draw(&mut self) -> GameResult {
+        // sprite batches always enable blending, same as the OpenGL renderer
+        self.state.borrow_mut().blend_enabled = true;
+
+        let state = self.state.borrow();
+        rasterize_from(&state, &self.vertices, &self.surface, |surface| Sampler::Texture(surface));
+
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// CPU renderer drawing into an RGBA buffer, used where no GPU is available (render tests, headless screenshots).
+///
+/// It aims to produce the same output as the OpenGL renderer, imgui output is not rasterized though.
+pub struct SoftwareRenderer {
+    state: Rc<RefCell<RasterState>>,
+    imgui: RefCell<imgui::Context>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u16, height: u16) -> SoftwareRenderer {
+        let mut imgui = imgui::Context::create();
+        imgui.io_mut().display_size = [width as f32, height as f32];
+        imgui.fonts().build_alpha8_texture();
+
+        let screen = Rc::new(RefCell::new(Surface::new(width, height)));
+
+        SoftwareRenderer {
+            state: Rc::new(RefCell::new(RasterState {
+                screen: screen.clone(),
+                target: screen,
+                blend_enabled: true,
+                blend_func: BlendMode::Alpha,
+                clip: None,
+            })),
+            imgui: RefCell::new(imgui),
+        }
+    }
+
+    /// Returns the size and RGBA8 contents of the screen.
+    pub fn screen(&self) -> (u16, u16, Vec<u8>) {
+        let state = self.state.borrow();
+        let screen = state.screen.borrow();
+
+        (screen.width, screen.height, screen.pixels.clone())
+    }
+
+    /// Encodes the contents of the screen as a PNG image.
+    pub fn write_png<W: io::Write>(&self, writer: W) -> GameResult {
+        let (width, height, pixels) = self.screen();
+        PngEncoder::new(writer).write_image(&pixels, width as u32, height as u32, ColorType::Rgba8)?;
+
+        Ok(())
+    }
+
+    fn draw_vertices(&mut self, vertices: &[VertexData], sampler: &Sampler) {
+        let state = self.state.borrow();
+        rasterize(&mut state.target.borrow_mut(), state.clip, state.blend(), vertices, sampler);
+    }
+}
+
+impl BackendRenderer for SoftwareRenderer {
+    fn renderer_name(&self) -> String {
+        "Software".to_owned()
+    }
+
+    fn clear(&mut self, color: Color) {
+        let state = self.state.borrow();
+        let mut target = state.target.borrow_mut();
+        let (width, height) = (target.width as isize, target.height as isize);
+        let color = color.to_rgba();
+        let color = [color.0, color.1, color.2, color.3];
+
+        let rect = state.clip.unwrap_or(Rect::new(0, 0, width, height));
+        for y in rect.top.max(0)..rect.bottom.min(height) {
+            for x in rect.left.max(0)..rect.right.min(width) {
+                let idx = (y * width + x) as usize * 4;
+                target.pixels[idx..idx + 4].copy_from_slice(&color);
+            }
+        }
+    }
+
+    fn present(&mut self) -> GameResult {
+        Ok(())
+    }
+
+    fn prepare_draw(&mut self, width: f32, height: f32) -> GameResult {
+        let mut state = self.state.borrow_mut();
+        let (width, height) = (width as u16, height as u16);
+
+        {
+            let mut screen = state.screen.borrow_mut();
+            if (screen.width, screen.height) != (width, height) {
+                *screen = Surface::new(width, height);
+            } else {
+                screen.pixels.fill(0);
+            }
+        }
+
+        state.target = state.screen.clone();
+        state.blend_enabled = true;
+        state.blend_func = BlendMode::Alpha;
+        self.imgui.borrow_mut().io_mut().display_size = [width as f32, height as f32];
+
+        Ok(())
+    }
+
+    fn create_texture_mutable(&mut self, width: u16, height: u16) -> GameResult<Box<dyn BackendTexture>> {
+        Ok(Box::new(SoftwareTexture {
+            width,
+            height,
+            surface: Rc::new(RefCell::new(Surface::new(width, height))),
+            vertices: Vec::new(),
+            state: self.state.clone(),
+        }))
+    }
+
+    fn create_texture(&mut self, width: u16, height: u16, data: &[u8]) -> GameResult<Box<dyn BackendTexture>> {
+        let mut surface = Surface::new(width, height);
+        if data.len() != surface.pixels.len() {
+            return Err(RenderError(format!(
+                "Invalid texture data size, expected {} bytes, got {}.",
+                surface.pixels.len(),
+                data.len()
+            )));
+        }
+        surface.pixels.copy_from_slice(data);
+
+        Ok(Box::new(SoftwareTexture {
+            width,
+            height,
+            surface: Rc::new(RefCell::new(surface)),
+            vertices: Vec::new(),
+            state: self.state.clone(),
+        }))
+    }
+
+    fn set_blend_mode(&mut self, blend: BlendMode) -> GameResult {
+        let mut state = self.state.borrow_mut();
+
+        if blend == BlendMode::None {
+            state.blend_enabled = false;
+        } else {
+            state.blend_enabled = true;
+            state.blend_func = blend;
+        }
+
+        Ok(())
+    }
+
+    fn set_render_target(&mut self, texture: Option<&Box<dyn BackendTexture>>) -> GameResult {
+        let mut state = self.state.borrow_mut();
+
+        if let Some(texture) = texture {
+            let sw_texture = texture
+                .as_any()
+                .downcast_ref::<SoftwareTexture>()
+                .ok_or_else(|| RenderError("This texture was not created by software backend.".to_string()))?;
+
+            state.target = sw_texture.surface.clone();
+        } else {
+            state.target = state.screen.clone();
+        }
+
+        Ok(())
+    }
+
+    fn draw_rect(&mut self, rect: Rect<isize>, color: Color) -> GameResult {
+        let mut vertices = Vec::with_capacity(6);
+        let dest = Rect::new(rect.left as f32, rect.top as f32, rect.right as f32, rect.bottom as f32);
+        push_quad(&mut vertices, Rect::new(0.0, 0.0, 0.0, 0.0), dest, color.to_rgba());
+
+        self.draw_vertices(&vertices, &Sampler::Fill);
+
+        Ok(())
+    }
+
+    fn draw_outline_rect(&mut self, rect: Rect<isize>, line_width: usize, color: Color) -> GameResult {
+        let line_width = line_width as isize;
+
+        self.draw_rect(Rect::new(rect.left, rect.top, rect.right, rect.top + line_width), color)?;
+        self.draw_rect(Rect::new(rect.left, rect.bottom - line_width, rect.right, rect.bottom), color)?;
+        self.draw_rect(
+            Rect::new(rect.left, rect.top + line_width, rect.left + line_width, rect.bottom - line_width),
+            color,
+        )?;
+        self.draw_rect(
+            Rect::new(rect.right - line_width, rect.top + line_width, rect.right, rect.bottom - line_width),
+            color,
+        )?;
+
+        Ok(())
+    }
+
+    fn set_clip_rect(&mut self, rect: Option<Rect>) -> GameResult {
+        self.state.borrow_mut().clip = rect;
+
+        Ok(())
+    }
+
+    fn imgui(&self) -> GameResult<&mut imgui::Context> {
+        unsafe { Ok(&mut *self.imgui.as_ptr()) }
+    }
+
+    fn imgui_texture_id(&self, _texture: &Box<dyn BackendTexture>) -> GameResult<TextureId> {
+        Ok(TextureId::from(0))
+    }
+
+    fn prepare_imgui(&mut self, _ui: &Ui) -> GameResult {
+        Ok(())
+    }
+
+    fn render_imgui(&mut self, _draw_data: &DrawData) -> GameResult {
+        Ok(())
+    }
+
+    fn supports_vertex_draw(&self) -> bool {
+        true
+    }
+
+    fn draw_triangle_list(
+        &mut self,
+        vertices: &[VertexData],
+        texture: Option<&Box<dyn BackendTexture>>,
+        shader: BackendShader,
+    ) -> GameResult<()> {
+        let texture = if let Some(texture) = texture {
+            let sw_texture = texture
+                .as_any()
+                .downcast_ref::<SoftwareTexture>()
+                .ok_or_else(|| RenderError("This texture was not created by software backend.".to_string()))?;
+
+            Some(&sw_texture.surface)
+        } else {
+            None
+        };
+
+        let state = self.state.borrow();
+        match (shader, texture) {
+            (BackendShader::Texture, Some(texture)) => {
+                rasterize_from(&state, vertices, texture, |surface| Sampler::Texture(surface))
+            }
+            (BackendShader::WaterFill(scale, t, frame_pos), _) => {
+                // the water shader samples the screen it's drawing into
+                rasterize_from(&state, vertices, &state.screen, |screen| Sampler::Water(screen, scale, t, frame_pos))
+            }
+            _ => rasterize(&mut state.target.borrow_mut(), state.clip, state.blend(), vertices, &Sampler::Fill),
+        }
+
+        Ok(())
+    }
+
+    fn read_framebuffer(&mut self) -> GameResult<(u16, u16, Vec<u8>)> {
+        Ok(self.screen())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[test]
+fn test_software_renderer() {
+    let mut renderer = SoftwareRenderer::new(4, 4);
+    let pixel = |renderer: &SoftwareRenderer, x: usize, y: usize| {
+        let (width, _, pixels) = renderer.screen();
+        let idx = (y * width as usize + x) * 4;
+        [pixels[idx], pixels[idx + 1], pixels[idx + 2], pixels[idx + 3]]
+    };
+
+    renderer.prepare_draw(4.0, 4.0).unwrap();
+    renderer.clear(Color::new(0.0, 0.0, 0.0, 1.0));
+    renderer.draw_rect(Rect::new(0, 0, 2, 2), Color::new(1.0, 0.0, 0.0, 1.0)).unwrap();
+
+    assert_eq!(pixel(&renderer, 0, 0), [255, 0, 0, 255]);
+    assert_eq!(pixel(&renderer, 1, 1), [255, 0, 0, 255]);
+    assert_eq!(pixel(&renderer, 2, 2), [0, 0, 0, 255]);
+
+    // flipped sprite
+    let mut texture = renderer.create_texture(2, 1, &[255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+    texture.add(SpriteBatchCommand::DrawRectFlip(
+        Rect::new(0.0, 0.0, 2.0, 1.0),
+        Rect::new(0.0, 3.0, 2.0, 4.0),
+        true,
+        false,
+    ));
+    texture.draw().unwrap();
+
+    assert_eq!(pixel(&renderer, 0, 3), [0, 0, 255, 255]);
+    assert_eq!(pixel(&renderer, 1, 3), [255, 0, 0, 255]);
+
+    // clip rect
+    renderer.set_clip_rect(Some(Rect::new(3, 0, 4, 4))).unwrap();
+    renderer.draw_rect(Rect::new(0, 0, 4, 4), Color::new(0.0, 1.0, 0.0, 1.0)).unwrap();
+    renderer.set_clip_rect(None).unwrap();
+
+    assert_eq!(pixel(&renderer, 3, 0), [0, 255, 0, 255]);
+    assert_eq!(pixel(&renderer, 2, 0), [0, 0, 0, 255]);
+
+    // render target and tint
+    let mut canvas = renderer.create_texture_mutable(1, 1).unwrap();
+    renderer.set_render_target(Some(&canvas)).unwrap();
+    renderer.clear(Color::new(1.0, 1.0, 1.0, 1.0));
+    renderer.set_render_target(None).unwrap();
+
+    canvas.add(SpriteBatchCommand::DrawRectTinted(
+        Rect::new(0.0, 0.0, 1.0, 1.0),
+        Rect::new(2.0, 1.0, 3.0, 2.0),
+        Color::new(0.0, 0.0, 1.0, 1.0),
+    ));
+    canvas.draw().unwrap();
+
+    assert_eq!(pixel(&renderer, 2, 1), [0, 0, 255, 255]);
+
+    // additive blending
+    renderer.set_blend_mode(BlendMode::Add).unwrap();
+    renderer.draw_rect(Rect::new(0, 0, 1, 1), Color::new(0.0, 1.0, 0.0, 1.0)).unwrap();
+    renderer.set_blend_mode(BlendMode::Alpha).unwrap();
+
+    assert_eq!(pixel(&renderer, 0, 0), [255, 255, 0, 255]);
+
+    let mut png = Vec::new();
+    renderer.write_png(&mut png).unwrap();
+    assert_eq!(&png[1..4], b"PNG");
+}
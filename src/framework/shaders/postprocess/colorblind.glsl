@@ -0,0 +1,30 @@
+// FILTER is defined by the renderer: 1 - protanopia, 2 - deuteranopia, 3 - tritanopia
+
+void main()
+{
+    vec4 color = texture2D(Texture, Frag_UV);
+
+    float l = 17.8824 * color.r + 43.5161 * color.g + 4.11935 * color.b;
+    float m = 3.45565 * color.r + 27.1554 * color.g + 3.86714 * color.b;
+    float s = 0.0299566 * color.r + 0.184309 * color.g + 1.46709 * color.b;
+
+#if FILTER == 1
+    l = 2.02344 * m - 2.52581 * s;
+#elif FILTER == 2
+    m = 0.494207 * l + 1.24827 * s;
+#else
+    s = -0.395913 * l + 0.801109 * m;
+#endif
+
+    vec3 simulated = vec3(
+        0.0809444479 * l - 0.130504409 * m + 0.116721066 * s,
+        -0.0102485335 * l + 0.0540193266 * m - 0.113614708 * s,
+        -0.000365296938 * l - 0.00412161469 * m + 0.693511405 * s
+    );
+
+    // shift the colors the viewer can't distinguish towards the ones they can
+    vec3 error = color.rgb - simulated;
+    vec3 correction = vec3(0.0, 0.7 * error.r + error.g, 0.7 * error.r + error.b);
+
+    gl_FragColor = vec4(clamp(color.rgb + correction, 0.0, 1.0), color.a);
+}
@@ -0,0 +1,11 @@
+uniform sampler2D Texture;
+// size of the input texture in pixels
+uniform vec2 TextureSize;
+// size of the output in pixels
+uniform vec2 OutputSize;
+// size of the game image in game pixels
+uniform vec2 SourceSize;
+// number of presented frames
+uniform float Time;
+varying vec2 Frag_UV;
+varying vec4 Frag_Color;
@@ -0,0 +1,36 @@
+vec2 curve(vec2 uv)
+{
+    uv = uv * 2.0 - 1.0;
+    vec2 offset = abs(uv.yx) / vec2(6.0, 5.0);
+    uv = uv + uv * offset * offset;
+    return uv * 0.5 + 0.5;
+}
+
+void main()
+{
+    vec2 uv = curve(Frag_UV);
+
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec3 color = texture2D(Texture, uv).rgb;
+
+    // aperture grille
+    float column = mod(floor(Frag_UV.x * OutputSize.x), 3.0);
+    vec3 grille = vec3(0.85);
+    if (column < 1.0) {
+        grille.r = 1.0;
+    } else if (column < 2.0) {
+        grille.g = 1.0;
+    } else {
+        grille.b = 1.0;
+    }
+    color *= grille * 1.1;
+
+    float vignette = 16.0 * uv.x * uv.y * (1.0 - uv.x) * (1.0 - uv.y);
+    color *= pow(vignette, 0.15);
+
+    gl_FragColor = vec4(color, 1.0);
+}
@@ -0,0 +1,5 @@
+void main()
+{
+    vec2 uv = (floor(Frag_UV * TextureSize) + 0.5) / TextureSize;
+    gl_FragColor = texture2D(Texture, uv);
+}
@@ -0,0 +1,8 @@
+void main()
+{
+    vec4 color = texture2D(Texture, Frag_UV);
+    float line = fract(Frag_UV.y * SourceSize.y);
+    float intensity = 0.75 + 0.25 * cos((line - 0.5) * 6.2831853);
+
+    gl_FragColor = vec4(color.rgb * intensity, color.a);
+}
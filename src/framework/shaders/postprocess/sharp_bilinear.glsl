@@ -0,0 +1,12 @@
+void main()
+{
+    vec2 texel = Frag_UV * TextureSize;
+    vec2 scale = max(floor(OutputSize / TextureSize), vec2(1.0));
+
+    vec2 texel_floored = floor(texel);
+    vec2 center_dist = fract(texel) - 0.5;
+    vec2 region_range = 0.5 - 0.5 / scale;
+    vec2 f = (center_dist - clamp(center_dist, -region_range, region_range)) * scale + 0.5;
+
+    gl_FragColor = texture2D(Texture, (texel_floored + f) / TextureSize);
+}
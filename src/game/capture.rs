@@ -0,0 +1,227 @@
+use std::io::{BufWriter, Write};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+
+use crate::framework::context::Context;
+use crate::framework::error::{GameError, GameResult};
+use crate::framework::filesystem::File;
+use crate::framework::{filesystem, graphics};
+use crate::game::shared_game_state::TimingMode;
+use crate::sound::SoundManager;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CaptureFormat {
+    /// Every frame is written as a separate `frame_NNNNNN.png` file.
+    PngSequence,
+    /// All frames are written into a single uncompressed `video.y4m` file (4:4:4, full range BT.601).
+    Y4M,
+}
+
+struct CaptureSession {
+    format: CaptureFormat,
+    directory: String,
+    frame: u32,
+    size: Option<(u16, u16)>,
+    y4m: Option<BufWriter<File>>,
+    /// Rate at which the game ticked before the capture started, used as the video frame rate.
+    tps: usize,
+    realtime: bool,
+    started_at: Instant,
+    saved_timing_mode: TimingMode,
+}
+
+/// Dumps rendered frames into the user directory.
+///
+/// While capturing, the game is switched to frame synchronized timing, so exactly one tick happens per captured
+/// frame and the output has a constant frame rate regardless of rendering speed. The mixed audio output is recorded
+/// alongside into `audio.wav`, one tick worth of samples per frame, so it stays aligned with the video even when
+/// capturing faster than real time. In real-time mode frames are additionally paced to the original tick rate.
+pub struct FrameCapture {
+    session: Option<CaptureSession>,
+}
+
+impl FrameCapture {
+    pub fn new() -> FrameCapture {
+        FrameCapture { session: None }
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.session.is_some()
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        self.session.as_ref().map(|s| s.frame).unwrap_or(0)
+    }
+
+    pub fn directory(&self) -> Option<&str> {
+        self.session.as_ref().map(|s| s.directory.as_str())
+    }
+
+    /// Creates the output directory and prepares the capture session, returns the timing mode that should be
+    /// used while capturing.
+    pub fn start(
+        &mut self,
+        ctx: &Context,
+        format: CaptureFormat,
+        realtime: bool,
+        timing_mode: TimingMode,
+    ) -> GameResult<TimingMode> {
+        if self.session.is_some() {
+            return Err(GameError::InvalidValue("Capture is already in progress.".to_owned()));
+        }
+
+        let directory = format!("/capture/{}/", Local::now().format("%Y-%m-%d_%H-%M-%S"));
+        filesystem::user_create_dir(ctx, &directory)?;
+
+        let y4m = match format {
+            CaptureFormat::PngSequence => None,
+            CaptureFormat::Y4M => {
+                Some(BufWriter::new(filesystem::user_create(ctx, format!("{}video.y4m", directory))?))
+            }
+        };
+
+        let tps = match timing_mode {
+            TimingMode::FrameSynchronized => 50,
+            mode => mode.get_tps(),
+        };
+
+        log::info!("Started capturing frames to {}", directory);
+
+        self.session = Some(CaptureSession {
+            format,
+            directory,
+            frame: 0,
+            size: None,
+            y4m,
+            tps,
+            realtime,
+            started_at: Instant::now(),
+            saved_timing_mode: timing_mode,
+        });
+
+        Ok(TimingMode::FrameSynchronized)
+    }
+
+    /// Finishes the capture session, returns the timing mode that was used before the capture started.
+    pub fn stop(&mut self) -> GameResult<Option<TimingMode>> {
+        if let Some(mut session) = self.session.take() {
+            if let Some(y4m) = &mut session.y4m {
+                y4m.flush()?;
+            }
+
+            log::info!("Captured {} frames to {}", session.frame, session.directory);
+
+            return Ok(Some(session.saved_timing_mode));
+        }
+
+        Ok(None)
+    }
+
+    pub fn is_realtime(&self) -> bool {
+        self.session.as_ref().map(|s| s.realtime).unwrap_or(false)
+    }
+
+    pub fn audio_path(&self) -> Option<String> {
+        self.session.as_ref().map(|s| format!("{}audio.wav", s.directory))
+    }
+
+    /// Reads back the current contents of the screen (at the window size, with post-processing applied) and writes
+    /// them out along with audio of the last tick, should be called once per drawn frame.
+    pub fn capture(&mut self, ctx: &mut Context, sound_manager: &mut SoundManager) -> GameResult {
+        let session = if let Some(session) = &mut self.session {
+            session
+        } else {
+            return Ok(());
+        };
+
+        let (width, height, mut data) = graphics::read_framebuffer(ctx)?;
+
+        match session.size {
+            Some(size) if size != (width, height) => {
+                return Err(GameError::InvalidValue(format!(
+                    "Screen size changed during capture ({}x{} -> {}x{}).",
+                    size.0, size.1, width, height
+                )));
+            }
+            Some(_) => {}
+            None => {
+                session.size = Some((width, height));
+
+                if let Some(y4m) = &mut session.y4m {
+                    writeln!(y4m, "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444 XCOLORRANGE=FULL", width, height, session.tps)?;
+                }
+            }
+        }
+
+        match session.format {
+            CaptureFormat::PngSequence => {
+                let rgb = rgba_to_rgb(&mut data);
+                let file =
+                    filesystem::user_create(ctx, format!("{}frame_{:06}.png", session.directory, session.frame))?;
+
+                PngEncoder::new(BufWriter::new(file)).write_image(rgb, width as u32, height as u32, ColorType::Rgb8)?;
+            }
+            CaptureFormat::Y4M => {
+                if let Some(y4m) = &mut session.y4m {
+                    y4m.write_all(b"FRAME\n")?;
+                    y4m.write_all(&rgba_to_yuv444(&data))?;
+                }
+            }
+        }
+
+        sound_manager.capture_tick(session.tps)?;
+        session.frame += 1;
+
+        if session.realtime {
+            let target =
+                session.started_at + Duration::from_nanos(session.frame as u64 * 1_000_000_000 / session.tps as u64);
+            let now = Instant::now();
+
+            if target > now {
+                std::thread::sleep(target - now);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Packs RGBA pixels into RGB in place, returning the packed part of the buffer.
+fn rgba_to_rgb(data: &mut [u8]) -> &[u8] {
+    let pixels = data.len() / 4;
+
+    for i in 0..pixels {
+        data.copy_within(i * 4..i * 4 + 3, i * 3);
+    }
+
+    &data[..pixels * 3]
+}
+
+/// Converts RGBA pixels into planar full range BT.601 YUV 4:4:4.
+fn rgba_to_yuv444(data: &[u8]) -> Vec<u8> {
+    let pixels = data.len() / 4;
+    let mut out = vec![0u8; pixels * 3];
+    let (y_plane, uv_planes) = out.split_at_mut(pixels);
+    let (u_plane, v_plane) = uv_planes.split_at_mut(pixels);
+
+    for (i, px) in data.chunks_exact(4).enumerate() {
+        let (r, g, b) = (px[0] as f32, px[1] as f32, px[2] as f32);
+
+        y_plane[i] = (0.299 * r + 0.587 * g + 0.114 * b).round().clamp(0.0, 255.0) as u8;
+        u_plane[i] = (128.0 - 0.168736 * r - 0.331264 * g + 0.5 * b).round().clamp(0.0, 255.0) as u8;
+        v_plane[i] = (128.0 + 0.5 * r - 0.418688 * g - 0.081312 * b).round().clamp(0.0, 255.0) as u8;
+    }
+
+    out
+}
+
+#[test]
+fn test_rgba_conversion() {
+    let mut data = vec![255, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255, 128];
+
+    assert_eq!(rgba_to_yuv444(&data), vec![76, 0, 255, 85, 128, 128, 255, 128, 128]);
+    assert_eq!(rgba_to_rgb(&mut data), &[255, 0, 0, 0, 0, 0, 255, 255, 255]);
+}
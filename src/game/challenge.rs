@@ -0,0 +1,361 @@
+use crate::common::get_timestamp;
+use crate::engine_constants::EngineConstants;
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::filesystem;
+use crate::game::inventory::Inventory;
+use crate::game::npc::boss::BossNPC;
+use crate::game::player::Player;
+use crate::game::run_timer::{current_version, BossKillTracker};
+use crate::game::shared_game_state::SharedGameState;
+use crate::game::weapon::{WeaponLevel, WeaponType};
+use crate::scene::game_scene::GameScene;
+use crate::util::bitvec::BitVec;
+
+/// A time-attack challenge, loaded from `challenges.json`.
+#[derive(Clone, serde::Deserialize)]
+pub struct ChallengeDefinition {
+    /// Used for naming leaderboard and replay files, must be unique.
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub start_stage: u16,
+    /// Starting position in tiles.
+    #[serde(default)]
+    pub start_pos: (i16, i16),
+    pub start_event: u16,
+    #[serde(default)]
+    pub loadout: ChallengeLoadout,
+    pub win_condition: WinCondition,
+    #[serde(default)]
+    pub rules: ChallengeRules,
+}
+
+#[derive(Clone, Default, serde::Deserialize)]
+pub struct ChallengeLoadout {
+    #[serde(default)]
+    pub max_life: Option<u16>,
+    #[serde(default)]
+    pub weapons: Vec<LoadoutWeapon>,
+    #[serde(default)]
+    pub items: Vec<u16>,
+    #[serde(default)]
+    pub equipment: u16,
+    /// Game flags set before the start event runs.
+    #[serde(default)]
+    pub flags: Vec<u16>,
+}
+
+#[derive(Clone, Copy, serde::Deserialize)]
+pub struct LoadoutWeapon {
+    pub id: u8,
+    #[serde(default = "default_weapon_level")]
+    pub level: u8,
+    #[serde(default)]
+    pub ammo: u16,
+}
+
+fn default_weapon_level() -> u8 {
+    1
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WinCondition {
+    FlagSet {
+        flag: u16,
+    },
+    /// A boss of given type has to be defeated, see [BossKillTracker].
+    BossKilled {
+        boss: u16,
+    },
+    StageReached {
+        stage: u16,
+    },
+}
+
+#[derive(Clone, Default, serde::Deserialize)]
+pub struct ChallengeRules {
+    /// Taking any damage fails the run.
+    #[serde(default)]
+    pub no_damage: bool,
+    /// Weapons that can be used, any other weapon is taken away as soon as it's obtained.
+    #[serde(default)]
+    pub allowed_weapons: Option<Vec<u8>>,
+}
+
+pub struct ChallengeList {
+    pub challenges: Vec<ChallengeDefinition>,
+}
+
+impl ChallengeList {
+    pub fn load(ctx: &Context, constants: &EngineConstants) -> ChallengeList {
+        let mut challenges = Vec::new();
+
+        if let Ok(file) = filesystem::open_find(ctx, &constants.base_paths, "challenges.json") {
+            match serde_json::from_reader::<_, Vec<ChallengeDefinition>>(file) {
+                Ok(list) => {
+                    for challenge in list {
+                        if challenges.iter().any(|c: &ChallengeDefinition| c.id == challenge.id) {
+                            log::warn!("Duplicate challenge id {}, skipping.", challenge.id);
+                            continue;
+                        }
+
+                        challenges.push(challenge);
+                    }
+                }
+                Err(err) => log::warn!("Failed to deserialize challenges: {}", err),
+            }
+        }
+
+        ChallengeList { challenges }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&ChallengeDefinition> {
+        self.challenges.get(index)
+    }
+}
+
+impl ChallengeLoadout {
+    pub fn apply(&self, state: &mut SharedGameState, game_scene: &mut GameScene) {
+        if let Some(max_life) = self.max_life {
+            game_scene.player1.max_life = max_life;
+            game_scene.player1.life = max_life;
+        }
+
+        for weapon in &self.weapons {
+            if let Some(wtype) = WeaponType::from_id(weapon.id, &state.constants) {
+                let level = match weapon.level {
+                    2 => WeaponLevel::Level2,
+                    3 => WeaponLevel::Level3,
+                    _ => WeaponLevel::Level1,
+                };
+
+                game_scene.inventory_player1.add_weapon_data(wtype, weapon.ammo, weapon.ammo, 0, level);
+            } else {
+                log::warn!("Unknown weapon {} in challenge loadout.", weapon.id);
+            }
+        }
+
+        for &item in &self.items {
+            game_scene.inventory_player1.add_item(item);
+        }
+
+        game_scene.player1.equip.0 |= self.equipment;
+
+        for &flag in &self.flags {
+            state.set_flag(flag as usize, true);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ChallengeSplit {
+    pub stage: u16,
+    /// Time at which the stage was entered.
+    pub ticks: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengeStatus {
+    Running,
+    Failed,
+    Finished,
+}
+
+/// State of a challenge being played, kept across stage transitions.
+pub struct ChallengeRun {
+    pub index: usize,
+    pub def: ChallengeDefinition,
+    pub ticks: u32,
+    pub splits: Vec<ChallengeSplit>,
+    pub status: ChallengeStatus,
+    /// Best run from the leaderboard, splits are compared against it.
+    pub best: Option<LeaderboardEntry>,
+    /// Counts ticks since the run has ended.
+    pub end_counter: u16,
+    boss_kill: BossKillTracker,
+    last_life: u16,
+}
+
+impl ChallengeRun {
+    pub fn new(index: usize, def: ChallengeDefinition) -> ChallengeRun {
+        ChallengeRun {
+            index,
+            def,
+            ticks: 0,
+            splits: Vec::new(),
+            status: ChallengeStatus::Running,
+            best: None,
+            end_counter: 0,
+            boss_kill: BossKillTracker::default(),
+            last_life: 0,
+        }
+    }
+
+    /// Records a split if the stage is different from the current one. Called whenever the game scene is
+    /// created, so it also forgets the boss being tracked.
+    pub fn enter_stage(&mut self, stage: u16) {
+        self.boss_kill.reset();
+
+        if self.status != ChallengeStatus::Running || self.splits.last().map(|split| split.stage) == Some(stage) {
+            return;
+        }
+
+        self.splits.push(ChallengeSplit { stage, ticks: self.ticks });
+    }
+
+    /// Difference in ticks between the last split and the same split of the best run.
+    pub fn split_delta(&self) -> Option<i64> {
+        let idx = self.splits.len().checked_sub(1)?;
+        let best = self.best.as_ref()?.splits.get(idx)?;
+        let current = self.splits[idx];
+
+        if best.stage != current.stage {
+            return None;
+        }
+
+        Some(current.ticks as i64 - best.ticks as i64)
+    }
+
+    pub fn tick(
+        &mut self,
+        flags: &BitVec,
+        stage: u16,
+        boss: &BossNPC,
+        player: &Player,
+        inventory: &mut Inventory,
+    ) -> ChallengeStatus {
+        if self.status != ChallengeStatus::Running {
+            self.end_counter = self.end_counter.saturating_add(1);
+            return self.status;
+        }
+
+        self.ticks = self.ticks.saturating_add(1);
+
+        if self.def.rules.no_damage && player.life < self.last_life {
+            self.status = ChallengeStatus::Failed;
+        }
+        self.last_life = player.life;
+
+        if let Some(allowed) = &self.def.rules.allowed_weapons {
+            let forbidden: Vec<WeaponType> = (0..inventory.get_weapon_count())
+                .filter_map(|idx| inventory.get_weapon(idx))
+                .map(|weapon| weapon.wtype)
+                .filter(|wtype| !allowed.contains(&wtype.id()))
+                .collect();
+
+            for wtype in forbidden {
+                inventory.remove_weapon(wtype);
+            }
+
+            if inventory.current_weapon as usize >= inventory.get_weapon_count() {
+                inventory.current_weapon = 0;
+            }
+        }
+
+        let won = match self.def.win_condition {
+            WinCondition::FlagSet { flag } => flags.get(flag as usize).unwrap_or(false),
+            WinCondition::BossKilled { boss: boss_type } => self.boss_kill.update(boss, boss_type, stage),
+            WinCondition::StageReached { stage: target } => stage == target,
+        };
+
+        if won && self.status == ChallengeStatus::Running {
+            self.status = ChallengeStatus::Finished;
+        }
+
+        self.status
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LeaderboardEntry {
+    pub ticks: u32,
+    /// Ticks per second of the timing mode the run was played in.
+    pub tps: u32,
+    pub timestamp: u64,
+    /// [crate::game::settings::AssistFlags] used during the run.
+    #[serde(default)]
+    pub assist: u8,
+    #[serde(default)]
+    pub splits: Vec<ChallengeSplit>,
+}
+
+impl LeaderboardEntry {
+    pub fn new(run: &ChallengeRun, tps: u32, assist: u8) -> LeaderboardEntry {
+        LeaderboardEntry { ticks: run.ticks, tps, timestamp: get_timestamp(), assist, splits: run.splits.clone() }
+    }
+
+    pub fn millis(&self) -> u64 {
+        self.ticks as u64 * 1000 / self.tps.max(1) as u64
+    }
+}
+
+/// Local best times of a challenge, stored in the user directory.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Leaderboard {
+    #[serde(default = "current_version")]
+    pub version: u32,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    const MAX_ENTRIES: usize = 10;
+
+    fn filename(challenge_id: &str) -> String {
+        format!("/challenge_{}.json", challenge_id)
+    }
+
+    pub fn load(ctx: &Context, challenge_id: &str) -> Leaderboard {
+        if let Ok(file) = filesystem::user_open(ctx, Leaderboard::filename(challenge_id)) {
+            match serde_json::from_reader::<_, Leaderboard>(file) {
+                Ok(leaderboard) => return leaderboard,
+                Err(err) => log::warn!("Failed to deserialize leaderboard: {}", err),
+            }
+        }
+
+        Leaderboard { version: current_version(), entries: Vec::new() }
+    }
+
+    pub fn save(&self, ctx: &Context, challenge_id: &str) -> GameResult {
+        let file = filesystem::user_create(ctx, Leaderboard::filename(challenge_id))?;
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+
+    /// Inserts a finished run, returns its place on the board or None if it didn't make it.
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let rank = self.entries.iter().position(|e| entry.millis() < e.millis()).unwrap_or(self.entries.len());
+        if rank >= Leaderboard::MAX_ENTRIES {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(Leaderboard::MAX_ENTRIES);
+
+        Some(rank)
+    }
+
+    pub fn best(&self) -> Option<&LeaderboardEntry> {
+        self.entries.first()
+    }
+}
+
+#[test]
+fn test_leaderboard() {
+    let entry = |ticks: u32, tps: u32| LeaderboardEntry { ticks, tps, timestamp: 0, assist: 0, splits: Vec::new() };
+    let mut leaderboard = Leaderboard { version: current_version(), entries: Vec::new() };
+
+    assert_eq!(leaderboard.insert(entry(600, 60)), Some(0));
+    assert_eq!(leaderboard.insert(entry(550, 50)), Some(1));
+    assert_eq!(leaderboard.insert(entry(300, 60)), Some(0));
+    assert_eq!(leaderboard.best().map(|e| e.ticks), Some(300));
+
+    for _ in 0..10 {
+        leaderboard.insert(entry(100, 60));
+    }
+    assert_eq!(leaderboard.entries.len(), 10);
+    assert_eq!(leaderboard.insert(entry(1000, 60)), None);
+}
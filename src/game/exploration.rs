@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+
+use crate::framework::error::GameResult;
+
+/// Tiles of a stage that were seen by the player, one bit per tile.
+#[derive(Clone)]
+pub struct ExploredStage {
+    pub width: u16,
+    pub height: u16,
+    tiles: Vec<u8>,
+}
+
+impl ExploredStage {
+    pub fn new(width: u16, height: u16) -> ExploredStage {
+        ExploredStage { width, height, tiles: vec![0; width as usize * height as usize / 8 + 1] }
+    }
+
+    pub fn is_explored(&self, x: usize, y: usize) -> bool {
+        if x >= self.width as usize || y >= self.height as usize {
+            return false;
+        }
+
+        let idx = y * self.width as usize + x;
+        self.tiles[idx / 8] & (1 << (idx % 8)) != 0
+    }
+
+    /// Marks tiles within given rectangle as explored, the parts outside of the stage are ignored.
+    pub fn reveal(&mut self, left: isize, top: isize, right: isize, bottom: isize) {
+        let (left, right) = (left.max(0) as usize, right.clamp(0, self.width as isize) as usize);
+        let (top, bottom) = (top.max(0) as usize, bottom.clamp(0, self.height as isize) as usize);
+
+        for y in top..bottom {
+            for x in left..right {
+                let idx = y * self.width as usize + x;
+                self.tiles[idx / 8] |= 1 << (idx % 8);
+            }
+        }
+    }
+}
+
+/// Explored parts of every stage visited during a playthrough, saved in the profile.
+#[derive(Clone, Default)]
+pub struct ExplorationMap {
+    stages: BTreeMap<u16, ExploredStage>,
+}
+
+impl ExplorationMap {
+    pub fn clear(&mut self) {
+        self.stages.clear();
+    }
+
+    pub fn get(&self, stage_id: u16) -> Option<&ExploredStage> {
+        self.stages.get(&stage_id)
+    }
+
+    /// Returns explored tiles of a stage, starting over if the stage was resized since the last visit.
+    pub fn stage_mut(&mut self, stage_id: u16, width: u16, height: u16) -> &mut ExploredStage {
+        let explored = self.stages.entry(stage_id).or_insert_with(|| ExploredStage::new(width, height));
+        if explored.width != width || explored.height != height {
+            *explored = ExploredStage::new(width, height);
+        }
+
+        explored
+    }
+
+    /// IDs of visited stages, in ascending order.
+    pub fn visited_stages(&self) -> impl Iterator<Item = u16> + '_ {
+        self.stages.keys().copied()
+    }
+
+    pub fn write<W: io::Write>(&self, mut data: W) -> GameResult {
+        data.write_u16::<LE>(self.stages.len() as u16)?;
+
+        for (&stage_id, explored) in self.stages.iter() {
+            data.write_u16::<LE>(stage_id)?;
+            data.write_u16::<LE>(explored.width)?;
+            data.write_u16::<LE>(explored.height)?;
+            data.write_all(&explored.tiles)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read<R: io::Read>(mut data: R) -> GameResult<ExplorationMap> {
+        let mut map = ExplorationMap::default();
+        let count = data.read_u16::<LE>()?;
+
+        for _ in 0..count {
+            let stage_id = data.read_u16::<LE>()?;
+            let mut explored = ExploredStage::new(data.read_u16::<LE>()?, data.read_u16::<LE>()?);
+            data.read_exact(&mut explored.tiles)?;
+
+            map.stages.insert(stage_id, explored);
+        }
+
+        Ok(map)
+    }
+}
+
+#[test]
+fn test_exploration_map() {
+    let mut map = ExplorationMap::default();
+    map.stage_mut(12, 20, 10).reveal(-2, 8, 3, 14);
+    map.stage_mut(3, 5, 5).reveal(4, 4, 5, 5);
+
+    let mut data = Vec::new();
+    map.write(&mut data).unwrap();
+    let map = ExplorationMap::read(data.as_slice()).unwrap();
+
+    assert_eq!(map.visited_stages().collect::<Vec<_>>(), vec![3, 12]);
+
+    let stage = map.get(12).unwrap();
+    assert!(stage.is_explored(0, 8) && stage.is_explored(2, 9));
+    assert!(!stage.is_explored(3, 9) && !stage.is_explored(0, 7) && !stage.is_explored(0, 10));
+    assert!(map.get(3).unwrap().is_explored(4, 4));
+}
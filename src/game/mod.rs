@@ -18,7 +18,10 @@ use crate::graphics::texture_set::{G_MAG, I_MAG};
 use crate::scene::loading_scene::LoadingScene;
 use crate::scene::Scene;
 
+pub mod capture;
 pub mod caret;
+pub mod challenge;
+pub mod exploration;
 pub mod filesystem_container;
 pub mod frame;
 pub mod inventory;
@@ -27,8 +30,10 @@ pub mod npc;
 pub mod physics;
 pub mod player;
 pub mod profile;
+pub mod run_timer;
 pub mod scripting;
 pub mod settings;
+pub mod speedrun;
 pub mod shared_game_state;
 pub mod stage;
 pub mod weapon;
@@ -36,6 +41,7 @@ pub mod weapon;
 pub struct LaunchOptions {
     pub server_mode: bool,
     pub editor: bool,
+    pub headless_render: bool,
 }
 
 lazy_static! {
@@ -200,6 +206,13 @@ impl Game {
                 )?;
             }
 
+            if state_ref.frame_capture.is_capturing() {
+                if let Err(err) = state_ref.frame_capture.capture(ctx, &mut state_ref.sound_manager) {
+                    log::error!("Frame capture failed: {}", err);
+                    state_ref.stop_capture(ctx)?;
+                }
+            }
+
             if state_ref.settings.fps_counter {
                 self.fps.act(state_ref, ctx, self.start_time.elapsed().as_nanos())?;
             }
@@ -303,6 +316,11 @@ pub fn init(options: LaunchOptions) -> GameResult {
         context.headless = true;
     }
 
+    if options.headless_render {
+        log::info!("Running without a window, using the software renderer...");
+        context.headless_render = true;
+    }
+
     let mut game = Box::pin(Game::new(&mut context)?);
     #[cfg(feature = "scripting-lua")]
     unsafe {
@@ -7,6 +7,7 @@ use crate::game::npc::NPC;
 use crate::game::player::Player;
 use crate::game::shared_game_state::SharedGameState;
 use crate::game::stage::Stage;
+use crate::sound::mixer::MixerBus;
 use crate::util::rng::RNG;
 
 impl NPC {
@@ -280,8 +281,8 @@ impl BossNPC {
                     self.parts[0].action_counter = 0;
                     self.parts[11].npc_flags.set_shootable(true);
 
-                    state.sound_manager.loop_sfx_freq(40, 1000.0 / 2205.0);
-                    state.sound_manager.loop_sfx_freq(41, 1100.0 / 2205.0);
+                    state.sound_manager.loop_sfx_freq(40, 1000.0 / 2205.0, MixerBus::Gameplay);
+                    state.sound_manager.loop_sfx_freq(41, 1100.0 / 2205.0, MixerBus::Gameplay);
                     state.quake_counter = 100;
                     state.quake_rumble_counter = 100;
                     state.npc_super_pos.1 = 1;
@@ -0,0 +1,173 @@
+use crate::common::{interpolate_fix9_scale, Direction};
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::game::frame::Frame;
+use crate::game::npc::boss::BossNPC;
+use crate::game::npc::list::NPCList;
+use crate::game::npc::{NPCFlag, NPC};
+use crate::game::player::Player;
+use crate::game::shared_game_state::SharedGameState;
+
+/// Boss type defined in `npc_definitions.json`.
+///
+/// Every part is an NPC of given type, driven by the behaviors from its NPC definition. The boss stays hidden
+/// until the main part's `action_num` is changed with `<BOA`, which is then shared with the other parts, so
+/// definitions can react to it using `actions`.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct BossDefinition {
+    pub boss_type: u16,
+    /// Initial position of the main part, in tiles.
+    #[serde(default)]
+    pub position: Option<[i32; 2]>,
+    /// Event executed when the main part gets killed.
+    #[serde(default)]
+    pub event_num: u16,
+    pub parts: Vec<BossPartDefinition>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct BossPartDefinition {
+    pub npc_type: u16,
+    /// Position relative to the main part in pixels, mirrored horizontally when the main part faces right.
+    #[serde(default)]
+    pub offset: [i32; 2],
+    /// Whether the part is attached to the main part, ignored for the main part.
+    #[serde(default = "default_follow")]
+    pub follow: bool,
+    /// Redirects the damage taken by this part to the main part.
+    #[serde(default)]
+    pub damage_boss: bool,
+    /// Overrides the hurt sound from NPC table.
+    #[serde(default)]
+    pub hurt_sound: Option<u8>,
+    /// Overrides the death sound from NPC table.
+    #[serde(default)]
+    pub death_sound: Option<u8>,
+}
+
+fn default_follow() -> bool {
+    true
+}
+
+impl BossNPC {
+    pub(crate) fn tick_custom(
+        &mut self,
+        state: &mut SharedGameState,
+        players: [&mut Player; 2],
+        npc_list: &NPCList,
+    ) -> GameResult {
+        let def = if let Some(def) = state.npc_table.get_boss_definition(self.boss_type) {
+            def
+        } else {
+            return Ok(());
+        };
+
+        let part_count = def.parts.len().min(self.parts.len());
+
+        if self.parts[0].action_num == 0 {
+            if !self.parts[0].cond.hidden() {
+                self.init_custom(state, &def.parts[..part_count], def.position);
+            }
+
+            return Ok(());
+        }
+
+        if self.parts[0].cond.hidden() {
+            for (part, part_def) in self.parts.iter_mut().zip(def.parts.iter()) {
+                if let Some(entry) = state.npc_table.get_entry(part_def.npc_type) {
+                    part.npc_flags = NPCFlag(entry.npc_flags.0);
+                    part.damage = entry.damage as u16;
+                }
+
+                part.cond.set_hidden(false);
+                part.cond.set_alive(true);
+            }
+
+            if def.event_num != 0 {
+                self.parts[0].event_num = def.event_num;
+                self.parts[0].npc_flags.set_event_when_killed(true);
+            }
+        }
+
+        let action_num = self.parts[0].action_num;
+        for part in self.parts[..part_count].iter_mut().filter(|part| part.cond.alive()) {
+            part.action_num = action_num;
+            part.tick_custom(state, &players, npc_list)?;
+        }
+
+        let (main, parts) = self.parts.split_at_mut(1);
+        let main = &main[0];
+        for (part, part_def) in parts.iter_mut().zip(def.parts.iter().skip(1)) {
+            if !part_def.follow {
+                continue;
+            }
+
+            let offset_x = if main.direction == Direction::Right { -part_def.offset[0] } else { part_def.offset[0] };
+
+            part.x = main.x + offset_x * 0x200;
+            part.y = main.y + part_def.offset[1] * 0x200;
+            part.direction = main.direction;
+        }
+
+        Ok(())
+    }
+
+    fn init_custom(&mut self, state: &SharedGameState, part_defs: &[BossPartDefinition], position: Option<[i32; 2]>) {
+        for (i, part_def) in part_defs.iter().enumerate() {
+            let mut npc = NPC::create(part_def.npc_type, &state.npc_table);
+            npc.rng = self.parts[i].rng.clone();
+            npc.npc_flags = NPCFlag(0);
+            npc.damage = 0;
+            npc.cond.set_drs_boss(true);
+            npc.cond.set_damage_boss(part_def.damage_boss);
+            npc.cond.set_hidden(true);
+            npc.cond.set_alive(i == 0);
+
+            let entry = state.npc_table.get_entry(part_def.npc_type);
+            self.hurt_sound[i] = part_def.hurt_sound.or_else(|| entry.map(|e| e.hurt_sound)).unwrap_or(0);
+            self.death_sound[i] = part_def.death_sound.or_else(|| entry.map(|e| e.death_sound)).unwrap_or(0);
+
+            self.parts[i] = npc;
+        }
+
+        if let Some([x, y]) = position {
+            let ti = state.tile_size.as_int() * 0x200;
+
+            self.parts[0].x = x * ti;
+            self.parts[0].y = y * ti;
+            self.parts[0].prev_x = self.parts[0].x;
+            self.parts[0].prev_y = self.parts[0].y;
+        }
+    }
+
+    /// Draws the parts using their own spritesheets.
+    pub(crate) fn draw_custom(&self, state: &mut SharedGameState, ctx: &mut Context, frame: &Frame) -> GameResult {
+        for npc in self.parts.iter().rev() {
+            if !npc.cond.alive() || npc.cond.hidden() {
+                continue;
+            }
+
+            let texture_ref = state.npc_table.get_texture_ref(npc.spritesheet_id);
+            let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, &*texture_ref)?;
+
+            let off_x =
+                if npc.direction == Direction::Left { npc.display_bounds.left } else { npc.display_bounds.right }
+                    as i32;
+            let shock = if npc.shock > 0 { (2 * ((npc.shock as i32 / 2) & 1) - 1) as f32 } else { 0.0 };
+            let (frame_x, frame_y) = frame.xy_interpolated(state.frame_time);
+
+            batch.add_rect(
+                interpolate_fix9_scale(npc.prev_x - off_x, npc.x - off_x, state.frame_time) + shock - frame_x,
+                interpolate_fix9_scale(
+                    npc.prev_y - npc.display_bounds.top as i32,
+                    npc.y - npc.display_bounds.top as i32,
+                    state.frame_time,
+                ) - frame_y,
+                &npc.anim_rect,
+            );
+            batch.draw(ctx)?;
+        }
+
+        Ok(())
+    }
+}
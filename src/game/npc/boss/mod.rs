@@ -17,6 +17,7 @@ use crate::game::weapon::bullet::BulletManager;
 pub mod balfrog;
 pub mod ballos;
 pub mod core;
+pub mod custom;
 pub mod heavy_press;
 pub mod ironhead;
 pub mod monster_x;
@@ -83,10 +84,28 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &BulletManager, &mut Fl
         if !self.parts[0].cond.alive() {
             // Kind of hacky but fixes Monster X's damage popup being stuck on screen
             self.parts[0].popup.tick(state, ())?;
+
+            if state.npc_table.has_boss_definition(self.boss_type) {
+                for part in &mut self.parts[1..] {
+                    part.cond.set_alive(false);
+                }
+            }
+
             return Ok(());
         }
 
+        #[allow(unused_mut, unused_assignments)]
+        let mut boss_hook_ran = false;
+        #[cfg(feature = "scripting-lua")]
+        {
+            boss_hook_ran = state.lua.try_run_boss_hook(self.boss_type);
+        }
+
         match self.boss_type {
+            _ if boss_hook_ran => {}
+            _ if state.npc_table.has_boss_definition(self.boss_type) => {
+                self.tick_custom(state, players, npc_list)?;
+            }
             1 => self.tick_b01_omega(state, players, npc_list, bullet_manager, flash),
             2 => self.tick_b02_balfrog(state, players, npc_list),
             3 => self.tick_b03_monster_x(state, players, npc_list, flash),
@@ -114,6 +133,10 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &BulletManager, &mut Fl
     }
 
     fn draw(&self, state: &mut SharedGameState, ctx: &mut Context, frame: &Frame) -> GameResult {
+        if state.npc_table.has_boss_definition(self.boss_type) {
+            return self.draw_custom(state, ctx, frame);
+        }
+
         let batch = state.texture_set.get_or_load_batch(
             ctx,
             &state.constants,
@@ -0,0 +1,487 @@
+//! Data-driven NPC types loaded from `npc_definitions.json`.
+//!
+//! Each definition assigns a table entry and a list of behaviors to one or more NPC ids, allowing mods to add
+//! enemies without writing any code. All positions and velocities use the engine's fixed point units
+//! (`0x200` = 1 pixel), sprite rects and bounds use pixels, same as `npc.tbl`. Multi-part bosses built out of
+//! these NPCs can be defined in the `bosses` list, see [BossDefinition].
+//!
+//! ```json
+//! {
+//!   "npcs": [
+//!     {
+//!       "id": [400, 401],
+//!       "base": 5,
+//!       "table": { "life": 6, "experience": 3, "texture": "Npc/NpcMyEnemy" },
+//!       "behaviors": [
+//!         { "type": "face_player" },
+//!         { "type": "hop", "interval": 40, "vel_x": 256, "vel_y": 1535, "sound": 30 },
+//!         { "type": "gravity" },
+//!         { "type": "clamp_fall_speed" }
+//!       ],
+//!       "animations": {
+//!         "idle": { "frames": [[0, 0, 16, 16]], "frames_right": [[0, 16, 16, 32]] },
+//!         "air": { "frames": [[32, 0, 48, 16]], "frames_right": [[32, 16, 48, 32]] }
+//!       }
+//!     }
+//!   ]
+//! }
+//! ```
+use std::collections::HashMap;
+use std::io;
+
+use crate::common::{Direction, Rect, CDEG_RAD};
+use crate::framework::error::{GameError, GameResult};
+use crate::game::npc::boss::custom::BossDefinition;
+use crate::game::npc::list::NPCList;
+use crate::game::npc::{NPCFlag, NPCTable, NPCTableEntry, NPC};
+use crate::game::player::Player;
+use crate::game::shared_game_state::SharedGameState;
+use crate::util::rng::RNG;
+
+/// Spritesheet ids starting from this value refer to textures registered by NPC definitions.
+pub const CUSTOM_SPRITESHEET_BASE: u16 = 0x100;
+
+#[derive(serde::Deserialize)]
+pub struct NPCDefinitionFile {
+    #[serde(default)]
+    pub npcs: Vec<NPCDefinition>,
+    #[serde(default)]
+    pub bosses: Vec<BossDefinition>,
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(untagged)]
+pub enum NPCIdRange {
+    Single(u16),
+    /// Inclusive range of ids.
+    Range([u16; 2]),
+}
+
+impl NPCIdRange {
+    pub fn iter(&self) -> impl Iterator<Item = u16> {
+        match *self {
+            NPCIdRange::Single(id) => id..=id,
+            NPCIdRange::Range([start, end]) => start..=end,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct NPCDefinition {
+    pub id: NPCIdRange,
+    /// Vanilla NPC type whose table entry is used as a starting point.
+    #[serde(default)]
+    pub base: Option<u16>,
+    #[serde(default)]
+    pub table: NPCTableOverrides,
+    /// Behaviors executed every tick.
+    #[serde(default)]
+    pub behaviors: Vec<NPCBehavior>,
+    /// Behaviors executed only while `action_num` has given value, which can be changed with `<ANP` or `<BOA`.
+    #[serde(default)]
+    pub actions: HashMap<u16, Vec<NPCBehavior>>,
+    #[serde(default)]
+    pub animations: NPCAnimations,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct NPCTableOverrides {
+    pub flags: Option<u16>,
+    pub life: Option<u16>,
+    pub spritesheet_id: Option<u16>,
+    /// Path to a texture, takes priority over `spritesheet_id`.
+    pub texture: Option<String>,
+    pub death_sound: Option<u8>,
+    pub hurt_sound: Option<u8>,
+    pub size: Option<u8>,
+    pub experience: Option<u32>,
+    pub damage: Option<u32>,
+    pub display_bounds: Option<Rect<u8>>,
+    pub hit_bounds: Option<Rect<u8>>,
+}
+
+/// Reusable pieces of NPC logic, executed in order once per tick.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NPCBehavior {
+    /// Turns towards the closest player.
+    FacePlayer,
+    /// Accelerates downwards.
+    Gravity {
+        #[serde(default = "default_gravity")]
+        accel: i32,
+    },
+    /// Limits the falling speed to the vanilla maximum.
+    ClampFallSpeed,
+    /// Moves horizontally in the facing direction.
+    Walk {
+        speed: i32,
+        #[serde(default = "default_true")]
+        turn_at_walls: bool,
+    },
+    /// Accelerates towards the closest player.
+    Chase {
+        accel: i32,
+        max_speed: i32,
+        #[serde(default)]
+        vertical: bool,
+    },
+    /// Stops on the ground and jumps every `interval` ticks. Uses `action_counter`.
+    Hop {
+        interval: u16,
+        #[serde(default)]
+        vel_x: i32,
+        vel_y: i32,
+        #[serde(default)]
+        sound: Option<u8>,
+    },
+    /// Spawns a projectile NPC every `interval` ticks. Uses `action_counter2`.
+    Shoot {
+        npc_type: u16,
+        interval: u16,
+        speed: i32,
+        /// Aims at the closest player instead of shooting straight ahead.
+        #[serde(default = "default_true")]
+        aim: bool,
+        /// Random angle deviation, in degrees.
+        #[serde(default)]
+        spread: i32,
+        /// Maximum horizontal distance to the player at which the NPC shoots.
+        #[serde(default)]
+        range: Option<i32>,
+        #[serde(default)]
+        sound: Option<u8>,
+    },
+    /// Removes the NPC after given amount of ticks. Uses `action_counter3`.
+    Lifetime {
+        ticks: u16,
+        #[serde(default)]
+        smoke: bool,
+        #[serde(default)]
+        drop_experience: bool,
+    },
+}
+
+fn default_gravity() -> i32 {
+    0x40
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_anim_ticks() -> u16 {
+    4
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct NPCAnimation {
+    /// Frames used when facing left.
+    pub frames: Vec<Rect<u16>>,
+    /// Frames used when facing right, left facing frames are used if empty.
+    #[serde(default)]
+    pub frames_right: Vec<Rect<u16>>,
+    #[serde(default = "default_anim_ticks")]
+    pub ticks: u16,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct NPCAnimations {
+    pub idle: Option<NPCAnimation>,
+    pub walk: Option<NPCAnimation>,
+    pub air: Option<NPCAnimation>,
+    pub hurt: Option<NPCAnimation>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum AnimationState {
+    Idle,
+    Walk,
+    Air,
+    Hurt,
+}
+
+impl NPCAnimations {
+    fn get(&self, anim_state: AnimationState) -> Option<&NPCAnimation> {
+        match anim_state {
+            AnimationState::Idle => self.idle.as_ref(),
+            AnimationState::Walk => self.walk.as_ref(),
+            AnimationState::Air => self.air.as_ref(),
+            AnimationState::Hurt => self.hurt.as_ref(),
+        }
+    }
+}
+
+impl NPCTable {
+    /// Loads NPC definitions and applies their table entries, replacing existing ones with the same ids.
+    pub fn load_definitions<R: io::Read>(&mut self, data: R) -> GameResult {
+        let file: NPCDefinitionFile = serde_json::from_reader(data)
+            .map_err(|err| GameError::ResourceLoadError(format!("Failed to parse NPC definitions: {}", err)))?;
+
+        for def in file.npcs {
+            let mut entry = def.base.and_then(|base| self.get_entry(base).cloned()).unwrap_or_else(|| NPCTableEntry {
+                npc_flags: NPCFlag(0),
+                life: 0,
+                spritesheet_id: 0,
+                death_sound: 0,
+                hurt_sound: 0,
+                size: 0,
+                experience: 0,
+                damage: 0,
+                display_bounds: Rect::new(0, 0, 0, 0),
+                hit_bounds: Rect::new(0, 0, 0, 0),
+            });
+
+            let overrides = &def.table;
+            if let Some(flags) = overrides.flags {
+                entry.npc_flags = NPCFlag(flags);
+            }
+            if let Some(life) = overrides.life {
+                entry.life = life;
+            }
+            if let Some(spritesheet_id) = overrides.spritesheet_id {
+                entry.spritesheet_id = spritesheet_id;
+            }
+            if let Some(texture) = &overrides.texture {
+                entry.spritesheet_id = self.register_custom_texture(texture);
+            }
+            if let Some(death_sound) = overrides.death_sound {
+                entry.death_sound = death_sound;
+            }
+            if let Some(hurt_sound) = overrides.hurt_sound {
+                entry.hurt_sound = hurt_sound;
+            }
+            if let Some(size) = overrides.size {
+                entry.size = size;
+            }
+            if let Some(experience) = overrides.experience {
+                entry.experience = experience;
+            }
+            if let Some(damage) = overrides.damage {
+                entry.damage = damage;
+            }
+            if let Some(display_bounds) = overrides.display_bounds {
+                entry.display_bounds = display_bounds;
+            }
+            if let Some(hit_bounds) = overrides.hit_bounds {
+                entry.hit_bounds = hit_bounds;
+            }
+
+            let def = std::rc::Rc::new(def);
+            for npc_type in def.id.iter() {
+                self.set_entry(npc_type, entry.clone());
+                self.definitions.insert(npc_type, def.clone());
+            }
+        }
+
+        for def in file.bosses {
+            self.boss_definitions.insert(def.boss_type, std::rc::Rc::new(def));
+        }
+
+        log::info!(
+            "Loaded {} custom NPC types and {} custom boss types.",
+            self.definitions.len(),
+            self.boss_definitions.len()
+        );
+
+        Ok(())
+    }
+}
+
+impl NPC {
+    pub(crate) fn tick_custom(
+        &mut self,
+        state: &mut SharedGameState,
+        players: &[&mut Player; 2],
+        npc_list: &NPCList,
+    ) -> GameResult {
+        let def = if let Some(def) = state.npc_table.get_definition(self.npc_type) {
+            def
+        } else {
+            return Ok(());
+        };
+
+        let player = self.get_closest_player_ref(players);
+        let action_behaviors = def.actions.get(&self.action_num).into_iter().flatten();
+
+        for behavior in def.behaviors.iter().chain(action_behaviors) {
+            match *behavior {
+                NPCBehavior::FacePlayer => {
+                    self.face_player(player);
+                }
+                NPCBehavior::Gravity { accel } => {
+                    self.vel_y += accel;
+                }
+                NPCBehavior::ClampFallSpeed => {
+                    self.clamp_fall_speed();
+                }
+                NPCBehavior::Walk { speed, turn_at_walls } => {
+                    if turn_at_walls {
+                        if self.flags.hit_left_wall() {
+                            self.direction = Direction::Right;
+                        } else if self.flags.hit_right_wall() {
+                            self.direction = Direction::Left;
+                        }
+                    }
+
+                    self.vel_x = self.direction.vector_x() * speed;
+                }
+                NPCBehavior::Chase { accel, max_speed, vertical } => {
+                    self.vel_x += if self.x < player.x { accel } else { -accel };
+                    self.vel_x = self.vel_x.clamp(-max_speed, max_speed);
+
+                    if vertical {
+                        self.vel_y += if self.y < player.y { accel } else { -accel };
+                        self.vel_y = self.vel_y.clamp(-max_speed, max_speed);
+                    }
+                }
+                NPCBehavior::Hop { interval, vel_x, vel_y, sound } => {
+                    if self.flags.hit_bottom_wall() {
+                        self.vel_x = 0;
+                        self.action_counter += 1;
+
+                        if self.action_counter > interval {
+                            self.action_counter = 0;
+                            self.vel_x = self.direction.vector_x() * vel_x;
+                            self.vel_y = -vel_y;
+
+                            if let Some(sound) = sound {
+                                state.sound_manager.play_sfx(sound);
+                            }
+                        }
+                    }
+                }
+                NPCBehavior::Shoot { npc_type, interval, speed, aim, spread, range, sound } => {
+                    self.action_counter2 += 1;
+                    if self.action_counter2 < interval {
+                        continue;
+                    }
+
+                    self.action_counter2 = 0;
+
+                    if let Some(range) = range {
+                        if (self.x - player.x).abs() > range {
+                            continue;
+                        }
+                    }
+
+                    let mut angle = if aim {
+                        f64::atan2((player.y - self.y) as f64, (player.x - self.x) as f64)
+                    } else if self.direction == Direction::Left {
+                        std::f64::consts::PI
+                    } else {
+                        0.0
+                    };
+
+                    if spread > 0 {
+                        angle += self.rng.range(-spread..spread) as f64 * CDEG_RAD;
+                    }
+
+                    let mut npc = NPC::create(npc_type, &state.npc_table);
+                    npc.cond.set_alive(true);
+                    npc.x = self.x;
+                    npc.y = self.y;
+                    npc.direction = self.direction;
+                    npc.vel_x = (angle.cos() * speed as f64) as i32;
+                    npc.vel_y = (angle.sin() * speed as f64) as i32;
+
+                    let _ = npc_list.spawn(0x100, npc);
+
+                    if let Some(sound) = sound {
+                        state.sound_manager.play_sfx(sound);
+                    }
+                }
+                NPCBehavior::Lifetime { ticks, smoke, drop_experience } => {
+                    self.action_counter3 += 1;
+                    if self.action_counter3 < ticks {
+                        continue;
+                    }
+
+                    if smoke {
+                        npc_list.create_death_smoke(
+                            self.x,
+                            self.y,
+                            self.display_bounds.right as usize,
+                            4,
+                            state,
+                            &self.rng,
+                        );
+                    }
+
+                    if drop_experience {
+                        self.create_xp_drop(state, npc_list);
+                    }
+
+                    self.cond.set_alive(false);
+                    return Ok(());
+                }
+            }
+        }
+
+        self.x += self.vel_x;
+        self.y += self.vel_y;
+
+        let anims = &def.animations;
+        let anim_state = if self.shock > 0 && anims.hurt.is_some() {
+            AnimationState::Hurt
+        } else if !self.flags.hit_bottom_wall() && anims.air.is_some() {
+            AnimationState::Air
+        } else if self.vel_x != 0 && anims.walk.is_some() {
+            AnimationState::Walk
+        } else {
+            AnimationState::Idle
+        };
+
+        if let Some(anim) = anims.get(anim_state) {
+            let frames = if self.direction == Direction::Right && !anim.frames_right.is_empty() {
+                &anim.frames_right
+            } else {
+                &anim.frames
+            };
+
+            if !frames.is_empty() {
+                self.animate(anim.ticks, 0, frames.len() as u16 - 1);
+                self.anim_rect = frames[self.anim_num as usize % frames.len()];
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_npc_definitions() {
+    let json = r#"{
+        "npcs": [
+            {
+                "id": [400, 402],
+                "table": { "life": 12, "damage": 3, "texture": "Npc/NpcCustom", "hit_bounds": [4, 4, 4, 4] },
+                "behaviors": [{ "type": "gravity" }, { "type": "walk", "speed": 256 }],
+                "animations": { "idle": { "frames": [[0, 0, 16, 16]] } }
+            }
+        ],
+        "bosses": [
+            { "boss_type": 10, "parts": [{ "npc_type": 400 }, { "npc_type": 401, "offset": [-16, 0] }] }
+        ]
+    }"#;
+
+    let mut table = NPCTable::new();
+    table.load_definitions(json.as_bytes()).unwrap();
+
+    let entry = table.get_entry(401).unwrap();
+    assert_eq!(entry.life, 12);
+    assert_eq!(entry.damage, 3);
+    assert_eq!(entry.spritesheet_id, CUSTOM_SPRITESHEET_BASE);
+    assert_eq!(&*table.get_texture_ref(entry.spritesheet_id), "Npc/NpcCustom");
+    assert!(table.get_entry(403).is_none());
+
+    let def = table.get_definition(402).unwrap();
+    assert_eq!(def.behaviors.len(), 2);
+    assert!(matches!(def.behaviors[0], NPCBehavior::Gravity { accel: 0x40 }));
+    assert!(table.get_definition(399).is_none());
+
+    let boss = table.get_boss_definition(10).unwrap();
+    assert_eq!(boss.parts.len(), 2);
+    assert!(boss.parts[1].follow);
+    assert!(!table.has_boss_definition(9));
+}
@@ -1,19 +1,25 @@
 use std::cell::{Cell, UnsafeCell};
-use std::mem::{MaybeUninit, transmute};
 
 use crate::framework::error::{GameError, GameResult};
 use crate::game::npc::NPC;
 
-/// Maximum capacity of NPCList
-const NPC_LIST_MAX_CAP: usize = 512;
+/// Default maximum capacity of NPCList, same as in the original game.
+pub const NPC_LIST_DEFAULT_MAX_CAP: u16 = 512;
+
+/// Amount of NPC slots allocated at once when the list grows.
+const NPC_LIST_CHUNK_SIZE: usize = 256;
 
 /// A data structure for storing an NPC list for current stage.
 /// Provides multiple mutable references to NPC objects with internal sanity checks and lifetime bounds.
+///
+/// Slots are allocated in fixed size chunks that never move in memory, which allows the list to grow up to
+/// its maximum capacity while references to already allocated NPCs are alive.
 pub struct NPCList {
     // UnsafeCell is required because we do mutable aliasing (ik, discouraged), prevents Rust/LLVM
     // from theoretically performing some optimizations that might break the code.
-    npcs: Box<UnsafeCell<[NPC; NPC_LIST_MAX_CAP]>>,
+    chunks: UnsafeCell<Vec<Box<[NPC]>>>,
     max_npc: Cell<u16>,
+    max_capacity: u16,
     seed: i32,
 }
 
@@ -21,25 +27,13 @@ pub struct NPCList {
 impl NPCList {
     pub fn new() -> NPCList {
         let map = NPCList {
-            npcs: Box::new(UnsafeCell::new(unsafe {
-                const PART: MaybeUninit<NPC> = MaybeUninit::uninit();
-                let mut parts_uninit: [MaybeUninit<NPC>; NPC_LIST_MAX_CAP] = [PART; NPC_LIST_MAX_CAP];
-
-                for part in &mut parts_uninit {
-                    part.write(NPC::empty());
-                }
-
-                transmute(parts_uninit)
-            })),
+            chunks: UnsafeCell::new(Vec::new()),
             max_npc: Cell::new(0),
+            max_capacity: NPC_LIST_DEFAULT_MAX_CAP,
             seed: 0,
         };
 
-        unsafe {
-            for (idx, npc_ref) in map.npcs_mut().iter_mut().enumerate() {
-                npc_ref.id = idx as u16;
-            }
-        }
+        map.allocate_slots(NPC_LIST_DEFAULT_MAX_CAP as usize);
 
         map
     }
@@ -48,16 +42,24 @@ impl NPCList {
         self.seed = seed;
     }
 
+    /// Sets the maximum amount of NPC slots, slots beyond the vanilla limit are allocated as they are needed.
+    /// Cannot be lower than the amount of already allocated slots.
+    pub fn set_max_capacity(&mut self, max_capacity: u16) {
+        self.max_capacity = max_capacity.max(self.allocated_slots() as u16);
+    }
+
     /// Inserts NPC into list in first available slot after given ID.
     pub fn spawn(&self, min_id: u16, mut npc: NPC) -> GameResult {
-        let npc_len = unsafe { self.npcs().len() };
-
-        if min_id as usize >= npc_len {
+        if min_id >= self.max_capacity {
             return Err(GameError::InvalidValue("NPC ID is out of bounds".to_string()));
         }
 
-        for id in min_id..(npc_len as u16) {
-            let npc_ref = unsafe { self.npcs_mut().get_unchecked_mut(id as usize) };
+        for id in min_id..self.max_capacity {
+            if id as usize >= self.allocated_slots() {
+                self.allocate_slots(id as usize + 1);
+            }
+
+            let npc_ref = unsafe { self.slot_unchecked(id as usize) };
 
             if !npc_ref.cond.alive() {
                 npc.id = id;
@@ -83,12 +85,14 @@ impl NPCList {
 
     /// Inserts the NPC at specified slot.
     pub fn spawn_at_slot(&self, id: u16, mut npc: NPC) -> GameResult {
-        let npc_len = unsafe { self.npcs().len() };
-
-        if id as usize >= npc_len {
+        if id >= self.max_capacity {
             return Err(GameError::InvalidValue("NPC ID is out of bounds".to_string()));
         }
 
+        if id as usize >= self.allocated_slots() {
+            self.allocate_slots(id as usize + 1);
+        }
+
         npc.id = id;
 
         if npc.tsc_direction == 0 {
@@ -98,7 +102,7 @@ impl NPCList {
         npc.init_rng(self.seed);
 
         unsafe {
-            let npc_ref = self.npcs_mut().get_unchecked_mut(id as usize);
+            let npc_ref = self.slot_unchecked(id as usize);
             *npc_ref = npc;
         }
 
@@ -111,7 +115,11 @@ impl NPCList {
 
     /// Returns a mutable reference to NPC from this list.
     pub fn get_npc<'a: 'b, 'b>(&'a self, id: usize) -> Option<&'b mut NPC> {
-        unsafe { self.npcs_mut().get_mut(id) }
+        if id >= self.allocated_slots() {
+            return None;
+        }
+
+        Some(unsafe { self.slot_unchecked(id) })
     }
 
     /// Returns an iterator that iterates over allocated (not up to it's capacity) NPC slots.
@@ -141,15 +149,37 @@ impl NPCList {
 
     /// Returns maximum capacity of this NPC list.
     pub fn max_capacity(&self) -> u16 {
-        NPC_LIST_MAX_CAP as u16
+        self.max_capacity
     }
 
-    unsafe fn npcs<'a: 'b, 'b>(&'a self) -> &'b [NPC; NPC_LIST_MAX_CAP] {
-        &*self.npcs.get()
+    /// Returns the amount of slots that currently have memory allocated for them.
+    pub fn allocated_slots(&self) -> usize {
+        unsafe { (*self.chunks.get()).len() * NPC_LIST_CHUNK_SIZE }
     }
 
-    unsafe fn npcs_mut<'a: 'b, 'b>(&'a self) -> &'b mut [NPC; NPC_LIST_MAX_CAP] {
-        &mut *self.npcs.get()
+    /// Allocates chunks until at least given amount of slots is available.
+    fn allocate_slots(&self, count: usize) {
+        let chunks = unsafe { &mut *self.chunks.get() };
+
+        while chunks.len() * NPC_LIST_CHUNK_SIZE < count {
+            let base_id = chunks.len() * NPC_LIST_CHUNK_SIZE;
+            let chunk = (0..NPC_LIST_CHUNK_SIZE)
+                .map(|i| {
+                    let mut npc = NPC::empty();
+                    npc.id = (base_id + i) as u16;
+                    npc
+                })
+                .collect::<Vec<_>>();
+
+            chunks.push(chunk.into_boxed_slice());
+        }
+    }
+
+    /// Caller has to make sure the slot has been allocated.
+    unsafe fn slot_unchecked<'a: 'b, 'b>(&'a self, id: usize) -> &'b mut NPC {
+        let chunks = &mut *self.chunks.get();
+
+        chunks.get_unchecked_mut(id / NPC_LIST_CHUNK_SIZE).get_unchecked_mut(id % NPC_LIST_CHUNK_SIZE)
     }
 }
 
@@ -172,7 +202,7 @@ impl<'a> Iterator for NPCListMutableIterator<'a> {
             return None;
         }
 
-        let item = unsafe { self.map.npcs_mut().get_mut(self.index as usize) };
+        let item = self.map.get_npc(self.index as usize);
         self.index += 1;
 
         item
@@ -199,7 +229,7 @@ impl<'a> Iterator for NPCListMutableAliveIterator<'a> {
                 return None;
             }
 
-            let item = unsafe { self.map.npcs_mut().get_mut(self.index as usize) };
+            let item = self.map.get_npc(self.index as usize);
             self.index += 1;
 
             match item {
@@ -260,7 +290,7 @@ pub fn test_npc_list() -> GameResult {
 
         assert_eq!(map.iter_alive().count(), 22);
 
-        assert!(map.spawn((NPC_LIST_MAX_CAP + 1) as u16, npc.clone()).is_err());
+        assert!(map.spawn(NPC_LIST_DEFAULT_MAX_CAP + 1, npc.clone()).is_err());
 
         map.clear();
         assert_eq!(map.iter_alive().count(), 0);
@@ -272,5 +302,22 @@ pub fn test_npc_list() -> GameResult {
         assert!(map.spawn(0, npc.clone()).is_err());
     }
 
+    {
+        let mut map = Box::new(NPCList::new());
+        map.set_max_capacity(2000);
+
+        assert_eq!(map.allocated_slots(), NPC_LIST_DEFAULT_MAX_CAP as usize);
+        assert!(map.get_npc(1500).is_none());
+
+        map.spawn(1500, npc.clone())?;
+        assert_eq!(map.get_npc(1500).unwrap().id, 1500);
+        assert_eq!(map.current_capacity(), 1501);
+
+        map.spawn(1500, npc.clone())?;
+        assert_eq!(map.get_npc(1501).unwrap().id, 1501);
+
+        assert!(map.spawn(2000, npc.clone()).is_err());
+    }
+
     Ok(())
 }
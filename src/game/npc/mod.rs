@@ -1,4 +1,5 @@
 use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
 use std::io;
 use std::io::Cursor;
 use std::ops::Deref;
@@ -16,7 +17,9 @@ use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::game::frame::Frame;
+use crate::game::npc::boss::custom::BossDefinition;
 use crate::game::npc::boss::BossNPC;
+use crate::game::npc::custom::{CUSTOM_SPRITESHEET_BASE, NPCDefinition};
 use crate::game::npc::list::NPCList;
 use crate::game::physics::PhysicalEntity;
 use crate::game::player::Player;
@@ -27,7 +30,10 @@ use crate::util::rng::Xoroshiro32PlusPlus;
 
 pub mod ai;
 pub mod boss;
+pub mod custom;
 pub mod list;
+#[cfg(test)]
+mod trace;
 pub mod utils;
 
 bitfield! {
@@ -251,6 +257,7 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &mut BulletManager, &mu
 
         match self.npc_type {
             _ if npc_hook_ran => Ok(()),
+            _ if state.npc_table.has_definition(self.npc_type) => self.tick_custom(state, &players, npc_list),
             0 => self.tick_n000_null(),
             1 => self.tick_n001_experience(state, stage),
             2 => self.tick_n002_behemoth(state, npc_list),
@@ -799,10 +806,11 @@ impl PhysicalEntity for NPC {
     }
 }
 
+#[derive(Clone)]
 pub struct NPCTableEntry {
     pub npc_flags: NPCFlag,
     pub life: u16,
-    pub spritesheet_id: u8,
+    pub spritesheet_id: u16,
     pub death_sound: u8,
     pub hurt_sound: u8,
     pub size: u8,
@@ -814,13 +822,22 @@ pub struct NPCTableEntry {
 
 pub struct NPCTable {
     entries: Vec<NPCTableEntry>,
+    definitions: HashMap<u16, Rc<NPCDefinition>>,
+    boss_definitions: HashMap<u16, Rc<BossDefinition>>,
+    custom_textures: Vec<String>,
     pub stage_textures: Rc<RefCell<StageTexturePaths>>,
 }
 
 impl NPCTable {
     #[allow(clippy::new_without_default)]
     pub fn new() -> NPCTable {
-        NPCTable { entries: Vec::new(), stage_textures: Rc::new(RefCell::new(StageTexturePaths::new())) }
+        NPCTable {
+            entries: Vec::new(),
+            definitions: HashMap::new(),
+            boss_definitions: HashMap::new(),
+            custom_textures: Vec::new(),
+            stage_textures: Rc::new(RefCell::new(StageTexturePaths::new())),
+        }
     }
 
     pub fn load_from<R: io::Read>(mut data: R) -> GameResult<NPCTable> {
@@ -856,7 +873,7 @@ impl NPCTable {
         }
 
         for npc in &mut table.entries {
-            npc.spritesheet_id = f.read_u8()?;
+            npc.spritesheet_id = f.read_u8()? as u16;
         }
 
         for npc in &mut table.entries {
@@ -900,6 +917,58 @@ impl NPCTable {
         self.entries.get(npc_type as usize)
     }
 
+    /// Replaces the entry for given NPC type, growing the table if needed.
+    pub fn set_entry(&mut self, npc_type: u16, entry: NPCTableEntry) {
+        let idx = npc_type as usize;
+        while self.entries.len() <= idx {
+            self.entries.push(NPCTableEntry {
+                npc_flags: NPCFlag(0),
+                life: 0,
+                spritesheet_id: 0,
+                death_sound: 0,
+                hurt_sound: 0,
+                size: 0,
+                experience: 0,
+                damage: 0,
+                display_bounds: Rect::new(0, 0, 0, 0),
+                hit_bounds: Rect::new(0, 0, 0, 0),
+            });
+        }
+
+        self.entries[idx] = entry;
+    }
+
+    #[inline]
+    pub fn has_definition(&self, npc_type: u16) -> bool {
+        self.definitions.contains_key(&npc_type)
+    }
+
+    pub fn get_definition(&self, npc_type: u16) -> Option<Rc<NPCDefinition>> {
+        self.definitions.get(&npc_type).cloned()
+    }
+
+    #[inline]
+    pub fn has_boss_definition(&self, boss_type: u16) -> bool {
+        self.boss_definitions.contains_key(&boss_type)
+    }
+
+    pub fn get_boss_definition(&self, boss_type: u16) -> Option<Rc<BossDefinition>> {
+        self.boss_definitions.get(&boss_type).cloned()
+    }
+
+    /// Returns the spritesheet id for given texture path, registering it if it wasn't used before.
+    pub fn register_custom_texture(&mut self, path: &str) -> u16 {
+        let idx = match self.custom_textures.iter().position(|p| p == path) {
+            Some(idx) => idx,
+            None => {
+                self.custom_textures.push(path.to_owned());
+                self.custom_textures.len() - 1
+            }
+        };
+
+        CUSTOM_SPRITESHEET_BASE + idx as u16
+    }
+
     pub fn get_display_bounds(&self, npc_type: u16) -> Rect<u32> {
         if let Some(npc) = self.entries.get(npc_type as usize) {
             Rect {
@@ -945,6 +1014,12 @@ impl NPCTable {
             23 => TexRef::from_str("Npc/NpcRegu"),
             26 => TexRef::from_str("TextBox"),
             27 => TexRef::from_str("Face"),
+            id if id >= CUSTOM_SPRITESHEET_BASE => {
+                match self.custom_textures.get((id - CUSTOM_SPRITESHEET_BASE) as usize) {
+                    Some(path) => TexRef { variant: TexRefVariant::Custom(path) },
+                    None => TexRef::from_str("Npc/Npc0"),
+                }
+            }
             _ => TexRef::from_str("Npc/Npc0"),
         }
     }
@@ -956,6 +1031,7 @@ pub struct TexRef<'a> {
 
 enum TexRefVariant<'a> {
     Str(&'static str),
+    Custom(&'a str),
     StageTileset(Ref<'a, StageTexturePaths>),
     StageNPC1(Ref<'a, StageTexturePaths>),
     StageNPC2(Ref<'a, StageTexturePaths>),
@@ -975,6 +1051,7 @@ impl Deref for TexRef<'_> {
     fn deref(&self) -> &Self::Target {
         match &self.variant {
             TexRefVariant::Str(str) => str,
+            TexRefVariant::Custom(str) => str,
             TexRefVariant::StageTileset(paths) => &paths.tileset_fg,
             TexRefVariant::StageNPC1(paths) => &paths.npc1,
             TexRefVariant::StageNPC2(paths) => &paths.npc2,
@@ -10,6 +10,7 @@ use crate::game::npc::list::NPCList;
 use crate::game::player::Player;
 use crate::game::shared_game_state::{SharedGameState, TileSize};
 use crate::game::weapon::bullet::Bullet;
+use crate::sound::spatial::SfxPositioning;
 use crate::util::rng::{RNG, Xoroshiro32PlusPlus};
 
 const MAX_FALL_SPEED: i32 = 0x5FF;
@@ -42,7 +43,7 @@ impl NPC {
                 entry.damage as u16,
                 entry.npc_flags,
                 entry.experience as u16,
-                entry.spritesheet_id as u16,
+                entry.spritesheet_id,
             ),
             None => (2, 0, 0, NPCFlag(0), 0, 0),
         };
@@ -261,7 +262,7 @@ impl NPCList {
 
             if smoke {
                 if let Some(table_entry) = state.npc_table.get_entry(npc.npc_type) {
-                    state.sound_manager.play_sfx(table_entry.death_sound);
+                    state.sound_manager.play_sfx_at(table_entry.death_sound, npc.x, npc.y, SfxPositioning::DEFAULT);
                 }
 
                 match npc.size {
@@ -284,7 +285,7 @@ impl NPCList {
     pub fn kill_npc(&self, id: usize, vanish: bool, can_drop_missile: bool, state: &mut SharedGameState) {
         if let Some(npc) = self.get_npc(id) {
             if let Some(table_entry) = state.npc_table.get_entry(npc.npc_type) {
-                state.sound_manager.play_sfx(table_entry.death_sound);
+                state.sound_manager.play_sfx_at(table_entry.death_sound, npc.x, npc.y, SfxPositioning::DEFAULT);
             }
 
             match npc.size {
@@ -904,6 +904,10 @@ pub trait PhysicalEntity {
             }
         }
 
+        if self.is_player() && stage.map.get_attribute(x as usize, y as usize) == 0x0c {
+            self.flags().set_on_ladder(true);
+        }
+
         if (self.y() - 0x800) > state.water_level {
             self.flags().set_in_water(true);
         }
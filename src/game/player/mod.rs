@@ -1,10 +1,11 @@
 use std::clone::Clone;
 
 use num_derive::FromPrimitive;
-use num_traits::clamp;
+use num_traits::{clamp, FromPrimitive};
 
 use crate::common::{interpolate_fix9_scale, Condition, Direction, Equipment, Flag, Rect};
 use crate::components::number_popup::NumberPopup;
+use crate::engine_constants::{PhysicsConsts, PhysicsPreset};
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
@@ -12,8 +13,7 @@ use crate::game::caret::CaretType;
 use crate::game::frame::Frame;
 use crate::game::npc::list::NPCList;
 use crate::game::npc::NPC;
-use crate::game::player::skin::basic::BasicPlayerSkin;
-use crate::game::player::skin::{PlayerAnimationState, PlayerAppearanceState, PlayerSkin};
+use crate::game::player::skin::{load_skin, PlayerAnimationState, PlayerAppearanceState, PlayerSkin};
 use crate::game::shared_game_state::SharedGameState;
 use crate::input::dummy_player_controller::DummyPlayerController;
 use crate::input::player_controller::PlayerController;
@@ -22,11 +22,18 @@ use crate::util::rng::RNG;
 mod player_hit;
 pub mod skin;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+/// Damage dealt by presses and other hazards meant to kill the player outright.
+const INSTANT_DEATH_DAMAGE: i32 = 127;
+const PIT_PROTECTION_DAMAGE: i32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 #[repr(u8)]
 pub enum ControlMode {
     Normal = 0,
     IronHead,
+    /// Free movement in all directions without gravity, used for top-down and flying sections.
+    Jetpack,
 }
 
 #[derive(PartialEq, Eq, Copy, Clone)]
@@ -95,6 +102,7 @@ pub struct Player {
     pub display_bounds: Rect<u32>,
     pub hit_bounds: Rect<u32>,
     pub control_mode: ControlMode,
+    pub physics_preset: Option<PhysicsPreset>,
     pub question: bool,
     pub booster_fuel: u32,
     pub up: bool,
@@ -122,12 +130,14 @@ pub struct Player {
     dog_stack: Vec<DogStack>,
     pub has_dog: bool,
     pub teleport_counter: u16,
+    safe_x: i32,
+    safe_y: i32,
 }
 
 impl Player {
     pub fn new(state: &mut SharedGameState, ctx: &mut Context) -> Player {
         let constants = &state.constants;
-        let skin = Box::new(BasicPlayerSkin::new("MyChar".to_string(), state, ctx));
+        let skin = load_skin("MyChar".to_string(), state, ctx);
 
         Player {
             x: 0,
@@ -149,6 +159,7 @@ impl Player {
             display_bounds: skin.get_display_bounds(),
             hit_bounds: skin.get_hit_bounds(),
             control_mode: constants.player.control_mode,
+            physics_preset: None,
             question: false,
             booster_fuel: 0,
             splash: false,
@@ -176,6 +187,8 @@ impl Player {
             dog_stack: Vec::new(),
             has_dog: false,
             teleport_counter: 0,
+            safe_x: 0,
+            safe_y: 0,
         }
     }
 
@@ -188,17 +201,46 @@ impl Player {
     }
 
     pub fn load_skin(&mut self, texture_name: String, state: &mut SharedGameState, ctx: &mut Context) {
-        self.skin = Box::new(BasicPlayerSkin::new(texture_name, state, ctx));
+        self.skin = load_skin(texture_name, state, ctx);
         self.display_bounds = self.skin.get_display_bounds();
         self.hit_bounds = self.skin.get_hit_bounds();
     }
 
+    /// Switches the control mode using a `<UNI` value, physics presets take priority over built-in modes.
+    pub fn set_control_mode(&mut self, state: &SharedGameState, id: u8) {
+        if let Some(preset) = state.constants.physics_presets.iter().find(|preset| preset.id == id) {
+            self.control_mode = preset.control_mode.unwrap_or(ControlMode::Normal);
+            self.physics_preset = Some(preset.clone());
+        } else if let Some(mode) = ControlMode::from_u8(id) {
+            self.control_mode = mode;
+            self.physics_preset = None;
+        }
+    }
+
+    /// Returns the `<UNI` value of current control mode.
+    pub fn control_mode_id(&self) -> u8 {
+        match self.physics_preset {
+            Some(ref preset) => preset.id,
+            None => self.control_mode as u8,
+        }
+    }
+
+    fn get_physics(&self, state: &SharedGameState, in_water: bool) -> PhysicsConsts {
+        let preset = self.physics_preset.as_ref();
+
+        if in_water {
+            preset.and_then(|p| p.water_physics).unwrap_or(state.constants.player.water_physics)
+        } else {
+            preset.and_then(|p| p.air_physics).unwrap_or(state.constants.player.air_physics)
+        }
+    }
+
     fn tick_normal(&mut self, state: &mut SharedGameState, npc_list: &NPCList) -> GameResult {
         if !state.control_flags.interactions_disabled() && state.control_flags.control_enabled() {
             if self.equip.has_air_tank() {
                 self.air = 1000;
                 self.air_counter = 0;
-            } else if !state.settings.god_mode && self.flags.in_water() {
+            } else if !state.settings.god_mode && !state.get_assist().infinite_air && self.flags.in_water() {
                 self.air_counter = 60;
                 if self.air > 0 {
                     self.air -= 1;
@@ -225,11 +267,12 @@ impl Player {
             return Ok(());
         }
 
-        let physics = if self.flags.in_water() {
-            state.constants.player.water_physics
-        } else {
-            state.constants.player.air_physics
+        let physics = self.get_physics(state, self.flags.in_water());
+        let (wall_kick, ladder_speed, swim_stroke) = match self.physics_preset {
+            Some(ref preset) => (preset.wall_kick, preset.ladder_speed, preset.swim_stroke),
+            None => (None, 0, 0),
         };
+        let on_ladder = ladder_speed != 0 && self.flags.on_ladder();
 
         self.question = false;
 
@@ -322,7 +365,24 @@ impl Player {
         } else {
             // air movement
             if state.control_flags.control_enabled() {
-                if self.controller.trigger_jump() && self.booster_fuel != 0 {
+                let kick_direction = if self.flags.hit_left_wall() {
+                    Some(Direction::Right)
+                } else if self.flags.hit_right_wall() {
+                    Some(Direction::Left)
+                } else {
+                    None
+                };
+
+                if let (Some(kick), Some(direction), true) = (wall_kick, kick_direction, self.controller.trigger_jump())
+                {
+                    self.direction = direction;
+                    self.vel_x = kick.vel_x * direction.vector_x();
+                    self.vel_y = -kick.vel_y;
+                    state.sound_manager.play_sfx(15);
+                } else if self.controller.trigger_jump() && swim_stroke != 0 && self.flags.in_water() {
+                    self.vel_y = -swim_stroke;
+                    state.sound_manager.play_sfx(15);
+                } else if self.controller.trigger_jump() && self.booster_fuel != 0 {
                     if self.equip.has_booster_0_8() {
                         self.booster_switch = BoosterSwitch::Up;
 
@@ -390,13 +450,23 @@ impl Player {
             }
         }
 
+        // mouse aiming turns the player towards the cursor, movement works like strafing
+        if state.control_flags.control_enabled() {
+            if let Some(direction) = self.controller.aim_direction() {
+                self.direction = direction;
+            }
+        }
+
         // jumping
         if state.control_flags.control_enabled() {
             self.up = self.controller.move_up() || self.strafe_up;
             self.down = self.controller.move_down() && !self.flags.hit_bottom_wall();
 
             if self.controller.trigger_jump()
-                && (self.flags.hit_bottom_wall() || self.flags.hit_right_slope() || self.flags.hit_left_slope())
+                && (self.flags.hit_bottom_wall()
+                    || self.flags.hit_right_slope()
+                    || self.flags.hit_left_slope()
+                    || on_ladder)
                 && !self.flags.force_up()
             {
                 self.vel_y = -physics.jump;
@@ -536,16 +606,27 @@ impl Player {
             }
         }
 
-        let max_move = if self.flags.in_water()
-            && !(self.flags.force_left()
-                || self.flags.force_up()
-                || self.flags.force_right()
-                || self.flags.force_down())
-        {
-            state.constants.player.water_physics.max_move
-        } else {
-            state.constants.player.air_physics.max_move
-        };
+        // ladder climbing
+        if on_ladder && state.control_flags.control_enabled() && !self.controller.jump() {
+            self.vel_y = if self.controller.move_up() {
+                -ladder_speed
+            } else if self.controller.move_down() {
+                ladder_speed
+            } else {
+                0
+            };
+        }
+
+        let max_move = self
+            .get_physics(
+                state,
+                self.flags.in_water()
+                    && !(self.flags.force_left()
+                        || self.flags.force_up()
+                        || self.flags.force_right()
+                        || self.flags.force_down()),
+            )
+            .max_move;
 
         self.vel_x = self.vel_x.clamp(-max_move, max_move);
         self.vel_y = self.vel_y.clamp(-max_move, max_move);
@@ -587,6 +668,10 @@ impl Player {
         // spike damage
         if self.flags.hit_by_spike() {
             self.damage(10, state, npc_list);
+        } else if self.flags.hit_bottom_wall() && self.shock_counter == 0 {
+            // remembered for pit protection assist
+            self.safe_x = self.x;
+            self.safe_y = self.y;
         }
 
         // camera
@@ -737,6 +822,98 @@ impl Player {
         Ok(())
     }
 
+    fn tick_jetpack(&mut self, state: &mut SharedGameState, npc_list: &NPCList) -> GameResult {
+        if self.cond.hidden() {
+            return Ok(());
+        }
+
+        let jetpack = self.physics_preset.as_ref().and_then(|p| p.jetpack).unwrap_or(state.constants.player.jetpack);
+
+        self.question = false;
+        self.up = false;
+        self.down = false;
+        self.air = 1000;
+        self.air_counter = 0;
+
+        let mut move_x = 0;
+        let mut move_y = 0;
+
+        if state.control_flags.control_enabled() {
+            if self.controller.move_left() {
+                move_x -= 1;
+            }
+            if self.controller.move_right() {
+                move_x += 1;
+            }
+            if self.controller.move_up() {
+                move_y -= 1;
+            }
+            if self.controller.move_down() {
+                move_y += 1;
+            }
+
+            if !self.controller.strafe() || !state.settings.allow_strafe {
+                if self.controller.move_left() {
+                    self.direction = Direction::Left;
+                }
+
+                if self.controller.move_right() {
+                    self.direction = Direction::Right;
+                }
+            }
+
+            self.up = self.controller.move_up();
+            self.down = self.controller.move_down();
+
+            // there's no ground to stand on, so NPCs are examined with the jump button
+            if self.controller.trigger_jump() && !self.cond.interacted() && !state.control_flags.interactions_disabled()
+            {
+                self.cond.set_interacted(true);
+                self.question = true;
+            } else if move_x != 0 || move_y != 0 || self.controller.shoot() {
+                self.cond.set_interacted(false);
+            }
+        }
+
+        if move_x != 0 {
+            self.vel_x += move_x * jetpack.accel;
+        } else if self.vel_x > jetpack.resist || self.vel_x < -jetpack.resist {
+            self.vel_x -= jetpack.resist * self.vel_x.signum();
+        } else {
+            self.vel_x = 0;
+        }
+
+        if move_y != 0 {
+            self.vel_y += move_y * jetpack.accel;
+        } else if self.vel_y > jetpack.resist || self.vel_y < -jetpack.resist {
+            self.vel_y -= jetpack.resist * self.vel_y.signum();
+        } else {
+            self.vel_y = 0;
+        }
+
+        self.vel_x = self.vel_x.clamp(-jetpack.max_speed, jetpack.max_speed);
+        self.vel_y = self.vel_y.clamp(-jetpack.max_speed, jetpack.max_speed);
+
+        if (move_x != 0 || move_y != 0) && self.tick % 4 == 0 {
+            state.create_caret(self.x, self.y + 0xc00, CaretType::Exhaust, Direction::Bottom);
+        }
+
+        if self.flags.hit_by_spike() {
+            self.damage(10, state, npc_list);
+        }
+
+        self.camera_target_x = clamp(self.camera_target_x + self.direction.vector_x() * 0x200, -0x8000, 0x8000);
+        self.camera_target_y = clamp(self.camera_target_y + move_y * 0x200, -0x4000, 0x4000);
+
+        self.target_x = self.x + self.camera_target_x;
+        self.target_y = self.y + self.camera_target_y;
+
+        self.x += self.vel_x;
+        self.y += self.vel_y;
+
+        Ok(())
+    }
+
     fn tick_animation(&mut self, state: &mut SharedGameState) {
         if self.cond.hidden() {
             return;
@@ -880,7 +1057,13 @@ impl Player {
             self.vel_y = -0x400; // -2.0fix9
         }
 
-        let final_hp = state.get_damage(hp);
+        let mut final_hp = state.get_damage(hp);
+
+        // instant death hazards (presses, falling spikes) put the player back on safe ground instead
+        if hp >= INSTANT_DEATH_DAMAGE && state.get_assist().pit_protection {
+            self.return_to_safe_position();
+            final_hp = state.get_damage(PIT_PROTECTION_DAMAGE);
+        }
 
         self.life = self.life.saturating_sub(final_hp as u16);
 
@@ -918,6 +1101,28 @@ impl Player {
         let _ = state.discord_rpc.update_hp(&self);
     }
 
+    /// Marks the current position as the one pit protection returns the player to.
+    pub fn reset_safe_position(&mut self) {
+        self.safe_x = self.x;
+        self.safe_y = self.y;
+    }
+
+    fn return_to_safe_position(&mut self) {
+        self.x = self.safe_x;
+        self.y = self.safe_y;
+        self.vel_x = 0;
+        self.vel_y = 0;
+    }
+
+    /// Called by pit protection when a script jumps to a death event while the player is still alive, puts the
+    /// player back on the last solid ground and deals regular damage instead.
+    pub fn rescue_from_pit(&mut self, state: &mut SharedGameState, npc_list: &NPCList) {
+        self.return_to_safe_position();
+        self.shock_counter = 0;
+        self.damage(PIT_PROTECTION_DAMAGE, state, npc_list);
+        self.vel_y = 0;
+    }
+
     pub fn update_teleport_counter(&mut self, state: &SharedGameState) {
         self.teleport_counter += 1;
 
@@ -957,6 +1162,7 @@ impl GameEntity<&NPCList> for Player {
             (_, true) => self.tick_ironhead(state)?,
             (ControlMode::Normal, _) => self.tick_normal(state, npc_list)?,
             (ControlMode::IronHead, _) => self.tick_ironhead(state)?,
+            (ControlMode::Jetpack, _) => self.tick_jetpack(state, npc_list)?,
         }
 
         self.damage_popup.x = self.x;
@@ -1001,7 +1207,7 @@ impl GameEntity<&NPCList> for Player {
                 let vec_y = 0x1400;
 
                 if let Some(entry) = state.npc_table.get_entry(136) {
-                    let sprite = &*state.npc_table.get_texture_ref(entry.spritesheet_id as u16);
+                    let sprite = &*state.npc_table.get_texture_ref(entry.spritesheet_id);
                     let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, sprite)?;
 
                     let (off_x, frame_id) = if self.direction == Direction::Left {
@@ -1038,7 +1244,7 @@ impl GameEntity<&NPCList> for Player {
 
         if self.current_weapon != 0 {
             let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "Arms")?;
-            let (gun_off_x, gun_off_y) = self.skin.get_gun_offset();
+            let (gun_off_x, gun_off_y) = self.skin.get_gun_offset(self.current_weapon);
 
             batch.add_rect(
                 interpolate_fix9_scale(
@@ -1061,22 +1267,28 @@ impl GameEntity<&NPCList> for Player {
         }
 
         {
+            let x = interpolate_fix9_scale(
+                self.prev_x - self.display_bounds.left as i32,
+                self.x - self.display_bounds.left as i32,
+                state.frame_time,
+            ) - frame_x;
+            let y = interpolate_fix9_scale(
+                self.prev_y - self.display_bounds.top as i32,
+                self.y - self.display_bounds.top as i32,
+                state.frame_time,
+            ) - frame_y;
+
             let batch =
                 state.texture_set.get_or_load_batch(ctx, &state.constants, self.skin.get_skin_texture_name())?;
-            batch.add_rect(
-                interpolate_fix9_scale(
-                    self.prev_x - self.display_bounds.left as i32,
-                    self.x - self.display_bounds.left as i32,
-                    state.frame_time,
-                ) - frame_x,
-                interpolate_fix9_scale(
-                    self.prev_y - self.display_bounds.top as i32,
-                    self.y - self.display_bounds.top as i32,
-                    state.frame_time,
-                ) - frame_y,
-                &self.anim_rect,
-            );
+            batch.add_rect(x, y, &self.anim_rect);
             batch.draw(ctx)?;
+
+            let mask_texture_name = self.skin.get_mask_texture_name();
+            if !mask_texture_name.is_empty() {
+                let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, mask_texture_name)?;
+                batch.add_rect_tinted(x, y, self.skin.get_color().to_rgba(), &self.anim_rect);
+                batch.draw(ctx)?;
+            }
         }
 
         if (self.equip.has_air_tank() && self.flags.in_water()) || self.control_mode == ControlMode::IronHead {
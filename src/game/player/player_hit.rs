@@ -223,7 +223,7 @@ impl Player {
                     state.sound_manager.play_sfx(23);
                 }
 
-                if self.control_mode == ControlMode::IronHead {
+                if self.control_mode != ControlMode::Normal {
                     self.y = npc.y - npc.hit_bounds.top as i32 - self.hit_bounds.bottom as i32 + 0x200;
                     flags.set_hit_bottom_wall(true);
                 } else if npc.npc_flags.bouncy() {
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::common::{Color, Direction, Rect};
+use crate::game::player::skin::basic::{default_frame_id, SkinMeta};
+use crate::game::player::skin::{PlayerAnimationState, PlayerAppearanceState, PlayerSkin};
+use crate::game::shared_game_state::SharedGameState;
+
+#[derive(Clone, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkinAnimation {
+    /// Frame indexes in the spritesheet row.
+    pub frames: Vec<u16>,
+    /// Number of ticks each frame is displayed for.
+    #[serde(default = "skin_animation_default_ticks")]
+    pub ticks: u16,
+}
+
+const fn skin_animation_default_ticks() -> u16 {
+    5
+}
+
+#[derive(Clone, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkinWeaponGunOffset {
+    pub weapon: u8,
+    pub x: i16,
+    pub y: i16,
+}
+
+#[derive(Clone, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkinAppearance {
+    pub appearance: PlayerAppearanceState,
+    /// Index of the left/right facing row pair used by this appearance.
+    pub row: u16,
+}
+
+/// Version 2 of skin metadata, extends the basic one with custom animations, weapon offsets and color masks.
+#[derive(Clone, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimatedSkinMeta {
+    #[serde(flatten)]
+    pub base: SkinMeta,
+    /// Animations overriding the vanilla frame layout, states without one use the vanilla layout.
+    #[serde(default)]
+    pub animations: HashMap<PlayerAnimationState, SkinAnimation>,
+    /// Gun offsets for specific weapon types, other weapons use `gunOffsetX` and `gunOffsetY`.
+    #[serde(default)]
+    pub weapon_gun_offsets: Vec<SkinWeaponGunOffset>,
+    #[serde(default)]
+    pub supports_color: bool,
+    /// Spritesheet with the same layout, drawn over the skin and tinted with the skin color.
+    #[serde(default)]
+    pub mask_texture: String,
+    #[serde(default)]
+    pub appearances: Vec<SkinAppearance>,
+}
+
+#[derive(Clone)]
+pub struct AnimatedPlayerSkin {
+    texture_name: String,
+    color: Color,
+    state: PlayerAnimationState,
+    appearance: PlayerAppearanceState,
+    direction: Direction,
+    metadata: Rc<AnimatedSkinMeta>,
+    tick: u16,
+    skinsheet_offset: u16,
+}
+
+impl AnimatedPlayerSkin {
+    pub fn new(texture_name: String, metadata: AnimatedSkinMeta, skinsheet_offset: u16) -> AnimatedPlayerSkin {
+        AnimatedPlayerSkin {
+            texture_name,
+            color: Color::new(1.0, 1.0, 1.0, 1.0),
+            state: PlayerAnimationState::Idle,
+            appearance: PlayerAppearanceState::Default,
+            direction: Direction::Left,
+            metadata: Rc::new(metadata),
+            tick: 0,
+            skinsheet_offset,
+        }
+    }
+
+    fn get_appearance_offset(&self) -> u16 {
+        let frame_height = self.metadata.base.frame_size_height;
+
+        let offset = match self.metadata.appearances.iter().find(|a| a.appearance == self.appearance) {
+            Some(appearance) => appearance.row.saturating_mul(frame_height.saturating_mul(2)),
+            None => match self.appearance {
+                PlayerAppearanceState::Default => 0,
+                PlayerAppearanceState::MimigaMask => frame_height.saturating_mul(2),
+                PlayerAppearanceState::Custom(i) => (i as u16).saturating_mul(frame_height),
+            },
+        };
+
+        self.skinsheet_offset.saturating_mul(frame_height.saturating_mul(2)).saturating_add(offset)
+    }
+}
+
+impl PlayerSkin for AnimatedPlayerSkin {
+    fn animation_frame_for(&self, state: PlayerAnimationState, direction: Direction, tick: u16) -> Rect<u16> {
+        let frame_id = match self.metadata.animations.get(&state) {
+            Some(anim) if !anim.frames.is_empty() => {
+                anim.frames[(tick / anim.ticks.max(1)) as usize % anim.frames.len()]
+            }
+            _ => default_frame_id(state, tick),
+        };
+
+        let frame_width = self.metadata.base.frame_size_width;
+        let frame_height = self.metadata.base.frame_size_height;
+        let y_offset = if direction == Direction::Left { 0 } else { frame_height } + self.get_appearance_offset();
+
+        Rect::new_size(frame_id.saturating_mul(frame_width), y_offset, frame_width, frame_height)
+    }
+
+    fn animation_frame(&self) -> Rect<u16> {
+        self.animation_frame_for(self.state, self.direction, self.tick)
+    }
+
+    fn tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+    }
+
+    fn set_state(&mut self, state: PlayerAnimationState, _tick: u16) {
+        if self.state != state {
+            self.state = state;
+            self.tick = 0;
+        }
+    }
+
+    fn get_state(&self) -> PlayerAnimationState {
+        self.state
+    }
+
+    fn set_appearance(&mut self, appearance: PlayerAppearanceState) {
+        self.appearance = appearance;
+    }
+
+    fn get_appearance(&mut self) -> PlayerAppearanceState {
+        self.appearance
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    fn get_color(&self) -> Color {
+        self.color
+    }
+
+    fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    fn get_direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn get_skin_texture_name(&self) -> &str {
+        &self.texture_name
+    }
+
+    fn get_mask_texture_name(&self) -> &str {
+        if self.metadata.supports_color {
+            &self.metadata.mask_texture
+        } else {
+            ""
+        }
+    }
+
+    fn get_hit_bounds(&self) -> Rect<u32> {
+        let ubox = &self.metadata.base.hit_box;
+
+        Rect {
+            left: ubox.left as u32 * 0x200,
+            top: ubox.top as u32 * 0x200,
+            right: ubox.right as u32 * 0x200,
+            bottom: ubox.bottom as u32 * 0x200,
+        }
+    }
+
+    fn get_display_bounds(&self) -> Rect<u32> {
+        let ubox = &self.metadata.base.display_box;
+
+        Rect {
+            left: ubox.left as u32 * 0x200,
+            top: ubox.top as u32 * 0x200,
+            right: ubox.right as u32 * 0x200,
+            bottom: ubox.bottom as u32 * 0x200,
+        }
+    }
+
+    fn get_gun_offset(&self, weapon: u8) -> (i32, i32) {
+        match self.metadata.weapon_gun_offsets.iter().find(|offset| offset.weapon == weapon) {
+            Some(offset) => (offset.x as i32, offset.y as i32),
+            None => (self.metadata.base.gun_offset_x as i32, self.metadata.base.gun_offset_y as i32),
+        }
+    }
+
+    fn get_whimsical_star_rect(&self, index: usize) -> Rect<u16> {
+        let mut rect = self.metadata.base.whimsical_star_rect;
+        rect.top += (8 * index) as u16;
+        rect.bottom = rect.top + 8;
+        rect
+    }
+
+    fn apply_gamestate(&mut self, state: &SharedGameState) {
+        self.skinsheet_offset = state.get_skinsheet_offset();
+    }
+
+    fn set_skinsheet_offset(&mut self, offset: u16) {
+        self.skinsheet_offset = offset;
+    }
+}
+
+#[test]
+fn test_animated_skin_meta() {
+    let json = r#"{
+        "version": 2,
+        "frameSizeWidth": 24,
+        "frameSizeHeight": 32,
+        "animations": { "walking": { "frames": [1, 2, 3], "ticks": 2 } },
+        "weaponGunOffsets": [{ "weapon": 2, "x": 3, "y": -1 }],
+        "appearances": [{ "appearance": { "custom": 1 }, "row": 3 }]
+    }"#;
+
+    let meta = serde_json::from_str::<AnimatedSkinMeta>(json).unwrap();
+    let mut skin = AnimatedPlayerSkin::new("Skin".to_owned(), meta, 0);
+
+    assert_eq!(skin.animation_frame_for(PlayerAnimationState::Walking, Direction::Left, 5).left, 3 * 24);
+    assert_eq!(skin.animation_frame_for(PlayerAnimationState::Idle, Direction::Right, 0).top, 32);
+    assert_eq!(skin.get_gun_offset(2), (3, -1));
+    assert_eq!(skin.get_gun_offset(3), (0, 0));
+
+    skin.set_appearance(PlayerAppearanceState::Custom(1));
+    assert_eq!(skin.animation_frame_for(PlayerAnimationState::Idle, Direction::Left, 0).top, 3 * 64);
+}
@@ -1,9 +1,6 @@
 use lazy_static::lazy_static;
 
 use crate::common::{Color, Direction, Rect};
-use crate::framework::context::Context;
-use crate::framework::filesystem;
-use crate::framework::filesystem::File;
 use crate::game::player::skin::{PlayerAnimationState, PlayerAppearanceState, PlayerSkin};
 use crate::game::shared_game_state::SharedGameState;
 
@@ -54,7 +51,8 @@ const fn skinmeta_default_whimsical_star() -> Rect<u16> {
     Rect { left: 192, top: 0, right: 200, bottom: 8 }
 }
 
-pub static SUPPORTED_SKINMETA_VERSIONS: [u8; 1] = [1];
+/// Version 2 metadata is handled by `AnimatedPlayerSkin`.
+pub static SUPPORTED_SKINMETA_VERSIONS: [u8; 2] = [1, 2];
 
 lazy_static! {
     pub static ref DEFAULT_SKINMETA: SkinMeta = SkinMeta {
@@ -85,25 +83,7 @@ pub struct BasicPlayerSkin {
 }
 
 impl BasicPlayerSkin {
-    pub fn new(texture_name: String, state: &SharedGameState, ctx: &mut Context) -> BasicPlayerSkin {
-        let mut metadata = DEFAULT_SKINMETA.clone();
-
-        let meta_path = format!("{}.dskinmeta", texture_name);
-
-        if let Ok(file) = filesystem::open_find(ctx, &state.constants.base_paths, &meta_path) {
-            match serde_json::from_reader::<File, SkinMeta>(file) {
-                Ok(meta) if SUPPORTED_SKINMETA_VERSIONS.contains(&meta.version) => {
-                    metadata = meta;
-                }
-                Ok(meta) => {
-                    log::warn!("{}: Unsupported skin metadata file version: {}", meta_path, meta.version);
-                }
-                Err(err) => {
-                    log::warn!("Failed to load skin metadata file: {:?}", err);
-                }
-            }
-        }
-
+    pub fn new(texture_name: String, metadata: SkinMeta, skinsheet_offset: u16) -> BasicPlayerSkin {
         BasicPlayerSkin {
             texture_name,
             color: Color::new(1.0, 1.0, 1.0, 1.0),
@@ -112,7 +92,7 @@ impl BasicPlayerSkin {
             direction: Direction::Left,
             metadata,
             tick: 0,
-            skinsheet_offset: state.get_skinsheet_offset(),
+            skinsheet_offset,
         }
     }
 
@@ -124,31 +104,36 @@ impl BasicPlayerSkin {
     }
 }
 
-impl PlayerSkin for BasicPlayerSkin {
-    fn animation_frame_for(&self, state: PlayerAnimationState, direction: Direction, tick: u16) -> Rect<u16> {
-        let frame_id = match state {
-            PlayerAnimationState::Idle => 0u16,
-            PlayerAnimationState::Walking => {
-                const WALK_INDEXES: [u16; 4] = [1, 0, 2, 0];
+/// Returns the frame index used by vanilla spritesheet layout for given state.
+pub(crate) fn default_frame_id(state: PlayerAnimationState, tick: u16) -> u16 {
+    match state {
+        PlayerAnimationState::Idle => 0u16,
+        PlayerAnimationState::Walking => {
+            const WALK_INDEXES: [u16; 4] = [1, 0, 2, 0];
 
-                WALK_INDEXES[(tick as usize / 5) % 4]
-            }
-            PlayerAnimationState::WalkingUp => {
-                const WALK_UP_INDEXES: [u16; 4] = [4, 3, 5, 3];
+            WALK_INDEXES[(tick as usize / 5) % 4]
+        }
+        PlayerAnimationState::WalkingUp => {
+            const WALK_UP_INDEXES: [u16; 4] = [4, 3, 5, 3];
 
-                WALK_UP_INDEXES[(tick as usize / 5) % 4]
-            }
-            PlayerAnimationState::LookingUp => 3,
-            PlayerAnimationState::Examining => 7,
-            PlayerAnimationState::Sitting => 8,
-            PlayerAnimationState::Collapsed => 9,
-            PlayerAnimationState::Jumping => 2,
-            PlayerAnimationState::Falling => 1,
-            PlayerAnimationState::FallingLookingUp => 4,
-            PlayerAnimationState::FallingLookingDown => 6,
-            PlayerAnimationState::FallingUpsideDown => 10,
-            PlayerAnimationState::Drowned => 11,
-        };
+            WALK_UP_INDEXES[(tick as usize / 5) % 4]
+        }
+        PlayerAnimationState::LookingUp => 3,
+        PlayerAnimationState::Examining => 7,
+        PlayerAnimationState::Sitting => 8,
+        PlayerAnimationState::Collapsed => 9,
+        PlayerAnimationState::Jumping => 2,
+        PlayerAnimationState::Falling => 1,
+        PlayerAnimationState::FallingLookingUp => 4,
+        PlayerAnimationState::FallingLookingDown => 6,
+        PlayerAnimationState::FallingUpsideDown => 10,
+        PlayerAnimationState::Drowned => 11,
+    }
+}
+
+impl PlayerSkin for BasicPlayerSkin {
+    fn animation_frame_for(&self, state: PlayerAnimationState, direction: Direction, tick: u16) -> Rect<u16> {
+        let frame_id = default_frame_id(state, tick);
 
         let y_offset = if direction == Direction::Left { 0 } else { self.metadata.frame_size_height }
             + match self.appearance {
@@ -249,7 +234,7 @@ impl PlayerSkin for BasicPlayerSkin {
         }
     }
 
-    fn get_gun_offset(&self) -> (i32, i32) {
+    fn get_gun_offset(&self, _weapon: u8) -> (i32, i32) {
         (self.metadata.gun_offset_x as i32, self.metadata.gun_offset_y as i32)
     }
 
@@ -1,7 +1,13 @@
 use crate::bitfield;
 use crate::common::{Color, Direction, Rect};
+use crate::framework::context::Context;
+use crate::framework::filesystem;
+use crate::framework::filesystem::File;
+use crate::game::player::skin::animated::{AnimatedPlayerSkin, AnimatedSkinMeta};
+use crate::game::player::skin::basic::{BasicPlayerSkin, DEFAULT_SKINMETA, SUPPORTED_SKINMETA_VERSIONS};
 use crate::game::shared_game_state::SharedGameState;
 
+pub mod animated;
 pub mod basic;
 
 bitfield! {
@@ -13,7 +19,8 @@ bitfield! {
     pub supports_color, _: 0;
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
 /// Represents a player animation state.
 pub enum PlayerAnimationState {
     Idle,
@@ -32,7 +39,8 @@ pub enum PlayerAnimationState {
 }
 
 /// Represents an alternative appearance of player eg. wearing a Mimiga Mask
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum PlayerAppearanceState {
     Default,
     MimigaMask,
@@ -88,8 +96,8 @@ pub trait PlayerSkin: PlayerSkinClone {
     /// Returns display bounds of skin.
     fn get_display_bounds(&self) -> Rect<u32>;
 
-    /// Returns gun offset
-    fn get_gun_offset(&self) -> (i32, i32);
+    /// Returns gun offset for specified weapon type.
+    fn get_gun_offset(&self, weapon: u8) -> (i32, i32);
 
     /// Returns Whimsical Star rect location
     fn get_whimsical_star_rect(&self, index: usize) -> Rect<u16>;
@@ -101,6 +109,52 @@ pub trait PlayerSkin: PlayerSkinClone {
     fn set_skinsheet_offset(&mut self, offset: u16);
 }
 
+/// Loads the `.dskinmeta` file of given spritesheet, returns `None` if it's missing or invalid.
+pub fn load_skin_metadata(texture_name: &str, roots: &Vec<String>, ctx: &mut Context) -> Option<AnimatedSkinMeta> {
+    let meta_path = format!("{}.dskinmeta", texture_name);
+    let file = filesystem::open_find(ctx, roots, &meta_path).ok()?;
+
+    match serde_json::from_reader::<File, AnimatedSkinMeta>(file) {
+        Ok(mut meta) if SUPPORTED_SKINMETA_VERSIONS.contains(&meta.base.version) => {
+            // mask textures are relative to the skin's directory
+            if !meta.mask_texture.is_empty() && !meta.mask_texture.contains('/') {
+                if let Some((dir, _)) = texture_name.rsplit_once('/') {
+                    meta.mask_texture = format!("{}/{}", dir, meta.mask_texture);
+                }
+            }
+
+            Some(meta)
+        }
+        Ok(meta) => {
+            log::warn!("{}: Unsupported skin metadata file version: {}", meta_path, meta.base.version);
+            None
+        }
+        Err(err) => {
+            log::warn!("Failed to load skin metadata file: {:?}", err);
+            None
+        }
+    }
+}
+
+/// Creates a skin using the implementation matching the metadata version.
+pub fn create_skin(
+    texture_name: String,
+    metadata: Option<AnimatedSkinMeta>,
+    skinsheet_offset: u16,
+) -> Box<dyn PlayerSkin> {
+    match metadata {
+        Some(meta) if meta.base.version >= 2 => Box::new(AnimatedPlayerSkin::new(texture_name, meta, skinsheet_offset)),
+        Some(meta) => Box::new(BasicPlayerSkin::new(texture_name, meta.base, skinsheet_offset)),
+        None => Box::new(BasicPlayerSkin::new(texture_name, DEFAULT_SKINMETA.clone(), skinsheet_offset)),
+    }
+}
+
+pub fn load_skin(texture_name: String, state: &SharedGameState, ctx: &mut Context) -> Box<dyn PlayerSkin> {
+    let metadata = load_skin_metadata(&texture_name, &state.constants.base_paths, ctx);
+
+    create_skin(texture_name, metadata, state.get_skinsheet_offset())
+}
+
 pub trait PlayerSkinClone {
     fn clone_box(&self) -> Box<dyn PlayerSkin>;
 }
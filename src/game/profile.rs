@@ -1,13 +1,15 @@
 use std::io;
 
 use byteorder::{BE, LE, ReadBytesExt, WriteBytesExt};
-use num_traits::{clamp, FromPrimitive};
+use num_traits::clamp;
 
 use crate::common::{Direction, FadeState, get_timestamp};
 use crate::framework::context::Context;
 use crate::framework::error::GameError::ResourceLoadError;
 use crate::framework::error::GameResult;
+use crate::game::exploration::ExplorationMap;
 use crate::game::player::{ControlMode, TargetPlayer};
+use crate::game::settings::AssistFlags;
 use crate::game::shared_game_state::{GameDifficulty, SharedGameState};
 use crate::game::weapon::{WeaponLevel, WeaponType};
 use crate::scene::game_scene::GameScene;
@@ -46,6 +48,10 @@ pub struct GameProfile {
     pub flags: [u8; 1000],
     pub timestamp: u64,
     pub difficulty: u8,
+    /// [AssistFlags] of gameplay assists used during the playthrough.
+    pub assist: u8,
+    pub variables: Vec<i32>,
+    pub exploration: ExplorationMap,
 }
 
 impl GameProfile {
@@ -64,7 +70,7 @@ impl GameProfile {
             }
 
             let _ = state.mod_requirements.append_weapon(ctx, weapon.weapon_id as u16);
-            let weapon_type: Option<WeaponType> = FromPrimitive::from_u8(weapon.weapon_id as u8);
+            let weapon_type = WeaponType::from_id(weapon.weapon_id as u8, &state.constants);
 
             if let Some(wtype) = weapon_type {
                 game_scene.inventory_player1.add_weapon_data(
@@ -139,8 +145,9 @@ impl GameProfile {
         game_scene.player1.x = self.pos_x;
         game_scene.player1.y = self.pos_y;
 
-        game_scene.player1.control_mode =
-            if self.control_mode == 1 { ControlMode::IronHead } else { ControlMode::Normal };
+        game_scene.player1.control_mode = ControlMode::Normal;
+        game_scene.player1.physics_preset = None;
+        game_scene.player1.set_control_mode(state, self.control_mode as u8);
         game_scene.player1.direction = self.direction;
         game_scene.player1.life = self.life;
         game_scene.player1.max_life = self.max_life;
@@ -152,6 +159,9 @@ impl GameProfile {
         game_scene.player1.cond.0 = 0x80;
 
         state.difficulty = GameDifficulty::from_primitive(self.difficulty);
+        state.assist_used = AssistFlags(self.assist);
+        state.script_variables = self.variables.clone();
+        state.exploration = self.exploration.clone();
 
         game_scene.player1.skin.apply_gamestate(state);
         game_scene.player2.skin.apply_gamestate(state);
@@ -179,7 +189,7 @@ impl GameProfile {
         let current_weapon = inventory_player.current_weapon as u32;
         let current_item = inventory_player.current_item as u32;
         let equipment = player.equip.0 as u32;
-        let control_mode = player.control_mode as u32;
+        let control_mode = player.control_mode_id() as u32;
         let counter = 0; // TODO
         let mut weapon_data = [
             WeaponData { weapon_id: 0, level: 0, exp: 0, max_ammo: 0, ammo: 0 },
@@ -205,7 +215,7 @@ impl GameProfile {
 
         for (idx, weap) in weapon_data.iter_mut().enumerate() {
             if let Some(weapon) = inventory_player.get_weapon(idx) {
-                weap.weapon_id = weapon.wtype as u32;
+                weap.weapon_id = weapon.wtype.id() as u32;
                 weap.level = weapon.level as u32;
                 weap.exp = weapon.experience as u32;
                 weap.max_ammo = weapon.max_ammo as u32;
@@ -240,6 +250,9 @@ impl GameProfile {
 
         let timestamp = get_timestamp();
         let difficulty = state.difficulty as u8;
+        let assist = state.assist_used.0;
+        let variables = state.script_variables.clone();
+        let exploration = state.exploration.clone();
 
         GameProfile {
             current_map,
@@ -262,6 +275,9 @@ impl GameProfile {
             flags,
             timestamp,
             difficulty,
+            assist,
+            variables,
+            exploration,
         }
     }
 
@@ -310,6 +326,14 @@ impl GameProfile {
 
         data.write_u64::<LE>(self.timestamp)?;
         data.write_u8(self.difficulty)?;
+        data.write_u8(self.assist)?;
+
+        data.write_u16::<LE>(self.variables.len() as u16)?;
+        for variable in self.variables.iter().copied() {
+            data.write_i32::<LE>(variable)?;
+        }
+
+        self.exploration.write(&mut data)?;
 
         Ok(())
     }
@@ -387,6 +411,16 @@ impl GameProfile {
 
         let timestamp = data.read_u64::<LE>().unwrap_or(0);
         let difficulty = data.read_u8().unwrap_or(0);
+        let assist = data.read_u8().unwrap_or(0);
+
+        let variable_count = data.read_u16::<LE>().unwrap_or(0) as usize;
+        let mut variables = Vec::with_capacity(variable_count);
+        for _ in 0..variable_count {
+            variables.push(data.read_i32::<LE>()?);
+        }
+
+        // saves from older versions don't have the explored areas
+        let exploration = ExplorationMap::read(&mut data).unwrap_or_default();
 
         Ok(GameProfile {
             current_map,
@@ -409,6 +443,9 @@ impl GameProfile {
             flags,
             timestamp,
             difficulty,
+            assist,
+            variables,
+            exploration,
         })
     }
 }
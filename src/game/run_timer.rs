@@ -0,0 +1,81 @@
+//! Helpers shared by challenge runs and the speedrun timer.
+
+use crate::game::npc::boss::BossNPC;
+
+/// Version of the files storing runs: challenge leaderboards, speedrun routes and records.
+#[inline(always)]
+pub fn current_version() -> u32 {
+    1
+}
+
+/// Detects a boss of given type (as in stage table) being seen alive and then defeated.
+///
+/// The boss has to die in the stage it was seen in, and [BossKillTracker::reset] has to be called whenever
+/// the game scene is recreated (e.g. a profile gets loaded after dying to the boss), as the boss is gone then.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BossKillTracker {
+    seen_in_stage: Option<u16>,
+}
+
+impl BossKillTracker {
+    pub fn reset(&mut self) {
+        self.seen_in_stage = None;
+    }
+
+    /// Returns true on the tick the boss got defeated.
+    pub fn update(&mut self, boss: &BossNPC, boss_type: u16, stage: u16) -> bool {
+        let alive = boss.boss_type == boss_type && boss.parts[0].cond.alive();
+
+        if alive {
+            self.seen_in_stage = Some(stage);
+            return false;
+        }
+
+        match self.seen_in_stage.take() {
+            Some(seen_stage) => seen_stage == stage,
+            None => false,
+        }
+    }
+}
+
+/// Formats game time as `m:ss.cc`.
+pub fn format_millis(millis: u64) -> String {
+    let centis = millis / 10;
+
+    format!("{}:{:02}.{:02}", centis / 6000, (centis / 100) % 60, centis % 100)
+}
+
+/// Formats a tick count as `m:ss.cc`.
+pub fn format_time(ticks: u32, tps: u32) -> String {
+    format_millis(ticks as u64 * 1000 / tps.max(1) as u64)
+}
+
+#[test]
+fn test_boss_kill_tracker() {
+    let mut boss = BossNPC::new();
+    let mut tracker = BossKillTracker::default();
+
+    boss.boss_type = 2;
+    assert!(!tracker.update(&boss, 2, 10));
+
+    boss.parts[0].cond.set_alive(true);
+    assert!(!tracker.update(&boss, 2, 10));
+
+    boss.parts[0].cond.set_alive(false);
+    assert!(tracker.update(&boss, 2, 10));
+    assert!(!tracker.update(&boss, 2, 10));
+
+    // a fresh boss after reloading the game isn't a kill
+    boss.parts[0].cond.set_alive(true);
+    tracker.update(&boss, 2, 10);
+    tracker.reset();
+    assert!(!tracker.update(&BossNPC::new(), 2, 10));
+
+    // neither is leaving the stage
+    tracker.update(&boss, 2, 10);
+    assert!(!tracker.update(&BossNPC::new(), 2, 11));
+
+    assert_eq!(format_millis(74500), "1:14.50");
+    assert_eq!(format_time(3725, 50), "1:14.50");
+    assert_eq!(format_time(59, 60), "0:00.98");
+}
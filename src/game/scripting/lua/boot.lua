@@ -116,6 +116,41 @@ __doukutsu_rs_runtime_dont_touch._tryNPCHook = function(npc_id, npc_type)
     return false
 end
 
+__doukutsu_rs_runtime_dont_touch._registeredBossHooks = {}
+
+__doukutsu_rs_runtime_dont_touch._tryBossHook = function(boss_type)
+    local hook = __doukutsu_rs_runtime_dont_touch._registeredBossHooks[boss_type]
+    if hook ~= nil then
+        local status, err = pcall(hook, __doukutsu_rs_runtime_dont_touch._getBossRef())
+
+        if not status then
+            print("error in boss handler:" .. err)
+        end
+
+        return true
+    end
+
+    return false
+end
+
+__doukutsu_rs_runtime_dont_touch._registeredTSCHandlers = {}
+
+__doukutsu_rs_runtime_dont_touch._tryTSCHandler = function(name, ...)
+    local hook = __doukutsu_rs_runtime_dont_touch._registeredTSCHandlers[name]
+    if hook ~= nil then
+        local status, result = pcall(hook, ...)
+
+        if not status then
+            print("error in tsc handler:" .. result)
+            return true, false
+        end
+
+        return true, result == true
+    end
+
+    return false, false
+end
+
 __doukutsu_rs_runtime_dont_touch._initializeScript = function(script)
     -- for compatibility with Lua 5.2+, copy-pasted from Lua mailing list
     -- http://lua-users.org/lists/lua-l/2010-06/msg00313.html
@@ -201,52 +236,58 @@ __doukutsu_rs_runtime_dont_touch._getNPCRef = function(npc_id)
     return __doukutsu_rs_runtime_dont_touch._npcRefs[npc_id]
 end
 
-__doukutsu_rs_runtime_dont_touch._createNPCRef = function(npc_id)
+__doukutsu_rs_runtime_dont_touch._createNPCRef = function(npc_id, command)
     local npc_ref = { id = npc_id }
 
+    if command == nil then
+        command = function(...)
+            return __doukutsu_rs:npcCommand(...)
+        end
+    end
+
     function npc_ref.closestPlayer(self)
-        return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x200)
+        return command(rawget(self, "id"), 0x200)
     end
 
     function npc_ref.random(self, min, max)
-        return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x201, min, max)
+        return command(rawget(self, "id"), 0x201, min, max)
     end
 
     function npc_ref.hitLeftWall(self)
-        local flags = __doukutsu_rs:npcCommand(rawget(self, "id"), 0x0f)
+        local flags = command(rawget(self, "id"), 0x0f)
         return (flags & 1) ~= 0
     end
 
     function npc_ref.hitCeiling(self)
-        local flags = __doukutsu_rs:npcCommand(rawget(self, "id"), 0x0f)
+        local flags = command(rawget(self, "id"), 0x0f)
         return (flags & 2) ~= 0
     end
 
     function npc_ref.hitRightWall(self)
-        local flags = __doukutsu_rs:npcCommand(rawget(self, "id"), 0x0f)
+        local flags = command(rawget(self, "id"), 0x0f)
         return (flags & 4) ~= 0
     end
 
     function npc_ref.hitFloor(self)
-        local flags = __doukutsu_rs:npcCommand(rawget(self, "id"), 0x0f)
+        local flags = command(rawget(self, "id"), 0x0f)
         return (flags & 8) ~= 0
     end
 
     function npc_ref.parentNPC(self)
-        local id = __doukutsu_rs:npcCommand(rawget(self, "id"), 0x1c)
+        local id = command(rawget(self, "id"), 0x1c)
         return __doukutsu_rs_runtime_dont_touch._getNPCRef(id)
     end
 
     function npc_ref.getAnimRect(self)
-        local l, t, r, b = __doukutsu_rs:npcCommand(rawget(self, "id"), 0x202)
+        local l, t, r, b = command(rawget(self, "id"), 0x202)
         return { l, t, r, b }
     end
 
     function npc_ref.setAnimRect(self, l, t, r, b)
         if type(l) == "number" then
-            __doukutsu_rs:npcCommand(rawget(self, "id"), 0x203, l, t, r, b)
+            command(rawget(self, "id"), 0x203, l, t, r, b)
         elseif type(l) == "table" then
-            __doukutsu_rs:npcCommand(rawget(self, "id"), 0x203, l[1], l[2], l[3], l[4])
+            command(rawget(self, "id"), 0x203, l[1], l[2], l[3], l[4])
         else
             error("Invalid parameters supplied.")
         end
@@ -255,86 +296,94 @@ __doukutsu_rs_runtime_dont_touch._createNPCRef = function(npc_id)
     setmetatable(npc_ref, {
         __index = function(self, property)
             if property == "x" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x10)
+                return command(rawget(self, "id"), 0x10)
             elseif property == "y" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x11)
+                return command(rawget(self, "id"), 0x11)
             elseif property == "velX" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x12)
+                return command(rawget(self, "id"), 0x12)
             elseif property == "velY" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x13)
+                return command(rawget(self, "id"), 0x13)
             elseif property == "velX2" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x14)
+                return command(rawget(self, "id"), 0x14)
             elseif property == "velY2" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x15)
+                return command(rawget(self, "id"), 0x15)
             elseif property == "actionNum" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x16)
+                return command(rawget(self, "id"), 0x16)
             elseif property == "animNum" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x17)
+                return command(rawget(self, "id"), 0x17)
             elseif property == "actionCounter" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x18)
+                return command(rawget(self, "id"), 0x18)
             elseif property == "actionCounter2" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x19)
+                return command(rawget(self, "id"), 0x19)
             elseif property == "actionCounter3" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x1a)
+                return command(rawget(self, "id"), 0x1a)
             elseif property == "animCounter" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x1b)
+                return command(rawget(self, "id"), 0x1b)
             elseif property == "parentId" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x1c)
+                return command(rawget(self, "id"), 0x1c)
             elseif property == "npcType" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x1d)
+                return command(rawget(self, "id"), 0x1d)
             elseif property == "life" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x1e)
+                return command(rawget(self, "id"), 0x1e)
             elseif property == "flagNum" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x1f)
+                return command(rawget(self, "id"), 0x1f)
             elseif property == "eventNum" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x20)
+                return command(rawget(self, "id"), 0x20)
             elseif property == "direction" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x21)
+                return command(rawget(self, "id"), 0x21)
             elseif property == "rawDirection" then
-                return __doukutsu_rs:npcCommand(rawget(self, "id"), 0x22)
+                return command(rawget(self, "id"), 0x22)
+            elseif property == "hurtSound" then
+                return command(rawget(self, "id"), 0x23)
+            elseif property == "deathSound" then
+                return command(rawget(self, "id"), 0x24)
             else
                 return nil
             end
         end,
         __newindex = function(self, property, val)
             if property == "x" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x110, val)
+                command(rawget(self, "id"), 0x110, val)
             elseif property == "y" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x111, val)
+                command(rawget(self, "id"), 0x111, val)
             elseif property == "velX" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x112, val)
+                command(rawget(self, "id"), 0x112, val)
             elseif property == "velY" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x113, val)
+                command(rawget(self, "id"), 0x113, val)
             elseif property == "velX2" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x114, val)
+                command(rawget(self, "id"), 0x114, val)
             elseif property == "velY2" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x115, val)
+                command(rawget(self, "id"), 0x115, val)
             elseif property == "actionNum" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x116, val)
+                command(rawget(self, "id"), 0x116, val)
             elseif property == "animNum" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x117, val)
+                command(rawget(self, "id"), 0x117, val)
             elseif property == "actionCounter" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x118, val)
+                command(rawget(self, "id"), 0x118, val)
             elseif property == "actionCounter2" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x119, val)
+                command(rawget(self, "id"), 0x119, val)
             elseif property == "actionCounter3" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x11a, val)
+                command(rawget(self, "id"), 0x11a, val)
             elseif property == "animCounter" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x11b, val)
+                command(rawget(self, "id"), 0x11b, val)
             elseif property == "parentId" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x11c, val)
+                command(rawget(self, "id"), 0x11c, val)
             elseif property == "npcType" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x11d, val)
+                command(rawget(self, "id"), 0x11d, val)
             elseif property == "life" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x11e, val)
+                command(rawget(self, "id"), 0x11e, val)
             elseif property == "flagNum" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x11f, val)
+                command(rawget(self, "id"), 0x11f, val)
             elseif property == "eventNum" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x120, val)
+                command(rawget(self, "id"), 0x120, val)
             elseif property == "direction" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x121, val)
+                command(rawget(self, "id"), 0x121, val)
             elseif property == "rawDirection" then
-                __doukutsu_rs:npcCommand(rawget(self, "id"), 0x121, val) -- <- not a typo
+                command(rawget(self, "id"), 0x121, val) -- <- not a typo
+            elseif property == "hurtSound" then
+                command(rawget(self, "id"), 0x123, val)
+            elseif property == "deathSound" then
+                command(rawget(self, "id"), 0x124, val)
             end
 
             return nil
@@ -344,6 +393,25 @@ __doukutsu_rs_runtime_dont_touch._createNPCRef = function(npc_id)
     return npc_ref
 end
 
+__doukutsu_rs_runtime_dont_touch._bossRef = nil
+
+__doukutsu_rs_runtime_dont_touch._getBossRef = function()
+    if __doukutsu_rs_runtime_dont_touch._bossRef == nil then
+        local boss_command = function(...)
+            return __doukutsu_rs:bossCommand(...)
+        end
+
+        local parts = {}
+        for i = 0, 19 do
+            parts[i] = __doukutsu_rs_runtime_dont_touch._createNPCRef(i, boss_command)
+        end
+
+        __doukutsu_rs_runtime_dont_touch._bossRef = { parts = parts }
+    end
+
+    return __doukutsu_rs_runtime_dont_touch._bossRef
+end
+
 __doukutsu_rs_runtime_dont_touch._playerRef0 = __doukutsu_rs_runtime_dont_touch._createPlayerRef(0)
 __doukutsu_rs_runtime_dont_touch._playerRef1 = __doukutsu_rs_runtime_dont_touch._createPlayerRef(1)
 
@@ -394,6 +462,25 @@ function doukutsu.playSong(id, fadeout)
     __doukutsu_rs:playSong(id, fadeout)
 end
 
+doukutsu.buses = {
+    music = 0,
+    gameplay = 1,
+    ambience = 2,
+    ui = 3,
+}
+
+function doukutsu.setBusVolume(bus, volume)
+    __doukutsu_rs:setBusVolume(bus, volume)
+end
+
+function doukutsu.setBusMuted(bus, muted)
+    __doukutsu_rs:setBusMuted(bus, muted)
+end
+
+function doukutsu.setBusDucking(bus, amount)
+    __doukutsu_rs:setBusDucking(bus, amount)
+end
+
 function doukutsu.players()
     return { __doukutsu_rs_runtime_dont_touch._playerRef0, __doukutsu_rs_runtime_dont_touch._playerRef1 }
 end
@@ -413,6 +500,22 @@ function doukutsu.setNPCHandler(npc_type, handler)
     __doukutsu_rs_runtime_dont_touch._registeredNPCHooks[npc_type] = handler
 end
 
+function doukutsu.setBossHandler(boss_type, handler)
+    assert(type(boss_type) == "number", "boss type must be an integer.")
+
+    __doukutsu_rs_runtime_dont_touch._registeredBossHooks[boss_type] = handler
+end
+
+function doukutsu.setTSCHandler(name, handler)
+    assert(type(name) == "string", "opcode name must be a string.")
+
+    __doukutsu_rs_runtime_dont_touch._registeredTSCHandlers[name] = handler
+end
+
+function doukutsu.resumeTSC()
+    return __doukutsu_rs:resumeTSC()
+end
+
 function doukutsu.on(event, handler)
     assert(type(event) == "string", "event type must be a string.")
     assert(type(handler) == "function", "event handler must be a function.")
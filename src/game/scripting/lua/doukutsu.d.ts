@@ -148,6 +148,31 @@ declare interface NPC {
     setAnimRect(left: number, top: number, right: number, bottom: number): void;
 }
 
+/**
+ * Represents a part of the current boss.
+ */
+declare interface BossPart extends NPC {
+    /**
+     * ID of the sound effect played when this part gets hurt.
+     */
+    hurtSound: number;
+
+    /**
+     * ID of the sound effect played when this part gets destroyed.
+     */
+    deathSound: number;
+}
+
+/**
+ * Represents the boss of current stage.
+ */
+declare interface Boss {
+    /**
+     * Parts of the boss, indexed from 0 to 19. Part 0 is the main part, which is targeted by <BOA and <BSL.
+     */
+    parts: { [index: number]: BossPart };
+}
+
 /**
  * Represents an in-game player.
  */
@@ -233,6 +258,37 @@ declare namespace doukutsu {
      */
     function playMusic(id: number, fadeout: boolean = false): void;
 
+    /**
+     * Mixer bus IDs, used by the setBus* functions.
+     */
+    const buses: {
+        music: number,
+        gameplay: number,
+        ambience: number,
+        ui: number,
+    };
+
+    /**
+     * Sets the volume of a mixer bus.
+     * @param bus the bus ID from doukutsu.buses
+     * @param volume volume in 0.0 - 1.0 range
+     */
+    function setBusVolume(bus: number, volume: number): void;
+
+    /**
+     * Mutes or unmutes a mixer bus.
+     * @param bus the bus ID from doukutsu.buses
+     * @param muted whether the bus should be muted
+     */
+    function setBusMuted(bus: number, muted: boolean): void;
+
+    /**
+     * Sets how much a mixer bus is lowered while a text box is open.
+     * @param bus the bus ID from doukutsu.buses
+     * @param amount 0.0 - not lowered at all, 1.0 - silenced
+     */
+    function setBusDucking(bus: number, amount: number): void;
+
     /**
      * Returns the value of a certain TSC flag.
      * @param id the flag number
@@ -291,6 +347,29 @@ declare namespace doukutsu {
      */
     function setNPCHandler(npcType: number, handler: (this: void, npc: NPC) => void | null): void;
 
+    /**
+     * Sets the handler override for specified boss type, called once per tick instead of the built-in boss logic.
+     * Passing a null removes the handler.
+     * @param bossType
+     * @param handler
+     */
+    function setBossHandler(bossType: number, handler: (this: void, boss: Boss) => void | null): void;
+
+    /**
+     * Sets the handler for a custom TSC opcode declared in the "opcodes" list of textscript.json.
+     * The handler receives the numeric operands of the opcode. Returning true pauses the script
+     * until resumeTSC is called. Passing a null removes the handler.
+     * @param name three character opcode name, without the leading <
+     * @param handler
+     */
+    function setTSCHandler(name: string, handler: (this: void, ...operands: number[]) => boolean | void | null): void;
+
+    /**
+     * Resumes the text script paused by a custom TSC opcode handler.
+     * Returns false if the script wasn't waiting for a handler.
+     */
+    function resumeTSC(): boolean;
+
     /**
      * Registers an event handler called after all scripts are loaded.
      * @param event event name
@@ -7,9 +7,12 @@ use lua_ffi::lua_method;
 
 use crate::common::{Direction, Rect};
 use crate::framework::filesystem;
+use crate::game::npc::NPC;
+use crate::game::player::Player;
 use crate::game::scripting::lua::{check_status, DRS_RUNTIME_GLOBAL, LuaScriptingState};
-use crate::game::scripting::tsc::text_script::TextScriptEncoding;
+use crate::game::scripting::tsc::text_script::{TextScriptEncoding, TextScriptExecutionState};
 use crate::scene::game_scene::LightingMode;
+use crate::sound::mixer::MixerBus;
 use crate::util::rng::RNG;
 
 pub struct Doukutsu {
@@ -36,7 +39,43 @@ impl Doukutsu {
         if let Some(index) = state.to_int(2) {
             let game_state = &mut (*(*self.ptr).state_ptr);
 
-            game_state.sound_manager.loop_sfx(index as u8);
+            game_state.sound_manager.loop_sfx(index as u8, MixerBus::Gameplay);
+        }
+
+        0
+    }
+
+    unsafe fn lua_set_bus_volume(&self, state: &mut State) -> c_int {
+        if let (Some(bus), Some(volume)) = (state.to_int(2), state.to_float(3)) {
+            let game_state = &mut (*(*self.ptr).state_ptr);
+
+            if let Some(bus) = MixerBus::from_index(bus.max(0) as usize) {
+                game_state.sound_manager.set_bus_volume(bus, volume.clamp(0.0, 1.0));
+            }
+        }
+
+        0
+    }
+
+    unsafe fn lua_set_bus_muted(&self, state: &mut State) -> c_int {
+        if let (Some(bus), Some(muted)) = (state.to_int(2), state.to_bool(3)) {
+            let game_state = &mut (*(*self.ptr).state_ptr);
+
+            if let Some(bus) = MixerBus::from_index(bus.max(0) as usize) {
+                game_state.sound_manager.set_bus_muted(bus, muted);
+            }
+        }
+
+        0
+    }
+
+    unsafe fn lua_set_bus_ducking(&self, state: &mut State) -> c_int {
+        if let (Some(bus), Some(amount)) = (state.to_int(2), state.to_float(3)) {
+            let game_state = &mut (*(*self.ptr).state_ptr);
+
+            if let Some(bus) = MixerBus::from_index(bus.max(0) as usize) {
+                game_state.sound_manager.set_bus_duck_amount(bus, amount);
+            }
         }
 
         0
@@ -222,176 +261,224 @@ impl Doukutsu {
                 }
             };
 
+            Doukutsu::npc_command(state, npc, [&mut game_scene.player1, &mut game_scene.player2], param_type);
+        } else {
+            state.push_nil()
+        }
+
+        1
+    }
+
+    unsafe fn lua_boss_command(&self, state: &mut State) -> c_int {
+        if (*self.ptr).game_scene.is_null() {
+            state.push_nil();
+            return 1;
+        }
+
+        if let (Some(part_id), Some(param_type)) = (state.to_int(2), state.to_int(3)) {
+            let game_scene = &mut *(*self.ptr).game_scene;
+            let boss = &mut game_scene.boss;
+            let idx = part_id as usize;
+
+            if idx >= boss.parts.len() {
+                state.push_nil();
+                return 1;
+            }
+
             match param_type {
-                0x0e => state.push(npc.cond.0 as i32),
-                0x0f => state.push(npc.flags.0),
-                0x10 => state.push(npc.x as f32 / 512.0),
-                0x11 => state.push(npc.y as f32 / 512.0),
-                0x12 => state.push(npc.vel_x as f32 / 512.0),
-                0x13 => state.push(npc.vel_y as f32 / 512.0),
-                0x14 => state.push(npc.vel_x2 as f32 / 512.0),
-                0x15 => state.push(npc.vel_y2 as f32 / 512.0),
-                0x16 => state.push(npc.action_num as i32),
-                0x17 => state.push(npc.anim_num as i32),
-                0x18 => state.push(npc.action_counter as i32),
-                0x19 => state.push(npc.action_counter2 as i32),
-                0x1a => state.push(npc.action_counter3 as i32),
-                0x1b => state.push(npc.anim_counter as i32),
-                0x1c => state.push(npc.parent_id as i32),
-                0x1d => state.push(npc.npc_type as i32),
-                0x1e => state.push(npc.life as i32),
-                0x1f => state.push(npc.flag_num as i32),
-                0x20 => state.push(npc.event_num as i32),
-                0x21 => state.push(npc.direction as i32),
-                0x22 => state.push(npc.tsc_direction as i32),
-                0x10e => {
-                    if let Some(v) = state.to_uint(4) {
-                        npc.cond.0 = v as u16;
+                0x23 => state.push(boss.hurt_sound[idx] as i32),
+                0x24 => state.push(boss.death_sound[idx] as i32),
+                0x123 => {
+                    if let Some(v) = state.to_int(4) {
+                        boss.hurt_sound[idx] = v as u8;
                     }
                 }
-                0x10f => {
-                    if let Some(v) = state.to_uint(4) {
-                        npc.flags.0 = v;
+                0x124 => {
+                    if let Some(v) = state.to_int(4) {
+                        boss.death_sound[idx] = v as u8;
                     }
                 }
-                0x110 => {
-                    if let Some(v) = state.to_float(4) {
-                        // set x
-                        npc.x = (v * 512.0) as i32;
-                    }
+                _ => Doukutsu::npc_command(
+                    state,
+                    &mut boss.parts[idx],
+                    [&mut game_scene.player1, &mut game_scene.player2],
+                    param_type,
+                ),
+            }
+        } else {
+            state.push_nil()
+        }
+
+        1
+    }
+
+    /// Handles the commands shared between NPCs and boss parts.
+    unsafe fn npc_command(state: &mut State, npc: &mut NPC, players: [&mut Player; 2], param_type: i32) {
+        match param_type {
+            0x0e => state.push(npc.cond.0 as i32),
+            0x0f => state.push(npc.flags.0),
+            0x10 => state.push(npc.x as f32 / 512.0),
+            0x11 => state.push(npc.y as f32 / 512.0),
+            0x12 => state.push(npc.vel_x as f32 / 512.0),
+            0x13 => state.push(npc.vel_y as f32 / 512.0),
+            0x14 => state.push(npc.vel_x2 as f32 / 512.0),
+            0x15 => state.push(npc.vel_y2 as f32 / 512.0),
+            0x16 => state.push(npc.action_num as i32),
+            0x17 => state.push(npc.anim_num as i32),
+            0x18 => state.push(npc.action_counter as i32),
+            0x19 => state.push(npc.action_counter2 as i32),
+            0x1a => state.push(npc.action_counter3 as i32),
+            0x1b => state.push(npc.anim_counter as i32),
+            0x1c => state.push(npc.parent_id as i32),
+            0x1d => state.push(npc.npc_type as i32),
+            0x1e => state.push(npc.life as i32),
+            0x1f => state.push(npc.flag_num as i32),
+            0x20 => state.push(npc.event_num as i32),
+            0x21 => state.push(npc.direction as i32),
+            0x22 => state.push(npc.tsc_direction as i32),
+            0x10e => {
+                if let Some(v) = state.to_uint(4) {
+                    npc.cond.0 = v as u16;
                 }
-                0x111 => {
-                    if let Some(v) = state.to_float(4) {
-                        // set y
-                        npc.y = (v * 512.0) as i32;
-                    }
+            }
+            0x10f => {
+                if let Some(v) = state.to_uint(4) {
+                    npc.flags.0 = v;
                 }
-                0x112 => {
-                    if let Some(v) = state.to_float(4) {
-                        // set vel x
-                        npc.vel_x = (v * 512.0) as i32;
-                    }
+            }
+            0x110 => {
+                if let Some(v) = state.to_float(4) {
+                    // set x
+                    npc.x = (v * 512.0) as i32;
                 }
-                0x113 => {
-                    if let Some(v) = state.to_float(4) {
-                        // set vel y
-                        npc.vel_y = (v * 512.0) as i32;
-                    }
+            }
+            0x111 => {
+                if let Some(v) = state.to_float(4) {
+                    // set y
+                    npc.y = (v * 512.0) as i32;
                 }
-                0x114 => {
-                    if let Some(v) = state.to_float(4) {
-                        // set vel x 2
-                        npc.vel_x2 = (v * 512.0) as i32;
-                    }
+            }
+            0x112 => {
+                if let Some(v) = state.to_float(4) {
+                    // set vel x
+                    npc.vel_x = (v * 512.0) as i32;
                 }
-                0x115 => {
-                    if let Some(v) = state.to_float(4) {
-                        // set vel y 2
-                        npc.vel_y2 = (v * 512.0) as i32;
-                    }
+            }
+            0x113 => {
+                if let Some(v) = state.to_float(4) {
+                    // set vel y
+                    npc.vel_y = (v * 512.0) as i32;
                 }
-                0x116 => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.action_num = v as u16;
-                    }
+            }
+            0x114 => {
+                if let Some(v) = state.to_float(4) {
+                    // set vel x 2
+                    npc.vel_x2 = (v * 512.0) as i32;
                 }
-                0x117 => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.anim_num = v as u16;
-                    }
+            }
+            0x115 => {
+                if let Some(v) = state.to_float(4) {
+                    // set vel y 2
+                    npc.vel_y2 = (v * 512.0) as i32;
                 }
-                0x118 => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.action_counter = v as u16;
-                    }
+            }
+            0x116 => {
+                if let Some(v) = state.to_int(4) {
+                    npc.action_num = v as u16;
                 }
-                0x119 => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.action_counter2 = v as u16;
-                    }
+            }
+            0x117 => {
+                if let Some(v) = state.to_int(4) {
+                    npc.anim_num = v as u16;
                 }
-                0x11a => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.action_counter3 = v as u16;
-                    }
+            }
+            0x118 => {
+                if let Some(v) = state.to_int(4) {
+                    npc.action_counter = v as u16;
                 }
-                0x11b => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.anim_counter = v as u16;
-                    }
+            }
+            0x119 => {
+                if let Some(v) = state.to_int(4) {
+                    npc.action_counter2 = v as u16;
                 }
-                0x11c => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.parent_id = v as u16;
-                    }
+            }
+            0x11a => {
+                if let Some(v) = state.to_int(4) {
+                    npc.action_counter3 = v as u16;
                 }
-                0x11d => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.npc_type = v as u16;
-                    }
+            }
+            0x11b => {
+                if let Some(v) = state.to_int(4) {
+                    npc.anim_counter = v as u16;
                 }
-                0x11e => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.life = v as u16;
-                    }
+            }
+            0x11c => {
+                if let Some(v) = state.to_int(4) {
+                    npc.parent_id = v as u16;
                 }
-                0x11f => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.flag_num = v as u16;
-                    }
+            }
+            0x11d => {
+                if let Some(v) = state.to_int(4) {
+                    npc.npc_type = v as u16;
                 }
-                0x120 => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.event_num = v as u16;
-                    }
+            }
+            0x11e => {
+                if let Some(v) = state.to_int(4) {
+                    npc.life = v as u16;
                 }
-                0x121 | 0x122 => {
-                    if let Some(v) = state.to_int(4) {
-                        npc.direction = Direction::from_int_facing(v as _).unwrap_or(Direction::Left);
-                        npc.tsc_direction = v as _;
-                    }
+            }
+            0x11f => {
+                if let Some(v) = state.to_int(4) {
+                    npc.flag_num = v as u16;
                 }
-                0x200 => {
-                    // get player idx
-                    let index = npc.get_closest_player_idx_mut(&[&mut game_scene.player1, &mut game_scene.player2]);
-                    state.push(index as i32);
-                }
-                0x201 => {
-                    // random
-                    if let (Some(min), Some(max)) = (state.to_int(4), state.to_int(5)) {
-                        if max < min {
-                            state.error("max < min");
-                        } else {
-                            state.push(npc.rng.range(min..max));
-                        }
-                    } else {
-                        state.error("Invalid parameters supplied.");
-                    }
+            }
+            0x120 => {
+                if let Some(v) = state.to_int(4) {
+                    npc.event_num = v as u16;
                 }
-                0x202 => {
-                    // get anim rect
-                    state.push(npc.anim_rect.left as i32);
-                    state.push(npc.anim_rect.top as i32);
-                    state.push(npc.anim_rect.right as i32);
-                    state.push(npc.anim_rect.bottom as i32);
+            }
+            0x121 | 0x122 => {
+                if let Some(v) = state.to_int(4) {
+                    npc.direction = Direction::from_int_facing(v as _).unwrap_or(Direction::Left);
+                    npc.tsc_direction = v as _;
                 }
-                0x203 => {
-                    // set anim rect
-                    if let (Some(l), Some(t), Some(r), Some(b)) =
-                    (state.to_int(4), state.to_int(5), state.to_int(6), state.to_int(7))
-                    {
-                        npc.anim_rect = Rect { left: l as u16, top: t as u16, right: r as u16, bottom: b as u16 };
+            }
+            0x200 => {
+                // get player idx
+                let index = npc.get_closest_player_idx_mut(&players);
+                state.push(index as i32);
+            }
+            0x201 => {
+                // random
+                if let (Some(min), Some(max)) = (state.to_int(4), state.to_int(5)) {
+                    if max < min {
+                        state.error("max < min");
                     } else {
-                        state.error("Invalid parameters supplied.");
+                        state.push(npc.rng.range(min..max));
                     }
+                } else {
+                    state.error("Invalid parameters supplied.");
                 }
-                _ => state.push_nil(),
             }
-        } else {
-            state.push_nil()
+            0x202 => {
+                // get anim rect
+                state.push(npc.anim_rect.left as i32);
+                state.push(npc.anim_rect.top as i32);
+                state.push(npc.anim_rect.right as i32);
+                state.push(npc.anim_rect.bottom as i32);
+            }
+            0x203 => {
+                // set anim rect
+                if let (Some(l), Some(t), Some(r), Some(b)) =
+                    (state.to_int(4), state.to_int(5), state.to_int(6), state.to_int(7))
+                {
+                    npc.anim_rect = Rect { left: l as u16, top: t as u16, right: r as u16, bottom: b as u16 };
+                } else {
+                    state.error("Invalid parameters supplied.");
+                }
+            }
+            _ => state.push_nil(),
         }
-
-        1
     }
 
     unsafe fn lua_player_command(&self, state: &mut State) -> c_int {
@@ -498,6 +585,19 @@ impl Doukutsu {
         1
     }
 
+    unsafe fn lua_resume_tsc(&self, state: &mut State) -> c_int {
+        let game_state = &mut (*(*self.ptr).state_ptr);
+
+        if let TextScriptExecutionState::WaitScript(event, ip) = game_state.textscript_vm.state {
+            game_state.textscript_vm.state = TextScriptExecutionState::Running(event, ip);
+            state.push(true);
+        } else {
+            state.push(false);
+        }
+
+        1
+    }
+
     unsafe fn lua_load_script(&mut self, state: &mut State) -> c_int {
         let lua_state = &mut (*self.ptr);
 
@@ -555,6 +655,9 @@ impl LuaObject for Doukutsu {
         vec![
             lua_method!("playSfx", Doukutsu, Doukutsu::lua_play_sfx),
             lua_method!("playSong", Doukutsu, Doukutsu::lua_play_song),
+            lua_method!("setBusVolume", Doukutsu, Doukutsu::lua_set_bus_volume),
+            lua_method!("setBusMuted", Doukutsu, Doukutsu::lua_set_bus_muted),
+            lua_method!("setBusDucking", Doukutsu, Doukutsu::lua_set_bus_ducking),
             lua_method!("getFlag", Doukutsu, Doukutsu::lua_get_flag),
             lua_method!("setFlag", Doukutsu, Doukutsu::lua_set_flag),
             lua_method!("getSkipFlag", Doukutsu, Doukutsu::lua_get_skip_flag),
@@ -562,8 +665,10 @@ impl LuaObject for Doukutsu {
             lua_method!("setEngineConstant", Doukutsu, Doukutsu::lua_set_engine_constant),
             lua_method!("playerCommand", Doukutsu, Doukutsu::lua_player_command),
             lua_method!("npcCommand", Doukutsu, Doukutsu::lua_npc_command),
+            lua_method!("bossCommand", Doukutsu, Doukutsu::lua_boss_command),
             lua_method!("stageCommand", Doukutsu, Doukutsu::lua_stage_command),
             lua_method!("loadScript", Doukutsu, Doukutsu::lua_load_script),
+            lua_method!("resumeTSC", Doukutsu, Doukutsu::lua_resume_tsc),
         ]
     }
 }
@@ -592,4 +697,58 @@ impl LuaScriptingState {
 
         result
     }
+
+    pub fn try_run_boss_hook(&mut self, boss_type: u16) -> bool {
+        let mut result = false;
+
+        if let Some(state) = &mut self.state {
+            state.get_global(DRS_RUNTIME_GLOBAL);
+            state.get_field(-1, "_tryBossHook");
+
+            state.push(boss_type as i32);
+
+            if let Err((_, err)) = state.pcall(1, 1, 0) {
+                log::error!("boss_hook error: {}", err);
+            }
+
+            if let Some(val) = state.to_bool(-1) {
+                result = val;
+            }
+
+            state.pop(2);
+        }
+
+        result
+    }
+
+    /// Calls the Lua handler of a custom TSC opcode. Returns whether the text script should wait
+    /// until the handler resumes it, or None if no handler is registered.
+    pub fn try_run_tsc_handler(&mut self, name: &str, operands: &[i32]) -> Option<bool> {
+        let mut result = None;
+
+        if let Some(state) = &mut self.state {
+            state.get_global(DRS_RUNTIME_GLOBAL);
+            state.get_field(-1, "_tryTSCHandler");
+
+            state.push(name);
+            for &operand in operands {
+                state.push(operand);
+            }
+
+            if let Err((_, err)) = state.pcall(operands.len() as i32 + 1, 2, 0) {
+                log::error!("tsc_handler error: {}", err);
+                state.pop(2);
+
+                return None;
+            }
+
+            if state.to_bool(-2) == Some(true) {
+                result = Some(state.to_bool(-1).unwrap_or(false));
+            }
+
+            state.pop(3);
+        }
+
+        result
+    }
 }
@@ -86,6 +86,25 @@ pub fn put_string(buffer: &mut Vec<u8>, out: &mut Vec<u8>, encoding: TextScriptE
     out.append(&mut tmp_buf);
 }
 
+/// Decodes raw script text, as it would be displayed in game.
+pub fn read_string(buffer: &[u8], encoding: TextScriptEncoding) -> String {
+    let mut cursor = Cursor::new(buffer);
+    let mut result = String::with_capacity(buffer.len());
+    let mut remaining = buffer.len() as u32;
+
+    while remaining > 0 {
+        let (consumed, chr) = match encoding {
+            TextScriptEncoding::UTF8 => read_cur_wtf8(&mut cursor, remaining),
+            TextScriptEncoding::ShiftJIS => read_cur_shift_jis(&mut cursor, remaining),
+        };
+
+        remaining -= consumed;
+        result.push(chr);
+    }
+
+    result
+}
+
 #[test]
 fn test_varint() {
     for n in -4000..=4000 {
@@ -4,17 +4,35 @@ use std::str::FromStr;
 
 use itertools::Itertools;
 
+use crate::engine_constants::TextScriptExtensions;
 use crate::framework::error::GameError::ParseError;
 use crate::framework::error::GameResult;
-use crate::game::scripting::tsc::bytecode_utils::{put_string, put_varint};
+use crate::game::scripting::tsc::bytecode_utils::{put_string, put_varint, read_string};
 use crate::game::scripting::tsc::credit_script::CreditScript;
 use crate::game::scripting::tsc::opcodes::{CreditOpCode, TSCOpCode};
 use crate::game::scripting::tsc::parse_utils::{expect_char, read_number, skip_until};
 use crate::game::scripting::tsc::text_script::{TextScript, TextScriptEncoding};
+use crate::game::scripting::tsc::translation::StringHook;
 
 impl TextScript {
     /// Compiles a decrypted text script data into internal bytecode.
-    pub fn compile(data: &[u8], strict: bool, encoding: TextScriptEncoding) -> GameResult<TextScript> {
+    pub fn compile(
+        data: &[u8],
+        strict: bool,
+        encoding: TextScriptEncoding,
+        extensions: &TextScriptExtensions,
+    ) -> GameResult<TextScript> {
+        TextScript::compile_with_hook(data, strict, encoding, extensions, None)
+    }
+
+    /// Compiles a decrypted text script, passing every message string through given hook first.
+    pub fn compile_with_hook(
+        data: &[u8],
+        strict: bool,
+        encoding: TextScriptEncoding,
+        extensions: &TextScriptExtensions,
+        mut hook: Option<&mut StringHook>,
+    ) -> GameResult<TextScript> {
         let mut event_map = HashMap::new();
         let mut iter = data.iter().copied().peekable();
         let mut last_event = 0;
@@ -45,7 +63,14 @@ impl TextScript {
                         }
                     }
 
-                    let bytecode = TextScript::compile_event(&mut iter, strict, encoding)?;
+                    let bytecode = TextScript::compile_event(
+                        &mut iter,
+                        event_num,
+                        strict,
+                        encoding,
+                        extensions,
+                        hook.as_deref_mut(),
+                    )?;
                     log::info!("Successfully compiled event #{} ({} bytes generated).", event_num, bytecode.len());
                     event_map.insert(event_num, bytecode);
                 }
@@ -69,19 +94,30 @@ impl TextScript {
 
     fn compile_event<I: Iterator<Item=u8>>(
         iter: &mut Peekable<I>,
+        event_num: u16,
         strict: bool,
         encoding: TextScriptEncoding,
+        extensions: &TextScriptExtensions,
+        mut hook: Option<&mut StringHook>,
     ) -> GameResult<Vec<u8>> {
         let mut bytecode = Vec::new();
         let mut char_buf = Vec::with_capacity(16);
         let mut allow_next_event = true;
+        let mut string_index = 0;
 
         while let Some(&chr) = iter.peek() {
             match chr {
                 b'#' if allow_next_event => {
                     if !char_buf.is_empty() {
                         put_varint(TSCOpCode::_STR as i32, &mut bytecode);
-                        put_string(&mut char_buf, &mut bytecode, encoding);
+                        put_message(
+                            &mut char_buf,
+                            &mut bytecode,
+                            encoding,
+                            event_num,
+                            &mut string_index,
+                            hook.as_deref_mut(),
+                        );
                     }
 
                     // some events end without <END marker.
@@ -93,7 +129,14 @@ impl TextScript {
 
                     if !char_buf.is_empty() {
                         put_varint(TSCOpCode::_STR as i32, &mut bytecode);
-                        put_string(&mut char_buf, &mut bytecode, encoding);
+                        put_message(
+                            &mut char_buf,
+                            &mut bytecode,
+                            encoding,
+                            event_num,
+                            &mut string_index,
+                            hook.as_deref_mut(),
+                        );
                     }
 
                     iter.next();
@@ -104,7 +147,7 @@ impl TextScript {
 
                     let code = String::from_utf8_lossy(&n);
 
-                    TextScript::compile_code(&code, strict, iter, &mut bytecode)?;
+                    TextScript::compile_code(&code, strict, extensions, iter, &mut bytecode)?;
                 }
                 b'\r' => {
                     iter.next();
@@ -134,10 +177,54 @@ impl TextScript {
     fn compile_code<I: Iterator<Item=u8>>(
         code: &str,
         strict: bool,
+        extensions: &TextScriptExtensions,
         iter: &mut Peekable<I>,
         out: &mut Vec<u8>,
     ) -> GameResult {
-        let instr = TSCOpCode::from_str(code).map_err(|_| ParseError(format!("Unknown opcode: {}", code)))?;
+        let instr = match TSCOpCode::from_str(code) {
+            Ok(instr) => instr,
+            Err(_) => {
+                let opcode =
+                    extensions.get_opcode(code).ok_or_else(|| ParseError(format!("Unknown opcode: {}", code)))?;
+
+                put_varint(TSCOpCode::_CUS as i32, out);
+                for chr in opcode.name.bytes() {
+                    put_varint(chr as i32, out);
+                }
+                put_varint(opcode.operands as i32, out);
+
+                for idx in 0..opcode.operands {
+                    if idx != 0 {
+                        if strict {
+                            expect_char(b':', iter)?;
+                        } else {
+                            iter.next().ok_or_else(|| ParseError("Script unexpectedly ended.".to_owned()))?;
+                        }
+                    }
+
+                    put_varint(read_number(iter)?, out);
+                }
+
+                return Ok(());
+            }
+        };
+
+        let is_variable_op = matches!(
+            instr,
+            TSCOpCode::VAs
+                | TSCOpCode::VAp
+                | TSCOpCode::VAm
+                | TSCOpCode::VAx
+                | TSCOpCode::VAd
+                | TSCOpCode::VAr
+                | TSCOpCode::VAJ
+                | TSCOpCode::RND
+                | TSCOpCode::RNJ
+                | TSCOpCode::VNM
+        );
+        if is_variable_op && !extensions.variables {
+            return Err(ParseError(format!("Opcode {} requires variables to be enabled in textscript.json.", code)));
+        }
 
         match instr {
             // Zero operand codes
@@ -221,7 +308,8 @@ impl TextScript {
             | TSCOpCode::ACH
             | TSCOpCode::S2MV
             | TSCOpCode::S2PJ
-            | TSCOpCode::PSH => {
+            | TSCOpCode::PSH
+            | TSCOpCode::VNM => {
                 let operand = read_number(iter)?;
                 put_varint(instr as i32, out);
                 put_varint(operand as i32, out);
@@ -241,7 +329,14 @@ impl TextScript {
             | TSCOpCode::SMP
             | TSCOpCode::PSp
             | TSCOpCode::IpN
-            | TSCOpCode::FFm => {
+            | TSCOpCode::FFm
+            | TSCOpCode::VAs
+            | TSCOpCode::VAp
+            | TSCOpCode::VAm
+            | TSCOpCode::VAx
+            | TSCOpCode::VAd
+            | TSCOpCode::VAr
+            | TSCOpCode::RNJ => {
                 let operand_a = read_number(iter)?;
                 if strict {
                     expect_char(b':', iter)?;
@@ -255,7 +350,13 @@ impl TextScript {
                 put_varint(operand_b as i32, out);
             }
             // Three operand codes
-            TSCOpCode::ANP | TSCOpCode::CNP | TSCOpCode::INP | TSCOpCode::TAM | TSCOpCode::CMP | TSCOpCode::INJ => {
+            TSCOpCode::ANP
+            | TSCOpCode::CNP
+            | TSCOpCode::INP
+            | TSCOpCode::TAM
+            | TSCOpCode::CMP
+            | TSCOpCode::INJ
+            | TSCOpCode::RND => {
                 let operand_a = read_number(iter)?;
                 if strict {
                     expect_char(b':', iter)?;
@@ -276,7 +377,7 @@ impl TextScript {
                 put_varint(operand_c as i32, out);
             }
             // Four operand codes
-            TSCOpCode::TRA | TSCOpCode::MNP | TSCOpCode::SNP => {
+            TSCOpCode::TRA | TSCOpCode::MNP | TSCOpCode::SNP | TSCOpCode::VAJ => {
                 let operand_a = read_number(iter)?;
                 if strict {
                     expect_char(b':', iter)?;
@@ -303,7 +404,7 @@ impl TextScript {
                 put_varint(operand_c as i32, out);
                 put_varint(operand_d as i32, out);
             }
-            TSCOpCode::_NOP | TSCOpCode::_UNI | TSCOpCode::_STR | TSCOpCode::_END => {
+            TSCOpCode::_NOP | TSCOpCode::_UNI | TSCOpCode::_STR | TSCOpCode::_END | TSCOpCode::_CUS => {
                 unreachable!()
             }
         }
@@ -312,11 +413,51 @@ impl TextScript {
     }
 }
 
+/// Compiles a message string, numbering the ones with any visible text for the hook.
+fn put_message(
+    char_buf: &mut Vec<u8>,
+    out: &mut Vec<u8>,
+    encoding: TextScriptEncoding,
+    event_num: u16,
+    string_index: &mut u16,
+    hook: Option<&mut StringHook>,
+) {
+    if let Some(hook) = hook {
+        let text = read_string(char_buf, encoding);
+
+        if !text.trim().is_empty() {
+            let index = *string_index;
+            *string_index += 1;
+
+            if let Some(replacement) = hook(event_num, index, &text).filter(|s| !s.is_empty()) {
+                char_buf.clear();
+                put_string(&mut replacement.into_bytes(), out, TextScriptEncoding::UTF8);
+                return;
+            }
+        }
+    }
+
+    put_string(char_buf, out, encoding);
+}
+
 impl CreditScript {
     pub fn compile(data: &[u8], strict: bool, encoding: TextScriptEncoding) -> GameResult<CreditScript> {
+        CreditScript::compile_with_hook(data, strict, encoding, None)
+    }
+
+    /// Compiles a credit script, passing every line of text through given hook first.
+    /// Lines are numbered from the last label, which is used in place of the event number.
+    pub fn compile_with_hook(
+        data: &[u8],
+        strict: bool,
+        encoding: TextScriptEncoding,
+        mut hook: Option<&mut StringHook>,
+    ) -> GameResult<CreditScript> {
         let mut labels = HashMap::new();
         let mut bytecode = Vec::new();
         let mut iter = data.iter().copied().peekable();
+        let mut last_label = 0;
+        let mut line_index = 0;
 
         while let Some(chr) = iter.next() {
             match chr {
@@ -339,7 +480,14 @@ impl CreditScript {
                     if let Ok(cast_tile) = read_number(&mut iter) {
                         put_varint(CreditOpCode::PushLine as i32, &mut bytecode);
                         put_varint((cast_tile as u16) as i32, &mut bytecode);
-                        put_string(&mut char_buf, &mut bytecode, encoding);
+                        put_message(
+                            &mut char_buf,
+                            &mut bytecode,
+                            encoding,
+                            last_label,
+                            &mut line_index,
+                            hook.as_deref_mut(),
+                        );
                     }
                 }
                 b'-' => {
@@ -368,6 +516,8 @@ impl CreditScript {
                     let pos = bytecode.len() as u32;
 
                     labels.insert(label, pos);
+                    last_label = label;
+                    line_index = 0;
                 }
                 b'j' => {
                     let label = read_number(&mut iter)? as u16;
@@ -409,3 +559,39 @@ impl CreditScript {
         Ok(CreditScript { labels, bytecode })
     }
 }
+
+#[test]
+fn test_variable_opcodes() {
+    let script = b"#0100\n<VA+0001:0005<VAJ0001:0004:0003:0200<END";
+    let disabled = TextScriptExtensions::default();
+    let enabled = TextScriptExtensions { variables: true, ..Default::default() };
+
+    assert!(TextScript::compile(script, true, TextScriptEncoding::UTF8, &disabled).is_err());
+
+    let text_script = TextScript::compile(script, true, TextScriptEncoding::UTF8, &enabled).unwrap();
+    let mut expected = Vec::new();
+    for n in [TSCOpCode::VAp as i32, 1, 5, TSCOpCode::VAJ as i32, 1, 4, 3, 200, TSCOpCode::END as i32] {
+        put_varint(n, &mut expected);
+    }
+    assert_eq!(text_script.event_map.get(&100), Some(&expected));
+}
+
+#[test]
+fn test_custom_opcodes() {
+    use crate::engine_constants::CustomOpcode;
+
+    let script = b"#0100\n<CAM0010:0020<END";
+    let extensions = TextScriptExtensions {
+        opcodes: vec![CustomOpcode { name: "CAM".to_owned(), operands: 2 }],
+        ..Default::default()
+    };
+
+    assert!(TextScript::compile(script, true, TextScriptEncoding::UTF8, &TextScriptExtensions::default()).is_err());
+
+    let text_script = TextScript::compile(script, true, TextScriptEncoding::UTF8, &extensions).unwrap();
+    let mut expected = Vec::new();
+    for n in [TSCOpCode::_CUS as i32, b'C' as i32, b'A' as i32, b'M' as i32, 2, 10, 20, TSCOpCode::END as i32] {
+        put_varint(n, &mut expected);
+    }
+    assert_eq!(text_script.event_map.get(&100), Some(&expected));
+}
@@ -36,7 +36,13 @@ impl CreditScript {
             decrypt_tsc(&mut buf);
         }
 
-        CreditScript::compile(&buf, false, constants.textscript.encoding)
+        let translation = &constants.textscript.translation;
+        if translation.is_empty() {
+            return CreditScript::compile(&buf, false, constants.textscript.encoding);
+        }
+
+        let mut hook = |label: u16, index: u16, text: &str| translation.translate("Credit", label, index, text);
+        CreditScript::compile_with_hook(&buf, false, constants.textscript.encoding, Some(&mut hook))
     }
 }
 
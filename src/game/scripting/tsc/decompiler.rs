@@ -102,7 +102,8 @@ impl TextScript {
                         | TSCOpCode::ACH
                         | TSCOpCode::S2MV
                         | TSCOpCode::S2PJ
-                        | TSCOpCode::PSH => {
+                        | TSCOpCode::PSH
+                        | TSCOpCode::VNM => {
                             let par_a = read_cur_varint(&mut cursor)?;
 
                             writeln!(&mut result, "{:?}({})", op, par_a).unwrap();
@@ -121,14 +122,27 @@ impl TextScript {
                         | TSCOpCode::SMP
                         | TSCOpCode::PSp
                         | TSCOpCode::IpN
-                        | TSCOpCode::FFm => {
+                        | TSCOpCode::FFm
+                        | TSCOpCode::VAs
+                        | TSCOpCode::VAp
+                        | TSCOpCode::VAm
+                        | TSCOpCode::VAx
+                        | TSCOpCode::VAd
+                        | TSCOpCode::VAr
+                        | TSCOpCode::RNJ => {
                             let par_a = read_cur_varint(&mut cursor)?;
                             let par_b = read_cur_varint(&mut cursor)?;
 
                             writeln!(&mut result, "{:?}({}, {})", op, par_a, par_b).unwrap();
                         }
                         // Three operand codes
-                        TSCOpCode::ANP | TSCOpCode::CNP | TSCOpCode::INP | TSCOpCode::TAM | TSCOpCode::CMP | TSCOpCode::INJ => {
+                        TSCOpCode::ANP
+                        | TSCOpCode::CNP
+                        | TSCOpCode::INP
+                        | TSCOpCode::TAM
+                        | TSCOpCode::CMP
+                        | TSCOpCode::INJ
+                        | TSCOpCode::RND => {
                             let par_a = read_cur_varint(&mut cursor)?;
                             let par_b = read_cur_varint(&mut cursor)?;
                             let par_c = read_cur_varint(&mut cursor)?;
@@ -136,7 +150,7 @@ impl TextScript {
                             writeln!(&mut result, "{:?}({}, {}, {})", op, par_a, par_b, par_c).unwrap();
                         }
                         // Four operand codes
-                        TSCOpCode::TRA | TSCOpCode::MNP | TSCOpCode::SNP => {
+                        TSCOpCode::TRA | TSCOpCode::MNP | TSCOpCode::SNP | TSCOpCode::VAJ => {
                             let par_a = read_cur_varint(&mut cursor)?;
                             let par_b = read_cur_varint(&mut cursor)?;
                             let par_c = read_cur_varint(&mut cursor)?;
@@ -170,6 +184,20 @@ impl TextScript {
                             }
                             result.push_str("\")\n");
                         }
+                        TSCOpCode::_CUS => {
+                            let mut name = String::new();
+                            for _ in 0..3 {
+                                name.push(std::char::from_u32(read_cur_varint(&mut cursor)? as u32).unwrap_or('?'));
+                            }
+
+                            let count = read_cur_varint(&mut cursor)?;
+                            let mut operands = Vec::new();
+                            for _ in 0..count {
+                                operands.push(read_cur_varint(&mut cursor)?.to_string());
+                            }
+
+                            writeln!(&mut result, "%custom({}, [{}])", name, operands.join(", ")).unwrap();
+                        }
                         TSCOpCode::_NOP => result.push_str("%no_op()\n"),
                         TSCOpCode::_UNI => result.push_str("%unimplemented()\n"),
                         TSCOpCode::_END => result.push_str("%end_marker()\n"),
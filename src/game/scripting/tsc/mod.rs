@@ -3,6 +3,8 @@ mod compiler;
 pub mod credit_script;
 mod decompiler;
 mod encryption;
-mod opcodes;
+pub mod opcodes;
 mod parse_utils;
+pub mod rich_text;
 pub mod text_script;
+pub mod translation;
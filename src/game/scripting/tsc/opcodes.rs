@@ -12,6 +12,8 @@ pub enum TSCOpCode {
     _STR,
     /// internal: implicit END marker
     _END,
+    /// internal: opcode declared in `textscript.json`, followed by its name and operands
+    _CUS,
 
     // ---- Vanilla opcodes ----
     /// <BOAxxxx, Starts boss animation
@@ -265,6 +267,33 @@ pub enum TSCOpCode {
     /// <FRE related to player 2?
     FR2,
     // ---- Custom opcodes, for use by modders ----
+    /// <VA=xxxx:yyyy, Sets variable xxxx to yyyy
+    #[strum(serialize = "VA=")]
+    VAs,
+    /// <VA+xxxx:yyyy, Adds yyyy to variable xxxx
+    #[strum(serialize = "VA+")]
+    VAp,
+    /// <VA-xxxx:yyyy, Subtracts yyyy from variable xxxx
+    #[strum(serialize = "VA-")]
+    VAm,
+    /// <VA*xxxx:yyyy, Multiplies variable xxxx by yyyy
+    #[strum(serialize = "VA*")]
+    VAx,
+    /// <VA/xxxx:yyyy, Divides variable xxxx by yyyy, does nothing if yyyy is 0
+    #[strum(serialize = "VA/")]
+    VAd,
+    /// <VA%xxxx:yyyy, Sets variable xxxx to the remainder of dividing it by yyyy, does nothing if yyyy is 0
+    #[strum(serialize = "VA%")]
+    VAr,
+    /// <VAJxxxx:yyyy:zzzz:wwww, Jumps to event wwww if variable xxxx compared to zzzz using operator yyyy is true
+    /// 0000 - equal, 0001 - not equal, 0002 - less, 0003 - less or equal, 0004 - greater, 0005 - greater or equal
+    VAJ,
+    /// <RNDxxxx:yyyy:zzzz, Sets variable xxxx to a random number in range yyyy-zzzz
+    RND,
+    /// <RNJxxxx:yyyy, Jumps to a random event in range xxxx-yyyy
+    RNJ,
+    /// <VNMxxxx, Displays the value of variable xxxx
+    VNM,
 }
 
 #[derive(FromPrimitive, PartialEq, Copy, Clone)]
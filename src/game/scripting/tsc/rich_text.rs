@@ -0,0 +1,188 @@
+//! Markup for message text, enabled with the `rich_text` option in `textscript.json`.
+//!
+//! Tags are enclosed in curly braces and are consumed by the script VM as the text is printed:
+//!
+//! - `{c:RRGGBB}` / `{c}` - changes the text color / restores the default one,
+//! - `{shake}`, `{wave}` / `{fx}` - enables an effect for following characters / disables it,
+//! - `{s:N}` / `{s}` - sets the delay between characters in ticks / restores the default speed,
+//! - `{i:N}` - inline item icon from `ItemImage`,
+//! - `{w:N}` - inline weapon icon from `ArmsImage`.
+//!
+//! Styles and icons are stored in the text box lines as characters from the private use planes,
+//! so they can be moved between lines along with the text they belong to.
+
+use crate::graphics::font::TextBuilder;
+
+const STYLE_MARKER_BASE: u32 = 0xe000;
+const STYLE_MARKER_LAST: u32 = 0xf8ff;
+const ITEM_ICON_BASE: u32 = 0xf0000;
+const ITEM_ICON_LAST: u32 = 0xf7fff;
+const WEAPON_ICON_BASE: u32 = 0xf8000;
+const WEAPON_ICON_LAST: u32 = 0xfffff;
+
+pub const ITEM_ICON_WIDTH: f32 = 32.0;
+pub const WEAPON_ICON_WIDTH: f32 = 16.0;
+pub const MAX_STYLES: usize = (STYLE_MARKER_LAST - STYLE_MARKER_BASE + 1) as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEffect {
+    None,
+    Shake,
+    Wave,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextStyle {
+    pub color: Option<(u8, u8, u8)>,
+    pub effect: TextEffect,
+}
+
+impl TextStyle {
+    pub const fn new() -> TextStyle {
+        TextStyle { color: None, effect: TextEffect::None }
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RichTextTag {
+    Color(Option<(u8, u8, u8)>),
+    Effect(TextEffect),
+    Speed(Option<u8>),
+    ItemIcon(u16),
+    WeaponIcon(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RichChar {
+    Char(char),
+    Style(usize),
+    ItemIcon(u16),
+    WeaponIcon(u16),
+}
+
+impl RichChar {
+    pub fn from_char(chr: char) -> RichChar {
+        match chr as u32 {
+            c @ STYLE_MARKER_BASE..=STYLE_MARKER_LAST => RichChar::Style((c - STYLE_MARKER_BASE) as usize),
+            c @ ITEM_ICON_BASE..=ITEM_ICON_LAST => RichChar::ItemIcon((c - ITEM_ICON_BASE) as u16),
+            c @ WEAPON_ICON_BASE..=WEAPON_ICON_LAST => RichChar::WeaponIcon((c - WEAPON_ICON_BASE) as u16),
+            _ => RichChar::Char(chr),
+        }
+    }
+
+    /// Same as [RichChar::from_char] if rich text is enabled, otherwise every character is plain text,
+    /// as Shift-JIS user-defined characters are decoded into the same private use area as style markers.
+    pub fn parse(chr: char, rich_text: bool) -> RichChar {
+        if rich_text {
+            RichChar::from_char(chr)
+        } else {
+            RichChar::Char(chr)
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        let code = match self {
+            RichChar::Char(chr) => return chr,
+            RichChar::Style(idx) => (STYLE_MARKER_BASE + idx as u32).min(STYLE_MARKER_LAST),
+            RichChar::ItemIcon(id) => (ITEM_ICON_BASE + id as u32).min(ITEM_ICON_LAST),
+            RichChar::WeaponIcon(id) => (WEAPON_ICON_BASE + id as u32).min(WEAPON_ICON_LAST),
+        };
+
+        char::from_u32(code).unwrap_or('\u{fffd}')
+    }
+}
+
+/// Parses the contents of a tag, without the curly braces.
+pub fn parse_tag(tag: &str) -> Option<RichTextTag> {
+    let (name, arg) = match tag.split_once(':') {
+        Some((name, arg)) => (name.trim(), Some(arg.trim())),
+        None => (tag.trim(), None),
+    };
+
+    match (name, arg) {
+        ("c", None) => Some(RichTextTag::Color(None)),
+        ("c", Some(color)) => {
+            let color = color.trim_start_matches('#');
+            if color.len() != 6 {
+                return None;
+            }
+
+            let rgb = u32::from_str_radix(color, 16).ok()?;
+            Some(RichTextTag::Color(Some(((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))))
+        }
+        ("shake", None) => Some(RichTextTag::Effect(TextEffect::Shake)),
+        ("wave", None) => Some(RichTextTag::Effect(TextEffect::Wave)),
+        ("fx", None) => Some(RichTextTag::Effect(TextEffect::None)),
+        ("s", None) => Some(RichTextTag::Speed(None)),
+        ("s", Some(speed)) => speed.parse().ok().map(|speed| RichTextTag::Speed(Some(speed))),
+        ("i", Some(id)) => id.parse().ok().map(RichTextTag::ItemIcon),
+        ("w", Some(id)) => id.parse().ok().map(RichTextTag::WeaponIcon),
+        _ => None,
+    }
+}
+
+/// Returns the style active at the end of given line.
+pub fn last_style(line: &[char]) -> Option<usize> {
+    line.iter().rev().find_map(|&chr| match RichChar::from_char(chr) {
+        RichChar::Style(idx) => Some(idx),
+        _ => None,
+    })
+}
+
+/// Computes the width of a text box line, skipping style markers and accounting for inline icons.
+pub fn compute_width(builder: &TextBuilder, line: &[char], rich_text: bool) -> f32 {
+    if !rich_text {
+        return builder.compute_width_iter(line.iter().copied());
+    }
+
+    let mut width = builder
+        .compute_width_iter(line.iter().copied().filter(|&chr| matches!(RichChar::from_char(chr), RichChar::Char(_))));
+
+    for &chr in line {
+        match RichChar::from_char(chr) {
+            RichChar::ItemIcon(_) => width += ITEM_ICON_WIDTH,
+            RichChar::WeaponIcon(_) => width += WEAPON_ICON_WIDTH,
+            _ => (),
+        }
+    }
+
+    width
+}
+
+#[test]
+fn test_rich_text_tags() {
+    assert_eq!(parse_tag("c:FF8000"), Some(RichTextTag::Color(Some((0xff, 0x80, 0x00)))));
+    assert_eq!(parse_tag("c"), Some(RichTextTag::Color(None)));
+    assert_eq!(parse_tag("c:FF80"), None);
+    assert_eq!(parse_tag("wave"), Some(RichTextTag::Effect(TextEffect::Wave)));
+    assert_eq!(parse_tag("s:10"), Some(RichTextTag::Speed(Some(10))));
+    assert_eq!(parse_tag("i:35"), Some(RichTextTag::ItemIcon(35)));
+    assert_eq!(parse_tag("w"), None);
+    assert_eq!(parse_tag("hello"), None);
+
+    for rich in [RichChar::Char('a'), RichChar::Style(3), RichChar::ItemIcon(35), RichChar::WeaponIcon(13)] {
+        assert_eq!(RichChar::from_char(rich.to_char()), rich);
+    }
+}
+
+#[test]
+fn test_rich_text_width() {
+    use crate::graphics::font::{Font, MonospaceFont};
+
+    let font = MonospaceFont(5.0);
+    let line =
+        ['a', RichChar::Style(1).to_char(), 'b', RichChar::ItemIcon(2).to_char(), RichChar::WeaponIcon(3).to_char()];
+    assert_eq!(compute_width(&font.builder(), &line, true), 10.0 + ITEM_ICON_WIDTH + WEAPON_ICON_WIDTH);
+
+    // user-defined Shift-JIS characters share the range with style markers, they're only text without rich text
+    let sjis_user_char = '\u{e001}';
+    assert_eq!(RichChar::parse(sjis_user_char, true), RichChar::Style(1));
+    assert_eq!(RichChar::parse(sjis_user_char, false), RichChar::Char(sjis_user_char));
+    assert_eq!(compute_width(&font.builder(), &['a', sjis_user_char], false), 10.0);
+}
@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io;
 use std::io::Cursor;
 use std::io::Seek;
@@ -19,18 +19,23 @@ use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::game::frame::UpdateTarget;
 use crate::game::npc::NPC;
-use crate::game::player::{ControlMode, TargetPlayer};
+use crate::game::player::TargetPlayer;
 use crate::game::scripting::tsc::bytecode_utils::read_cur_varint;
 use crate::game::scripting::tsc::encryption::decrypt_tsc;
 use crate::game::scripting::tsc::opcodes::TSCOpCode;
+use crate::game::scripting::tsc::rich_text;
+use crate::game::scripting::tsc::rich_text::{RichChar, RichTextTag, TextStyle};
 use crate::game::shared_game_state::ReplayState;
 use crate::game::shared_game_state::SharedGameState;
 use crate::game::weapon::WeaponType;
-use crate::graphics::font::{Font, Symbols};
+use crate::graphics::font::{Font, Symbols, TextBuilder};
 use crate::input::touch_controls::TouchControlType;
 use crate::scene::game_scene::GameScene;
+use crate::sound::mixer::MixerBus;
+use crate::util::rng::RNG;
 
 const TSC_SUBSTITUTION_MAP_SIZE: usize = 1;
+pub const MAX_DIALOGUE_HISTORY: usize = 500;
 
 bitfield! {
     pub struct TextScriptFlags(u16);
@@ -118,6 +123,8 @@ pub enum TextScriptExecutionState {
     WaitStanding(u16, u32),
     WaitConfirmation(u16, u32, u16, u8, ConfirmSelection),
     WaitFade(u16, u32),
+    /// Waits until a Lua handler of a custom opcode calls `doukutsu.resumeTSC`.
+    WaitScript(u16, u32),
     FallingIsland(u16, u32, i32, i32, u16, bool),
     MapSystem,
     SaveProfile(u16, u32),
@@ -133,6 +140,15 @@ pub enum IllustrationState {
     FadeOut(f32),
 }
 
+/// A line of text that has been shown in the text box.
+#[derive(Clone)]
+pub struct DialogueHistoryEntry {
+    pub face: u16,
+    pub player: TargetPlayer,
+    /// Line contents, inline icons are kept but styles are not.
+    pub text: Vec<char>,
+}
+
 pub struct TextScriptVM {
     pub scripts: Rc<RefCell<Scripts>>,
     pub state: TextScriptExecutionState,
@@ -159,6 +175,19 @@ pub struct TextScriptVM {
     pub illustration_state: IllustrationState,
     prev_char: char,
     pub substitution_rect_map: [(char, Rect<u16>); TSC_SUBSTITUTION_MAP_SIZE],
+    /// Styles referenced by style markers in the text box lines, see [rich_text].
+    pub text_styles: Vec<TextStyle>,
+    pub current_style: usize,
+    /// Delay between characters set with the `{s:N}` tag.
+    pub text_speed: Option<u8>,
+    /// Word that has been wrapped past the last line, printed after the text box scrolls.
+    wrap_carry: Vec<char>,
+    /// Lines shown in the text box during this session, oldest first.
+    pub history: VecDeque<DialogueHistoryEntry>,
+    /// Copy of `constants.textscript.extensions.rich_text`, style markers in the lines are only recognized if set.
+    pub rich_text: bool,
+    /// Face shown when the current line has been started.
+    line_face: u16,
 }
 
 pub struct Scripts {
@@ -235,6 +264,13 @@ impl TextScriptVM {
             illustration_state: IllustrationState::Hidden,
             prev_char: '\x00',
             substitution_rect_map: [('=', Rect::new(0, 0, 0, 0))],
+            text_styles: vec![TextStyle::new()],
+            current_style: 0,
+            text_speed: None,
+            wrap_carry: Vec::new(),
+            history: VecDeque::new(),
+            rich_text: false,
+            line_face: 0,
         }
     }
 
@@ -289,11 +325,146 @@ impl TextScriptVM {
     }
 
     pub fn clear_text_box(&mut self) {
+        self.archive_current_line();
         self.item = 0;
         self.current_line = TextScriptLine::Line1;
         self.line_1.clear();
         self.line_2.clear();
         self.line_3.clear();
+        self.text_styles.truncate(1);
+        self.current_style = 0;
+        self.text_speed = None;
+        self.wrap_carry.clear();
+    }
+
+    /// Puts the line that's currently being printed into the dialogue history, called when the text moves
+    /// past it. Lines before the current one have been archived already.
+    fn archive_current_line(&mut self) {
+        let line = match self.current_line {
+            TextScriptLine::Line1 => &self.line_1,
+            TextScriptLine::Line2 => &self.line_2,
+            TextScriptLine::Line3 => &self.line_3,
+        };
+
+        let rich_text_enabled = self.rich_text;
+        let text: Vec<char> = line
+            .iter()
+            .copied()
+            .filter(|&chr| !matches!(RichChar::parse(chr, rich_text_enabled), RichChar::Style(_)))
+            .collect();
+        if text.is_empty() {
+            return;
+        }
+
+        if self.history.len() >= MAX_DIALOGUE_HISTORY {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(DialogueHistoryEntry { face: self.line_face, player: self.executor_player, text });
+    }
+
+    fn current_line_mut(&mut self) -> &mut Vec<char> {
+        match self.current_line {
+            TextScriptLine::Line1 => &mut self.line_1,
+            TextScriptLine::Line2 => &mut self.line_2,
+            TextScriptLine::Line3 => &mut self.line_3,
+        }
+    }
+
+    /// Appends a character to the current line and switches to the next one if the line exceeds `max_width`,
+    /// with `word_wrap` the last word is moved to the next line too. Returns true if the text box has to scroll.
+    fn push_text_char(&mut self, chr: char, builder: &TextBuilder, max_width: f32, word_wrap: bool) -> bool {
+        if self.current_line_mut().is_empty() {
+            self.line_face = self.face;
+
+            let style = self.current_style;
+            if style != 0 && !matches!(RichChar::from_char(chr), RichChar::Style(_)) {
+                self.current_line_mut().push(RichChar::Style(style).to_char());
+            }
+        }
+
+        let rich_text_enabled = self.rich_text;
+        let line = self.current_line_mut();
+        line.push(chr);
+
+        if rich_text::compute_width(builder, line, rich_text_enabled) < max_width {
+            return false;
+        }
+
+        let mut carry = Vec::new();
+        if word_wrap && chr != ' ' {
+            if let Some(pos) = line.iter().rposition(|&c| c == ' ') {
+                let word = line.split_off(pos + 1);
+
+                if let Some(style) = rich_text::last_style(line).filter(|&style| style != 0) {
+                    carry.push(RichChar::Style(style).to_char());
+                }
+                carry.extend(word);
+            }
+        }
+
+        self.archive_current_line();
+        self.line_face = self.face;
+
+        match self.current_line {
+            TextScriptLine::Line1 => {
+                self.current_line = TextScriptLine::Line2;
+                self.line_2.append(&mut carry);
+            }
+            TextScriptLine::Line2 => {
+                self.current_line = TextScriptLine::Line3;
+                self.line_3.append(&mut carry);
+            }
+            TextScriptLine::Line3 => {
+                self.wrap_carry = carry;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Applies a rich text tag. Returns `Some` with the result of [Self::push_text_char] if the tag has put
+    /// an icon in the text box.
+    fn apply_rich_text_tag(&mut self, tag: RichTextTag, builder: &TextBuilder, max_width: f32) -> Option<bool> {
+        let mut style = self.text_styles[self.current_style];
+
+        match tag {
+            RichTextTag::Color(color) => style.color = color,
+            RichTextTag::Effect(effect) => style.effect = effect,
+            RichTextTag::Speed(speed) => {
+                self.text_speed = speed;
+                return None;
+            }
+            RichTextTag::ItemIcon(id) => {
+                return Some(self.push_text_char(RichChar::ItemIcon(id).to_char(), builder, max_width, true));
+            }
+            RichTextTag::WeaponIcon(id) => {
+                return Some(self.push_text_char(RichChar::WeaponIcon(id).to_char(), builder, max_width, true));
+            }
+        }
+
+        if style == self.text_styles[self.current_style] {
+            return None;
+        }
+
+        self.current_style = match self.text_styles.iter().position(|&s| s == style) {
+            Some(idx) => idx,
+            None if self.text_styles.len() < rich_text::MAX_STYLES => {
+                self.text_styles.push(style);
+                self.text_styles.len() - 1
+            }
+            None => return None,
+        };
+
+        if self.current_line_mut().is_empty() {
+            self.line_face = self.face;
+        }
+
+        let marker = RichChar::Style(self.current_style).to_char();
+        self.current_line_mut().push(marker);
+
+        None
     }
 
     pub fn set_mode(&mut self, mode: ScriptMode) {
@@ -349,7 +520,7 @@ impl TextScriptVM {
                         state.textscript_vm.reset();
                     }
                 }
-                TextScriptExecutionState::Msg(event, ip, remaining, counter) => {
+                TextScriptExecutionState::Msg(event, ip, mut remaining, counter) => {
                     if counter > 0 {
                         state.textscript_vm.state = TextScriptExecutionState::Msg(event, ip, remaining, counter - 1);
                         break;
@@ -376,54 +547,72 @@ impl TextScriptVM {
                         cursor.seek(SeekFrom::Start(ip as u64))?;
 
                         let chr = std::char::from_u32(read_cur_varint(&mut cursor)? as u32).unwrap_or('\u{fffd}');
-                        let builder = state.font.builder().with_symbols(Some(Symbols {
-                            symbols: &state.textscript_vm.substitution_rect_map,
-                            texture: "",
-                        }));
+                        let substitution_rect_map = state.textscript_vm.substitution_rect_map;
+                        let builder = state
+                            .font
+                            .builder()
+                            .with_symbols(Some(Symbols { symbols: &substitution_rect_map, texture: "" }));
+
+                        let rich_text_enabled = state.constants.textscript.extensions.rich_text;
+                        // inner width of the text box, vanilla scripts are wrapped by hand so keep the old limit
+                        let max_width = match (rich_text_enabled, state.textscript_vm.face) {
+                            (false, _) => 284.0,
+                            (true, 0) => 216.0,
+                            (true, _) => 160.0,
+                        };
+                        let mut visible = true;
 
                         match chr {
+                            '{' if rich_text_enabled => {
+                                let mut tag = String::new();
+                                while remaining > 1 {
+                                    let chr =
+                                        std::char::from_u32(read_cur_varint(&mut cursor)? as u32).unwrap_or('\u{fffd}');
+                                    remaining -= 1;
+
+                                    if chr == '}' {
+                                        break;
+                                    }
+                                    tag.push(chr);
+                                }
+
+                                match rich_text::parse_tag(&tag) {
+                                    Some(tag) => {
+                                        match state.textscript_vm.apply_rich_text_tag(tag, &builder, max_width) {
+                                            Some(scroll) => new_line = scroll,
+                                            None => visible = false,
+                                        }
+                                    }
+                                    None => {
+                                        log::warn!("Unknown rich text tag: {{{}}}", tag);
+                                        visible = false;
+                                    }
+                                }
+                            }
                             '\n' if state.textscript_vm.current_line == TextScriptLine::Line1 => {
+                                state.textscript_vm.archive_current_line();
                                 state.textscript_vm.current_line = TextScriptLine::Line2;
                             }
                             '\n' if state.textscript_vm.current_line == TextScriptLine::Line2 => {
+                                state.textscript_vm.archive_current_line();
                                 state.textscript_vm.current_line = TextScriptLine::Line3;
                             }
                             '\n' => {
+                                state.textscript_vm.archive_current_line();
                                 new_line = true;
                             }
                             '\r' => {}
-                            _ if state.textscript_vm.current_line == TextScriptLine::Line1 => {
+                            _ => {
                                 state.textscript_vm.prev_char = chr;
-                                state.textscript_vm.line_1.push(chr);
-
-                                let text_len = builder.compute_width_iter(state.textscript_vm.line_1.iter().copied());
-                                if text_len >= 284.0 {
-                                    state.textscript_vm.current_line = TextScriptLine::Line2;
-                                }
+                                new_line =
+                                    state.textscript_vm.push_text_char(chr, &builder, max_width, rich_text_enabled);
                             }
-                            _ if state.textscript_vm.current_line == TextScriptLine::Line2 => {
-                                state.textscript_vm.prev_char = chr;
-                                state.textscript_vm.line_2.push(chr);
-
-                                let text_len = builder.compute_width_iter(state.textscript_vm.line_2.iter().copied());
-                                if text_len >= 284.0 {
-                                    state.textscript_vm.current_line = TextScriptLine::Line3;
-                                }
-                            }
-                            _ if state.textscript_vm.current_line == TextScriptLine::Line3 => {
-                                state.textscript_vm.prev_char = chr;
-                                state.textscript_vm.line_3.push(chr);
-
-                                let text_len = builder.compute_width_iter(state.textscript_vm.line_3.iter().copied());
-                                if text_len >= 284.0 {
-                                    new_line = true;
-                                }
-                            }
-                            _ => {}
                         }
 
                         if remaining > 1 {
-                            let ticks = if state.textscript_vm.flags.fast() || state.textscript_vm.flags.cutscene_skip()
+                            let ticks = if !visible
+                                || state.textscript_vm.flags.fast()
+                                || state.textscript_vm.flags.cutscene_skip()
                             {
                                 0
                             } else if remaining != 2
@@ -434,7 +623,7 @@ impl TextScriptVM {
                             {
                                 state.constants.textscript.text_speed_fast
                             } else {
-                                state.constants.textscript.text_speed_normal
+                                state.textscript_vm.text_speed.unwrap_or(state.constants.textscript.text_speed_normal)
                             };
 
                             if ticks > 0 {
@@ -453,7 +642,9 @@ impl TextScriptVM {
                                 )
                             };
                         } else {
-                            let ticks = if state.textscript_vm.flags.fast() || state.textscript_vm.flags.cutscene_skip()
+                            let ticks = if !visible
+                                || state.textscript_vm.flags.fast()
+                                || state.textscript_vm.flags.cutscene_skip()
                             {
                                 0
                             } else {
@@ -491,6 +682,7 @@ impl TextScriptVM {
                         state.textscript_vm.line_1.clear();
                         state.textscript_vm.line_1.append(&mut state.textscript_vm.line_2);
                         state.textscript_vm.line_2.append(&mut state.textscript_vm.line_3);
+                        state.textscript_vm.line_3.append(&mut state.textscript_vm.wrap_carry);
                         state.textscript_vm.state = if remaining < 2 {
                             TextScriptExecutionState::Running(event, ip)
                         } else {
@@ -616,6 +808,9 @@ impl TextScriptVM {
                     }
                     break;
                 }
+                TextScriptExecutionState::WaitScript(_, _) => {
+                    break;
+                }
                 TextScriptExecutionState::FallingIsland(event, ip, pos_x, mut pos_y, mut tick, mode) => {
                     if tick == 900 {
                         state.textscript_vm.state = TextScriptExecutionState::Running(event, ip);
@@ -690,6 +885,39 @@ impl TextScriptVM {
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
             TSCOpCode::_UNI => {}
+            TSCOpCode::_CUS => {
+                let mut name = String::with_capacity(3);
+                for _ in 0..3 {
+                    name.push(std::char::from_u32(read_cur_varint(&mut cursor)? as u32).unwrap_or('?'));
+                }
+
+                let count = read_cur_varint(&mut cursor)?;
+                let mut operands = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    operands.push(read_cur_varint(&mut cursor)?);
+                }
+
+                #[cfg(feature = "scripting-lua")]
+                let wait = match state.lua.try_run_tsc_handler(&name, &operands) {
+                    Some(wait) => wait,
+                    None => {
+                        log::warn!("No Lua handler registered for opcode <{}.", name);
+                        false
+                    }
+                };
+
+                #[cfg(not(feature = "scripting-lua"))]
+                let wait = {
+                    log::warn!("Opcode <{} requires Lua scripting support.", name);
+                    false
+                };
+
+                exec_state = if wait {
+                    TextScriptExecutionState::WaitScript(event, cursor.position() as u32)
+                } else {
+                    TextScriptExecutionState::Running(event, cursor.position() as u32)
+                };
+            }
             TSCOpCode::_STR => {
                 let mut len = read_cur_varint(&mut cursor)? as u32;
                 if state.textscript_vm.flags.render() {
@@ -974,7 +1202,7 @@ impl TextScriptVM {
             TSCOpCode::AMJ => {
                 let weapon = read_cur_varint(&mut cursor)? as u8;
                 let event_num = read_cur_varint(&mut cursor)? as u16;
-                let weapon_type: Option<WeaponType> = FromPrimitive::from_u8(weapon);
+                let weapon_type = WeaponType::from_id(weapon, &state.constants);
 
                 if weapon_type.is_some() && game_scene.inventory_player1.has_weapon(weapon_type.unwrap()) {
                     state.textscript_vm.clear_text_box();
@@ -1031,6 +1259,29 @@ impl TextScriptVM {
 
                 state.textscript_vm.clear_text_box();
                 exec_state = TextScriptExecutionState::Running(event_num, 0);
+
+                // pits jump to the death events while the player is still alive
+                if state.constants.textscript.pit_death_events.contains(&event_num)
+                    && state.get_assist().pit_protection
+                    && game_scene.player1.life > 0
+                {
+                    let mut died = false;
+                    for player in [&mut game_scene.player1, &mut game_scene.player2] {
+                        if player.cond.alive() && !player.cond.hidden() {
+                            player.rescue_from_pit(state, &game_scene.npc_list);
+                            died |= player.life == 0;
+                        }
+                    }
+
+                    if !died {
+                        state.control_flags.set_tick_world(true);
+                        state.control_flags.set_control_enabled(true);
+                        state.textscript_vm.flags.set_render(false);
+                        state.textscript_vm.stack.clear();
+
+                        exec_state = TextScriptExecutionState::Ended;
+                    }
+                }
             }
             TSCOpCode::PSH => {
                 let event_num = read_cur_varint(&mut cursor)? as u16;
@@ -1104,6 +1355,7 @@ impl TextScriptVM {
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
             TSCOpCode::CLR => {
+                state.textscript_vm.archive_current_line();
                 state.textscript_vm.current_line = TextScriptLine::Line1;
                 state.textscript_vm.line_1.clear();
                 state.textscript_vm.line_2.clear();
@@ -1112,6 +1364,7 @@ impl TextScriptVM {
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
             TSCOpCode::MSG | TSCOpCode::MS2 | TSCOpCode::MS3 => {
+                state.textscript_vm.archive_current_line();
                 state.textscript_vm.current_line = TextScriptLine::Line1;
                 state.textscript_vm.line_1.clear();
                 state.textscript_vm.line_2.clear();
@@ -1234,6 +1487,7 @@ impl TextScriptVM {
                 state.textscript_vm.flags.set_cutscene_skip(skip);
                 state.textscript_vm.face = 0;
                 state.textscript_vm.item = 0;
+                state.textscript_vm.archive_current_line();
                 state.textscript_vm.current_line = TextScriptLine::Line1;
                 state.textscript_vm.line_1.clear();
                 state.textscript_vm.line_2.clear();
@@ -1308,11 +1562,8 @@ impl TextScriptVM {
             TSCOpCode::UNI => {
                 let control_mode = read_cur_varint(&mut cursor)? as u8;
 
-                let mode: Option<ControlMode> = FromPrimitive::from_u8(control_mode);
-                if let Some(mode) = mode {
-                    game_scene.player1.control_mode = mode;
-                    game_scene.player2.control_mode = mode;
-                }
+                game_scene.player1.set_control_mode(state, control_mode);
+                game_scene.player2.set_control_mode(state, control_mode);
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
@@ -1494,7 +1745,7 @@ impl TextScriptVM {
                         npc.size = entry.size;
                         npc.exp = entry.experience as u16;
                         npc.damage = entry.damage as u16;
-                        npc.spritesheet_id = entry.spritesheet_id as u16;
+                        npc.spritesheet_id = entry.spritesheet_id;
 
                         npc.cond.set_alive(true);
                         npc.action_num = 0;
@@ -1651,7 +1902,7 @@ impl TextScriptVM {
             TSCOpCode::AMp => {
                 let weapon_id = read_cur_varint(&mut cursor)? as u8;
                 let max_ammo = read_cur_varint(&mut cursor)? as u16;
-                let weapon_type: Option<WeaponType> = FromPrimitive::from_u8(weapon_id);
+                let weapon_type = WeaponType::from_id(weapon_id, &state.constants);
 
                 state.textscript_vm.numbers[0] = max_ammo;
 
@@ -1667,7 +1918,7 @@ impl TextScriptVM {
             }
             TSCOpCode::AMm => {
                 let weapon_id = read_cur_varint(&mut cursor)? as u8;
-                let weapon_type: Option<WeaponType> = FromPrimitive::from_u8(weapon_id);
+                let weapon_type = WeaponType::from_id(weapon_id, &state.constants);
 
                 if let Some(wtype) = weapon_type {
                     game_scene.inventory_player1.remove_weapon(wtype);
@@ -1686,8 +1937,8 @@ impl TextScriptVM {
                 let old_weapon_id = read_cur_varint(&mut cursor)? as u8;
                 let new_weapon_id = read_cur_varint(&mut cursor)? as u8;
                 let max_ammo = read_cur_varint(&mut cursor)? as u16;
-                let old_weapon_type: Option<WeaponType> = FromPrimitive::from_u8(old_weapon_id);
-                let new_weapon_type: Option<WeaponType> = FromPrimitive::from_u8(new_weapon_id);
+                let old_weapon_type = WeaponType::from_id(old_weapon_id, &state.constants);
+                let new_weapon_type = WeaponType::from_id(new_weapon_id, &state.constants);
 
                 if let Some(wtype) = new_weapon_type {
                     game_scene.inventory_player1.trade_weapon(old_weapon_type, wtype, max_ammo);
@@ -1802,7 +2053,7 @@ impl TextScriptVM {
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
             TSCOpCode::SPS => {
-                state.sound_manager.loop_sfx(58);
+                state.sound_manager.loop_sfx(58, MixerBus::Ambience);
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
@@ -1815,8 +2066,8 @@ impl TextScriptVM {
             TSCOpCode::SSS => {
                 let freq = read_cur_varint(&mut cursor)? as f32 / 2205.0;
 
-                state.sound_manager.loop_sfx_freq(40, freq);
-                state.sound_manager.loop_sfx_freq(41, freq);
+                state.sound_manager.loop_sfx_freq(40, freq, MixerBus::Ambience);
+                state.sound_manager.loop_sfx_freq(41, freq, MixerBus::Ambience);
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
@@ -1865,6 +2116,83 @@ impl TextScriptVM {
 
                 log::info!("achievement get: {}", idx);
 
+                exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+            }
+            TSCOpCode::VAs | TSCOpCode::VAp | TSCOpCode::VAm | TSCOpCode::VAx | TSCOpCode::VAd | TSCOpCode::VAr => {
+                let var_num = read_cur_varint(&mut cursor)? as usize;
+                let operand = read_cur_varint(&mut cursor)?;
+                let value = state.get_variable(var_num);
+
+                let result = match op {
+                    TSCOpCode::VAs => Some(operand),
+                    TSCOpCode::VAp => Some(value.wrapping_add(operand)),
+                    TSCOpCode::VAm => Some(value.wrapping_sub(operand)),
+                    TSCOpCode::VAx => Some(value.wrapping_mul(operand)),
+                    TSCOpCode::VAd => value.checked_div(operand),
+                    _ => value.checked_rem(operand),
+                };
+
+                if let Some(result) = result {
+                    state.set_variable(var_num, result);
+                }
+
+                exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+            }
+            TSCOpCode::VAJ => {
+                let var_num = read_cur_varint(&mut cursor)? as usize;
+                let operator = read_cur_varint(&mut cursor)?;
+                let operand = read_cur_varint(&mut cursor)?;
+                let event_num = read_cur_varint(&mut cursor)? as u16;
+                let value = state.get_variable(var_num);
+
+                let matches = match operator {
+                    0 => value == operand,
+                    1 => value != operand,
+                    2 => value < operand,
+                    3 => value <= operand,
+                    4 => value > operand,
+                    5 => value >= operand,
+                    _ => {
+                        log::warn!("Unknown <VAJ comparison operator: {}", operator);
+                        false
+                    }
+                };
+
+                if matches {
+                    state.textscript_vm.clear_text_box();
+                    exec_state = TextScriptExecutionState::Running(event_num, 0);
+                } else {
+                    exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+                }
+            }
+            TSCOpCode::RND => {
+                let var_num = read_cur_varint(&mut cursor)? as usize;
+                let min = read_cur_varint(&mut cursor)?;
+                let max = read_cur_varint(&mut cursor)?;
+
+                let value = state.game_rng.range(min.min(max)..min.max(max));
+                state.set_variable(var_num, value);
+
+                exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+            }
+            TSCOpCode::RNJ => {
+                let min = read_cur_varint(&mut cursor)?;
+                let max = read_cur_varint(&mut cursor)?;
+
+                let event_num = state.game_rng.range(min.min(max)..min.max(max)) as u16;
+                state.textscript_vm.clear_text_box();
+                exec_state = TextScriptExecutionState::Running(event_num, 0);
+            }
+            TSCOpCode::VNM => {
+                let var_num = read_cur_varint(&mut cursor)? as usize;
+                let mut str = state.get_variable(var_num).to_string().chars().collect();
+
+                match state.textscript_vm.current_line {
+                    TextScriptLine::Line1 => state.textscript_vm.line_1.append(&mut str),
+                    TextScriptLine::Line2 => state.textscript_vm.line_2.append(&mut str),
+                    TextScriptLine::Line3 => state.textscript_vm.line_3.append(&mut str),
+                }
+
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
         }
@@ -1895,7 +2223,8 @@ impl TextScript {
     }
 
     /// Loads, decrypts and compiles a text script from specified stream.
-    pub fn load_from<R: io::Read>(mut data: R, constants: &EngineConstants) -> GameResult<TextScript> {
+    /// Name of the script (like `Head` or `Stage/Mimi`) is used to look up its translated strings.
+    pub fn load_from<R: io::Read>(mut data: R, name: &str, constants: &EngineConstants) -> GameResult<TextScript> {
         let mut buf = Vec::new();
         data.read_to_end(&mut buf)?;
 
@@ -1903,7 +2232,19 @@ impl TextScript {
             decrypt_tsc(&mut buf);
         }
 
-        TextScript::compile(&buf, false, constants.textscript.encoding)
+        let translation = &constants.textscript.translation;
+        if translation.is_empty() {
+            return TextScript::compile(&buf, false, constants.textscript.encoding, &constants.textscript.extensions);
+        }
+
+        let mut hook = |event_num: u16, index: u16, text: &str| translation.translate(name, event_num, index, text);
+        TextScript::compile_with_hook(
+            &buf,
+            false,
+            constants.textscript.encoding,
+            &constants.textscript.extensions,
+            Some(&mut hook),
+        )
     }
 
     pub fn get_event_ids(&self) -> Vec<u16> {
@@ -1916,3 +2257,46 @@ impl TextScript {
         self.event_map.contains_key(&id)
     }
 }
+
+#[test]
+fn test_push_text_char_word_wrap() {
+    use crate::graphics::font::{Font, MonospaceFont};
+
+    let font = MonospaceFont(10.0);
+    let builder = font.builder();
+    let mut vm = TextScriptVM::new();
+    vm.rich_text = true;
+
+    let push_str = |vm: &mut TextScriptVM, text: &str| {
+        text.chars().map(|chr| vm.push_text_char(chr, &builder, 60.0, true)).collect::<Vec<bool>>()
+    };
+    let text = |line: &[char]| line.iter().collect::<String>();
+
+    // the word that doesn't fit is moved to the next line
+    assert!(push_str(&mut vm, "ab cde").iter().all(|&scroll| !scroll));
+    assert_eq!(text(&vm.line_1), "ab ");
+    assert_eq!(text(&vm.line_2), "cde");
+    assert_eq!(vm.current_line, TextScriptLine::Line2);
+
+    // along with the style active before it
+    vm.apply_rich_text_tag(RichTextTag::Color(Some((255, 0, 0))), &builder, 60.0);
+    let marker = RichChar::Style(1).to_char();
+    push_str(&mut vm, " fg");
+    assert_eq!(vm.line_2, vec!['c', 'd', 'e', marker, ' ']);
+    assert_eq!(vm.line_3, vec![marker, 'f', 'g']);
+
+    // a word wider than the line is split, overflowing the last line scrolls the text box
+    assert_eq!(push_str(&mut vm, "hijk"), vec![false, false, false, true]);
+    assert!(vm.wrap_carry.is_empty());
+
+    let history: Vec<String> = vm.history.iter().map(|entry| text(&entry.text)).collect();
+    assert_eq!(history, vec!["ab ", "cde ", "fghijk"]);
+
+    // without word wrap lines are broken at the character that doesn't fit
+    let mut vm = TextScriptVM::new();
+    for chr in "ab cdef".chars() {
+        vm.push_text_char(chr, &builder, 60.0, false);
+    }
+    assert_eq!(text(&vm.line_1), "ab cde");
+    assert_eq!(text(&vm.line_2), "f");
+}
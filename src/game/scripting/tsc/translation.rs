@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::io::{BufRead, Read};
+
+use crate::engine_constants::EngineConstants;
+use crate::framework::context::Context;
+use crate::framework::error::GameError::ParseError;
+use crate::framework::error::GameResult;
+use crate::framework::filesystem;
+use crate::game::scripting::tsc::credit_script::CreditScript;
+use crate::game::scripting::tsc::encryption::decrypt_tsc;
+use crate::game::scripting::tsc::text_script::TextScript;
+use crate::game::stage::StageData;
+
+/// Called by the compiler for every message string with its event number, index within the event and text.
+/// Returned text is compiled in place of the original one.
+pub type StringHook<'a> = dyn FnMut(u16, u16, &str) -> Option<String> + 'a;
+
+/// Scripts outside of the `Stage/` directory that contain text.
+const GLOBAL_SCRIPTS: [&str; 4] = ["Head", "ArmsItem", "StageSelect", "Credit"];
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct TranslationEntry {
+    /// Original text, translations of strings that were changed since the catalog was made are ignored.
+    pub source: String,
+    /// Empty if the string hasn't been translated yet.
+    #[serde(default)]
+    pub translation: String,
+}
+
+/// Translated message strings of text scripts, substituted when the scripts are compiled so the original files
+/// don't have to be modified. Strings are keyed by script path, event number and index of the string within
+/// the event, eg. `Stage/Mimi/0200/3`. Credits use the last label in place of the event number.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct TranslationCatalog {
+    pub entries: BTreeMap<String, TranslationEntry>,
+}
+
+impl TranslationCatalog {
+    pub fn key(script: &str, event_num: u16, index: u16) -> String {
+        format!("{}/{:04}/{}", script, event_num, index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns translation of given string, unless it's missing or was made for a different text.
+    pub fn translate(&self, script: &str, event_num: u16, index: u16, source: &str) -> Option<String> {
+        let key = TranslationCatalog::key(script, event_num, index);
+        let entry = self.entries.get(&key)?;
+
+        if entry.translation.is_empty() {
+            return None;
+        }
+
+        if entry.source != source {
+            log::warn!("Translation of {} is outdated, using the original text.", key);
+            return None;
+        }
+
+        Some(entry.translation.clone())
+    }
+
+    /// Collects message strings from all scripts of the game, along with their translations from the currently
+    /// loaded catalog.
+    pub fn extract(ctx: &Context, constants: &EngineConstants, stages: &[StageData]) -> GameResult<TranslationCatalog> {
+        let mut scripts: Vec<String> = GLOBAL_SCRIPTS.iter().map(|name| name.to_string()).collect();
+        scripts.extend(stages.iter().filter(|stage| !stage.map.is_empty()).map(|stage| format!("Stage/{}", stage.map)));
+        scripts.sort();
+        scripts.dedup();
+
+        let mut catalog = TranslationCatalog::default();
+
+        for script in scripts {
+            let mut data = Vec::new();
+            match filesystem::open_find(ctx, &constants.base_paths, format!("{}.tsc", script)) {
+                Ok(mut file) => file.read_to_end(&mut data)?,
+                Err(_) => continue,
+            };
+
+            if constants.textscript.encrypted {
+                decrypt_tsc(&mut data);
+            }
+
+            if let Err(err) = catalog.extract_script(&script, &data, constants) {
+                log::warn!("Failed to extract text from {}: {}", script, err);
+            }
+        }
+
+        Ok(catalog)
+    }
+
+    fn extract_script(&mut self, script: &str, data: &[u8], constants: &EngineConstants) -> GameResult {
+        let previous = &constants.textscript.translation;
+        let entries = &mut self.entries;
+        let mut hook = |event_num: u16, index: u16, text: &str| {
+            let translation = previous.translate(script, event_num, index, text).unwrap_or_default();
+            let entry = TranslationEntry { source: text.to_owned(), translation };
+            entries.insert(TranslationCatalog::key(script, event_num, index), entry);
+
+            None
+        };
+
+        let encoding = constants.textscript.encoding;
+        if script == "Credit" {
+            CreditScript::compile_with_hook(data, false, encoding, Some(&mut hook))?;
+        } else {
+            TextScript::compile_with_hook(data, false, encoding, &constants.textscript.extensions, Some(&mut hook))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the catalog in gettext PO format, keys are stored as message contexts.
+    pub fn write_po<W: io::Write>(&self, mut data: W) -> GameResult {
+        writeln!(data, "msgid \"\"")?;
+        writeln!(data, "msgstr \"\"")?;
+        writeln!(data, "\"Content-Type: text/plain; charset=UTF-8\\n\"")?;
+
+        for (key, entry) in self.entries.iter() {
+            writeln!(data)?;
+            writeln!(data, "msgctxt {}", po_quote(key))?;
+            writeln!(data, "msgid {}", po_quote(&entry.source))?;
+            writeln!(data, "msgstr {}", po_quote(&entry.translation))?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a catalog in gettext PO format, messages without a context (like the header) are skipped.
+    pub fn read_po<R: BufRead>(data: R) -> GameResult<TranslationCatalog> {
+        let mut catalog = TranslationCatalog::default();
+        // msgctxt, msgid and msgstr of the current message
+        let mut fields: [Option<String>; 3] = Default::default();
+        let mut current = 0;
+
+        for line in data.lines() {
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let value = if line.starts_with('"') {
+                line
+            } else {
+                let (keyword, value) = match line.split_once(' ') {
+                    Some(pair) => pair,
+                    None => return Err(ParseError(format!("Invalid PO line: {}", line))),
+                };
+
+                current = match keyword {
+                    "msgctxt" => 0,
+                    "msgid" => 1,
+                    "msgstr" => 2,
+                    _ => return Err(ParseError(format!("Unsupported PO keyword: {}", keyword))),
+                };
+
+                if current != 2 && fields[2].is_some() {
+                    catalog.insert_po_message(&mut fields);
+                }
+                fields[current] = Some(String::new());
+
+                value.trim()
+            };
+
+            fields[current].get_or_insert_with(String::new).push_str(&po_unquote(value)?);
+        }
+
+        catalog.insert_po_message(&mut fields);
+
+        Ok(catalog)
+    }
+
+    fn insert_po_message(&mut self, fields: &mut [Option<String>; 3]) {
+        let [key, source, translation] = std::mem::take(fields);
+
+        if let Some(key) = key.filter(|key| !key.is_empty()) {
+            let entry =
+                TranslationEntry { source: source.unwrap_or_default(), translation: translation.unwrap_or_default() };
+            self.entries.insert(key, entry);
+        }
+    }
+}
+
+fn po_quote(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+
+    for chr in text.chars() {
+        match chr {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            _ => result.push(chr),
+        }
+    }
+
+    result.push('"');
+    result
+}
+
+fn po_unquote(value: &str) -> GameResult<String> {
+    let inner = match value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => return Err(ParseError(format!("Expected a quoted PO string, found: {}", value))),
+    };
+
+    let mut result = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(chr) = chars.next() {
+        if chr != '\\' {
+            result.push(chr);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some(chr) => result.push(chr),
+            None => return Err(ParseError("Unterminated escape sequence in PO string.".to_owned())),
+        }
+    }
+
+    Ok(result)
+}
+
+#[test]
+fn test_translation_catalog() {
+    use crate::engine_constants::TextScriptExtensions;
+    use crate::game::scripting::tsc::text_script::TextScriptEncoding;
+
+    let script = b"#0100\n<MSGHello!<NOD<CLR\"Quoted\"\nline<NOD<END\n#0200\n<KEY<MSGBye.<NOD<END\n";
+    let extensions = TextScriptExtensions::default();
+
+    let mut strings = Vec::new();
+    let mut hook = |event_num: u16, index: u16, text: &str| {
+        strings.push((TranslationCatalog::key("Head", event_num, index), text.to_owned()));
+        None
+    };
+    TextScript::compile_with_hook(script, true, TextScriptEncoding::UTF8, &extensions, Some(&mut hook)).unwrap();
+
+    assert_eq!(
+        strings,
+        vec![
+            ("Head/0100/0".to_owned(), "Hello!".to_owned()),
+            ("Head/0100/1".to_owned(), "\"Quoted\"\nline".to_owned()),
+            ("Head/0200/0".to_owned(), "Bye.".to_owned()),
+        ]
+    );
+
+    let mut catalog = TranslationCatalog::default();
+    for (key, source) in strings {
+        catalog.entries.insert(key, TranslationEntry { source, translation: String::new() });
+    }
+    catalog.entries.get_mut("Head/0100/1").unwrap().translation = "\"Zitat\"\nZeile".to_owned();
+    catalog.entries.get_mut("Head/0200/0").unwrap().source = "Goodbye.".to_owned();
+
+    let mut data = Vec::new();
+    catalog.write_po(&mut data).unwrap();
+    let catalog = TranslationCatalog::read_po(data.as_slice()).unwrap();
+    assert_eq!(catalog.entries.len(), 3);
+
+    assert_eq!(catalog.translate("Head", 100, 0, "Hello!"), None);
+    assert_eq!(catalog.translate("Head", 100, 1, "\"Quoted\"\nline").as_deref(), Some("\"Zitat\"\nZeile"));
+    assert_eq!(catalog.translate("Head", 200, 0, "Bye."), None);
+}
@@ -1,14 +1,18 @@
+use crate::bitfield;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::filesystem::{user_create, user_open};
 use crate::framework::gamepad::{Axis, AxisDirection, Button, PlayerControllerInputType};
-use crate::framework::graphics::VSyncMode;
+use crate::framework::graphics::{ColorblindFilter, UpscaleMode, VSyncMode};
 use crate::framework::keyboard::ScanCode;
 use crate::game::player::TargetPlayer;
 use crate::game::shared_game_state::{CutsceneSkipMode, ScreenShakeIntensity, TimingMode, WindowMode};
+use crate::input::bindings::{InputAction, InputBindings};
 use crate::input::combined_player_controller::CombinedPlayerController;
 use crate::input::gamepad_player_controller::GamepadController;
+use crate::input::input_layer::{InputLayerController, InputLayerSettings};
 use crate::input::keyboard_player_controller::KeyboardController;
+use crate::input::mouse_player_controller::{MousePlayerController, MouseSettings};
 use crate::input::player_controller::PlayerController;
 use crate::input::touch_player_controller::TouchPlayerController;
 use crate::sound::InterpolationMode;
@@ -20,6 +24,7 @@ pub struct Settings {
     #[serde(default = "default_true")]
     pub seasonal_textures: bool,
     pub original_textures: bool,
+    /// Enables effects drawn with shaders: stage lighting, scanlines and the CRT filter.
     pub shader_effects: bool,
     #[serde(default = "default_true")]
     pub light_cone: bool,
@@ -35,6 +40,12 @@ pub struct Settings {
     pub bgm_volume: f32,
     #[serde(default = "default_vol")]
     pub sfx_volume: f32,
+    #[serde(default = "default_vol")]
+    pub ambience_volume: f32,
+    #[serde(default = "default_vol")]
+    pub ui_volume: f32,
+    #[serde(default)]
+    pub music_ducking: f32,
     #[serde(default = "default_timing")]
     pub timing_mode: TimingMode,
     #[serde(default = "default_pause_on_focus_loss")]
@@ -46,13 +57,13 @@ pub struct Settings {
     #[serde(default = "default_p2_controller_type")]
     pub player2_controller_type: ControllerType,
     #[serde(default = "p1_default_keymap")]
-    pub player1_key_map: PlayerKeyMap,
+    pub player1_key_map: PlayerInputMap,
     #[serde(default = "p2_default_keymap")]
-    pub player2_key_map: PlayerKeyMap,
+    pub player2_key_map: PlayerInputMap,
     #[serde(default = "player_default_controller_button_map")]
-    pub player1_controller_button_map: PlayerControllerButtonMap,
+    pub player1_controller_button_map: PlayerInputMap,
     #[serde(default = "player_default_controller_button_map")]
-    pub player2_controller_button_map: PlayerControllerButtonMap,
+    pub player2_controller_button_map: PlayerInputMap,
     #[serde(default = "default_controller_axis_sensitivity")]
     pub player1_controller_axis_sensitivity: f64,
     #[serde(default = "default_controller_axis_sensitivity")]
@@ -61,7 +72,13 @@ pub struct Settings {
     pub player1_rumble: bool,
     #[serde(default = "default_rumble")]
     pub player2_rumble: bool,
-    #[serde(skip, default = "default_speed")]
+    #[serde(default)]
+    pub player1_input_layer: InputLayerSettings,
+    #[serde(default)]
+    pub player2_input_layer: InputLayerSettings,
+    #[serde(default)]
+    pub mouse: MouseSettings,
+    #[serde(default = "default_speed")]
     pub speed: f64,
     #[serde(skip)]
     pub god_mode: bool,
@@ -87,6 +104,33 @@ pub struct Settings {
     pub discord_rpc: bool,
     #[serde(default = "default_true")]
     pub allow_strafe: bool,
+    #[serde(default)]
+    pub positional_sfx: bool,
+    #[serde(default = "default_upscale_mode")]
+    pub upscale_mode: UpscaleMode,
+    #[serde(default)]
+    pub scanlines: bool,
+    #[serde(default)]
+    pub crt_filter: bool,
+    #[serde(default = "default_colorblind_filter")]
+    pub colorblind_filter: ColorblindFilter,
+    /// Names of post-processing shaders from the `shaders/postprocess/` data directory, applied in order.
+    #[serde(default)]
+    pub user_shaders: Vec<String>,
+    #[serde(default)]
+    pub assist: AssistSettings,
+    #[serde(default)]
+    pub speedrun_timer: bool,
+    #[serde(default)]
+    pub livesplit: bool,
+    #[serde(default = "default_livesplit_address")]
+    pub livesplit_address: String,
+    /// Shows explored parts of the stage around the player in a corner of the screen.
+    #[serde(default)]
+    pub minimap: bool,
+    /// The Map System only shows explored parts of stages.
+    #[serde(default)]
+    pub fog_of_war: bool,
 }
 
 fn default_true() -> bool {
@@ -95,7 +139,12 @@ fn default_true() -> bool {
 
 #[inline(always)]
 fn current_version() -> u32 {
-    24
+    33
+}
+
+#[inline(always)]
+fn default_livesplit_address() -> String {
+    "127.0.0.1:16834".to_owned()
 }
 
 #[inline(always)]
@@ -103,6 +152,16 @@ fn default_timing() -> TimingMode {
     TimingMode::_50Hz
 }
 
+#[inline(always)]
+fn default_upscale_mode() -> UpscaleMode {
+    UpscaleMode::Native
+}
+
+#[inline(always)]
+fn default_colorblind_filter() -> ColorblindFilter {
+    ColorblindFilter::Off
+}
+
 #[inline(always)]
 fn default_window_mode() -> WindowMode {
     WindowMode::Windowed
@@ -204,8 +263,8 @@ impl Settings {
 
         if self.version == 5 {
             self.version = 6;
-            self.player1_key_map.strafe = ScanCode::LShift;
-            self.player2_key_map.strafe = ScanCode::RShift;
+            self.player1_key_map.strafe = InputBindings::key(ScanCode::LShift);
+            self.player2_key_map.strafe = InputBindings::key(ScanCode::RShift);
         }
 
         if self.version == 6 {
@@ -246,12 +305,12 @@ impl Settings {
         if self.version == 12 {
             self.version = 13;
 
-            if self.player1_key_map.skip == ScanCode::E {
-                self.player1_key_map.skip = ScanCode::Q;
+            if self.player1_key_map.skip == InputBindings::key(ScanCode::E) {
+                self.player1_key_map.skip = InputBindings::key(ScanCode::Q);
             }
 
-            if self.player2_key_map.skip == ScanCode::U {
-                self.player2_key_map.skip = ScanCode::T;
+            if self.player2_key_map.skip == InputBindings::key(ScanCode::U) {
+                self.player2_key_map.skip = InputBindings::key(ScanCode::T);
             }
 
             // reset controller mappings since we've updated enums
@@ -275,34 +334,37 @@ impl Settings {
         if self.version == 15 {
             self.version = 16;
 
-            self.player1_key_map.menu_ok = self.player1_key_map.jump;
-            self.player1_key_map.menu_back = self.player1_key_map.shoot;
-            self.player1_controller_button_map.menu_ok = self.player1_controller_button_map.jump;
-            self.player1_controller_button_map.menu_back = self.player1_controller_button_map.shoot;
+            self.player1_key_map.menu_ok = self.player1_key_map.jump.clone();
+            self.player1_key_map.menu_back = self.player1_key_map.shoot.clone();
+            self.player1_controller_button_map.menu_ok = self.player1_controller_button_map.jump.clone();
+            self.player1_controller_button_map.menu_back = self.player1_controller_button_map.shoot.clone();
 
-            self.player2_key_map.menu_ok = self.player2_key_map.jump;
-            self.player2_key_map.menu_back = self.player2_key_map.shoot;
-            self.player2_controller_button_map.menu_ok = self.player2_controller_button_map.jump;
-            self.player2_controller_button_map.menu_back = self.player2_controller_button_map.shoot;
+            self.player2_key_map.menu_ok = self.player2_key_map.jump.clone();
+            self.player2_key_map.menu_back = self.player2_key_map.shoot.clone();
+            self.player2_controller_button_map.menu_ok = self.player2_controller_button_map.jump.clone();
+            self.player2_controller_button_map.menu_back = self.player2_controller_button_map.shoot.clone();
         }
 
         if self.version == 16 {
             self.version = 17;
 
-            if self.player1_controller_button_map.shoot == PlayerControllerInputType::ButtonInput(Button::East) {
-                self.player1_controller_button_map.shoot = PlayerControllerInputType::ButtonInput(Button::West);
+            let east = InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::East));
+            let west = InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::West));
+
+            if self.player1_controller_button_map.shoot == east {
+                self.player1_controller_button_map.shoot = west.clone();
             }
 
-            if self.player2_controller_button_map.shoot == PlayerControllerInputType::ButtonInput(Button::East) {
-                self.player2_controller_button_map.shoot = PlayerControllerInputType::ButtonInput(Button::West);
+            if self.player2_controller_button_map.shoot == east {
+                self.player2_controller_button_map.shoot = west.clone();
             }
 
-            if self.player1_controller_button_map.map == PlayerControllerInputType::ButtonInput(Button::West) {
-                self.player1_controller_button_map.map = PlayerControllerInputType::ButtonInput(Button::East);
+            if self.player1_controller_button_map.map == west {
+                self.player1_controller_button_map.map = east.clone();
             }
 
-            if self.player2_controller_button_map.map == PlayerControllerInputType::ButtonInput(Button::West) {
-                self.player2_controller_button_map.map = PlayerControllerInputType::ButtonInput(Button::East);
+            if self.player2_controller_button_map.map == west {
+                self.player2_controller_button_map.map = east;
             }
         }
 
@@ -348,6 +410,74 @@ impl Settings {
             self.allow_strafe = true;
         }
 
+        if self.version == 24 {
+            self.version = 25;
+            self.positional_sfx = false;
+        }
+
+        if self.version == 25 {
+            self.version = 26;
+            self.ambience_volume = self.sfx_volume;
+            self.ui_volume = self.sfx_volume;
+            self.music_ducking = 0.0;
+        }
+
+        if self.version == 26 {
+            self.version = 27;
+            self.upscale_mode = default_upscale_mode();
+            self.scanlines = false;
+            self.crt_filter = false;
+            self.colorblind_filter = default_colorblind_filter();
+            self.user_shaders = Vec::new();
+        }
+
+        if self.version == 27 {
+            self.version = 28;
+            self.speed = default_speed();
+            self.assist = AssistSettings::default();
+        }
+
+        if self.version == 28 {
+            self.version = 29;
+            self.speedrun_timer = false;
+            self.livesplit = false;
+            self.livesplit_address = default_livesplit_address();
+        }
+
+        if self.version == 29 {
+            self.version = 30;
+            self.player1_input_layer = InputLayerSettings::default();
+            self.player2_input_layer = InputLayerSettings::default();
+        }
+
+        if self.version == 30 {
+            self.version = 31;
+            self.mouse = MouseSettings::default();
+        }
+
+        if self.version == 31 {
+            self.version = 32;
+            self.minimap = false;
+            self.fog_of_war = false;
+        }
+
+        if self.version == 32 {
+            self.version = 33;
+
+            // skip and inventory used to share the same key by default
+            if self.player1_key_map.skip == InputBindings::key(ScanCode::Q)
+                && self.player1_key_map.inventory == InputBindings::key(ScanCode::Q)
+            {
+                self.player1_key_map.skip = InputBindings::key(ScanCode::E);
+            }
+
+            if self.player2_key_map.skip == InputBindings::key(ScanCode::T)
+                && self.player2_key_map.inventory == InputBindings::key(ScanCode::T)
+            {
+                self.player2_key_map.skip = InputBindings::key(ScanCode::U);
+            }
+        }
+
         if self.version != initial_version {
             log::info!("Upgraded configuration file from version {} to {}.", initial_version, self.version);
         }
@@ -362,6 +492,51 @@ impl Settings {
         Ok(())
     }
 
+    fn binding_profile_path(target: TargetPlayer) -> &'static str {
+        match target {
+            TargetPlayer::Player1 => "/bindings_p1.json",
+            TargetPlayer::Player2 => "/bindings_p2.json",
+        }
+    }
+
+    /// Writes the keyboard and gamepad bindings of given player to a JSON file in the user directory.
+    pub fn export_binding_profile(&self, ctx: &Context, target: TargetPlayer) -> GameResult {
+        let profile = match target {
+            TargetPlayer::Player1 => BindingProfile {
+                key_map: self.player1_key_map.clone(),
+                controller_button_map: self.player1_controller_button_map.clone(),
+            },
+            TargetPlayer::Player2 => BindingProfile {
+                key_map: self.player2_key_map.clone(),
+                controller_button_map: self.player2_controller_button_map.clone(),
+            },
+        };
+
+        let file = user_create(ctx, Self::binding_profile_path(target))?;
+        serde_json::to_writer_pretty(file, &profile)?;
+
+        Ok(())
+    }
+
+    /// Replaces the bindings of given player with ones previously exported with `export_binding_profile`.
+    pub fn import_binding_profile(&mut self, ctx: &Context, target: TargetPlayer) -> GameResult {
+        let file = user_open(ctx, Self::binding_profile_path(target))?;
+        let profile = serde_json::from_reader::<_, BindingProfile>(file)?;
+
+        match target {
+            TargetPlayer::Player1 => {
+                self.player1_key_map = profile.key_map;
+                self.player1_controller_button_map = profile.controller_button_map;
+            }
+            TargetPlayer::Player2 => {
+                self.player2_key_map = profile.key_map;
+                self.player2_controller_button_map = profile.controller_button_map;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn create_player1_controller(&self) -> Box<dyn PlayerController> {
         if self.touch_controls {
             return Box::new(TouchPlayerController::new());
@@ -402,6 +577,19 @@ impl Settings {
         }
     }
 
+    /// Creates the controller of player 1 in game, with mouse controls and turbo, toggles and macros applied.
+    pub fn create_player1_game_controller(&self) -> Box<dyn PlayerController> {
+        let mut combined_player_controller = CombinedPlayerController::new();
+        combined_player_controller.add(self.create_player1_controller());
+        combined_player_controller.add(Box::new(MousePlayerController::new()));
+
+        Box::new(InputLayerController::new(Box::new(combined_player_controller), TargetPlayer::Player1))
+    }
+
+    pub fn create_player2_game_controller(&self) -> Box<dyn PlayerController> {
+        Box::new(InputLayerController::new(self.create_player2_controller(), TargetPlayer::Player2))
+    }
+
     pub fn get_gamepad_axis_sensitivity(&self, id: u32) -> f64 {
         if self.player1_controller_type == ControllerType::Gamepad(id) {
             self.player1_controller_axis_sensitivity
@@ -428,6 +616,9 @@ impl Default for Settings {
             soundtrack: "Organya".to_string(),
             bgm_volume: 1.0,
             sfx_volume: 1.0,
+            ambience_volume: 1.0,
+            ui_volume: 1.0,
+            music_ducking: 0.0,
             timing_mode: default_timing(),
             pause_on_focus_loss: default_pause_on_focus_loss(),
             organya_interpolation: InterpolationMode::Linear,
@@ -441,6 +632,9 @@ impl Default for Settings {
             player2_controller_axis_sensitivity: default_controller_axis_sensitivity(),
             player1_rumble: default_rumble(),
             player2_rumble: default_rumble(),
+            player1_input_layer: InputLayerSettings::default(),
+            player2_input_layer: InputLayerSettings::default(),
+            mouse: MouseSettings::default(),
             speed: 1.0,
             god_mode: false,
             infinite_booster: false,
@@ -456,65 +650,173 @@ impl Default for Settings {
             cutscene_skip_mode: CutsceneSkipMode::Hold,
             discord_rpc: true,
             allow_strafe: true,
+            positional_sfx: false,
+            upscale_mode: default_upscale_mode(),
+            scanlines: false,
+            crt_filter: false,
+            colorblind_filter: default_colorblind_filter(),
+            user_shaders: Vec::new(),
+            assist: AssistSettings::default(),
+            speedrun_timer: false,
+            livesplit: false,
+            livesplit_address: default_livesplit_address(),
+            minimap: false,
+            fog_of_war: false,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq)]
+pub struct AssistSettings {
+    /// Percentage of damage taken by players.
+    pub damage_taken: u8,
+    pub infinite_air: bool,
+    pub auto_fire: bool,
+    /// Falling into a pit (a script jumping to the death events while the player is alive) or getting hit by an
+    /// instant death hazard returns the player to the last solid ground and deals 10 damage instead.
+    pub pit_protection: bool,
+    pub high_contrast_text: bool,
+}
+
+impl Default for AssistSettings {
+    fn default() -> Self {
+        AssistSettings {
+            damage_taken: 100,
+            infinite_air: false,
+            auto_fire: false,
+            pit_protection: false,
+            high_contrast_text: false,
         }
     }
 }
 
+impl AssistSettings {
+    /// Returns which gameplay assists are active, visual ones are not included.
+    pub fn flags(&self, speed: f64) -> AssistFlags {
+        let mut flags = AssistFlags(0);
+        flags.set_damage_reduced(self.damage_taken < 100);
+        flags.set_infinite_air(self.infinite_air);
+        flags.set_slowed_down(speed < 1.0);
+        flags.set_auto_fire(self.auto_fire);
+        flags.set_pit_protection(self.pit_protection);
+        flags
+    }
+}
+
+bitfield! {
+    #[derive(Clone, Copy)]
+    pub struct AssistFlags(u8);
+    impl Debug;
+
+    pub damage_reduced, set_damage_reduced: 0; // 0x01
+    pub infinite_air, set_infinite_air: 1; // 0x02
+    pub slowed_down, set_slowed_down: 2; // 0x04
+    pub auto_fire, set_auto_fire: 3; // 0x08
+    pub pit_protection, set_pit_protection: 4; // 0x10
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct PlayerInputMap {
+    pub left: InputBindings,
+    pub up: InputBindings,
+    pub right: InputBindings,
+    pub down: InputBindings,
+    pub prev_weapon: InputBindings,
+    pub next_weapon: InputBindings,
+    pub jump: InputBindings,
+    pub shoot: InputBindings,
+    pub skip: InputBindings,
+    pub inventory: InputBindings,
+    pub map: InputBindings,
+    pub strafe: InputBindings,
+    pub menu_ok: InputBindings,
+    pub menu_back: InputBindings,
+}
+
+impl PlayerInputMap {
+    pub fn get(&self, action: InputAction) -> &InputBindings {
+        match action {
+            InputAction::Left => &self.left,
+            InputAction::Up => &self.up,
+            InputAction::Right => &self.right,
+            InputAction::Down => &self.down,
+            InputAction::PrevWeapon => &self.prev_weapon,
+            InputAction::NextWeapon => &self.next_weapon,
+            InputAction::Jump => &self.jump,
+            InputAction::Shoot => &self.shoot,
+            InputAction::Skip => &self.skip,
+            InputAction::Inventory => &self.inventory,
+            InputAction::Map => &self.map,
+            InputAction::Strafe => &self.strafe,
+            InputAction::MenuOk => &self.menu_ok,
+            InputAction::MenuBack => &self.menu_back,
+        }
+    }
+
+    pub fn get_mut(&mut self, action: InputAction) -> &mut InputBindings {
+        match action {
+            InputAction::Left => &mut self.left,
+            InputAction::Up => &mut self.up,
+            InputAction::Right => &mut self.right,
+            InputAction::Down => &mut self.down,
+            InputAction::PrevWeapon => &mut self.prev_weapon,
+            InputAction::NextWeapon => &mut self.next_weapon,
+            InputAction::Jump => &mut self.jump,
+            InputAction::Shoot => &mut self.shoot,
+            InputAction::Skip => &mut self.skip,
+            InputAction::Inventory => &mut self.inventory,
+            InputAction::Map => &mut self.map,
+            InputAction::Strafe => &mut self.strafe,
+            InputAction::MenuOk => &mut self.menu_ok,
+            InputAction::MenuBack => &mut self.menu_back,
+        }
+    }
+}
+
+/// Bindings of a single player, as stored in exported binding profiles.
 #[derive(serde::Serialize, serde::Deserialize)]
-pub struct PlayerKeyMap {
-    pub left: ScanCode,
-    pub up: ScanCode,
-    pub right: ScanCode,
-    pub down: ScanCode,
-    pub prev_weapon: ScanCode,
-    pub next_weapon: ScanCode,
-    pub jump: ScanCode,
-    pub shoot: ScanCode,
-    pub skip: ScanCode,
-    pub inventory: ScanCode,
-    pub map: ScanCode,
-    pub strafe: ScanCode,
-    pub menu_ok: ScanCode,
-    pub menu_back: ScanCode,
+pub struct BindingProfile {
+    pub key_map: PlayerInputMap,
+    pub controller_button_map: PlayerInputMap,
 }
 
 #[inline(always)]
-pub fn p1_default_keymap() -> PlayerKeyMap {
-    PlayerKeyMap {
-        left: ScanCode::Left,
-        up: ScanCode::Up,
-        right: ScanCode::Right,
-        down: ScanCode::Down,
-        prev_weapon: ScanCode::A,
-        next_weapon: ScanCode::S,
-        jump: ScanCode::Z,
-        shoot: ScanCode::X,
-        skip: ScanCode::Q,
-        inventory: ScanCode::Q,
-        map: ScanCode::W,
-        strafe: ScanCode::LShift,
-        menu_ok: ScanCode::Z,
-        menu_back: ScanCode::X,
+pub fn p1_default_keymap() -> PlayerInputMap {
+    PlayerInputMap {
+        left: InputBindings::key(ScanCode::Left),
+        up: InputBindings::key(ScanCode::Up),
+        right: InputBindings::key(ScanCode::Right),
+        down: InputBindings::key(ScanCode::Down),
+        prev_weapon: InputBindings::key(ScanCode::A),
+        next_weapon: InputBindings::key(ScanCode::S),
+        jump: InputBindings::key(ScanCode::Z),
+        shoot: InputBindings::key(ScanCode::X),
+        skip: InputBindings::key(ScanCode::E),
+        inventory: InputBindings::key(ScanCode::Q),
+        map: InputBindings::key(ScanCode::W),
+        strafe: InputBindings::key(ScanCode::LShift),
+        menu_ok: InputBindings::key(ScanCode::Z),
+        menu_back: InputBindings::key(ScanCode::X),
     }
 }
 
 #[inline(always)]
-pub fn p2_default_keymap() -> PlayerKeyMap {
-    PlayerKeyMap {
-        left: ScanCode::Comma,
-        up: ScanCode::L,
-        right: ScanCode::Slash,
-        down: ScanCode::Period,
-        prev_weapon: ScanCode::G,
-        next_weapon: ScanCode::H,
-        jump: ScanCode::B,
-        shoot: ScanCode::N,
-        skip: ScanCode::T,
-        inventory: ScanCode::T,
-        map: ScanCode::Y,
-        strafe: ScanCode::RShift,
-        menu_ok: ScanCode::B,
-        menu_back: ScanCode::N,
+pub fn p2_default_keymap() -> PlayerInputMap {
+    PlayerInputMap {
+        left: InputBindings::key(ScanCode::Comma),
+        up: InputBindings::key(ScanCode::L),
+        right: InputBindings::key(ScanCode::Slash),
+        down: InputBindings::key(ScanCode::Period),
+        prev_weapon: InputBindings::key(ScanCode::G),
+        next_weapon: InputBindings::key(ScanCode::H),
+        jump: InputBindings::key(ScanCode::B),
+        shoot: InputBindings::key(ScanCode::N),
+        skip: InputBindings::key(ScanCode::U),
+        inventory: InputBindings::key(ScanCode::T),
+        map: InputBindings::key(ScanCode::Y),
+        strafe: InputBindings::key(ScanCode::RShift),
+        menu_ok: InputBindings::key(ScanCode::B),
+        menu_back: InputBindings::key(ScanCode::N),
     }
 }
 
@@ -524,41 +826,35 @@ pub enum ControllerType {
     Gamepad(u32),
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-pub struct PlayerControllerButtonMap {
-    pub left: PlayerControllerInputType,
-    pub up: PlayerControllerInputType,
-    pub right: PlayerControllerInputType,
-    pub down: PlayerControllerInputType,
-    pub prev_weapon: PlayerControllerInputType,
-    pub next_weapon: PlayerControllerInputType,
-    pub jump: PlayerControllerInputType,
-    pub shoot: PlayerControllerInputType,
-    pub skip: PlayerControllerInputType,
-    pub inventory: PlayerControllerInputType,
-    pub map: PlayerControllerInputType,
-    pub strafe: PlayerControllerInputType,
-    pub menu_ok: PlayerControllerInputType,
-    pub menu_back: PlayerControllerInputType,
-}
-
 #[inline(always)]
-pub fn player_default_controller_button_map() -> PlayerControllerButtonMap {
-    PlayerControllerButtonMap {
-        left: PlayerControllerInputType::Either(Button::DPadLeft, Axis::LeftX, AxisDirection::Left),
-        up: PlayerControllerInputType::Either(Button::DPadUp, Axis::LeftY, AxisDirection::Up),
-        right: PlayerControllerInputType::Either(Button::DPadRight, Axis::LeftX, AxisDirection::Right),
-        down: PlayerControllerInputType::Either(Button::DPadDown, Axis::LeftY, AxisDirection::Down),
-        prev_weapon: PlayerControllerInputType::ButtonInput(Button::LeftShoulder),
-        next_weapon: PlayerControllerInputType::ButtonInput(Button::RightShoulder),
-        jump: PlayerControllerInputType::ButtonInput(Button::East),
-        shoot: PlayerControllerInputType::ButtonInput(Button::South),
-        skip: PlayerControllerInputType::ButtonInput(Button::West),
-        strafe: PlayerControllerInputType::AxisInput(Axis::TriggerRight, AxisDirection::Either),
-        inventory: PlayerControllerInputType::ButtonInput(Button::West),
-        map: PlayerControllerInputType::ButtonInput(Button::North),
-        menu_ok: PlayerControllerInputType::ButtonInput(Button::South),
-        menu_back: PlayerControllerInputType::ButtonInput(Button::East),
+pub fn player_default_controller_button_map() -> PlayerInputMap {
+    PlayerInputMap {
+        left: InputBindings::gamepad(PlayerControllerInputType::Either(
+            Button::DPadLeft,
+            Axis::LeftX,
+            AxisDirection::Left,
+        )),
+        up: InputBindings::gamepad(PlayerControllerInputType::Either(Button::DPadUp, Axis::LeftY, AxisDirection::Up)),
+        right: InputBindings::gamepad(PlayerControllerInputType::Either(
+            Button::DPadRight,
+            Axis::LeftX,
+            AxisDirection::Right,
+        )),
+        down: InputBindings::gamepad(PlayerControllerInputType::Either(
+            Button::DPadDown,
+            Axis::LeftY,
+            AxisDirection::Down,
+        )),
+        prev_weapon: InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::LeftShoulder)),
+        next_weapon: InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::RightShoulder)),
+        jump: InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::East)),
+        shoot: InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::South)),
+        skip: InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::West)),
+        strafe: InputBindings::gamepad(PlayerControllerInputType::AxisInput(Axis::TriggerRight, AxisDirection::Either)),
+        inventory: InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::West)),
+        map: InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::North)),
+        menu_ok: InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::South)),
+        menu_back: InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::East)),
     }
 }
 
@@ -566,3 +862,32 @@ pub fn player_default_controller_button_map() -> PlayerControllerButtonMap {
 pub fn default_controller_axis_sensitivity() -> f64 {
     0.3
 }
+
+#[test]
+fn test_assist_flags() {
+    let mut assist = AssistSettings::default();
+    assert_eq!(assist.flags(1.0).0, 0);
+
+    assist.damage_taken = 50;
+    assist.high_contrast_text = true;
+    let flags = assist.flags(0.75);
+    assert!(flags.damage_reduced());
+    assert!(flags.slowed_down());
+    assert!(!flags.infinite_air());
+    assert_eq!(flags.0, 0x05);
+}
+
+#[test]
+fn test_skip_key_upgrade() {
+    let mut settings = Settings::default();
+    settings.version = 32;
+    settings.player1_key_map.skip = InputBindings::key(ScanCode::Q);
+    settings.player1_key_map.inventory = InputBindings::key(ScanCode::Q);
+    settings.player2_key_map.skip = InputBindings::key(ScanCode::R);
+
+    let settings = settings.upgrade();
+    assert_eq!(settings.version, current_version());
+    assert_eq!(settings.player1_key_map.skip, InputBindings::key(ScanCode::E));
+    assert_eq!(settings.player1_key_map.inventory, InputBindings::key(ScanCode::Q));
+    assert_eq!(settings.player2_key_map.skip, InputBindings::key(ScanCode::R));
+}
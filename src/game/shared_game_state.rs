@@ -1,3 +1,4 @@
+use std::io::Read;
 use std::{cmp, ops::Div};
 
 use chrono::{Datelike, Local};
@@ -11,10 +12,15 @@ use crate::engine_constants::EngineConstants;
 use crate::framework::backend::BackendTexture;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
-use crate::framework::graphics::{create_texture_mutable, set_render_target};
+use crate::framework::graphics::{
+    create_texture_mutable, set_render_target, PostProcessConfig, UpscaleMode, UserShaderPass, VSyncMode,
+};
 use crate::framework::vfs::OpenOptions;
 use crate::framework::{filesystem, graphics};
+use crate::game::capture::{CaptureFormat, FrameCapture};
 use crate::game::caret::{Caret, CaretType};
+use crate::game::challenge::{ChallengeList, ChallengeRun, Leaderboard};
+use crate::game::exploration::ExplorationMap;
 use crate::game::npc::NPCTable;
 use crate::game::player::TargetPlayer;
 use crate::game::profile::GameProfile;
@@ -22,7 +28,8 @@ use crate::game::profile::GameProfile;
 use crate::game::scripting::lua::LuaScriptingState;
 use crate::game::scripting::tsc::credit_script::{CreditScript, CreditScriptVM};
 use crate::game::scripting::tsc::text_script::{ScriptMode, TextScript, TextScriptEncoding, TextScriptExecutionState, TextScriptVM};
-use crate::game::settings::Settings;
+use crate::game::settings::{AssistFlags, AssistSettings, Settings};
+use crate::game::speedrun::{SpeedrunRoute, SpeedrunTimer};
 use crate::game::stage::StageData;
 use crate::graphics::bmfont::BMFont;
 use crate::graphics::texture_set::TextureSet;
@@ -33,12 +40,16 @@ use crate::mod_requirements::ModRequirements;
 use crate::scene::game_scene::GameScene;
 use crate::scene::title_scene::TitleScene;
 use crate::scene::Scene;
+use crate::sound::mixer::MixerBus;
 use crate::sound::SoundManager;
 use crate::util::bitvec::BitVec;
 use crate::util::rng::XorShift;
 
 use super::filesystem_container::FilesystemContainer;
 
+/// TSC operands have 4 digits, so that's how many variables can be addressed.
+pub const MAX_SCRIPT_VARIABLES: usize = 10000;
+
 #[derive(PartialEq, Eq, Copy, Clone, serde::Serialize, serde::Deserialize)]
 pub enum TimingMode {
     _50Hz,
@@ -291,6 +302,10 @@ impl Default for PlayerSkinLocation {
 pub struct SharedGameState {
     pub control_flags: ControlFlags,
     pub game_flags: BitVec,
+    /// Integer variables used by TSC variable opcodes, saved in the profile.
+    pub script_variables: Vec<i32>,
+    /// Parts of the visited stages seen by the player, saved in the profile.
+    pub exploration: ExplorationMap,
     pub skip_flags: BitVec,
     pub map_flags: BitVec,
     pub fade_state: FadeState,
@@ -306,8 +321,14 @@ pub struct SharedGameState {
     pub teleporter_slots: Vec<(u16, u16)>,
     pub carets: Vec<Caret>,
     pub touch_controls: TouchControls,
+    /// Positions of the players on the canvas, updated by the game scene and used for mouse aiming.
+    pub player_canvas_pos: [(f32, f32); 2],
     pub mod_path: Option<String>,
     pub mod_list: ModList,
+    pub challenges: ChallengeList,
+    /// Challenge that is currently being played or watched.
+    pub challenge: Option<ChallengeRun>,
+    pub speedrun: SpeedrunTimer,
     pub npc_table: NPCTable,
     pub npc_super_pos: (i32, i32),
     pub npc_curly_target: (i32, i32),
@@ -317,6 +338,8 @@ pub struct SharedGameState {
     pub frame_time: f64,
     pub debugger: bool,
     pub command_line: bool,
+    /// Canvas pixels to screen pixels ratio. It's always 1 when the output is upscaled by post-processing, see
+    /// `handle_resize`.
     pub scale: f32,
     pub canvas_size: (f32, f32),
     pub screen_size: (f32, f32),
@@ -334,6 +357,7 @@ pub struct SharedGameState {
     #[cfg(feature = "scripting-lua")]
     pub lua: LuaScriptingState,
     pub sound_manager: SoundManager,
+    pub frame_capture: FrameCapture,
     pub settings: Settings,
     pub save_slot: usize,
     pub difficulty: GameDifficulty,
@@ -341,6 +365,10 @@ pub struct SharedGameState {
     pub player_count_modified_in_game: bool,
     pub player2_skin_location: PlayerSkinLocation,
     pub replay_state: ReplayState,
+    /// Assist settings a replay is being recorded or played back with.
+    pub replay_assist: Option<AssistSettings>,
+    /// Gameplay assists used at any point during the current playthrough.
+    pub assist_used: AssistFlags,
     pub mod_requirements: ModRequirements,
     pub loc: Locale,
     pub tutorial_counter: u16,
@@ -430,6 +458,12 @@ impl SharedGameState {
         })?;
 
         let mod_list = ModList::load(ctx, &constants.string_table)?;
+        let challenges = ChallengeList::load(ctx, &constants);
+        if settings.speedrun_timer {
+            SpeedrunRoute::create_default_file(ctx);
+        }
+        let mut speedrun = SpeedrunTimer::load(ctx);
+        speedrun.set_livesplit(settings.livesplit, &settings.livesplit_address);
 
         for i in 0..0xffu8 {
             let path = format!("pxt/fx{:02x}.pxt", i);
@@ -447,6 +481,10 @@ impl SharedGameState {
 
         sound_manager.set_song_volume(settings.bgm_volume);
         sound_manager.set_sfx_volume(settings.sfx_volume);
+        sound_manager.set_bus_volume(MixerBus::Ambience, settings.ambience_volume);
+        sound_manager.set_bus_volume(MixerBus::UI, settings.ui_volume);
+        sound_manager.set_bus_duck_amount(MixerBus::Music, settings.music_ducking);
+        sound_manager.set_positional_sfx(settings.positional_sfx);
 
         let current_time = Local::now();
         let more_rust = (current_time.month() == 7 && current_time.day() == 7) || settings.more_rust;
@@ -461,6 +499,8 @@ impl SharedGameState {
         Ok(SharedGameState {
             control_flags: ControlFlags(0),
             game_flags: BitVec::with_size(8000),
+            script_variables: Vec::new(),
+            exploration: ExplorationMap::default(),
             skip_flags: BitVec::with_size(64),
             map_flags: BitVec::with_size(128),
             fade_state: FadeState::Hidden,
@@ -474,8 +514,12 @@ impl SharedGameState {
             teleporter_slots: Vec::with_capacity(8),
             carets: Vec::with_capacity(32),
             touch_controls: TouchControls::new(),
+            player_canvas_pos: [(0.0, 0.0); 2],
             mod_path: None,
             mod_list,
+            challenges,
+            challenge: None,
+            speedrun,
             npc_table: NPCTable::new(),
             npc_super_pos: (0, 0),
             npc_curly_target: (0, 0),
@@ -502,6 +546,7 @@ impl SharedGameState {
             #[cfg(feature = "scripting-lua")]
             lua: LuaScriptingState::new(),
             sound_manager,
+            frame_capture: FrameCapture::new(),
             settings,
             save_slot: 1,
             difficulty: GameDifficulty::Normal,
@@ -509,6 +554,8 @@ impl SharedGameState {
             player_count_modified_in_game: false,
             player2_skin_location: PlayerSkinLocation::default(),
             replay_state: ReplayState::None,
+            replay_assist: None,
+            assist_used: AssistFlags(0),
             mod_requirements,
             loc: locale,
             tutorial_counter: 0,
@@ -528,38 +575,63 @@ impl SharedGameState {
         self.constants.load_csplus_tables(ctx)?;
         self.constants.load_animated_faces(ctx)?;
         self.constants.load_texture_size_hints(ctx)?;
+        self.constants.load_capacities(ctx)?;
+        self.constants.load_script_extensions(ctx)?;
+        let translation_filename = self.get_translation_filename();
+        self.constants.load_script_translation(ctx, &translation_filename)?;
+        self.constants.load_physics_presets(ctx)?;
+        self.constants.load_hud_layout(ctx)?;
+        self.constants.load_skin_packs(ctx)?;
+        self.constants.load_weapon_definitions(ctx)?;
+        if self.player2_skin_location.texture_index as usize >= self.constants.player_skin_paths.len() {
+            self.player2_skin_location = PlayerSkinLocation::default();
+        }
         let stages = StageData::load_stage_table(ctx, &self.constants.base_paths, self.constants.is_switch)?;
         self.stages = stages;
 
         let npc_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc.tbl")?;
-        let npc_table = NPCTable::load_from(npc_tbl)?;
+        let mut npc_table = NPCTable::load_from(npc_tbl)?;
+        if let Ok(file) = filesystem::open_find(ctx, &self.constants.base_paths, "npc_definitions.json") {
+            if let Err(err) = npc_table.load_definitions(file) {
+                log::warn!("{}", err);
+            }
+        }
         self.npc_table = npc_table;
 
+        self.load_scripts(ctx)?;
+
+        let substitution_rect_map = [('=', self.constants.textscript.textbox_item_marker_rect)];
+        self.textscript_vm.set_substitution_rect_map(substitution_rect_map);
+
+        self.texture_set.unload_all();
+
+        self.sound_manager.load_custom_sound_effects(ctx, &self.constants.base_paths)?;
+
+        Ok(())
+    }
+
+    /// Loads the scripts shared by all stages, translated with the current text script translation.
+    pub fn load_scripts(&mut self, ctx: &mut Context) -> GameResult {
+        self.textscript_vm.rich_text = self.constants.textscript.extensions.rich_text;
+
         let head_tsc = filesystem::open_find(ctx, &self.constants.base_paths, "Head.tsc")?;
-        let head_script = TextScript::load_from(head_tsc, &self.constants)?;
+        let head_script = TextScript::load_from(head_tsc, "Head", &self.constants)?;
         self.textscript_vm.set_global_script(head_script);
 
         let arms_item_tsc = filesystem::open_find(ctx, &self.constants.base_paths, "ArmsItem.tsc")?;
-        let arms_item_script = TextScript::load_from(arms_item_tsc, &self.constants)?;
+        let arms_item_script = TextScript::load_from(arms_item_tsc, "ArmsItem", &self.constants)?;
         self.textscript_vm.set_inventory_script(arms_item_script);
 
         let stage_select_tsc = filesystem::open_find(ctx, &self.constants.base_paths, "StageSelect.tsc")?;
-        let stage_select_script = TextScript::load_from(stage_select_tsc, &self.constants)?;
+        let stage_select_script = TextScript::load_from(stage_select_tsc, "StageSelect", &self.constants)?;
         self.textscript_vm.set_stage_select_script(stage_select_script);
 
-        let substitution_rect_map = [('=', self.constants.textscript.textbox_item_marker_rect)];
-        self.textscript_vm.set_substitution_rect_map(substitution_rect_map);
-
         if filesystem::exists_find(ctx, &self.constants.base_paths, "Credit.tsc") {
             let credit_tsc = filesystem::open_find(ctx, &self.constants.base_paths, "Credit.tsc")?;
             let credit_script = CreditScript::load_from(credit_tsc, &self.constants)?;
             self.creditscript_vm.set_script(credit_script);
         }
 
-        self.texture_set.unload_all();
-
-        self.sound_manager.load_custom_sound_effects(ctx, &self.constants.base_paths)?;
-
         Ok(())
     }
 
@@ -594,23 +666,47 @@ impl SharedGameState {
 
     pub fn start_new_game(&mut self, ctx: &mut Context) -> GameResult {
         self.reset();
+        // the dialogue backlog is kept when reloading after death, but not in a new game
+        self.textscript_vm.history.clear();
         #[cfg(feature = "scripting-lua")]
         self.lua.reload_scripts(ctx)?;
 
         #[cfg(feature = "discord-rpc")]
         self.discord_rpc.update_difficulty(self.difficulty)?;
 
-        let mut next_scene = GameScene::new(self, ctx, self.constants.game.new_game_stage as usize)?;
+        // challenges start a fresh run from their own stage and loadout
+        let challenge = self.challenge.take().map(|run| ChallengeRun::new(run.index, run.def));
+        let (stage, (pos_x, pos_y), event) = match &challenge {
+            Some(run) => (run.def.start_stage, run.def.start_pos, run.def.start_event),
+            None => (
+                self.constants.game.new_game_stage,
+                self.constants.game.new_game_player_pos,
+                self.constants.game.new_game_event,
+            ),
+        };
+
+        let mut next_scene = GameScene::new(self, ctx, stage as usize)?;
         next_scene.player1.cond.set_alive(true);
-        let (pos_x, pos_y) = self.constants.game.new_game_player_pos;
         next_scene.player1.x = pos_x as i32 * next_scene.stage.map.tile_size.as_int() * 0x200;
         next_scene.player1.y = pos_y as i32 * next_scene.stage.map.tile_size.as_int() * 0x200;
 
+        if let Some(mut run) = challenge {
+            run.def.loadout.apply(self, &mut next_scene);
+            run.best = Leaderboard::load(ctx, &run.def.id).best().cloned();
+            self.challenge = Some(run);
+        }
+
+        if self.settings.speedrun_timer && self.challenge.is_none() {
+            self.speedrun.start(self.settings.timing_mode.get_tps() as u32);
+        } else {
+            self.speedrun.reset(ctx);
+        }
+
         self.reset_map_flags();
         self.control_flags.set_control_enabled(true);
         self.control_flags.set_tick_world(true);
         self.fade_state = FadeState::Hidden;
-        self.textscript_vm.state = TextScriptExecutionState::Running(self.constants.game.new_game_event, 0);
+        self.textscript_vm.state = TextScriptExecutionState::Running(event, 0);
         self.tutorial_counter = 300;
 
         self.next_scene = Some(Box::new(next_scene));
@@ -697,6 +793,8 @@ impl SharedGameState {
     pub fn reset(&mut self) {
         self.control_flags.0 = 0;
         self.game_flags = BitVec::with_size(8000);
+        self.script_variables.clear();
+        self.exploration.clear();
         self.fade_state = FadeState::Hidden;
         self.game_rng = XorShift::new(chrono::Local::now().timestamp() as i32);
         self.teleporter_slots.clear();
@@ -704,15 +802,28 @@ impl SharedGameState {
         self.carets.clear();
         self.textscript_vm.set_mode(ScriptMode::Map);
         self.textscript_vm.suspend = true;
+        self.assist_used = AssistFlags(0);
     }
 
+    /// Converts a position in screen units, like the mouse cursor or a touch, to a position on the canvas.
+    pub fn screen_to_canvas(&self, ctx: &Context, position: (f32, f32)) -> (f32, f32) {
+        screen_to_canvas(ctx.screen_size, self.canvas_size, self.settings.upscale_mode, position)
+    }
+
+    /// Recalculates the canvas size and scale after the screen was resized or the upscaling mode was changed.
+    ///
+    /// In `UpscaleMode::Native` the game is drawn straight to the screen, `scale` is the largest integer factor
+    /// that fits the preferred viewport. Other modes draw the game at its base resolution and the renderer upscales
+    /// it when presenting, so `screen_size` is the size of that offscreen canvas and `scale` is 1. Screen positions
+    /// can't be divided by `scale` to get canvas positions in that case, they have to go through `screen_to_canvas`.
     pub fn handle_resize(&mut self, ctx: &mut Context) -> GameResult {
-        self.screen_size = graphics::screen_size(ctx);
-        let scale_x = self.screen_size.1.div(self.preferred_viewport_size.1).floor().max(1.0);
-        let scale_y = self.screen_size.0.div(self.preferred_viewport_size.0).floor().max(1.0);
+        let upscaled = self.settings.upscale_mode != UpscaleMode::Native && graphics::supports_post_process(ctx);
+        let (screen_size, scale) = viewport_size(upscaled, graphics::screen_size(ctx), self.preferred_viewport_size);
 
-        self.scale = f32::min(scale_x, scale_y);
+        self.screen_size = screen_size;
+        self.scale = scale;
         self.canvas_size = (self.screen_size.0 / self.scale, self.screen_size.1 / self.scale);
+        self.apply_post_process(ctx)?;
 
         let (width, height) = (self.screen_size.0 as u16, self.screen_size.1 as u16);
 
@@ -723,6 +834,46 @@ impl SharedGameState {
         Ok(())
     }
 
+    /// Builds the post-processing chain from current settings and passes it to the renderer.
+    pub fn apply_post_process(&mut self, ctx: &mut Context) -> GameResult {
+        if !graphics::supports_post_process(ctx) {
+            return Ok(());
+        }
+
+        let mut user_passes = Vec::new();
+        for name in &self.settings.user_shaders {
+            let path = format!("/shaders/postprocess/{}.glsl", name);
+
+            match filesystem::open(ctx, &path) {
+                Ok(mut file) => {
+                    let mut source = String::new();
+                    file.read_to_string(&mut source)?;
+                    user_passes.push(UserShaderPass { name: name.clone(), source });
+                }
+                Err(err) => log::warn!("Failed to load post-processing shader {}: {}", path, err),
+            }
+        }
+
+        let render_size = if self.settings.upscale_mode != UpscaleMode::Native {
+            Some((self.screen_size.0 as u32, self.screen_size.1 as u32))
+        } else {
+            None
+        };
+
+        graphics::set_post_process(
+            ctx,
+            PostProcessConfig {
+                upscale_mode: self.settings.upscale_mode,
+                render_size,
+                pixel_scale: self.scale,
+                colorblind_filter: self.settings.colorblind_filter,
+                user_passes,
+                scanlines: self.settings.shader_effects && self.settings.scanlines,
+                crt: self.settings.shader_effects && self.settings.crt_filter,
+            },
+        )
+    }
+
     pub fn tick_carets(&mut self) {
         for caret in &mut self.carets {
             caret.tick(&self.effect_rng, &self.constants);
@@ -732,6 +883,10 @@ impl SharedGameState {
     }
 
     pub fn create_caret(&mut self, x: i32, y: i32, ctype: CaretType, direct: Direction) {
+        if self.carets.len() >= self.constants.capacity.caret {
+            return;
+        }
+
         self.carets.push(Caret::new(x, y, ctype, direct, &self.constants));
     }
 
@@ -744,9 +899,42 @@ impl SharedGameState {
         self.settings.timing_mode.get_tps() as f64 * self.settings.speed
     }
 
+    /// Starts dumping rendered frames into the user directory, see `FrameCapture` for details.
+    pub fn start_capture(&mut self, ctx: &mut Context, format: CaptureFormat, realtime: bool) -> GameResult {
+        let timing_mode = self.frame_capture.start(ctx, format, realtime, self.settings.timing_mode)?;
+
+        if let Some(path) = self.frame_capture.audio_path() {
+            self.sound_manager.start_capture(filesystem::user_create(ctx, path)?)?;
+        }
+
+        if !realtime {
+            graphics::set_vsync_mode(ctx, VSyncMode::Uncapped)?;
+        }
+
+        self.settings.timing_mode = timing_mode;
+        self.frame_time = 1.0;
+
+        Ok(())
+    }
+
+    pub fn stop_capture(&mut self, ctx: &mut Context) -> GameResult {
+        if let Some(timing_mode) = self.frame_capture.stop()? {
+            self.settings.timing_mode = timing_mode;
+            self.sound_manager.stop_capture()?;
+            graphics::set_vsync_mode(ctx, self.settings.vsync_mode)?;
+        }
+
+        Ok(())
+    }
+
     pub fn shutdown(&mut self) {
         self.shutdown = true;
 
+        if let Ok(Some(timing_mode)) = self.frame_capture.stop() {
+            self.settings.timing_mode = timing_mode;
+            let _ = self.sound_manager.stop_capture();
+        }
+
         #[cfg(feature = "discord-rpc")]
         self.discord_rpc.dispose();
     }
@@ -774,6 +962,27 @@ impl SharedGameState {
         }
     }
 
+    pub fn set_variable(&mut self, id: usize, value: i32) {
+        if id >= MAX_SCRIPT_VARIABLES {
+            log::warn!("Attempted to set an out-of-bounds variable: {} to {}.", id, value);
+            return;
+        }
+
+        if id >= self.script_variables.len() {
+            if value == 0 {
+                return;
+            }
+
+            self.script_variables.resize(id + 1, 0);
+        }
+
+        self.script_variables[id] = value;
+    }
+
+    pub fn get_variable(&self, id: usize) -> i32 {
+        self.script_variables.get(id).copied().unwrap_or(0)
+    }
+
     pub fn reset_skip_flags(&mut self) {
         self.skip_flags = BitVec::with_size(64);
     }
@@ -815,6 +1024,10 @@ impl SharedGameState {
     }
 
     pub fn get_save_filename(&mut self, slot: usize) -> Option<String> {
+        if self.challenge.is_some() {
+            return None;
+        }
+
         if let Some(mod_path) = &self.mod_path {
             let save_slot = self.mod_list.get_save_from_path(mod_path.to_string());
             if save_slot < 0 {
@@ -832,6 +1045,10 @@ impl SharedGameState {
     }
 
     pub fn get_rec_filename(&self) -> String {
+        if let Some(run) = &self.challenge {
+            return format!("/challenge_{}", run.def.id);
+        }
+
         if let Some(mod_path) = &self.mod_path {
             let name = self.mod_list.get_name_from_path(mod_path.to_string());
             return format!("/{}", name);
@@ -840,6 +1057,22 @@ impl SharedGameState {
         }
     }
 
+    /// Path of the user's text script translation catalog without the extension, each mod and edition of the game
+    /// has its own.
+    pub fn get_translation_filename(&self) -> String {
+        let name = if let Some(mod_path) = &self.mod_path {
+            self.mod_list.get_id_from_path(mod_path.to_string())
+        } else if self.constants.is_switch {
+            "switch"
+        } else if self.constants.is_cs_plus {
+            "csplus"
+        } else {
+            "freeware"
+        };
+
+        format!("/tsc_translation_{}", name)
+    }
+
     pub fn has_replay_data(&self, ctx: &mut Context, replay_kind: ReplayKind) -> bool {
         filesystem::user_exists(ctx, [self.get_rec_filename(), replay_kind.get_suffix()].join(""))
     }
@@ -852,12 +1085,22 @@ impl SharedGameState {
     }
 
     pub fn get_damage(&self, hp: i32) -> i32 {
-        match self.difficulty {
+        let hp = match self.difficulty {
             GameDifficulty::Easy => cmp::max(hp / 2, 1),
             GameDifficulty::Normal | GameDifficulty::Hard => hp,
+        };
+
+        match self.get_assist().damage_taken {
+            100 => hp,
+            percent => cmp::max(hp * percent as i32 / 100, 1),
         }
     }
 
+    /// Returns the assist settings in effect, replays keep the ones they were started with.
+    pub fn get_assist(&self) -> &AssistSettings {
+        self.replay_assist.as_ref().unwrap_or(&self.settings.assist)
+    }
+
     pub fn get_skinsheet_offset(&self) -> u16 {
         if !self.constants.is_cs_plus {
             return 0;
@@ -899,3 +1142,57 @@ impl SharedGameState {
         return self.loc.tt(key, args);
     }
 }
+
+/// Returns the size the game is drawn at and the scale of the canvas, see `SharedGameState::handle_resize`.
+fn viewport_size(upscaled: bool, screen_size: (f32, f32), preferred_size: (f32, f32)) -> ((f32, f32), f32) {
+    if upscaled {
+        // the game is drawn at its base resolution and scaled up when presenting
+        return ((preferred_size.0.floor(), preferred_size.1.floor()), 1.0);
+    }
+
+    let scale_x = screen_size.1.div(preferred_size.1).floor().max(1.0);
+    let scale_y = screen_size.0.div(preferred_size.0).floor().max(1.0);
+
+    (screen_size, f32::min(scale_x, scale_y))
+}
+
+fn screen_to_canvas(
+    screen_size: (f32, f32),
+    canvas_size: (f32, f32),
+    upscale_mode: UpscaleMode,
+    position: (f32, f32),
+) -> (f32, f32) {
+    let mut scale = f32::min(screen_size.0 / canvas_size.0, screen_size.1 / canvas_size.1);
+
+    // the canvas is centered on the screen when it's upscaled
+    if upscale_mode == UpscaleMode::Integer {
+        scale = scale.floor().max(1.0);
+    }
+
+    let offset_x = (screen_size.0 - canvas_size.0 * scale) / 2.0;
+    let offset_y = (screen_size.1 - canvas_size.1 * scale) / 2.0;
+
+    ((position.0 - offset_x) / scale, (position.1 - offset_y) / scale)
+}
+
+#[test]
+fn test_viewport_coordinates() {
+    let window = (1280.0, 960.0);
+    let center = (640.0, 480.0);
+
+    // native mode draws to the window with an integer scale
+    let (screen_size, scale) = viewport_size(false, window, (320.0, 240.0));
+    assert_eq!((screen_size, scale), (window, 4.0));
+    let canvas_size = (screen_size.0 / scale, screen_size.1 / scale);
+    assert_eq!(screen_to_canvas(window, canvas_size, UpscaleMode::Native, center), (160.0, 120.0));
+
+    // upscaled modes draw at base resolution with a scale of 1, window positions must not be divided by it
+    let (screen_size, scale) = viewport_size(true, window, (320.0, 240.0));
+    assert_eq!((screen_size, scale), ((320.0, 240.0), 1.0));
+    let canvas_size = (screen_size.0 / scale, screen_size.1 / scale);
+    assert_eq!(screen_to_canvas(window, canvas_size, UpscaleMode::Integer, center), (160.0, 120.0));
+    assert_eq!(screen_to_canvas(window, canvas_size, UpscaleMode::SharpBilinear, (0.0, 0.0)), (0.0, 0.0));
+
+    // integer upscaling centers the canvas in a window that isn't a multiple of its size
+    assert_eq!(screen_to_canvas((1000.0, 720.0), canvas_size, UpscaleMode::Integer, (20.0, 0.0)), (0.0, 0.0));
+}
@@ -0,0 +1,406 @@
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::sync::mpsc::Sender;
+
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::filesystem;
+use crate::game::npc::boss::BossNPC;
+use crate::game::run_timer::{current_version, BossKillTracker};
+use crate::util::bitvec::BitVec;
+
+/// Condition that has to be met for the current split to be taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SplitCondition {
+    StageEntered {
+        stage: u16,
+    },
+    FlagSet {
+        flag: u16,
+    },
+    /// Split when a boss of given type is defeated, see [BossKillTracker].
+    BossKilled {
+        boss: u16,
+    },
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SplitDefinition {
+    pub name: String,
+    pub condition: SplitCondition,
+}
+
+/// List of splits of a run, stored in `speedrun.json` in the user directory.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SpeedrunRoute {
+    #[serde(default = "current_version")]
+    pub version: u32,
+    pub name: String,
+    pub splits: Vec<SplitDefinition>,
+}
+
+impl Default for SpeedrunRoute {
+    fn default() -> Self {
+        let boss = |name: &str, boss| SplitDefinition {
+            name: name.to_owned(),
+            condition: SplitCondition::BossKilled { boss },
+        };
+
+        SpeedrunRoute {
+            version: current_version(),
+            name: "Any%".to_owned(),
+            splits: vec![
+                boss("Balfrog", 2),
+                boss("Omega", 1),
+                boss("Monster X", 3),
+                boss("Core", 4),
+                boss("Ironhead", 5),
+                boss("Sisters", 6),
+                boss("Undead Core", 7),
+            ],
+        }
+    }
+}
+
+impl SpeedrunRoute {
+    pub fn load(ctx: &Context) -> SpeedrunRoute {
+        if let Ok(file) = filesystem::user_open(ctx, "/speedrun.json") {
+            match serde_json::from_reader::<_, SpeedrunRoute>(file) {
+                Ok(route) => return route,
+                Err(err) => log::warn!("Failed to deserialize speedrun route: {}", err),
+            }
+        }
+
+        SpeedrunRoute::default()
+    }
+
+    /// Writes out the default route so it can be edited, called once the speedrun timer is turned on.
+    pub fn create_default_file(ctx: &Context) {
+        if filesystem::user_exists(ctx, "/speedrun.json") {
+            return;
+        }
+
+        if let Err(err) = SpeedrunRoute::default().save(ctx) {
+            log::warn!("Failed to save default speedrun route: {}", err);
+        }
+    }
+
+    pub fn save(&self, ctx: &Context) -> GameResult {
+        let file = filesystem::user_create(ctx, "/speedrun.json")?;
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+}
+
+/// Personal best and best segments of a route, all times are game time in milliseconds.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct SpeedrunRecords {
+    #[serde(default = "current_version")]
+    pub version: u32,
+    /// Route the records were set on, records of other routes are discarded.
+    #[serde(default)]
+    pub route: String,
+    /// Cumulative split times of the personal best.
+    #[serde(default)]
+    pub pb: Vec<u64>,
+    /// Best time of each segment.
+    #[serde(default)]
+    pub golds: Vec<u64>,
+}
+
+impl SpeedrunRecords {
+    pub fn load(ctx: &Context, route: &SpeedrunRoute) -> SpeedrunRecords {
+        if let Ok(file) = filesystem::user_open(ctx, "/speedrun_records.json") {
+            match serde_json::from_reader::<_, SpeedrunRecords>(file) {
+                Ok(records) if records.route == route.name => return records,
+                Ok(_) => log::info!("Speedrun records belong to a different route, ignoring them."),
+                Err(err) => log::warn!("Failed to deserialize speedrun records: {}", err),
+            }
+        }
+
+        SpeedrunRecords { version: current_version(), route: route.name.clone(), pb: Vec::new(), golds: Vec::new() }
+    }
+
+    pub fn save(&self, ctx: &Context) -> GameResult {
+        let file = filesystem::user_create(ctx, "/speedrun_records.json")?;
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+
+    /// Updates best segments with the splits of a run, returns true if any of them improved.
+    pub fn update_golds(&mut self, splits: &[u64]) -> bool {
+        let mut updated = false;
+        let mut prev = 0;
+
+        for (idx, &time) in splits.iter().enumerate() {
+            let segment = time.saturating_sub(prev);
+            prev = time;
+
+            match self.golds.get_mut(idx) {
+                Some(gold) if *gold <= segment => {}
+                Some(gold) => {
+                    *gold = segment;
+                    updated = true;
+                }
+                None => {
+                    self.golds.push(segment);
+                    updated = true;
+                }
+            }
+        }
+
+        updated
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeedrunStatus {
+    NotRunning,
+    Running,
+    Finished,
+}
+
+/// Sends commands to a LiveSplit Server instance from a background thread, so a missing or slow
+/// connection never stalls the game.
+pub struct LiveSplitClient {
+    sender: Sender<String>,
+}
+
+impl LiveSplitClient {
+    pub fn start(address: String) -> LiveSplitClient {
+        let (sender, receiver) = mpsc::channel::<String>();
+
+        std::thread::spawn(move || {
+            let mut stream: Option<TcpStream> = None;
+            let mut warned = false;
+
+            while let Ok(command) = receiver.recv() {
+                if stream.is_none() {
+                    match TcpStream::connect(&address) {
+                        Ok(s) => {
+                            log::info!("Connected to LiveSplit Server at {}.", address);
+                            let _ = s.set_nodelay(true);
+                            stream = Some(s);
+                            warned = false;
+                        }
+                        Err(err) => {
+                            if !warned {
+                                log::warn!("Failed to connect to LiveSplit Server at {}: {}", address, err);
+                                warned = true;
+                            }
+                            continue;
+                        }
+                    }
+                }
+
+                if let Some(s) = &mut stream {
+                    if let Err(err) = s.write_all(format!("{}\r\n", command).as_bytes()) {
+                        log::warn!("Lost connection to LiveSplit Server: {}", err);
+                        stream = None;
+                    }
+                }
+            }
+        });
+
+        LiveSplitClient { sender }
+    }
+
+    pub fn send(&self, command: &str) {
+        let _ = self.sender.send(command.to_owned());
+    }
+
+    pub fn set_game_time(&self, millis: u64) {
+        self.send(&format!("setgametime {}", format_livesplit_time(millis)));
+    }
+}
+
+/// Run timer counting game ticks, so times don't depend on frame rate or lag and can be compared
+/// between timing modes.
+pub struct SpeedrunTimer {
+    pub route: SpeedrunRoute,
+    pub records: SpeedrunRecords,
+    pub status: SpeedrunStatus,
+    pub ticks: u32,
+    pub tps: u32,
+    /// Cumulative game time of every split taken so far.
+    pub splits: Vec<u64>,
+    livesplit: Option<LiveSplitClient>,
+    boss_kill: BossKillTracker,
+    last_stage: Option<u16>,
+}
+
+impl SpeedrunTimer {
+    pub fn load(ctx: &Context) -> SpeedrunTimer {
+        let route = SpeedrunRoute::load(ctx);
+        let records = SpeedrunRecords::load(ctx, &route);
+
+        SpeedrunTimer {
+            route,
+            records,
+            status: SpeedrunStatus::NotRunning,
+            ticks: 0,
+            tps: 50,
+            splits: Vec::new(),
+            livesplit: None,
+            boss_kill: BossKillTracker::default(),
+            last_stage: None,
+        }
+    }
+
+    pub fn set_livesplit(&mut self, enabled: bool, address: &str) {
+        if !enabled {
+            self.livesplit = None;
+        } else if self.livesplit.is_none() {
+            self.livesplit = Some(LiveSplitClient::start(address.to_owned()));
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == SpeedrunStatus::Running
+    }
+
+    pub fn millis(&self) -> u64 {
+        self.ticks as u64 * 1000 / self.tps.max(1) as u64
+    }
+
+    pub fn current_split(&self) -> Option<&SplitDefinition> {
+        self.route.splits.get(self.splits.len())
+    }
+
+    pub fn start(&mut self, tps: u32) {
+        self.status = SpeedrunStatus::Running;
+        self.ticks = 0;
+        // frame synchronized mode has no fixed tick rate, count it as 60 fps
+        self.tps = if tps == 0 { 60 } else { tps };
+        self.splits.clear();
+        self.boss_kill.reset();
+        self.last_stage = None;
+
+        if let Some(livesplit) = &self.livesplit {
+            // game time is driven by us, so it's paused between updates
+            livesplit.send("reset");
+            livesplit.send("initgametime");
+            livesplit.send("starttimer");
+            livesplit.send("pausegametime");
+            livesplit.set_game_time(0);
+        }
+    }
+
+    /// Stops the timer, best segments of an unfinished run are still kept.
+    /// Called whenever the game scene is created (stage transfers, loading a profile), the boss being tracked
+    /// is gone then.
+    pub fn scene_loaded(&mut self) {
+        self.boss_kill.reset();
+    }
+
+    pub fn reset(&mut self, ctx: &Context) {
+        if self.status == SpeedrunStatus::NotRunning {
+            return;
+        }
+
+        if self.status == SpeedrunStatus::Running && self.records.update_golds(&self.splits) {
+            if let Err(err) = self.records.save(ctx) {
+                log::warn!("Failed to save speedrun records: {}", err);
+            }
+        }
+
+        self.status = SpeedrunStatus::NotRunning;
+
+        if let Some(livesplit) = &self.livesplit {
+            livesplit.send("reset");
+        }
+    }
+
+    pub fn tick(&mut self, ctx: &Context, flags: &BitVec, stage: u16, boss: &BossNPC) {
+        if self.status != SpeedrunStatus::Running {
+            return;
+        }
+
+        self.ticks = self.ticks.saturating_add(1);
+
+        let entered_stage = self.last_stage.is_some() && self.last_stage != Some(stage);
+        self.last_stage = Some(stage);
+
+        let split = match self.current_split().map(|split| split.condition) {
+            Some(SplitCondition::StageEntered { stage: target }) => entered_stage && stage == target,
+            Some(SplitCondition::FlagSet { flag }) => flags.get(flag as usize).unwrap_or(false),
+            Some(SplitCondition::BossKilled { boss: boss_type }) => self.boss_kill.update(boss, boss_type, stage),
+            None => false,
+        };
+
+        if split {
+            self.split(ctx);
+        } else if self.ticks.is_multiple_of(self.tps.max(1)) {
+            if let Some(livesplit) = &self.livesplit {
+                livesplit.set_game_time(self.millis());
+            }
+        }
+    }
+
+    fn split(&mut self, ctx: &Context) {
+        let time = self.millis();
+        self.splits.push(time);
+        self.boss_kill.reset();
+
+        if let Some(livesplit) = &self.livesplit {
+            livesplit.set_game_time(time);
+            livesplit.send("split");
+        }
+
+        if self.splits.len() < self.route.splits.len() {
+            return;
+        }
+
+        self.status = SpeedrunStatus::Finished;
+        self.records.update_golds(&self.splits);
+
+        if self.records.pb.len() != self.splits.len() || self.records.pb.last().is_none_or(|&pb| time < pb) {
+            self.records.pb = self.splits.clone();
+        }
+
+        if let Err(err) = self.records.save(ctx) {
+            log::warn!("Failed to save speedrun records: {}", err);
+        }
+    }
+
+    /// Difference between a split and the same split of the personal best.
+    pub fn pb_delta(&self, idx: usize) -> Option<i64> {
+        let time = *self.splits.get(idx)?;
+        let pb = *self.records.pb.get(idx)?;
+
+        Some(time as i64 - pb as i64)
+    }
+
+    /// Whether the segment ending at given split was faster than the best one.
+    pub fn is_gold(&self, idx: usize) -> bool {
+        let time = match self.splits.get(idx) {
+            Some(&time) => time,
+            None => return false,
+        };
+        let segment = time - if idx > 0 { self.splits[idx - 1] } else { 0 };
+
+        self.records.golds.get(idx).is_none_or(|&gold| segment < gold)
+    }
+}
+
+/// Formats game time the way LiveSplit parses it, as `h:mm:ss.fff`.
+pub fn format_livesplit_time(millis: u64) -> String {
+    format!("{}:{:02}:{:02}.{:03}", millis / 3600000, (millis / 60000) % 60, (millis / 1000) % 60, millis % 1000)
+}
+
+#[test]
+fn test_speedrun_records() {
+    let mut records = SpeedrunRecords::default();
+
+    assert!(records.update_golds(&[1000, 3000]));
+    assert_eq!(records.golds, vec![1000, 2000]);
+    assert!(records.update_golds(&[1500, 3000, 4000]));
+    assert_eq!(records.golds, vec![1000, 1500, 1000]);
+    assert!(!records.update_golds(&[2000]));
+
+    assert_eq!(format_livesplit_time(3723456), "1:02:03.456");
+}
@@ -572,7 +572,7 @@ impl Stage {
         ctx: &mut Context,
     ) -> GameResult<TextScript> {
         let tsc_file = filesystem::open_find(ctx, roots, ["Stage/", &self.data.map, ".tsc"].join(""))?;
-        let text_script = TextScript::load_from(tsc_file, constants)?;
+        let text_script = TextScript::load_from(tsc_file, &["Stage/", &self.data.map].join(""), constants)?;
 
         Ok(text_script)
     }
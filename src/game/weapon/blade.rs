@@ -8,7 +8,7 @@ impl Weapon {
     pub(crate) fn tick_blade(&mut self, player: &Player, player_id: TargetPlayer, bullet_manager: &mut BulletManager, state: &mut SharedGameState) {
         const BULLETS: [u16; 3] = [25, 26, 27];
 
-        if !player.controller.trigger_shoot() || bullet_manager.count_bullets_multi(&BULLETS, player_id) > 0 {
+        if !self.shoot_triggered || bullet_manager.count_bullets_multi(&BULLETS, player_id) > 0 {
             return;
         }
 
@@ -16,7 +16,7 @@ impl Weapon {
         const BULLETS: [u16; 3] = [19, 20, 21];
 
         if self.level == WeaponLevel::Level1 {
-            if !player.controller.trigger_shoot() {
+            if !self.shoot_triggered {
                 self.counter2 += 1;
                 if self.counter2 > 20 {
                     self.counter2 = 0;
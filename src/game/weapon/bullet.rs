@@ -1,7 +1,7 @@
 use num_traits::clamp;
 
 use crate::common::{BulletFlag, Condition, Direction, Flag, Rect};
-use crate::engine_constants::{BulletData, EngineConstants};
+use crate::engine_constants::{BulletData, CapacityConsts, EngineConstants};
 use crate::game::caret::CaretType;
 use crate::game::npc::list::NPCList;
 use crate::game::npc::NPC;
@@ -15,6 +15,8 @@ pub struct BulletManager {
     pub bullets: Vec<Bullet>,
     pub new_bullets: Vec<Bullet>,
     pub seeder: XorShift,
+    /// Bullets created above this limit are discarded.
+    pub max_bullets: usize,
 }
 
 impl BulletManager {
@@ -24,6 +26,7 @@ impl BulletManager {
             bullets: Vec::with_capacity(64),
             new_bullets: Vec::with_capacity(8),
             seeder: XorShift::new(0x359c482f),
+            max_bullets: CapacityConsts::default().bullet,
         }
     }
 
@@ -36,6 +39,10 @@ impl BulletManager {
         direction: Direction,
         constants: &EngineConstants,
     ) {
+        if self.bullets.len() >= self.max_bullets {
+            return;
+        }
+
         let mut bullet = Bullet::new(x, y, btype, owner, direction, constants);
         bullet.rng = Xoroshiro32PlusPlus::new(self.seeder.next_u32());
 
@@ -43,6 +50,10 @@ impl BulletManager {
     }
 
     pub fn push_bullet(&mut self, mut bullet: Bullet) {
+        if self.bullets.len() >= self.max_bullets {
+            return;
+        }
+
         bullet.rng = Xoroshiro32PlusPlus::new(self.seeder.next_u32());
         self.bullets.push(bullet);
     }
@@ -57,6 +68,7 @@ impl BulletManager {
                 bullet.tick(state, players, npc_list, &mut self.new_bullets);
             }
 
+            self.new_bullets.truncate(self.max_bullets.saturating_sub(self.bullets.len()));
             for bullet in &mut self.new_bullets {
                 bullet.rng = Xoroshiro32PlusPlus::new(self.seeder.next_u32());
             }
@@ -134,7 +146,7 @@ impl Bullet {
         direction: Direction,
         constants: &EngineConstants,
     ) -> Bullet {
-        let bullet = constants.weapon.bullet_table.get(btype as usize).unwrap_or_else(|| &BulletData {
+        let bullet = constants.weapon.get_bullet_data(btype).unwrap_or_else(|| &BulletData {
             damage: 0,
             life: 0,
             lifetime: 0,
@@ -1685,7 +1697,7 @@ impl Bullet {
             37 | 38 | 39 => self.tick_spur(state, new_bullets),
             40 | 41 | 42 => self.tick_spur_trail(state),
             43 => self.tick_nemesis_curly(state, npc_list),
-            _ => self.cond.set_alive(false),
+            _ => self.tick_custom(state, npc_list),
         }
     }
 
@@ -0,0 +1,267 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::common::{Direction, Rect};
+use crate::engine_constants::FiringPattern;
+use crate::game::caret::CaretType;
+use crate::game::npc::list::NPCList;
+use crate::game::player::{Player, TargetPlayer};
+use crate::game::shared_game_state::SharedGameState;
+use crate::game::weapon::bullet::{Bullet, BulletManager};
+use crate::game::weapon::{Weapon, WeaponLevel};
+
+impl Weapon {
+    pub(crate) fn tick_custom(
+        &mut self,
+        id: u8,
+        player: &Player,
+        player_id: TargetPlayer,
+        bullet_manager: &mut BulletManager,
+        state: &mut SharedGameState,
+    ) {
+        let level = match (state.constants.weapon.get_custom_weapon(id), self.level) {
+            (Some(_), WeaponLevel::None) | (None, _) => return,
+            (Some(weapon), level) => weapon.levels[level as usize - 1],
+        };
+
+        let mut btype = level.bullet;
+        let bursting = self.counter2 > 0;
+
+        let shoot = match level.pattern {
+            FiringPattern::Charge { ticks, charged_bullet } => {
+                if player.controller.shoot() {
+                    if self.counter1 < ticks {
+                        self.counter1 += 1;
+
+                        if self.counter1 == ticks {
+                            state.sound_manager.play_sfx(65);
+                        }
+                    }
+
+                    false
+                } else if self.counter1 > 0 {
+                    if self.counter1 >= ticks {
+                        btype = charged_bullet;
+                    }
+
+                    self.counter1 = 0;
+                    true
+                } else {
+                    false
+                }
+            }
+            FiringPattern::Burst { interval, .. } if bursting => {
+                self.counter1 = self.counter1.saturating_sub(1);
+                if self.counter1 == 0 {
+                    self.counter1 = interval;
+                    self.counter2 -= 1;
+                    true
+                } else {
+                    false
+                }
+            }
+            _ if level.auto_fire => {
+                if !player.controller.shoot() {
+                    self.counter1 = 0;
+                    false
+                } else if self.counter1 == 0 {
+                    self.counter1 = level.refire_ticks.max(1);
+                    true
+                } else {
+                    self.counter1 -= 1;
+                    false
+                }
+            }
+            _ => self.shoot_triggered,
+        };
+
+        if !shoot {
+            return;
+        }
+
+        if level.max_bullets != 0 {
+            let btypes = match level.pattern {
+                FiringPattern::Charge { charged_bullet, .. } => [level.bullet, charged_bullet],
+                _ => [level.bullet, level.bullet],
+            };
+
+            if bullet_manager.count_bullets_multi(&btypes, player_id) >= level.max_bullets as usize {
+                self.counter2 = 0;
+                return;
+            }
+        }
+
+        if !self.consume_ammo(level.ammo_cost) {
+            self.counter2 = 0;
+            self.draw_empty(state, player.x, player.y);
+            return;
+        }
+
+        if let FiringPattern::Burst { count, interval } = level.pattern {
+            if !bursting && count > 1 {
+                self.counter1 = interval;
+                self.counter2 = count as u16 - 1;
+            }
+        }
+
+        let (x, y, caret_x, direction) = match player.direction {
+            Direction::Left if player.up => (player.x - 0x200, player.y - 0x1000, player.x - 0x200, Direction::Up),
+            Direction::Right if player.up => (player.x + 0x200, player.y - 0x1000, player.x + 0x200, Direction::Up),
+            Direction::Left if player.down => {
+                (player.x - 0x200, player.y + 0x1000, player.x - 0x200, Direction::Bottom)
+            }
+            Direction::Right if player.down => {
+                (player.x + 0x200, player.y + 0x1000, player.x + 0x200, Direction::Bottom)
+            }
+            Direction::Left => (player.x - 0xc00, player.y + 0x600, player.x - 0x1800, Direction::Left),
+            Direction::Right => (player.x + 0xc00, player.y + 0x600, player.x + 0x1800, Direction::Right),
+            _ => return,
+        };
+
+        let (count, spread) = match level.pattern {
+            FiringPattern::Spread { count, angle } => (count.max(1), angle as f64),
+            _ => (1, 0.0),
+        };
+
+        // vanilla bullets set their own velocity, only custom ones can be fired at an angle
+        let speed = state.constants.weapon.get_custom_bullet(btype).map(|bullet| bullet.speed);
+        let base_angle = match direction {
+            Direction::Left => PI,
+            Direction::Up => -FRAC_PI_2,
+            Direction::Bottom => FRAC_PI_2,
+            _ => 0.0,
+        };
+
+        for i in 0..count {
+            let mut bullet = Bullet::new(x, y, btype, player_id, direction, &state.constants);
+
+            if let Some(speed) = speed {
+                let offset = if count > 1 { spread * (i as f64 / (count - 1) as f64 - 0.5) } else { 0.0 };
+                let angle = base_angle + offset.to_radians();
+
+                bullet.vel_x = (angle.cos() * speed as f64) as i32;
+                bullet.vel_y = (angle.sin() * speed as f64) as i32;
+            }
+
+            bullet_manager.push_bullet(bullet);
+        }
+
+        state.create_caret(caret_x, y, CaretType::Shoot, Direction::Left);
+
+        if level.sound != 0 {
+            state.sound_manager.play_sfx(level.sound);
+        }
+    }
+}
+
+impl Bullet {
+    pub(crate) fn tick_custom(&mut self, state: &mut SharedGameState, npc_list: &NPCList) {
+        if state.constants.weapon.get_custom_bullet(self.btype).is_none() {
+            self.cond.set_alive(false);
+            return;
+        }
+
+        self.action_counter += 1;
+        if self.action_counter > self.lifetime {
+            self.cond.set_alive(false);
+            state.create_caret(self.x, self.y, CaretType::Shoot, Direction::Left);
+            return;
+        }
+
+        let def = state.constants.weapon.get_custom_bullet(self.btype).unwrap();
+
+        if self.action_num == 0 {
+            self.action_num = 1;
+
+            if self.vel_x == 0 && self.vel_y == 0 {
+                match self.direction {
+                    Direction::Left => self.vel_x = -def.speed,
+                    Direction::Up => self.vel_y = -def.speed,
+                    Direction::Right => self.vel_x = def.speed,
+                    Direction::Bottom => self.vel_y = def.speed,
+                    Direction::FacingPlayer => {}
+                }
+            }
+        } else {
+            if def.homing != 0 {
+                let target = npc_list
+                    .iter_alive()
+                    .filter(|npc| npc.npc_flags.shootable())
+                    .map(|npc| (npc.x, npc.y, (npc.x - self.x).abs().max((npc.y - self.y).abs())))
+                    .filter(|&(_, _, distance)| distance <= def.homing_range)
+                    .min_by_key(|&(_, _, distance)| distance);
+
+                if let Some((target_x, target_y, _)) = target {
+                    let angle = f64::atan2((target_y - self.y) as f64, (target_x - self.x) as f64);
+                    let max_speed = def.speed.abs();
+
+                    self.vel_x = (self.vel_x + (angle.cos() * def.homing as f64) as i32).clamp(-max_speed, max_speed);
+                    self.vel_y = (self.vel_y + (angle.sin() * def.homing as f64) as i32).clamp(-max_speed, max_speed);
+                }
+            }
+
+            self.vel_y += def.gravity;
+            self.x += self.vel_x;
+            self.y += self.vel_y;
+        }
+
+        let rects = match self.direction {
+            Direction::Left => &def.rects[0],
+            Direction::Up => &def.rects[1],
+            Direction::Right => &def.rects[2],
+            Direction::Bottom | Direction::FacingPlayer => &def.rects[3],
+        };
+
+        self.anim_counter += 1;
+        if self.anim_counter >= def.anim_ticks {
+            self.anim_counter = 0;
+            self.anim_num += 1;
+        }
+
+        if self.anim_num as usize >= rects.len() {
+            self.anim_num = 0;
+        }
+
+        self.anim_rect = rects.get(self.anim_num as usize).copied().unwrap_or(Rect::new(0, 0, 0, 0));
+    }
+}
+
+#[test]
+fn test_custom_weapon_definitions() {
+    use crate::engine_constants::EngineConstants;
+    use crate::game::weapon::WeaponType;
+
+    let weapon = r#"{
+        "id": 20,
+        "level_table": [10, 20, 30],
+        "levels": [
+            { "bullet": 64 },
+            { "bullet": 64, "pattern": { "type": "spread", "count": 3, "angle": 30 } },
+            { "bullet": 64, "pattern": { "type": "charge", "ticks": 40, "charged_bullet": 65 } }
+        ],
+        "arms_icon": [0, 16, 16, 32]
+    }"#;
+
+    let bullet = r#"{
+        "id": 64,
+        "data": {
+            "damage": 2, "life": 1, "lifetime": 30, "flags": 36,
+            "enemy_hit_width": 2, "enemy_hit_height": 2, "block_hit_width": 2, "block_hit_height": 2,
+            "display_bounds": [4, 4, 4, 4]
+        },
+        "speed": 1024,
+        "rects": [[[0, 0, 8, 8]], [[8, 0, 16, 8]], [[16, 0, 24, 8]], [[24, 0, 32, 8]]]
+    }"#;
+
+    let mut constants = EngineConstants::defaults();
+    constants.weapon.custom_weapons.push(serde_json::from_str(weapon).unwrap());
+    constants.weapon.custom_bullets.push(serde_json::from_str(bullet).unwrap());
+
+    assert_eq!(WeaponType::from_id(2, &constants), Some(WeaponType::PolarStar));
+    assert_eq!(WeaponType::from_id(20, &constants), Some(WeaponType::Custom(20)));
+    assert_eq!(WeaponType::from_id(21, &constants), None);
+    assert_eq!(WeaponType::Custom(20).id(), 20);
+    assert_eq!(constants.weapon.get_level_table(20), [10, 20, 30]);
+    assert_eq!(constants.weapon.get_arms_icon_rect(20).top, 16);
+    assert_eq!(constants.weapon.get_arms_icon_rect(2).left, 32);
+    assert_eq!(constants.weapon.get_bullet_data(64).map(|b| b.damage), Some(2));
+}
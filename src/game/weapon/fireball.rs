@@ -14,7 +14,7 @@ impl Weapon {
         state: &mut SharedGameState,
     ) {
         let max_bullets = self.level as usize + 1;
-        if !player.controller.trigger_shoot() || bullet_manager.count_bullets_multi(&[7, 8, 9], player_id) >= max_bullets {
+        if !self.shoot_triggered || bullet_manager.count_bullets_multi(&[7, 8, 9], player_id) >= max_bullets {
             return;
         }
 
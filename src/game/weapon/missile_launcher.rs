@@ -15,7 +15,7 @@ impl Weapon {
     ) {
         const BULLETS: [u16; 6] = [13, 14, 15, 16, 17, 18];
 
-        if !player.controller.trigger_shoot() {
+        if !self.shoot_triggered {
             return;
         }
 
@@ -1,5 +1,3 @@
-use num_derive::FromPrimitive;
-
 use crate::common::Direction;
 use crate::engine_constants::EngineConstants;
 use crate::game::caret::CaretType;
@@ -10,6 +8,7 @@ use crate::game::weapon::bullet::BulletManager;
 mod blade;
 mod bubbler;
 pub mod bullet;
+mod custom;
 mod fireball;
 mod machine_gun;
 mod missile_launcher;
@@ -19,20 +18,63 @@ mod snake;
 mod spur;
 mod super_missile_launcher;
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone, FromPrimitive)]
-#[repr(u8)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum WeaponType {
-    None = 0,
-    Snake = 1,
-    PolarStar = 2,
-    Fireball = 3,
-    MachineGun = 4,
-    MissileLauncher = 5,
-    Bubbler = 7,
-    Blade = 9,
-    SuperMissileLauncher = 10,
-    Nemesis = 12,
-    Spur = 13,
+    None,
+    Snake,
+    PolarStar,
+    Fireball,
+    MachineGun,
+    MissileLauncher,
+    Bubbler,
+    Blade,
+    SuperMissileLauncher,
+    Nemesis,
+    Spur,
+    /// Weapon defined in `weapons.json`.
+    Custom(u8),
+}
+
+impl WeaponType {
+    pub fn from_vanilla_id(id: u8) -> Option<WeaponType> {
+        match id {
+            0 => Some(WeaponType::None),
+            1 => Some(WeaponType::Snake),
+            2 => Some(WeaponType::PolarStar),
+            3 => Some(WeaponType::Fireball),
+            4 => Some(WeaponType::MachineGun),
+            5 => Some(WeaponType::MissileLauncher),
+            7 => Some(WeaponType::Bubbler),
+            9 => Some(WeaponType::Blade),
+            10 => Some(WeaponType::SuperMissileLauncher),
+            12 => Some(WeaponType::Nemesis),
+            13 => Some(WeaponType::Spur),
+            _ => None,
+        }
+    }
+
+    /// Returns a vanilla or custom weapon with given id, or `None` if no such weapon exists.
+    pub fn from_id(id: u8, constants: &EngineConstants) -> Option<WeaponType> {
+        WeaponType::from_vanilla_id(id)
+            .or_else(|| constants.weapon.get_custom_weapon(id).map(|weapon| WeaponType::Custom(weapon.id)))
+    }
+
+    pub fn id(self) -> u8 {
+        match self {
+            WeaponType::None => 0,
+            WeaponType::Snake => 1,
+            WeaponType::PolarStar => 2,
+            WeaponType::Fireball => 3,
+            WeaponType::MachineGun => 4,
+            WeaponType::MissileLauncher => 5,
+            WeaponType::Bubbler => 7,
+            WeaponType::Blade => 9,
+            WeaponType::SuperMissileLauncher => 10,
+            WeaponType::Nemesis => 12,
+            WeaponType::Spur => 13,
+            WeaponType::Custom(id) => id,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -75,11 +117,28 @@ pub struct Weapon {
     empty_counter: u16,
     counter1: u16,
     counter2: u16,
+    auto_fire_counter: u16,
+    shoot_triggered: bool,
 }
 
 impl Weapon {
+    /// Ticks between shots when the auto-fire assist is enabled.
+    const AUTO_FIRE_INTERVAL: u16 = 6;
+
     pub fn new(wtype: WeaponType, level: WeaponLevel, experience: u16, ammo: u16, max_ammo: u16) -> Weapon {
-        Weapon { wtype, level, experience, ammo, max_ammo, refire_timer: 0, empty_counter: 0, counter1: 0, counter2: 0 }
+        Weapon {
+            wtype,
+            level,
+            experience,
+            ammo,
+            max_ammo,
+            refire_timer: 0,
+            empty_counter: 0,
+            counter1: 0,
+            counter2: 0,
+            auto_fire_counter: 0,
+            shoot_triggered: false,
+        }
     }
 
     /// Consume a specified amount of bullets, returns true if there was enough ammo.
@@ -124,7 +183,7 @@ impl Weapon {
         }
 
         let level_idx = self.level as usize - 1;
-        let max_exp = constants.weapon.level_table[self.wtype as usize][level_idx];
+        let max_exp = constants.weapon.get_level_table(self.wtype.id())[level_idx];
         let max = self.level == WeaponLevel::Level3 && self.experience == max_exp;
 
         (self.experience, max_exp, max)
@@ -132,7 +191,7 @@ impl Weapon {
 
     pub fn add_xp(&mut self, exp: u16, player: &mut Player, state: &mut SharedGameState) {
         let curr_level_idx = self.level as usize - 1;
-        let lvl_table = state.constants.weapon.level_table[self.wtype as usize];
+        let lvl_table = state.constants.weapon.get_level_table(self.wtype.id());
 
         self.experience = self.experience.saturating_add(exp);
 
@@ -175,8 +234,20 @@ impl Weapon {
 
         self.empty_counter = self.empty_counter.saturating_sub(1);
         self.refire_timer = self.refire_timer.saturating_sub(1);
+        self.auto_fire_counter = self.auto_fire_counter.saturating_sub(1);
+
+        // auto-fire assist repeats the trigger while the button is held, spur is left alone since it needs charging
+        self.shoot_triggered = player.controller.trigger_shoot();
+        if state.get_assist().auto_fire && self.wtype != WeaponType::Spur && player.controller.shoot() {
+            if self.auto_fire_counter == 0 {
+                self.auto_fire_counter = Weapon::AUTO_FIRE_INTERVAL;
+                self.shoot_triggered = true;
+            }
+        } else {
+            self.auto_fire_counter = 0;
+        }
 
-        if player.controller.trigger_shoot() {
+        if self.shoot_triggered {
             if self.refire_timer > 0 {
                 return;
             }
@@ -199,6 +270,7 @@ impl Weapon {
             }
             WeaponType::Nemesis => self.tick_nemesis(player, player_id, bullet_manager, state),
             WeaponType::Spur => self.tick_spur(player, player_id, bullet_manager, state),
+            WeaponType::Custom(id) => self.tick_custom(id, player, player_id, bullet_manager, state),
         }
     }
 }
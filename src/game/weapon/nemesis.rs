@@ -15,7 +15,7 @@ impl Weapon {
     ) {
         const BULLETS: [u16; 3] = [34, 35, 36];
 
-        if !player.controller.trigger_shoot() || bullet_manager.count_bullets_multi(&BULLETS, player_id) > 1 {
+        if !self.shoot_triggered || bullet_manager.count_bullets_multi(&BULLETS, player_id) > 1 {
             return;
         }
 
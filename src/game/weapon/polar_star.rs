@@ -13,7 +13,7 @@ impl Weapon {
         bullet_manager: &mut BulletManager,
         state: &mut SharedGameState,
     ) {
-        if !player.controller.trigger_shoot() || bullet_manager.count_bullets_multi(&[4, 5, 6], player_id) > 1 {
+        if !self.shoot_triggered || bullet_manager.count_bullets_multi(&[4, 5, 6], player_id) > 1 {
             return;
         }
 
@@ -7,7 +7,7 @@ use crate::game::weapon::bullet::{Bullet, BulletManager};
 
 impl Weapon {
     pub(crate) fn tick_snake(&mut self, player: &Player, player_id: TargetPlayer, bullet_manager: &mut BulletManager, state: &mut SharedGameState) {
-        if !player.controller.trigger_shoot() || bullet_manager.count_bullets_multi(&[1, 2, 3], player_id) > 3 {
+        if !self.shoot_triggered || bullet_manager.count_bullets_multi(&[1, 2, 3], player_id) > 3 {
             return;
         }
 
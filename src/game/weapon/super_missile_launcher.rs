@@ -15,7 +15,7 @@ impl Weapon {
     ) {
         const BULLETS: [u16; 6] = [28, 29, 30, 31, 32, 33];
 
-        if !player.controller.trigger_shoot() {
+        if !self.shoot_triggered {
             return;
         }
 
@@ -206,3 +206,36 @@ impl<'a, 'b> TextBuilder<'a, 'b> {
         )
     }
 }
+
+/// Font with fixed width glyphs which doesn't draw anything, for testing text layout.
+#[cfg(test)]
+pub struct MonospaceFont(pub f32);
+
+#[cfg(test)]
+impl Font for MonospaceFont {
+    fn line_height(&self) -> f32 {
+        16.0
+    }
+
+    fn compute_width(&self, text: &mut dyn Iterator<Item = char>, _symbols: Option<&Symbols>) -> f32 {
+        text.count() as f32 * self.0
+    }
+
+    fn draw(
+        &self,
+        _text: &mut dyn Iterator<Item = char>,
+        _x: f32,
+        _y: f32,
+        _scale: f32,
+        _box_width: f32,
+        _shadow_color: (u8, u8, u8, u8),
+        _color: (u8, u8, u8, u8),
+        _flags: TextBuilderFlag,
+        _constants: &EngineConstants,
+        _texture_set: &mut TextureSet,
+        _symbols: Option<Symbols>,
+        _ctx: &mut Context,
+    ) -> GameResult {
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::Rect;
+use crate::engine_constants::EngineConstants;
+use crate::framework::context::Context;
+use crate::framework::gamepad::{self, Axis, AxisDirection, PlayerControllerInputType};
+use crate::framework::keyboard::{self, ScanCode};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum InputAction {
+    Left,
+    Up,
+    Right,
+    Down,
+    PrevWeapon,
+    NextWeapon,
+    Jump,
+    Shoot,
+    Skip,
+    Inventory,
+    Map,
+    Strafe,
+    MenuOk,
+    MenuBack,
+}
+
+/// A single way of triggering an action, keyboard and gamepad inputs can be freely mixed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum InputBinding {
+    Key(ScanCode),
+    /// Gamepad input, axes use the axis sensitivity of the player's controller.
+    Gamepad(PlayerControllerInputType),
+    /// Gamepad axis with its own deadzone, from 0.0 to 1.0.
+    Axis(Axis, AxisDirection, f64),
+    /// Active only while all of the inputs are held.
+    Chord(Vec<InputBinding>),
+}
+
+impl InputBinding {
+    pub fn is_active(&self, ctx: &Context, gamepad_index: Option<u32>) -> bool {
+        match self {
+            InputBinding::Key(key) => keyboard::is_key_pressed(ctx, *key),
+            InputBinding::Gamepad(input) => gamepad_index.is_some_and(|idx| gamepad::is_active(ctx, idx, input)),
+            InputBinding::Axis(axis, direction, deadzone) => gamepad_index.is_some_and(|idx| {
+                let value = gamepad::axis_value(ctx, idx, *axis);
+
+                match direction {
+                    AxisDirection::Either => value.abs() > *deadzone,
+                    _ => direction.compare(value, *deadzone),
+                }
+            }),
+            InputBinding::Chord(inputs) => {
+                !inputs.is_empty() && inputs.iter().all(|input| input.is_active(ctx, gamepad_index))
+            }
+        }
+    }
+
+    /// Returns the button sprite of single gamepad inputs.
+    pub fn get_rect(&self, offset: usize, constants: &EngineConstants) -> Option<Rect<u16>> {
+        match self {
+            InputBinding::Gamepad(input) => Some(input.get_rect(offset, constants)),
+            InputBinding::Axis(axis, _, _) => Some(axis.get_rect(offset, constants)),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> String {
+        match self {
+            InputBinding::Key(key) => format!("{:?}", key),
+            InputBinding::Gamepad(PlayerControllerInputType::ButtonInput(button))
+            | InputBinding::Gamepad(PlayerControllerInputType::Either(button, _, _)) => format!("{:?}", button),
+            InputBinding::Gamepad(PlayerControllerInputType::AxisInput(axis, direction))
+            | InputBinding::Axis(axis, direction, _) => format!("{:?} {:?}", axis, direction),
+            InputBinding::Chord(inputs) => {
+                inputs.iter().map(|input| input.display_name()).collect::<Vec<_>>().join("+")
+            }
+        }
+    }
+}
+
+/// Settings from before multiple bindings per action stored just a single key or gamepad input.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InputBindingsRepr {
+    List(Vec<InputBinding>),
+    Key(ScanCode),
+    Gamepad(PlayerControllerInputType),
+}
+
+/// All bindings of an action, the action is active if any of them is.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(from = "InputBindingsRepr", into = "Vec<InputBinding>")]
+pub struct InputBindings(pub Vec<InputBinding>);
+
+impl From<InputBindingsRepr> for InputBindings {
+    fn from(repr: InputBindingsRepr) -> Self {
+        match repr {
+            InputBindingsRepr::List(bindings) => InputBindings(bindings),
+            InputBindingsRepr::Key(key) => InputBindings::key(key),
+            InputBindingsRepr::Gamepad(input) => InputBindings::gamepad(input),
+        }
+    }
+}
+
+impl From<InputBindings> for Vec<InputBinding> {
+    fn from(bindings: InputBindings) -> Self {
+        bindings.0
+    }
+}
+
+impl InputBindings {
+    pub fn key(key: ScanCode) -> InputBindings {
+        InputBindings(vec![InputBinding::Key(key)])
+    }
+
+    pub fn gamepad(input: PlayerControllerInputType) -> InputBindings {
+        InputBindings(vec![InputBinding::Gamepad(input)])
+    }
+
+    pub fn is_active(&self, ctx: &Context, gamepad_index: Option<u32>) -> bool {
+        self.0.iter().any(|binding| binding.is_active(ctx, gamepad_index))
+    }
+
+    pub fn contains(&self, binding: &InputBinding) -> bool {
+        self.0.contains(binding)
+    }
+
+    /// Adds a binding unless it's already present, returns whether it was added.
+    pub fn add(&mut self, binding: InputBinding) -> bool {
+        if self.contains(&binding) {
+            return false;
+        }
+
+        self.0.push(binding);
+        true
+    }
+
+    pub fn remove(&mut self, binding: &InputBinding) {
+        self.0.retain(|b| b != binding);
+    }
+
+    /// Returns the name of the first binding, used for on-screen prompts.
+    pub fn display_name(&self) -> String {
+        self.0.first().map_or_else(|| "-".to_owned(), |binding| binding.display_name())
+    }
+
+    /// Returns the sprite of the first binding that's a single gamepad input, used for on-screen prompts.
+    pub fn first_gamepad_rect(&self, offset: usize, constants: &EngineConstants) -> Option<Rect<u16>> {
+        self.0.iter().find_map(|binding| binding.get_rect(offset, constants))
+    }
+}
+
+#[test]
+fn test_legacy_bindings() {
+    use crate::framework::gamepad::Button;
+
+    let key: InputBindings = serde_json::from_str("\"Q\"").unwrap();
+    assert_eq!(key, InputBindings::key(ScanCode::Q));
+
+    let button: InputBindings = serde_json::from_str("{\"ButtonInput\":\"West\"}").unwrap();
+    assert_eq!(button, InputBindings::gamepad(PlayerControllerInputType::ButtonInput(Button::West)));
+
+    let chord = InputBindings(vec![
+        InputBinding::Key(ScanCode::E),
+        InputBinding::Chord(vec![InputBinding::Key(ScanCode::LShift), InputBinding::Key(ScanCode::Q)]),
+    ]);
+    let json = serde_json::to_string(&chord).unwrap();
+    assert_eq!(serde_json::from_str::<InputBindings>(&json).unwrap(), chord);
+    assert_eq!(chord.0[1].display_name(), "LShift+Q");
+}
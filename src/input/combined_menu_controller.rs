@@ -1,15 +1,31 @@
+use crate::common::Rect;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
+use crate::framework::mouse::{self, MouseButton};
 use crate::game::shared_game_state::SharedGameState;
 use crate::input::player_controller::PlayerController;
 
+/// Mouse input used for menu navigation, in canvas coordinates.
+#[derive(Clone, Copy, Default)]
+struct MenuMouseState {
+    position: Option<(f32, f32)>,
+    moved: bool,
+    left: bool,
+    right: bool,
+    trigger_left: bool,
+    trigger_right: bool,
+    wheel_position: Option<i32>,
+    wheel_delta: i32,
+}
+
 pub struct CombinedMenuController {
     controllers: Vec<Box<dyn PlayerController>>,
+    mouse: MenuMouseState,
 }
 
 impl CombinedMenuController {
     pub fn new() -> CombinedMenuController {
-        CombinedMenuController { controllers: Vec::new() }
+        CombinedMenuController { controllers: Vec::new(), mouse: MenuMouseState::default() }
     }
 
     pub fn update(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
@@ -17,6 +33,30 @@ impl CombinedMenuController {
             cont.update(state, ctx)?;
         }
 
+        if !state.settings.mouse.menu_navigation {
+            self.mouse = MenuMouseState::default();
+            return Ok(());
+        }
+
+        let position = state.screen_to_canvas(ctx, mouse::position(ctx));
+        let (left, right) =
+            (mouse::is_button_pressed(ctx, MouseButton::Left), mouse::is_button_pressed(ctx, MouseButton::Right));
+        let wheel_position = mouse::wheel_position(ctx);
+
+        self.mouse = MenuMouseState {
+            position: Some(position),
+            moved: self.mouse.position.map_or(false, |last_position| last_position != position),
+            left,
+            right,
+            trigger_left: left && !self.mouse.left,
+            trigger_right: right && !self.mouse.right,
+            wheel_position: Some(wheel_position),
+            wheel_delta: self
+                .mouse
+                .wheel_position
+                .map_or(0, |last_position| wheel_position.wrapping_sub(last_position)),
+        };
+
         Ok(())
     }
 
@@ -31,6 +71,10 @@ impl CombinedMenuController {
     }
 
     pub fn trigger_up(&self) -> bool {
+        if self.mouse.wheel_delta > 0 {
+            return true;
+        }
+
         for cont in &self.controllers {
             if cont.trigger_up() {
                 return true;
@@ -41,6 +85,10 @@ impl CombinedMenuController {
     }
 
     pub fn trigger_down(&self) -> bool {
+        if self.mouse.wheel_delta < 0 {
+            return true;
+        }
+
         for cont in &self.controllers {
             if cont.trigger_down() {
                 return true;
@@ -81,6 +129,10 @@ impl CombinedMenuController {
     }
 
     pub fn trigger_back(&self) -> bool {
+        if self.mouse.trigger_right {
+            return true;
+        }
+
         for cont in &self.controllers {
             if cont.trigger_menu_back() {
                 return true;
@@ -109,4 +161,21 @@ impl CombinedMenuController {
 
         false
     }
+
+    /// Returns true if the mouse cursor was moved into given bounds, with mouse navigation enabled.
+    pub fn mouse_moved_in(&self, bounds: Rect) -> bool {
+        self.mouse.moved && self.mouse.position.map_or(false, |position| Self::contains(bounds, position))
+    }
+
+    /// Returns true if the left mouse button was clicked within given bounds, with mouse navigation enabled.
+    pub fn mouse_clicked_in(&self, bounds: Rect) -> bool {
+        self.mouse.trigger_left && self.mouse.position.map_or(false, |position| Self::contains(bounds, position))
+    }
+
+    fn contains(bounds: Rect, (x, y): (f32, f32)) -> bool {
+        (x as isize) > bounds.left
+            && (x as isize) < bounds.right
+            && (y as isize) > bounds.top
+            && (y as isize) < bounds.bottom
+    }
 }
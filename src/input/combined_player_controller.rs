@@ -1,6 +1,7 @@
 use crate::{
     framework::{context::Context, error::GameResult},
 };
+use crate::common::Direction;
 use crate::game::shared_game_state::SharedGameState;
 
 use super::player_controller::PlayerController;
@@ -167,6 +168,10 @@ impl PlayerController for CombinedPlayerController {
         self.controllers.iter().fold(0.0, |acc, cont| acc + cont.move_analog_y()) / self.controllers.len() as f64
     }
 
+    fn aim_direction(&self) -> Option<Direction> {
+        self.controllers.iter().find_map(|cont| cont.aim_direction())
+    }
+
     fn set_rumble(&mut self, low_freq: u16, hi_freq: u16, ticks: u32) {
         for cont in &mut self.controllers {
             cont.set_rumble(low_freq, hi_freq, ticks);
@@ -71,25 +71,25 @@ impl PlayerController for GamepadController {
             TargetPlayer::Player2 => &state.settings.player2_controller_button_map,
         };
 
-        self.state.set_up(gamepad::is_active(ctx, self.gamepad_id, &button_map.up));
-        self.state.set_down(gamepad::is_active(ctx, self.gamepad_id, &button_map.down));
-        self.state.set_left(gamepad::is_active(ctx, self.gamepad_id, &button_map.left));
-        self.state.set_right(gamepad::is_active(ctx, self.gamepad_id, &button_map.right));
-        self.state.set_map(gamepad::is_active(ctx, self.gamepad_id, &button_map.map));
-        self.state.set_inventory(gamepad::is_active(ctx, self.gamepad_id, &button_map.inventory));
-        self.state.set_jump(gamepad::is_active(ctx, self.gamepad_id, &button_map.jump));
-        self.state.set_shoot(gamepad::is_active(ctx, self.gamepad_id, &button_map.shoot));
-        self.state.set_next_weapon(gamepad::is_active(ctx, self.gamepad_id, &button_map.next_weapon));
-        self.state.set_prev_weapon(gamepad::is_active(ctx, self.gamepad_id, &button_map.prev_weapon));
+        self.state.set_up(button_map.up.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_down(button_map.down.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_left(button_map.left.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_right(button_map.right.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_map(button_map.map.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_inventory(button_map.inventory.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_jump(button_map.jump.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_shoot(button_map.shoot.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_next_weapon(button_map.next_weapon.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_prev_weapon(button_map.prev_weapon.is_active(ctx, Some(self.gamepad_id)));
         self.state.set_escape(gamepad::is_active(
             ctx,
             self.gamepad_id,
             &PlayerControllerInputType::ButtonInput(Button::Start),
         ));
-        self.state.set_skip(gamepad::is_active(ctx, self.gamepad_id, &button_map.skip));
-        self.state.set_strafe(gamepad::is_active(ctx, self.gamepad_id, &button_map.strafe));
-        self.state.set_menu_ok(gamepad::is_active(ctx, self.gamepad_id, &button_map.menu_ok));
-        self.state.set_menu_back(gamepad::is_active(ctx, self.gamepad_id, &button_map.menu_back));
+        self.state.set_skip(button_map.skip.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_strafe(button_map.strafe.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_menu_ok(button_map.menu_ok.is_active(ctx, Some(self.gamepad_id)));
+        self.state.set_menu_back(button_map.menu_back.is_active(ctx, Some(self.gamepad_id)));
 
         if let Some(rumble_data) = &self.rumble_state {
             gamepad::set_rumble(
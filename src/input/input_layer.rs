@@ -0,0 +1,490 @@
+use crate::common::Direction;
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::game::player::TargetPlayer;
+use crate::game::settings::ControllerType;
+use crate::game::shared_game_state::SharedGameState;
+use crate::input::bindings::{InputAction, InputBinding};
+use crate::input::keyboard_player_controller::KeyState;
+use crate::input::player_controller::PlayerController;
+
+/// Inputs that can be recorded in a macro, everything except the menu and pause buttons.
+pub const MACRO_MASK: u16 = 0x33ff;
+/// Recording stops after this many ticks without any input.
+pub const MACRO_IDLE_TICKS: u16 = 50;
+pub const MAX_MACRO_TICKS: usize = 300;
+
+#[inline(always)]
+fn default_turbo_rate() -> u8 {
+    4
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct InputMacro {
+    pub trigger: InputBinding,
+    /// Recorded inputs, a `KeyState` for every tick.
+    pub frames: Vec<u16>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct InputLayerSettings {
+    /// Actions that get repeatedly pressed and released while held.
+    #[serde(default)]
+    pub turbo: Vec<InputAction>,
+    /// Number of ticks a turbo action stays pressed, and then released.
+    #[serde(default = "default_turbo_rate")]
+    pub turbo_rate: u8,
+    /// Shoot and strafe get toggled by pressing them instead of being held.
+    #[serde(default)]
+    pub toggle_shoot: bool,
+    #[serde(default)]
+    pub toggle_strafe: bool,
+    #[serde(default)]
+    pub macros: Vec<InputMacro>,
+}
+
+impl Default for InputLayerSettings {
+    fn default() -> Self {
+        InputLayerSettings {
+            turbo: Vec::new(),
+            turbo_rate: default_turbo_rate(),
+            toggle_shoot: false,
+            toggle_strafe: false,
+            macros: Vec::new(),
+        }
+    }
+}
+
+/// Returns the `KeyState` bit of an action.
+pub fn action_bit(action: InputAction) -> u16 {
+    let bit = match action {
+        InputAction::Left => 0,
+        InputAction::Right => 1,
+        InputAction::Up => 2,
+        InputAction::Down => 3,
+        InputAction::Map => 4,
+        InputAction::Inventory => 5,
+        InputAction::Jump => 6,
+        InputAction::Shoot => 7,
+        InputAction::NextWeapon => 8,
+        InputAction::PrevWeapon => 9,
+        InputAction::Skip => 12,
+        InputAction::Strafe => 13,
+        InputAction::MenuOk => 14,
+        InputAction::MenuBack => 15,
+    };
+
+    1 << bit
+}
+
+/// Returns which of the macro-recordable inputs are held on given controller.
+pub fn held_inputs(controller: &dyn PlayerController) -> u16 {
+    let mut state = KeyState(0);
+    state.set_left(controller.move_left());
+    state.set_right(controller.move_right());
+    state.set_up(controller.move_up());
+    state.set_down(controller.move_down());
+    state.set_map(controller.map());
+    state.set_inventory(controller.inventory());
+    state.set_jump(controller.jump());
+    state.set_shoot(controller.shoot());
+    state.set_next_weapon(controller.next_weapon());
+    state.set_prev_weapon(controller.prev_weapon());
+    state.set_skip(controller.skip());
+    state.set_strafe(controller.strafe());
+
+    state.0 & MACRO_MASK
+}
+
+pub struct MacroRecorder {
+    frames: Vec<u16>,
+    idle_ticks: u16,
+}
+
+impl MacroRecorder {
+    pub fn new() -> MacroRecorder {
+        MacroRecorder { frames: Vec::new(), idle_ticks: 0 }
+    }
+
+    /// Records inputs held during a tick, returns true once the macro is finished.
+    /// Recording starts with the first tick that has any input held.
+    pub fn record(&mut self, inputs: u16) -> bool {
+        if self.frames.is_empty() && inputs == 0 {
+            return false;
+        }
+
+        self.frames.push(inputs);
+        self.idle_ticks = if inputs == 0 { self.idle_ticks + 1 } else { 0 };
+
+        self.idle_ticks >= MACRO_IDLE_TICKS || self.frames.len() >= MAX_MACRO_TICKS
+    }
+
+    pub fn finish(mut self) -> Vec<u16> {
+        while self.frames.last() == Some(&0) {
+            self.frames.pop();
+        }
+
+        self.frames
+    }
+}
+
+/// Sits between the player and the raw input controllers, applying turbo, toggles and macros
+/// configured in the player's `InputLayerSettings`. Replays record the inputs coming out of it.
+#[derive(Clone)]
+pub struct InputLayerController {
+    inner: Box<dyn PlayerController>,
+    target: TargetPlayer,
+    state: KeyState,
+    old_state: KeyState,
+    trigger: KeyState,
+    /// Raw inputs from the previous tick, used to detect presses of toggled actions.
+    old_inner_state: u16,
+    toggled: u16,
+    held_ticks: [u16; 16],
+    /// Index of the macro being played and the current frame.
+    macro_playback: Option<(usize, usize)>,
+    macro_trigger_held: bool,
+}
+
+impl InputLayerController {
+    pub fn new(inner: Box<dyn PlayerController>, target: TargetPlayer) -> InputLayerController {
+        InputLayerController {
+            inner,
+            target,
+            state: KeyState(0),
+            old_state: KeyState(0),
+            trigger: KeyState(0),
+            old_inner_state: 0,
+            toggled: 0,
+            held_ticks: [0; 16],
+            macro_playback: None,
+            macro_trigger_held: false,
+        }
+    }
+
+    /// Applies toggles, turbo and macros to the raw inputs held during a tick, `triggered_macro` is the index of
+    /// the macro whose trigger is held. Returns the resulting inputs.
+    fn apply_layer(&mut self, layer: &InputLayerSettings, inner_state: u16, triggered_macro: Option<usize>) -> u16 {
+        let mut held = inner_state;
+
+        for (enabled, bit) in [
+            (layer.toggle_shoot, action_bit(InputAction::Shoot)),
+            (layer.toggle_strafe, action_bit(InputAction::Strafe)),
+        ] {
+            if !enabled {
+                self.toggled &= !bit;
+                continue;
+            }
+
+            if inner_state & bit != 0 && self.old_inner_state & bit == 0 {
+                self.toggled ^= bit;
+            }
+
+            held = (held & !bit) | (self.toggled & bit);
+        }
+
+        self.old_inner_state = inner_state;
+
+        for (i, ticks) in self.held_ticks.iter_mut().enumerate() {
+            *ticks = if held & (1 << i) != 0 { ticks.saturating_add(1) } else { 0 };
+        }
+
+        let turbo_rate = layer.turbo_rate.max(1) as u16;
+        for &action in layer.turbo.iter() {
+            let bit = action_bit(action);
+            let ticks = self.held_ticks[bit.trailing_zeros() as usize];
+
+            if ticks > 0 && ((ticks - 1) / turbo_rate) % 2 == 1 {
+                held &= !bit;
+            }
+        }
+
+        if let (None, Some(idx), false) = (self.macro_playback, triggered_macro, self.macro_trigger_held) {
+            self.macro_playback = Some((idx, 0));
+        }
+        self.macro_trigger_held = triggered_macro.is_some();
+
+        if let Some((idx, frame)) = self.macro_playback {
+            match layer.macros.get(idx).and_then(|m| m.frames.get(frame)) {
+                Some(inputs) => {
+                    held |= inputs & MACRO_MASK;
+                    self.macro_playback = Some((idx, frame + 1));
+                }
+                None => self.macro_playback = None,
+            }
+        }
+
+        held
+    }
+}
+
+impl PlayerController for InputLayerController {
+    fn update(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        self.inner.update(state, ctx)?;
+
+        let (layer, controller_type) = match self.target {
+            TargetPlayer::Player1 => (&state.settings.player1_input_layer, state.settings.player1_controller_type),
+            TargetPlayer::Player2 => (&state.settings.player2_input_layer, state.settings.player2_controller_type),
+        };
+
+        let gamepad_index = match controller_type {
+            ControllerType::Keyboard => None,
+            ControllerType::Gamepad(index) => Some(index),
+        };
+
+        let inner_state = held_inputs(self.inner.as_ref());
+        let triggered_macro = layer.macros.iter().position(|m| m.trigger.is_active(ctx, gamepad_index));
+        let held = self.apply_layer(layer, inner_state, triggered_macro);
+
+        self.state = KeyState(held);
+
+        Ok(())
+    }
+
+    fn update_trigger(&mut self) {
+        self.inner.update_trigger();
+
+        let mut trigger = self.state.0 ^ self.old_state.0;
+        trigger &= self.state.0;
+        self.old_state = self.state;
+        self.trigger = KeyState(trigger);
+    }
+
+    fn move_up(&self) -> bool {
+        self.state.up()
+    }
+
+    fn move_left(&self) -> bool {
+        self.state.left()
+    }
+
+    fn move_down(&self) -> bool {
+        self.state.down()
+    }
+
+    fn move_right(&self) -> bool {
+        self.state.right()
+    }
+
+    fn prev_weapon(&self) -> bool {
+        self.state.prev_weapon()
+    }
+
+    fn next_weapon(&self) -> bool {
+        self.state.next_weapon()
+    }
+
+    fn map(&self) -> bool {
+        self.state.map()
+    }
+
+    fn inventory(&self) -> bool {
+        self.state.inventory()
+    }
+
+    fn jump(&self) -> bool {
+        self.state.jump()
+    }
+
+    fn shoot(&self) -> bool {
+        self.state.shoot()
+    }
+
+    fn skip(&self) -> bool {
+        self.state.skip()
+    }
+
+    fn strafe(&self) -> bool {
+        self.state.strafe()
+    }
+
+    fn trigger_up(&self) -> bool {
+        self.trigger.up()
+    }
+
+    fn trigger_left(&self) -> bool {
+        self.trigger.left()
+    }
+
+    fn trigger_down(&self) -> bool {
+        self.trigger.down()
+    }
+
+    fn trigger_right(&self) -> bool {
+        self.trigger.right()
+    }
+
+    fn trigger_prev_weapon(&self) -> bool {
+        self.trigger.prev_weapon()
+    }
+
+    fn trigger_next_weapon(&self) -> bool {
+        self.trigger.next_weapon()
+    }
+
+    fn trigger_map(&self) -> bool {
+        self.trigger.map()
+    }
+
+    fn trigger_inventory(&self) -> bool {
+        self.trigger.inventory()
+    }
+
+    fn trigger_jump(&self) -> bool {
+        self.trigger.jump()
+    }
+
+    fn trigger_shoot(&self) -> bool {
+        self.trigger.shoot()
+    }
+
+    fn trigger_skip(&self) -> bool {
+        self.trigger.skip()
+    }
+
+    fn trigger_strafe(&self) -> bool {
+        self.trigger.strafe()
+    }
+
+    fn trigger_menu_ok(&self) -> bool {
+        self.inner.trigger_menu_ok()
+    }
+
+    fn trigger_menu_back(&self) -> bool {
+        self.inner.trigger_menu_back()
+    }
+
+    fn trigger_menu_pause(&self) -> bool {
+        self.inner.trigger_menu_pause()
+    }
+
+    fn look_up(&self) -> bool {
+        self.inner.look_up()
+    }
+
+    fn look_left(&self) -> bool {
+        self.inner.look_left()
+    }
+
+    fn look_down(&self) -> bool {
+        self.inner.look_down()
+    }
+
+    fn look_right(&self) -> bool {
+        self.inner.look_right()
+    }
+
+    fn move_analog_x(&self) -> f64 {
+        // keep analog input unless the layer changed the direction
+        if self.state.left() == self.inner.move_left() && self.state.right() == self.inner.move_right() {
+            self.inner.move_analog_x()
+        } else if self.state.left() == self.state.right() {
+            0.0
+        } else if self.state.left() {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    fn move_analog_y(&self) -> f64 {
+        if self.state.up() == self.inner.move_up() && self.state.down() == self.inner.move_down() {
+            self.inner.move_analog_y()
+        } else if self.state.up() == self.state.down() {
+            0.0
+        } else if self.state.up() {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    fn aim_direction(&self) -> Option<Direction> {
+        self.inner.aim_direction()
+    }
+
+    fn set_rumble(&mut self, low_freq: u16, hi_freq: u16, ticks: u32) {
+        self.inner.set_rumble(low_freq, hi_freq, ticks);
+    }
+}
+
+#[test]
+fn test_macro_recorder() {
+    let jump = action_bit(InputAction::Jump);
+    let mut recorder = MacroRecorder::new();
+
+    assert!(!recorder.record(0));
+    assert!(!recorder.record(jump));
+    assert!(!recorder.record(jump | action_bit(InputAction::Right)));
+
+    let mut finished = false;
+    for _ in 0..MACRO_IDLE_TICKS {
+        finished = recorder.record(0);
+    }
+
+    assert!(finished);
+    assert_eq!(recorder.finish(), vec![jump, jump | action_bit(InputAction::Right)]);
+    assert_eq!(action_bit(InputAction::Strafe) & MACRO_MASK, action_bit(InputAction::Strafe));
+    assert_eq!(action_bit(InputAction::MenuOk) & MACRO_MASK, 0);
+}
+
+#[cfg(test)]
+fn test_controller() -> InputLayerController {
+    use crate::input::dummy_player_controller::DummyPlayerController;
+
+    InputLayerController::new(Box::new(DummyPlayerController::new()), TargetPlayer::Player1)
+}
+
+#[test]
+fn test_turbo_duty_cycle() {
+    let shoot = action_bit(InputAction::Shoot);
+    let jump = action_bit(InputAction::Jump);
+    let layer = InputLayerSettings { turbo: vec![InputAction::Shoot], turbo_rate: 2, ..Default::default() };
+    let mut controller = test_controller();
+
+    let held: Vec<u16> = (0..8).map(|_| controller.apply_layer(&layer, shoot | jump, None)).collect();
+    let pressed = shoot | jump;
+    assert_eq!(held, vec![pressed, pressed, jump, jump, pressed, pressed, jump, jump]);
+
+    // releasing the button restarts the cycle
+    assert_eq!(controller.apply_layer(&layer, 0, None), 0);
+    assert_eq!(controller.apply_layer(&layer, shoot, None), shoot);
+}
+
+#[test]
+fn test_toggle_edge_detection() {
+    let shoot = action_bit(InputAction::Shoot);
+    let layer = InputLayerSettings { toggle_shoot: true, ..Default::default() };
+    let mut controller = test_controller();
+
+    // holding the button only toggles once, pressing it again turns it off
+    let raw = [shoot, shoot, 0, 0, shoot, 0, shoot];
+    let held: Vec<u16> = raw.iter().map(|&inputs| controller.apply_layer(&layer, inputs, None)).collect();
+    assert_eq!(held, vec![shoot, shoot, shoot, shoot, 0, 0, shoot]);
+
+    // turning the toggle off in settings releases the action
+    let layer = InputLayerSettings::default();
+    assert_eq!(controller.apply_layer(&layer, 0, None), 0);
+}
+
+#[test]
+fn test_macro_playback() {
+    use crate::framework::keyboard::ScanCode;
+
+    let jump = action_bit(InputAction::Jump);
+    let right = action_bit(InputAction::Right);
+    let menu_ok = action_bit(InputAction::MenuOk);
+    let layer = InputLayerSettings {
+        macros: vec![InputMacro { trigger: InputBinding::Key(ScanCode::M), frames: vec![jump, 0, right | menu_ok] }],
+        ..Default::default()
+    };
+    let mut controller = test_controller();
+
+    // holding the trigger plays the macro once, inputs outside of the mask are dropped
+    let held: Vec<u16> = (0..5).map(|_| controller.apply_layer(&layer, 0, Some(0))).collect();
+    assert_eq!(held, vec![jump, 0, right, 0, 0]);
+
+    // macro inputs are merged with the held ones and a new press starts it again
+    assert_eq!(controller.apply_layer(&layer, 0, None), 0);
+    assert_eq!(controller.apply_layer(&layer, right, Some(0)), jump | right);
+}
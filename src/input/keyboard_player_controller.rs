@@ -51,22 +51,25 @@ impl PlayerController for KeyboardController {
             TargetPlayer::Player2 => &state.settings.player2_key_map,
         };
 
-        self.state.set_left(keyboard::is_key_pressed(ctx, keymap.left));
-        self.state.set_up(keyboard::is_key_pressed(ctx, keymap.up));
-        self.state.set_right(keyboard::is_key_pressed(ctx, keymap.right));
-        self.state.set_down(keyboard::is_key_pressed(ctx, keymap.down));
-        self.state.set_map(keyboard::is_key_pressed(ctx, keymap.map));
-        self.state.set_inventory(keyboard::is_key_pressed(ctx, keymap.inventory));
-        self.state.set_jump(keyboard::is_key_pressed(ctx, keymap.jump));
-        self.state.set_shoot(keyboard::is_key_pressed(ctx, keymap.shoot));
-        self.state.set_skip(keyboard::is_key_pressed(ctx, keymap.skip));
-        self.state.set_prev_weapon(keyboard::is_key_pressed(ctx, keymap.prev_weapon));
-        self.state.set_next_weapon(keyboard::is_key_pressed(ctx, keymap.next_weapon));
+        // the keyboard map is used with and without a gamepad, gamepad inputs belong to the gamepad map
+        let gamepad_index = None;
+
+        self.state.set_left(keymap.left.is_active(ctx, gamepad_index));
+        self.state.set_up(keymap.up.is_active(ctx, gamepad_index));
+        self.state.set_right(keymap.right.is_active(ctx, gamepad_index));
+        self.state.set_down(keymap.down.is_active(ctx, gamepad_index));
+        self.state.set_map(keymap.map.is_active(ctx, gamepad_index));
+        self.state.set_inventory(keymap.inventory.is_active(ctx, gamepad_index));
+        self.state.set_jump(keymap.jump.is_active(ctx, gamepad_index));
+        self.state.set_shoot(keymap.shoot.is_active(ctx, gamepad_index));
+        self.state.set_skip(keymap.skip.is_active(ctx, gamepad_index));
+        self.state.set_prev_weapon(keymap.prev_weapon.is_active(ctx, gamepad_index));
+        self.state.set_next_weapon(keymap.next_weapon.is_active(ctx, gamepad_index));
         self.state.set_enter(keyboard::is_key_pressed(ctx, ScanCode::Return));
         self.state.set_escape(keyboard::is_key_pressed(ctx, ScanCode::Escape));
-        self.state.set_strafe(keyboard::is_key_pressed(ctx, keymap.strafe));
-        self.state.set_menu_ok(keyboard::is_key_pressed(ctx, keymap.menu_ok));
-        self.state.set_menu_back(keyboard::is_key_pressed(ctx, keymap.menu_back));
+        self.state.set_strafe(keymap.strafe.is_active(ctx, gamepad_index));
+        self.state.set_menu_ok(keymap.menu_ok.is_active(ctx, gamepad_index));
+        self.state.set_menu_back(keymap.menu_back.is_active(ctx, gamepad_index));
 
         Ok(())
     }
@@ -1,8 +1,11 @@
+pub mod bindings;
 pub mod combined_menu_controller;
 pub mod combined_player_controller;
 pub mod dummy_player_controller;
 pub mod gamepad_player_controller;
+pub mod input_layer;
 pub mod keyboard_player_controller;
+pub mod mouse_player_controller;
 pub mod player_controller;
 pub mod replay_player_controller;
 pub mod touch_controls;
@@ -0,0 +1,328 @@
+use crate::common::Direction;
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::mouse::{self, MouseButton};
+use crate::game::shared_game_state::SharedGameState;
+use crate::input::keyboard_player_controller::KeyState;
+use crate::input::player_controller::PlayerController;
+
+/// Distance from the player in pixels, within which the cursor doesn't change the aim.
+const AIM_DEADZONE: f32 = 8.0;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct MouseSettings {
+    /// Player 1 faces the mouse cursor and aims up or down towards it.
+    #[serde(default)]
+    pub aim: bool,
+    #[serde(default)]
+    pub click_to_fire: bool,
+    #[serde(default)]
+    pub wheel_weapons: bool,
+    /// Menus can be navigated by hovering, clicking and scrolling.
+    #[serde(default)]
+    pub menu_navigation: bool,
+}
+
+/// Mouse controls for player 1, combined with the keyboard or gamepad used for movement.
+#[derive(Clone)]
+pub struct MousePlayerController {
+    state: KeyState,
+    old_state: KeyState,
+    trigger: KeyState,
+    aim: Option<Direction>,
+    wheel_position: Option<i32>,
+    /// Weapon switches queued by scrolling, positive values switch to next weapons.
+    pending_switches: i32,
+}
+
+impl MousePlayerController {
+    pub fn new() -> MousePlayerController {
+        MousePlayerController {
+            state: KeyState(0),
+            old_state: KeyState(0),
+            trigger: KeyState(0),
+            aim: None,
+            wheel_position: None,
+            pending_switches: 0,
+        }
+    }
+
+    /// Updates the state from the position of the cursor relative to the player (in canvas pixels), the left
+    /// button and the scroll wheel.
+    fn apply(&mut self, settings: &MouseSettings, (dx, dy): (f32, f32), left_button: bool, wheel_position: i32) {
+        let switched = self.state.next_weapon() || self.state.prev_weapon();
+
+        self.state = KeyState(0);
+        self.aim = None;
+
+        let firing = settings.click_to_fire && left_button;
+        self.state.set_shoot(firing);
+
+        if settings.aim {
+            if dx.abs() > AIM_DEADZONE {
+                self.aim = Some(if dx < 0.0 { Direction::Left } else { Direction::Right });
+            }
+
+            // aim vertically if the cursor is closer to being above or below the player than beside it
+            if dy.abs() > AIM_DEADZONE && dy.abs() > dx.abs() {
+                if dy < 0.0 {
+                    self.state.set_up(true);
+                } else if firing {
+                    // holding down alone is used to interact with objects
+                    self.state.set_down(true);
+                }
+            }
+        }
+
+        if settings.wheel_weapons {
+            if let Some(last_position) = self.wheel_position {
+                self.pending_switches -= wheel_position.wrapping_sub(last_position);
+            }
+        } else {
+            self.pending_switches = 0;
+        }
+        self.wheel_position = Some(wheel_position);
+
+        // weapons are switched on presses, so there has to be a tick without a press between the switches
+        if !switched && self.pending_switches > 0 {
+            self.state.set_next_weapon(true);
+            self.pending_switches -= 1;
+        } else if !switched && self.pending_switches < 0 {
+            self.state.set_prev_weapon(true);
+            self.pending_switches += 1;
+        }
+    }
+}
+
+impl PlayerController for MousePlayerController {
+    fn update(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let (cursor_x, cursor_y) = state.screen_to_canvas(ctx, mouse::position(ctx));
+        let (player_x, player_y) = state.player_canvas_pos[0];
+
+        self.apply(
+            &state.settings.mouse,
+            (cursor_x - player_x, cursor_y - player_y),
+            mouse::is_button_pressed(ctx, MouseButton::Left),
+            mouse::wheel_position(ctx),
+        );
+
+        Ok(())
+    }
+
+    fn update_trigger(&mut self) {
+        let mut trigger = self.state.0 ^ self.old_state.0;
+        trigger &= self.state.0;
+        self.old_state = self.state;
+        self.trigger = KeyState(trigger);
+    }
+
+    fn move_up(&self) -> bool {
+        self.state.up()
+    }
+
+    fn move_left(&self) -> bool {
+        false
+    }
+
+    fn move_down(&self) -> bool {
+        self.state.down()
+    }
+
+    fn move_right(&self) -> bool {
+        false
+    }
+
+    fn prev_weapon(&self) -> bool {
+        self.state.prev_weapon()
+    }
+
+    fn next_weapon(&self) -> bool {
+        self.state.next_weapon()
+    }
+
+    fn map(&self) -> bool {
+        false
+    }
+
+    fn inventory(&self) -> bool {
+        false
+    }
+
+    fn jump(&self) -> bool {
+        false
+    }
+
+    fn shoot(&self) -> bool {
+        self.state.shoot()
+    }
+
+    fn skip(&self) -> bool {
+        false
+    }
+
+    fn strafe(&self) -> bool {
+        false
+    }
+
+    fn trigger_up(&self) -> bool {
+        self.trigger.up()
+    }
+
+    fn trigger_left(&self) -> bool {
+        false
+    }
+
+    fn trigger_down(&self) -> bool {
+        self.trigger.down()
+    }
+
+    fn trigger_right(&self) -> bool {
+        false
+    }
+
+    fn trigger_prev_weapon(&self) -> bool {
+        self.trigger.prev_weapon()
+    }
+
+    fn trigger_next_weapon(&self) -> bool {
+        self.trigger.next_weapon()
+    }
+
+    fn trigger_map(&self) -> bool {
+        false
+    }
+
+    fn trigger_inventory(&self) -> bool {
+        false
+    }
+
+    fn trigger_jump(&self) -> bool {
+        false
+    }
+
+    fn trigger_shoot(&self) -> bool {
+        self.trigger.shoot()
+    }
+
+    fn trigger_skip(&self) -> bool {
+        false
+    }
+
+    fn trigger_strafe(&self) -> bool {
+        false
+    }
+
+    fn trigger_menu_ok(&self) -> bool {
+        false
+    }
+
+    fn trigger_menu_back(&self) -> bool {
+        false
+    }
+
+    fn trigger_menu_pause(&self) -> bool {
+        false
+    }
+
+    fn look_up(&self) -> bool {
+        false
+    }
+
+    fn look_left(&self) -> bool {
+        false
+    }
+
+    fn look_down(&self) -> bool {
+        false
+    }
+
+    fn look_right(&self) -> bool {
+        false
+    }
+
+    fn move_analog_x(&self) -> f64 {
+        0.0
+    }
+
+    fn move_analog_y(&self) -> f64 {
+        if self.state.up() {
+            -1.0
+        } else if self.state.down() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn aim_direction(&self) -> Option<Direction> {
+        self.aim
+    }
+
+    fn set_rumble(&mut self, _low_freq: u16, _hi_freq: u16, _ticks: u32) {}
+}
+
+#[test]
+fn test_mouse_aim() {
+    let settings = MouseSettings { aim: true, click_to_fire: true, ..Default::default() };
+    let mut controller = MousePlayerController::new();
+
+    // within the dead zone nothing changes
+    controller.apply(&settings, (AIM_DEADZONE, -AIM_DEADZONE), false, 0);
+    assert_eq!(controller.aim_direction(), None);
+    assert!(!controller.move_up() && !controller.move_down());
+
+    controller.apply(&settings, (-40.0, 10.0), false, 0);
+    assert_eq!(controller.aim_direction(), Some(Direction::Left));
+    assert!(!controller.move_down());
+
+    // mostly above or below the player aims vertically, facing still follows the cursor
+    controller.apply(&settings, (20.0, -40.0), false, 0);
+    assert_eq!(controller.aim_direction(), Some(Direction::Right));
+    assert!(controller.move_up());
+    assert_eq!(controller.move_analog_y(), -1.0);
+
+    // aiming down needs firing, holding down alone would interact with objects
+    controller.apply(&settings, (0.0, 40.0), false, 0);
+    assert!(!controller.move_down());
+    controller.apply(&settings, (0.0, 40.0), true, 0);
+    assert!(controller.move_down());
+    assert_eq!(controller.aim_direction(), None);
+
+    // nothing is aimed with aiming disabled
+    let settings = MouseSettings { click_to_fire: true, ..Default::default() };
+    controller.apply(&settings, (-40.0, -60.0), true, 0);
+    assert_eq!(controller.aim_direction(), None);
+    assert!(!controller.move_up());
+}
+
+#[test]
+fn test_mouse_buttons() {
+    let mut settings = MouseSettings { click_to_fire: true, wheel_weapons: true, ..Default::default() };
+    let mut controller = MousePlayerController::new();
+
+    controller.apply(&settings, (0.0, 0.0), true, 0);
+    controller.update_trigger();
+    assert!(controller.shoot() && controller.trigger_shoot());
+
+    controller.apply(&settings, (0.0, 0.0), true, 0);
+    controller.update_trigger();
+    assert!(controller.shoot() && !controller.trigger_shoot());
+
+    // each scroll step is a separate press of the weapon switch
+    controller.apply(&settings, (0.0, 0.0), false, -2);
+    assert!(!controller.shoot());
+    assert!(controller.next_weapon());
+    controller.apply(&settings, (0.0, 0.0), false, -2);
+    assert!(!controller.next_weapon());
+    controller.apply(&settings, (0.0, 0.0), false, -2);
+    assert!(controller.next_weapon());
+    controller.apply(&settings, (0.0, 0.0), false, -1);
+    controller.apply(&settings, (0.0, 0.0), false, -1);
+    assert!(controller.prev_weapon());
+
+    settings.click_to_fire = false;
+    settings.wheel_weapons = false;
+    controller.apply(&settings, (0.0, 0.0), true, 5);
+    controller.apply(&settings, (0.0, 0.0), true, 5);
+    assert!(!controller.shoot() && !controller.next_weapon() && !controller.prev_weapon());
+}
@@ -1,3 +1,4 @@
+use crate::common::Direction;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::game::shared_game_state::SharedGameState;
@@ -97,6 +98,12 @@ pub trait PlayerController: PlayerControllerClone {
     /// In case of non-analog controllers this should return -1.0, 0.0 or 1.0, depending on keys pressed.
     fn move_analog_y(&self) -> f64;
 
+    /// Optional, used by mouse aiming.
+    /// Returns the direction player should face regardless of movement, like while strafing.
+    fn aim_direction(&self) -> Option<Direction> {
+        None
+    }
+
     /// Activates the rumble motors to the specified intensities for a given amount of time (in millis).
     fn set_rumble(&mut self, low_freq: u16, hi_freq: u16, ticks: u32);
 }
@@ -1,4 +1,5 @@
 use crate::bitfield;
+use crate::common::Direction;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::game::shared_game_state::SharedGameState;
@@ -34,6 +35,8 @@ pub struct ReplayController {
     pub state: KeyState,
     pub old_state: KeyState,
     trigger: KeyState,
+    /// Direction the player was turned to with mouse aiming.
+    pub aim: Option<Direction>,
 }
 
 impl ReplayController {
@@ -43,6 +46,7 @@ impl ReplayController {
             state: KeyState(0),
             old_state: KeyState(0),
             trigger: KeyState(0),
+            aim: None,
         }
     }
 }
@@ -207,5 +211,9 @@ impl PlayerController for ReplayController {
         }
     }
 
+    fn aim_direction(&self) -> Option<Direction> {
+        self.aim
+    }
+
     fn set_rumble(&mut self, _low_freq: u16, _hi_freq: u16, _ticks: u32) {}
 }
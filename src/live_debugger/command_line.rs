@@ -1,8 +1,9 @@
-use num_traits::FromPrimitive;
-
+use crate::framework::context::Context;
 use crate::framework::error::{GameError::CommandLineError, GameResult};
+use crate::framework::filesystem;
 use crate::game::npc::NPC;
-use crate::game::scripting::tsc::text_script::{ScriptMode, TextScript, TextScriptEncoding};
+use crate::game::scripting::tsc::text_script::{ScriptMode, TextScript, TextScriptEncoding, TextScriptExecutionState};
+use crate::game::scripting::tsc::translation::TranslationCatalog;
 use crate::game::shared_game_state::SharedGameState;
 use crate::game::weapon::WeaponType;
 use crate::scene::game_scene::GameScene;
@@ -23,6 +24,10 @@ pub enum CommandLineCommand {
     SpawnNPC(u16),
     TeleportPlayer(f32, f32),
     TSC(String),
+    /// Writes text of all scripts to a translation catalog in the user directory, in PO format if set.
+    ExtractText(bool),
+    /// Reloads the translation catalog and recompiles the loaded scripts with it.
+    ReloadTranslation,
 }
 
 impl CommandLineCommand {
@@ -163,13 +168,28 @@ impl CommandLineCommand {
                 let script = components[1..].join(" ").replace("\\n", "\n");
                 return Some(CommandLineCommand::TSC(script));
             }
+            "extract_text" => {
+                return match components.get(1).copied() {
+                    None | Some("json") => Some(CommandLineCommand::ExtractText(false)),
+                    Some("po") => Some(CommandLineCommand::ExtractText(true)),
+                    _ => None,
+                };
+            }
+            "reload_translation" => {
+                return Some(CommandLineCommand::ReloadTranslation);
+            }
             _ => return None,
         }
 
         None
     }
 
-    pub fn execute(&mut self, game_scene: &mut GameScene, state: &mut SharedGameState) -> GameResult {
+    pub fn execute(
+        &mut self,
+        game_scene: &mut GameScene,
+        state: &mut SharedGameState,
+        ctx: &mut Context,
+    ) -> GameResult {
         match self.clone() {
             CommandLineCommand::AddItem(item_id) => {
                 game_scene.inventory_player1.add_item(item_id);
@@ -182,14 +202,14 @@ impl CommandLineCommand {
                 game_scene.inventory_player1.remove_item(item_id);
             }
             CommandLineCommand::AddWeapon(weapon_id, ammo_count) => {
-                let weapon_type: Option<WeaponType> = FromPrimitive::from_u16(weapon_id);
+                let weapon_type = u8::try_from(weapon_id).ok().and_then(|id| WeaponType::from_id(id, &state.constants));
                 match weapon_type {
                     Some(weapon_type) => game_scene.inventory_player1.add_weapon(weapon_type, ammo_count),
                     None => return Err(CommandLineError(format!("Invalid weapon id {}", weapon_id))),
                 }
             }
             CommandLineCommand::RemoveWeapon(weapon_id) => {
-                let weapon_type: Option<WeaponType> = FromPrimitive::from_u16(weapon_id);
+                let weapon_type = u8::try_from(weapon_id).ok().and_then(|id| WeaponType::from_id(id, &state.constants));
                 match weapon_type {
                     Some(weapon_type) => {
                         if !game_scene.inventory_player1.has_weapon(weapon_type) {
@@ -249,7 +269,12 @@ impl CommandLineCommand {
             }
             CommandLineCommand::TSC(script) => {
                 log::info!("Executing TSC script: {}", format!("#9999\n{}", script));
-                match TextScript::compile(format!("#9999\n{}", script).as_bytes(), true, TextScriptEncoding::UTF8) {
+                match TextScript::compile(
+                    format!("#9999\n{}", script).as_bytes(),
+                    true,
+                    TextScriptEncoding::UTF8,
+                    &state.constants.textscript.extensions,
+                ) {
                     Ok(text_script) => {
                         state.textscript_vm.set_debug_script(text_script);
                         state.textscript_vm.set_mode(ScriptMode::Debug);
@@ -260,6 +285,31 @@ impl CommandLineCommand {
                     }
                 };
             }
+            CommandLineCommand::ExtractText(po) => {
+                let catalog = TranslationCatalog::extract(ctx, &state.constants, &state.stages)?;
+                let filename = state.get_translation_filename();
+
+                if po {
+                    catalog.write_po(filesystem::user_create(ctx, format!("{}.po", filename))?)?;
+                } else {
+                    let file = filesystem::user_create(ctx, format!("{}.json", filename))?;
+                    serde_json::to_writer_pretty(file, &catalog)?;
+                }
+            }
+            CommandLineCommand::ReloadTranslation => {
+                // scripts can't be swapped under a running event, it would continue at the same offset of the new one
+                if state.textscript_vm.state != TextScriptExecutionState::Ended {
+                    return Err(CommandLineError("Can't reload the translation while an event is running".to_owned()));
+                }
+
+                let translation_filename = state.get_translation_filename();
+                state.constants.load_script_translation(ctx, &translation_filename)?;
+                state.load_scripts(ctx)?;
+
+                let scene_script =
+                    game_scene.stage.load_text_script(&state.constants.base_paths, &state.constants, ctx)?;
+                state.textscript_vm.set_scene_script(scene_script);
+            }
         }
 
         Ok(())
@@ -282,6 +332,8 @@ impl CommandLineCommand {
             CommandLineCommand::SpawnNPC(npc_id) => format!("/spawn_npc {}", npc_id),
             CommandLineCommand::TeleportPlayer(x, y) => format!("/teleport_player {} {}", x, y),
             CommandLineCommand::TSC(script) => format!("/tsc {}", script.replace("\n", "\\n")),
+            CommandLineCommand::ExtractText(po) => format!("/extract_text {}", if *po { "po" } else { "json" }),
+            CommandLineCommand::ReloadTranslation => "/reload_translation".to_string(),
         }
     }
 
@@ -305,6 +357,10 @@ impl CommandLineCommand {
             CommandLineCommand::SpawnNPC(npc_id) => format!("Spawned NPC ID {} in front of player.", npc_id),
             CommandLineCommand::TeleportPlayer(x, y) => format!("Teleported players to ({}, {}).", x, y),
             CommandLineCommand::TSC(_) => "Executed TSC script.".to_string(),
+            CommandLineCommand::ExtractText(po) => {
+                format!("Extracted script text to the user directory as {}.", if *po { "po" } else { "json" })
+            }
+            CommandLineCommand::ReloadTranslation => "Reloaded text script translation.".to_string(),
         }
     }
 }
@@ -3,6 +3,7 @@ use itertools::Itertools;
 
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
+use crate::game::capture::CaptureFormat;
 use crate::game::scripting::tsc::text_script::TextScriptExecutionState;
 use crate::game::shared_game_state::SharedGameState;
 use crate::scene::game_scene::GameScene;
@@ -26,6 +27,7 @@ pub struct LiveDebugger {
     flags_visible: bool,
     npc_inspector_visible: bool,
     hotkey_list_visible: bool,
+    capture_realtime: bool,
     command_line_parser: CommandLineParser,
     command_line_focused: bool,
     last_stage_id: usize,
@@ -46,6 +48,7 @@ impl LiveDebugger {
             flags_visible: false,
             npc_inspector_visible: false,
             hotkey_list_visible: false,
+            capture_realtime: true,
             command_line_parser: CommandLineParser::new(),
             command_line_focused: false,
             last_stage_id: usize::MAX,
@@ -108,7 +111,7 @@ impl LiveDebugger {
                     if ui.is_key_released(imgui::Key::Enter) || ui.button("Execute") {
                         log::info!("Executing command: {}", self.command_line_parser.buffer);
                         match self.command_line_parser.push(self.command_line_parser.buffer.clone()) {
-                            Some(mut command) => match command.execute(game_scene, state) {
+                            Some(mut command) => match command.execute(game_scene, state, ctx) {
                                 Ok(()) => {
                                     self.command_line_parser.last_feedback = command.feedback_string();
                                     self.command_line_parser.last_feedback_color = [0.0, 1.0, 0.0, 1.0];
@@ -144,7 +147,7 @@ impl LiveDebugger {
             .resizable(false)
             .collapsed(true, Condition::FirstUseEver)
             .position([5.0, 5.0], Condition::FirstUseEver)
-            .size([400.0, 265.0], Condition::FirstUseEver)
+            .size([400.0, 290.0], Condition::FirstUseEver)
             .build(ui, || {
                 ui.text(format!(
                     "Player position: ({:.1},{:.1}), velocity: ({:.1},{:.1})",
@@ -171,6 +174,21 @@ impl LiveDebugger {
                     game_scene.player1.booster_fuel
                 ));
 
+                ui.text(format!(
+                    "NPC slots allocated: {} ({:.0}% used)",
+                    game_scene.npc_list.allocated_slots(),
+                    game_scene.npc_list.current_capacity() as f32 * 100.0
+                        / game_scene.npc_list.max_capacity().max(1) as f32
+                ));
+
+                ui.text(format!(
+                    "Bullets: {}/{} Carets: {}/{}",
+                    game_scene.bullet_manager.bullets.len(),
+                    game_scene.bullet_manager.max_bullets,
+                    state.carets.len(),
+                    state.constants.capacity.caret
+                ));
+
                 ui.text(format!("Game speed ({:.1} TPS):", state.current_tps()));
                 let mut speed = state.settings.speed;
                 Slider::new("", 0.1, 3.0).build(ui, &mut speed);
@@ -243,6 +261,35 @@ impl LiveDebugger {
                 ui.checkbox("noclip", &mut state.settings.noclip);
                 ui.same_line();
                 ui.checkbox("more rust", &mut state.more_rust);
+
+                if state.frame_capture.is_capturing() {
+                    if ui.button("Stop Capture") {
+                        if let Err(err) = state.stop_capture(ctx) {
+                            self.error = Some(ImString::new(err.to_string()));
+                        }
+                    }
+                    ui.same_line();
+                    ui.text(format!("Captured {} frames", state.frame_capture.frame_count()));
+                } else {
+                    let mut format = None;
+
+                    if ui.button("Capture PNG") {
+                        format = Some(CaptureFormat::PngSequence);
+                    }
+                    ui.same_line();
+                    if ui.button("Capture Y4M") {
+                        format = Some(CaptureFormat::Y4M);
+                    }
+                    ui.same_line();
+                    ui.checkbox("real-time", &mut self.capture_realtime);
+
+                    if let Some(format) = format {
+                        if let Err(err) = state.start_capture(ctx, format, self.capture_realtime) {
+                            log::error!("Error starting capture: {:?}", err);
+                            self.error = Some(ImString::new(err.to_string()));
+                        }
+                    }
+                }
             });
 
         if self.map_selector_visible {
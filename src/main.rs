@@ -4,7 +4,7 @@ use std::process::exit;
 
 fn main() {
     let args = std::env::args();
-    let mut options = doukutsu_rs::game::LaunchOptions { server_mode: false, editor: false };
+    let mut options = doukutsu_rs::game::LaunchOptions { server_mode: false, editor: false, headless_render: false };
 
     for arg in args {
         if arg == "--server-mode" {
@@ -14,6 +14,10 @@ fn main() {
         if arg == "--editor" {
             options.editor = true;
         }
+
+        if arg == "--headless-render" {
+            options.headless_render = true;
+        }
     }
 
     if options.server_mode && options.editor {
@@ -21,6 +25,11 @@ fn main() {
         exit(1);
     }
 
+    if options.headless_render && (options.server_mode || options.editor) {
+        eprintln!("Cannot run headless rendering together with server mode or editor mode.");
+        exit(1);
+    }
+
     let result = doukutsu_rs::game::init(options);
 
     #[cfg(target_os = "windows")]
@@ -2,12 +2,16 @@ use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::gamepad::{self, Axis, AxisDirection, Button, PlayerControllerInputType};
 use crate::framework::keyboard::ScanCode;
+use crate::game::player::TargetPlayer;
 use crate::game::settings::{
-    p1_default_keymap, p2_default_keymap, player_default_controller_button_map, ControllerType,
-    PlayerControllerButtonMap, PlayerKeyMap,
+    p1_default_keymap, p2_default_keymap, player_default_controller_button_map, ControllerType, PlayerInputMap,
 };
 use crate::game::shared_game_state::SharedGameState;
+use crate::input::bindings::{InputAction, InputBinding, InputBindings};
 use crate::input::combined_menu_controller::CombinedMenuController;
+use crate::input::input_layer::{held_inputs, InputLayerSettings, InputMacro, MacroRecorder};
+use crate::input::player_controller::PlayerController;
+use crate::sound::mixer::MixerBus;
 
 use super::{ControlMenuData, Menu, MenuEntry, MenuSelectionResult};
 
@@ -26,14 +30,41 @@ const FORBIDDEN_SCANCODES: [ScanCode; 12] = [
     ScanCode::F12,
 ];
 
+const REBIND_ACTIONS: [InputAction; 14] = [
+    InputAction::MenuOk,
+    InputAction::MenuBack,
+    InputAction::Up,
+    InputAction::Down,
+    InputAction::Left,
+    InputAction::Right,
+    InputAction::Jump,
+    InputAction::Shoot,
+    InputAction::PrevWeapon,
+    InputAction::NextWeapon,
+    InputAction::Inventory,
+    InputAction::Map,
+    InputAction::Skip,
+    InputAction::Strafe,
+];
+
+const DEADZONE_STEP: f64 = 0.05;
+
+/// Ticks a turbo action stays pressed and released.
+const TURBO_RATES: [u8; 6] = [2, 3, 4, 5, 6, 8];
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 #[repr(u8)]
 enum CurrentMenu {
     MainMenu,
     SelectControllerMenu,
     RebindMenu,
+    BindingsMenu,
     ConfirmRebindMenu,
     ConfirmResetMenu,
+    InputLayerMenu,
+    MacroMenu,
+    RecordMacroMenu,
+    MouseMenu,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -41,6 +72,8 @@ enum MainMenuEntry {
     SelectedPlayer,
     Controller,
     Rebind,
+    InputLayer,
+    Mouse,
     Rumble,
     DisplayTouchControls,
     Back,
@@ -71,17 +104,66 @@ impl Default for SelectControllerMenuEntry {
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum RebindMenuEntry {
-    Control(ControlEntry),
+    Control(InputAction),
+    Import,
+    Export,
     Reset,
     Back,
 }
 
 impl Default for RebindMenuEntry {
     fn default() -> Self {
-        RebindMenuEntry::Control(ControlEntry::MenuOk)
+        RebindMenuEntry::Control(InputAction::MenuOk)
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+enum BindingsMenuEntry {
+    Title,
+    Hint,
+    Binding(usize),
+    #[default]
+    Add,
+    Back,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+enum InputLayerMenuEntry {
+    #[default]
+    TurboRate,
+    Turbo(InputAction),
+    ToggleShoot,
+    ToggleStrafe,
+    Macros,
+    Back,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+enum MacroMenuEntry {
+    Hint,
+    Macro(usize),
+    #[default]
+    Record,
+    Back,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+enum MouseMenuEntry {
+    #[default]
+    Aim,
+    ClickToFire,
+    WheelWeapons,
+    MenuNavigation,
+    Back,
+}
+
+/// What the inputs captured in `ConfirmRebindMenu` are used for.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum CaptureTarget {
+    Binding,
+    MacroTrigger,
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum ConfirmResetMenuEntry {
     Title,
@@ -108,64 +190,104 @@ impl Player {
             Player::Player2 => state.settings.player2_controller_type,
         }
     }
-}
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-enum ControlEntry {
-    Left,
-    Up,
-    Right,
-    Down,
-    PrevWeapon,
-    NextWeapon,
-    Jump,
-    Shoot,
-    Skip,
-    Inventory,
-    Map,
-    Strafe,
-    MenuOk,
-    MenuBack,
-}
+    fn target(self) -> TargetPlayer {
+        match self {
+            Player::Player1 => TargetPlayer::Player1,
+            Player::Player2 => TargetPlayer::Player2,
+        }
+    }
+
+    fn input_layer(self, state: &SharedGameState) -> &InputLayerSettings {
+        match self {
+            Player::Player1 => &state.settings.player1_input_layer,
+            Player::Player2 => &state.settings.player2_input_layer,
+        }
+    }
+
+    fn input_layer_mut(self, state: &mut SharedGameState) -> &mut InputLayerSettings {
+        match self {
+            Player::Player1 => &mut state.settings.player1_input_layer,
+            Player::Player2 => &mut state.settings.player2_input_layer,
+        }
+    }
 
-impl ControlEntry {
-    fn to_string(&self, state: &SharedGameState) -> String {
+    /// Creates a controller without the input layer, macros are recorded from raw inputs.
+    fn create_controller(self, state: &SharedGameState) -> Box<dyn PlayerController> {
         match self {
-            ControlEntry::Left => state.loc.t("menus.controls_menu.rebind_menu.left"),
-            ControlEntry::Up => state.loc.t("menus.controls_menu.rebind_menu.up"),
-            ControlEntry::Right => state.loc.t("menus.controls_menu.rebind_menu.right"),
-            ControlEntry::Down => state.loc.t("menus.controls_menu.rebind_menu.down"),
-            ControlEntry::PrevWeapon => state.loc.t("menus.controls_menu.rebind_menu.prev_weapon"),
-            ControlEntry::NextWeapon => state.loc.t("menus.controls_menu.rebind_menu.next_weapon"),
-            ControlEntry::Jump => state.loc.t("menus.controls_menu.rebind_menu.jump"),
-            ControlEntry::Shoot => state.loc.t("menus.controls_menu.rebind_menu.shoot"),
-            ControlEntry::Skip => state.loc.t("menus.controls_menu.rebind_menu.skip"),
-            ControlEntry::Inventory => state.loc.t("menus.controls_menu.rebind_menu.inventory"),
-            ControlEntry::Map => state.loc.t("menus.controls_menu.rebind_menu.map"),
-            ControlEntry::Strafe => state.loc.t("menus.controls_menu.rebind_menu.strafe"),
-            ControlEntry::MenuOk => state.loc.t("menus.controls_menu.rebind_menu.menu_ok"),
-            ControlEntry::MenuBack => state.loc.t("menus.controls_menu.rebind_menu.menu_back"),
+            Player::Player1 => state.settings.create_player1_controller(),
+            Player::Player2 => state.settings.create_player2_controller(),
         }
-        .to_owned()
     }
 }
 
+struct MacroRecording {
+    trigger: InputBinding,
+    recorder: MacroRecorder,
+    controller: Box<dyn PlayerController>,
+}
+
+fn action_name(action: InputAction, state: &SharedGameState) -> String {
+    match action {
+        InputAction::Left => state.loc.t("menus.controls_menu.rebind_menu.left"),
+        InputAction::Up => state.loc.t("menus.controls_menu.rebind_menu.up"),
+        InputAction::Right => state.loc.t("menus.controls_menu.rebind_menu.right"),
+        InputAction::Down => state.loc.t("menus.controls_menu.rebind_menu.down"),
+        InputAction::PrevWeapon => state.loc.t("menus.controls_menu.rebind_menu.prev_weapon"),
+        InputAction::NextWeapon => state.loc.t("menus.controls_menu.rebind_menu.next_weapon"),
+        InputAction::Jump => state.loc.t("menus.controls_menu.rebind_menu.jump"),
+        InputAction::Shoot => state.loc.t("menus.controls_menu.rebind_menu.shoot"),
+        InputAction::Skip => state.loc.t("menus.controls_menu.rebind_menu.skip"),
+        InputAction::Inventory => state.loc.t("menus.controls_menu.rebind_menu.inventory"),
+        InputAction::Map => state.loc.t("menus.controls_menu.rebind_menu.map"),
+        InputAction::Strafe => state.loc.t("menus.controls_menu.rebind_menu.strafe"),
+        InputAction::MenuOk => state.loc.t("menus.controls_menu.rebind_menu.menu_ok"),
+        InputAction::MenuBack => state.loc.t("menus.controls_menu.rebind_menu.menu_back"),
+    }
+    .to_owned()
+}
+
+/// Actions that can't share a binding, rebinding one of them to the other's input swaps them instead.
+fn paired_action(action: InputAction) -> Option<InputAction> {
+    match action {
+        InputAction::Jump => Some(InputAction::Shoot),
+        InputAction::Shoot => Some(InputAction::Jump),
+        InputAction::MenuOk => Some(InputAction::MenuBack),
+        InputAction::MenuBack => Some(InputAction::MenuOk),
+        _ => None,
+    }
+}
+
+fn bindings_summary(bindings: &InputBindings) -> String {
+    let mut summary = bindings.0.iter().take(2).map(|binding| binding.display_name()).collect::<Vec<_>>().join(", ");
+
+    if bindings.0.len() > 2 {
+        summary.push_str(&format!(" +{}", bindings.0.len() - 2));
+    }
+
+    summary
+}
+
 pub struct ControlsMenu {
     current: CurrentMenu,
     main: Menu<MainMenuEntry>,
     select_controller: Menu<SelectControllerMenuEntry>,
     rebind: Menu<RebindMenuEntry>,
+    bindings: Menu<BindingsMenuEntry>,
     confirm_rebind: Menu<usize>,
     confirm_reset: Menu<ConfirmResetMenuEntry>,
+    input_layer: Menu<InputLayerMenuEntry>,
+    macros: Menu<MacroMenuEntry>,
+    record_macro: Menu<usize>,
+    mouse: Menu<MouseMenuEntry>,
 
     selected_player: Player,
     selected_controller: ControllerType,
-    selected_control: Option<ControlEntry>,
-
-    player1_key_map: Vec<(ControlEntry, ScanCode)>,
-    player2_key_map: Vec<(ControlEntry, ScanCode)>,
-    player1_controller_button_map: Vec<(ControlEntry, PlayerControllerInputType)>,
-    player2_controller_button_map: Vec<(ControlEntry, PlayerControllerInputType)>,
+    selected_control: Option<InputAction>,
+    /// Inputs held so far while capturing a new binding, more than one makes a chord.
+    captured_inputs: Vec<InputBinding>,
+    capture_target: CaptureTarget,
+    recording: Option<MacroRecording>,
 
     input_busy: bool,
 }
@@ -175,25 +297,33 @@ impl ControlsMenu {
         let main = Menu::new(0, 0, 220, 0);
         let select_controller = Menu::new(0, 0, 220, 0);
         let rebind = Menu::new(0, 0, 220, 0);
+        let bindings = Menu::new(0, 0, 220, 0);
         let confirm_rebind = Menu::new(0, 0, 220, 0);
         let confirm_reset = Menu::new(0, 0, 160, 0);
+        let input_layer = Menu::new(0, 0, 220, 0);
+        let macros = Menu::new(0, 0, 220, 0);
+        let record_macro = Menu::new(0, 0, 220, 0);
+        let mouse = Menu::new(0, 0, 220, 0);
 
         ControlsMenu {
             current: CurrentMenu::MainMenu,
             main,
             select_controller,
             rebind,
+            bindings,
             confirm_rebind,
             confirm_reset,
+            input_layer,
+            macros,
+            record_macro,
+            mouse,
 
             selected_player: Player::Player1,
             selected_controller: ControllerType::Keyboard,
             selected_control: None,
-
-            player1_key_map: Vec::new(),
-            player2_key_map: Vec::new(),
-            player1_controller_button_map: Vec::new(),
-            player2_controller_button_map: Vec::new(),
+            captured_inputs: Vec::new(),
+            capture_target: CaptureTarget::Binding,
+            recording: None,
 
             input_busy: false,
         }
@@ -220,6 +350,14 @@ impl ControlsMenu {
             );
             self.main
                 .push_entry(MainMenuEntry::Rebind, MenuEntry::Active(state.loc.t("menus.controls_menu.rebind").to_owned()));
+            self.main.push_entry(
+                MainMenuEntry::InputLayer,
+                MenuEntry::Active(state.loc.t("menus.controls_menu.input_layer").to_owned()),
+            );
+            self.main.push_entry(
+                MainMenuEntry::Mouse,
+                MenuEntry::Active(state.loc.t("menus.controls_menu.mouse").to_owned()),
+            );
             self.main.push_entry(MainMenuEntry::Rumble, MenuEntry::Hidden);
         }
 
@@ -243,16 +381,51 @@ impl ControlsMenu {
         self.confirm_reset
             .push_entry(ConfirmResetMenuEntry::No, MenuEntry::Active(state.loc.t("common.no").to_owned()));
 
-        self.player1_key_map = self.init_key_map(&state.settings.player1_key_map);
-        self.player2_key_map = self.init_key_map(&state.settings.player2_key_map);
-        self.player1_controller_button_map =
-            self.init_controller_button_map(&state.settings.player1_controller_button_map);
-        self.player2_controller_button_map =
-            self.init_controller_button_map(&state.settings.player2_controller_button_map);
-
         self.confirm_rebind.draw_cursor = false;
         self.confirm_rebind.non_interactive = true;
 
+        self.record_macro.push_entry(
+            0,
+            MenuEntry::DisabledWhite(state.loc.t("menus.controls_menu.macro_menu.recording").to_owned()),
+        );
+        self.record_macro.push_entry(
+            1,
+            MenuEntry::Disabled(state.loc.t("menus.controls_menu.macro_menu.recording_hint").to_owned()),
+        );
+        self.record_macro.push_entry(
+            2,
+            MenuEntry::Disabled(state.loc.t("menus.controls_menu.rebind_confirm_menu.cancel").to_owned()),
+        );
+        self.record_macro.draw_cursor = false;
+        self.record_macro.non_interactive = true;
+
+        self.mouse.push_entry(
+            MouseMenuEntry::Aim,
+            MenuEntry::Toggle(state.loc.t("menus.controls_menu.mouse_menu.aim").to_owned(), state.settings.mouse.aim),
+        );
+        self.mouse.push_entry(
+            MouseMenuEntry::ClickToFire,
+            MenuEntry::Toggle(
+                state.loc.t("menus.controls_menu.mouse_menu.click_to_fire").to_owned(),
+                state.settings.mouse.click_to_fire,
+            ),
+        );
+        self.mouse.push_entry(
+            MouseMenuEntry::WheelWeapons,
+            MenuEntry::Toggle(
+                state.loc.t("menus.controls_menu.mouse_menu.wheel_weapons").to_owned(),
+                state.settings.mouse.wheel_weapons,
+            ),
+        );
+        self.mouse.push_entry(
+            MouseMenuEntry::MenuNavigation,
+            MenuEntry::Toggle(
+                state.loc.t("menus.controls_menu.mouse_menu.menu_navigation").to_owned(),
+                state.settings.mouse.menu_navigation,
+            ),
+        );
+        self.mouse.push_entry(MouseMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
+
         self.update_controller_options(state, ctx);
         self.update_rebind_menu(state, ctx);
         self.update_sizes(state);
@@ -277,6 +450,11 @@ impl ControlsMenu {
         self.rebind.x = ((state.canvas_size.0 - self.rebind.width as f32) / 2.0).floor() as isize;
         self.rebind.y = ((state.canvas_size.1 - self.rebind.height as f32) / 2.0).floor() as isize;
 
+        self.bindings.update_width(state);
+        self.bindings.update_height(state);
+        self.bindings.x = ((state.canvas_size.0 - self.bindings.width as f32) / 2.0).floor() as isize;
+        self.bindings.y = ((state.canvas_size.1 - self.bindings.height as f32) / 2.0).floor() as isize;
+
         self.confirm_rebind.update_width(state);
         self.confirm_rebind.update_height(state);
         self.confirm_rebind.x = ((state.canvas_size.0 - self.confirm_rebind.width as f32) / 2.0).floor() as isize;
@@ -286,119 +464,83 @@ impl ControlsMenu {
         self.confirm_reset.update_height(state);
         self.confirm_reset.x = ((state.canvas_size.0 - self.confirm_reset.width as f32) / 2.0).floor() as isize;
         self.confirm_reset.y = ((state.canvas_size.1 - self.confirm_reset.height as f32) / 2.0).floor() as isize;
+
+        self.input_layer.update_width(state);
+        self.input_layer.update_height(state);
+        self.input_layer.x = ((state.canvas_size.0 - self.input_layer.width as f32) / 2.0).floor() as isize;
+        self.input_layer.y = ((state.canvas_size.1 - self.input_layer.height as f32) / 2.0).floor() as isize;
+
+        self.macros.update_width(state);
+        self.macros.update_height(state);
+        self.macros.x = ((state.canvas_size.0 - self.macros.width as f32) / 2.0).floor() as isize;
+        self.macros.y = ((state.canvas_size.1 - self.macros.height as f32) / 2.0).floor() as isize;
+
+        self.record_macro.update_width(state);
+        self.record_macro.update_height(state);
+        self.record_macro.x = ((state.canvas_size.0 - self.record_macro.width as f32) / 2.0).floor() as isize;
+        self.record_macro.y = ((state.canvas_size.1 - self.record_macro.height as f32) / 2.0).floor() as isize;
+
+        self.mouse.update_width(state);
+        self.mouse.update_height(state);
+        self.mouse.x = ((state.canvas_size.0 - self.mouse.width as f32) / 2.0).floor() as isize;
+        self.mouse.y = ((state.canvas_size.1 - self.mouse.height as f32) / 2.0).floor() as isize;
+    }
+
+    fn selected_map<'a>(&self, state: &'a SharedGameState) -> &'a PlayerInputMap {
+        match (self.selected_player, self.selected_controller) {
+            (Player::Player1, ControllerType::Keyboard) => &state.settings.player1_key_map,
+            (Player::Player1, ControllerType::Gamepad(_)) => &state.settings.player1_controller_button_map,
+            (Player::Player2, ControllerType::Keyboard) => &state.settings.player2_key_map,
+            (Player::Player2, ControllerType::Gamepad(_)) => &state.settings.player2_controller_button_map,
+        }
     }
 
-    fn init_key_map(&self, settings_key_map: &PlayerKeyMap) -> Vec<(ControlEntry, ScanCode)> {
-        let mut map = Vec::new();
-
-        map.push((ControlEntry::MenuOk, settings_key_map.menu_ok));
-        map.push((ControlEntry::MenuBack, settings_key_map.menu_back));
-        map.push((ControlEntry::Up, settings_key_map.up));
-        map.push((ControlEntry::Down, settings_key_map.down));
-        map.push((ControlEntry::Left, settings_key_map.left));
-        map.push((ControlEntry::Right, settings_key_map.right));
-        map.push((ControlEntry::Jump, settings_key_map.jump));
-        map.push((ControlEntry::Shoot, settings_key_map.shoot));
-        map.push((ControlEntry::PrevWeapon, settings_key_map.prev_weapon));
-        map.push((ControlEntry::NextWeapon, settings_key_map.next_weapon));
-        map.push((ControlEntry::Inventory, settings_key_map.inventory));
-        map.push((ControlEntry::Map, settings_key_map.map));
-        map.push((ControlEntry::Skip, settings_key_map.skip));
-        map.push((ControlEntry::Strafe, settings_key_map.strafe));
-
-        map
+    fn selected_map_mut<'a>(&self, state: &'a mut SharedGameState) -> &'a mut PlayerInputMap {
+        match (self.selected_player, self.selected_controller) {
+            (Player::Player1, ControllerType::Keyboard) => &mut state.settings.player1_key_map,
+            (Player::Player1, ControllerType::Gamepad(_)) => &mut state.settings.player1_controller_button_map,
+            (Player::Player2, ControllerType::Keyboard) => &mut state.settings.player2_key_map,
+            (Player::Player2, ControllerType::Gamepad(_)) => &mut state.settings.player2_controller_button_map,
+        }
     }
 
-    fn init_controller_button_map(
-        &self,
-        settings_controller_button_map: &PlayerControllerButtonMap,
-    ) -> Vec<(ControlEntry, PlayerControllerInputType)> {
-        let mut map = Vec::new();
-
-        map.push((ControlEntry::MenuOk, settings_controller_button_map.menu_ok));
-        map.push((ControlEntry::MenuBack, settings_controller_button_map.menu_back));
-        map.push((ControlEntry::Up, settings_controller_button_map.up));
-        map.push((ControlEntry::Down, settings_controller_button_map.down));
-        map.push((ControlEntry::Left, settings_controller_button_map.left));
-        map.push((ControlEntry::Right, settings_controller_button_map.right));
-        map.push((ControlEntry::Jump, settings_controller_button_map.jump));
-        map.push((ControlEntry::Shoot, settings_controller_button_map.shoot));
-        map.push((ControlEntry::PrevWeapon, settings_controller_button_map.prev_weapon));
-        map.push((ControlEntry::NextWeapon, settings_controller_button_map.next_weapon));
-        map.push((ControlEntry::Inventory, settings_controller_button_map.inventory));
-        map.push((ControlEntry::Map, settings_controller_button_map.map));
-        map.push((ControlEntry::Skip, settings_controller_button_map.skip));
-        map.push((ControlEntry::Strafe, settings_controller_button_map.strafe));
-
-        map
+    fn gamepad_sprite_offset(&self, state: &SharedGameState, ctx: &Context) -> usize {
+        match self.selected_player.controller_type(state) {
+            ControllerType::Keyboard => 1,
+            ControllerType::Gamepad(index) => ctx.gamepad_context.get_gamepad_sprite_offset(index as usize),
+        }
     }
 
     fn update_rebind_menu(&mut self, state: &SharedGameState, ctx: &Context) {
         self.rebind.entries.clear();
 
-        match self.selected_player {
-            Player::Player1 => {
-                if self.selected_controller == ControllerType::Keyboard {
-                    for (k, v) in self.player1_key_map.iter() {
-                        self.rebind.push_entry(
-                            RebindMenuEntry::Control(*k),
-                            MenuEntry::Control(
-                                k.to_string(state).to_owned(),
-                                ControlMenuData::String(format!("{:?}", v)),
-                            ),
-                        );
-                    }
-                } else {
-                    for (k, v) in self.player1_controller_button_map.iter() {
-                        let gamepad_sprite_offset = match state.settings.player1_controller_type {
-                            ControllerType::Keyboard => 1,
-                            ControllerType::Gamepad(index) => {
-                                ctx.gamepad_context.get_gamepad_sprite_offset(index as usize)
-                            }
-                        };
+        let map = self.selected_map(state);
+        let gamepad_sprite_offset = self.gamepad_sprite_offset(state, ctx);
 
-                        self.rebind.push_entry(
-                            RebindMenuEntry::Control(*k),
-                            MenuEntry::Control(
-                                k.to_string(state).to_owned(),
-                                ControlMenuData::Rect(v.get_rect(gamepad_sprite_offset, &state.constants)),
-                            ),
-                        );
-                    }
-                }
-            }
-            Player::Player2 => {
-                if self.selected_controller == ControllerType::Keyboard {
-                    for (k, v) in self.player2_key_map.iter() {
-                        self.rebind.push_entry(
-                            RebindMenuEntry::Control(*k),
-                            MenuEntry::Control(
-                                k.to_string(state).to_owned(),
-                                ControlMenuData::String(format!("{:?}", v)),
-                            ),
-                        );
-                    }
-                } else {
-                    for (k, v) in self.player2_controller_button_map.iter() {
-                        let gamepad_sprite_offset = match state.settings.player2_controller_type {
-                            ControllerType::Keyboard => 1,
-                            ControllerType::Gamepad(index) => {
-                                ctx.gamepad_context.get_gamepad_sprite_offset(index as usize)
-                            }
-                        };
+        for action in REBIND_ACTIONS {
+            let bindings = map.get(action);
+            let single_rect = match bindings.0.as_slice() {
+                [binding] => binding.get_rect(gamepad_sprite_offset, &state.constants),
+                _ => None,
+            };
 
-                        self.rebind.push_entry(
-                            RebindMenuEntry::Control(*k),
-                            MenuEntry::Control(
-                                k.to_string(state).to_owned(),
-                                ControlMenuData::Rect(v.get_rect(gamepad_sprite_offset, &state.constants)),
-                            ),
-                        );
-                    }
-                }
-            }
+            let data = match single_rect {
+                Some(rect) => ControlMenuData::Rect(rect),
+                None => ControlMenuData::String(bindings_summary(bindings)),
+            };
+
+            self.rebind
+                .push_entry(RebindMenuEntry::Control(action), MenuEntry::Control(action_name(action, state), data));
         }
 
+        self.rebind.push_entry(
+            RebindMenuEntry::Import,
+            MenuEntry::Active(state.loc.t("menus.controls_menu.import_profile").to_owned()),
+        );
+        self.rebind.push_entry(
+            RebindMenuEntry::Export,
+            MenuEntry::Active(state.loc.t("menus.controls_menu.export_profile").to_owned()),
+        );
         self.rebind.push_entry(
             RebindMenuEntry::Reset,
             MenuEntry::Active(state.loc.t("menus.controls_menu.reset_confirm").to_owned()),
@@ -406,6 +548,143 @@ impl ControlsMenu {
         self.rebind.push_entry(RebindMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
     }
 
+    fn update_bindings_menu(&mut self, state: &SharedGameState, ctx: &Context) {
+        self.bindings.entries.clear();
+
+        let control = match self.selected_control {
+            Some(control) => control,
+            None => return,
+        };
+
+        self.bindings.push_entry(BindingsMenuEntry::Title, MenuEntry::DisabledWhite(action_name(control, state)));
+        self.bindings.push_entry(
+            BindingsMenuEntry::Hint,
+            MenuEntry::Disabled(state.loc.t("menus.controls_menu.bindings_menu.hint").to_owned()),
+        );
+
+        let gamepad_sprite_offset = self.gamepad_sprite_offset(state, ctx);
+
+        for (idx, binding) in self.selected_map(state).get(control).0.iter().enumerate() {
+            let data = match binding {
+                InputBinding::Axis(_, _, deadzone) => ControlMenuData::String(state.tt(
+                    "menus.controls_menu.bindings_menu.deadzone",
+                    &[("value", format!("{}%", (deadzone * 100.0).round()).as_str())],
+                )),
+                _ => match binding.get_rect(gamepad_sprite_offset, &state.constants) {
+                    Some(rect) => ControlMenuData::Rect(rect),
+                    None => ControlMenuData::String(String::new()),
+                },
+            };
+
+            self.bindings.push_entry(BindingsMenuEntry::Binding(idx), MenuEntry::Control(binding.display_name(), data));
+        }
+
+        self.bindings.push_entry(
+            BindingsMenuEntry::Add,
+            MenuEntry::Active(state.loc.t("menus.controls_menu.bindings_menu.add").to_owned()),
+        );
+        self.bindings.push_entry(BindingsMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
+
+        if !self.bindings.entries.iter().any(|(id, _)| *id == self.bindings.selected) {
+            self.bindings.selected = BindingsMenuEntry::Add;
+        }
+    }
+
+    fn update_input_layer_menu(&mut self, state: &SharedGameState) {
+        self.input_layer.entries.clear();
+
+        let layer = self.selected_player.input_layer(state);
+        let tps = match state.settings.timing_mode.get_tps() {
+            0 => 50,
+            tps => tps,
+        };
+
+        let rates = TURBO_RATES
+            .iter()
+            .map(|&rate| {
+                let presses = format!("{:.1}", tps as f32 / (rate as f32 * 2.0));
+                state.tt("menus.controls_menu.input_layer_menu.turbo_rate_value", &[("value", presses.as_str())])
+            })
+            .collect();
+        let rate_idx = TURBO_RATES.iter().position(|&rate| rate == layer.turbo_rate).unwrap_or(2);
+
+        self.input_layer.push_entry(
+            InputLayerMenuEntry::TurboRate,
+            MenuEntry::Options(
+                state.loc.t("menus.controls_menu.input_layer_menu.turbo_rate").to_owned(),
+                rate_idx,
+                rates,
+            ),
+        );
+
+        for action in REBIND_ACTIONS {
+            if matches!(action, InputAction::MenuOk | InputAction::MenuBack) {
+                continue;
+            }
+
+            self.input_layer.push_entry(
+                InputLayerMenuEntry::Turbo(action),
+                MenuEntry::Toggle(
+                    state.tt(
+                        "menus.controls_menu.input_layer_menu.turbo",
+                        &[("action", action_name(action, state).as_str())],
+                    ),
+                    layer.turbo.contains(&action),
+                ),
+            );
+        }
+
+        self.input_layer.push_entry(
+            InputLayerMenuEntry::ToggleShoot,
+            MenuEntry::Toggle(
+                state.loc.t("menus.controls_menu.input_layer_menu.toggle_shoot").to_owned(),
+                layer.toggle_shoot,
+            ),
+        );
+        self.input_layer.push_entry(
+            InputLayerMenuEntry::ToggleStrafe,
+            MenuEntry::Toggle(
+                state.loc.t("menus.controls_menu.input_layer_menu.toggle_strafe").to_owned(),
+                layer.toggle_strafe,
+            ),
+        );
+        self.input_layer.push_entry(
+            InputLayerMenuEntry::Macros,
+            MenuEntry::Active(state.loc.t("menus.controls_menu.input_layer_menu.macros").to_owned()),
+        );
+        self.input_layer
+            .push_entry(InputLayerMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
+    }
+
+    fn update_macro_menu(&mut self, state: &SharedGameState) {
+        self.macros.entries.clear();
+
+        self.macros.push_entry(
+            MacroMenuEntry::Hint,
+            MenuEntry::Disabled(state.loc.t("menus.controls_menu.macro_menu.hint").to_owned()),
+        );
+
+        for (idx, input_macro) in self.selected_player.input_layer(state).macros.iter().enumerate() {
+            self.macros.push_entry(
+                MacroMenuEntry::Macro(idx),
+                MenuEntry::Control(
+                    state.tt("menus.controls_menu.macro_menu.macro", &[("index", (idx + 1).to_string().as_str())]),
+                    ControlMenuData::String(input_macro.trigger.display_name()),
+                ),
+            );
+        }
+
+        self.macros.push_entry(
+            MacroMenuEntry::Record,
+            MenuEntry::Active(state.loc.t("menus.controls_menu.macro_menu.record").to_owned()),
+        );
+        self.macros.push_entry(MacroMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
+
+        if !self.macros.entries.iter().any(|(id, _)| *id == self.macros.selected) {
+            self.macros.selected = MacroMenuEntry::Record;
+        }
+    }
+
     fn update_controller_options(&mut self, state: &SharedGameState, ctx: &Context) {
         self.select_controller.entries.clear();
 
@@ -475,374 +754,176 @@ impl ControlsMenu {
     }
 
     fn update_confirm_controls_menu(&mut self, state: &SharedGameState) {
-        match self.selected_control {
-            Some(control) => {
-                self.confirm_rebind.entries.clear();
-
-                self.confirm_rebind.push_entry(
-                    0,
-                    MenuEntry::DisabledWhite(state.tt(
-                        "menus.controls_menu.rebind_confirm_menu.title",
-                        &[("control", control.to_string(state).as_str())],
-                    )),
-                );
-                self.confirm_rebind.push_entry(
-                    1,
-                    MenuEntry::Disabled(state.loc.t("menus.controls_menu.rebind_confirm_menu.cancel").to_owned()),
-                );
-            }
-            None => {}
+        let title = match (self.capture_target, self.selected_control) {
+            (CaptureTarget::MacroTrigger, _) => state.loc.t("menus.controls_menu.macro_menu.trigger").to_owned(),
+            (CaptureTarget::Binding, Some(control)) => state.tt(
+                "menus.controls_menu.rebind_confirm_menu.title",
+                &[("control", action_name(control, state).as_str())],
+            ),
+            (CaptureTarget::Binding, None) => return,
+        };
+
+        self.confirm_rebind.entries.clear();
+
+        self.confirm_rebind.push_entry(0, MenuEntry::DisabledWhite(title));
+        self.confirm_rebind.push_entry(
+            1,
+            MenuEntry::Disabled(state.loc.t("menus.controls_menu.rebind_confirm_menu.chord").to_owned()),
+        );
+        self.confirm_rebind.push_entry(
+            2,
+            MenuEntry::Disabled(state.loc.t("menus.controls_menu.rebind_confirm_menu.cancel").to_owned()),
+        );
+    }
+
+    /// Returns the menu that capturing inputs returns to.
+    fn capture_return_menu(&self) -> CurrentMenu {
+        match self.capture_target {
+            CaptureTarget::Binding => CurrentMenu::BindingsMenu,
+            CaptureTarget::MacroTrigger => CurrentMenu::MacroMenu,
         }
     }
 
-    fn reset_controls(&mut self, state: &mut SharedGameState, ctx: &Context) -> GameResult {
-        match self.selected_player {
-            Player::Player1 => {
-                if self.selected_controller == ControllerType::Keyboard {
-                    state.settings.player1_key_map = p1_default_keymap();
-                    self.player1_key_map = self.init_key_map(&state.settings.player1_key_map);
-                } else {
-                    state.settings.player1_controller_button_map = player_default_controller_button_map();
-                    self.player1_controller_button_map =
-                        self.init_controller_button_map(&state.settings.player1_controller_button_map);
-                }
-            }
-            Player::Player2 => {
-                if self.selected_controller == ControllerType::Keyboard {
-                    state.settings.player2_key_map = p2_default_keymap();
-                    self.player2_key_map = self.init_key_map(&state.settings.player2_key_map);
-                } else {
-                    state.settings.player2_controller_button_map = player_default_controller_button_map();
-                    self.player2_controller_button_map =
-                        self.init_controller_button_map(&state.settings.player2_controller_button_map);
-                }
-            }
+    fn is_cancel_held(&self, state: &SharedGameState, ctx: &Context) -> bool {
+        if ctx.keyboard_context.pressed_keys().contains(&ScanCode::Escape) {
+            return true;
         }
 
-        state.settings.save(ctx)
+        match self.selected_player.controller_type(state) {
+            ControllerType::Gamepad(idx) => ctx.gamepad_context.pressed_buttons(idx).contains(&Button::Start),
+            ControllerType::Keyboard => false,
+        }
     }
 
-    fn is_key_occupied(&self, scan_code: ScanCode) -> bool {
-        let other_player_keymap = match self.selected_player {
-            Player::Player1 => &self.player2_key_map,
-            Player::Player2 => &self.player1_key_map,
+    fn reset_controls(&mut self, state: &mut SharedGameState, ctx: &Context) -> GameResult {
+        let default_map = match (self.selected_player, self.selected_controller) {
+            (Player::Player1, ControllerType::Keyboard) => p1_default_keymap(),
+            (Player::Player2, ControllerType::Keyboard) => p2_default_keymap(),
+            (_, ControllerType::Gamepad(_)) => player_default_controller_button_map(),
         };
 
-        for (_, v) in other_player_keymap.iter() {
-            if *v == scan_code {
-                return true;
-            }
-        }
+        *self.selected_map_mut(state) = default_map;
 
-        false
+        state.settings.save(ctx)
     }
 
-    fn set_key(&mut self, state: &mut SharedGameState, scan_code: ScanCode, ctx: &Context) -> GameResult {
-        if self.selected_control.is_none() {
-            return Ok(());
+    fn is_binding_occupied(&self, state: &SharedGameState, binding: &InputBinding) -> bool {
+        if !matches!(binding, InputBinding::Key(_)) {
+            return false;
         }
 
-        let mut did_swap_controls = false;
-
-        match self.selected_control.unwrap() {
-            ControlEntry::Left => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.left = scan_code,
-                Player::Player2 => state.settings.player2_key_map.left = scan_code,
-            },
-            ControlEntry::Up => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.up = scan_code,
-                Player::Player2 => state.settings.player2_key_map.up = scan_code,
-            },
-            ControlEntry::Right => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.right = scan_code,
-                Player::Player2 => state.settings.player2_key_map.right = scan_code,
-            },
-            ControlEntry::Down => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.down = scan_code,
-                Player::Player2 => state.settings.player2_key_map.down = scan_code,
-            },
-            ControlEntry::PrevWeapon => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.prev_weapon = scan_code,
-                Player::Player2 => state.settings.player2_key_map.prev_weapon = scan_code,
-            },
-            ControlEntry::NextWeapon => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.next_weapon = scan_code,
-                Player::Player2 => state.settings.player2_key_map.next_weapon = scan_code,
-            },
-            ControlEntry::Jump => match self.selected_player {
-                Player::Player1 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player1_key_map.jump,
-                        &mut state.settings.player1_key_map.shoot,
-                        scan_code,
-                    );
-                }
-                Player::Player2 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player2_key_map.jump,
-                        &mut state.settings.player2_key_map.shoot,
-                        scan_code,
-                    );
-                }
-            },
-            ControlEntry::Shoot => match self.selected_player {
-                Player::Player1 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player1_key_map.shoot,
-                        &mut state.settings.player1_key_map.jump,
-                        scan_code,
-                    );
-                }
-                Player::Player2 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player2_key_map.shoot,
-                        &mut state.settings.player2_key_map.jump,
-                        scan_code,
-                    );
-                }
-            },
-            ControlEntry::Skip => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.skip = scan_code,
-                Player::Player2 => state.settings.player2_key_map.skip = scan_code,
-            },
-            ControlEntry::Inventory => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.inventory = scan_code,
-                Player::Player2 => state.settings.player2_key_map.inventory = scan_code,
-            },
-            ControlEntry::Map => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.map = scan_code,
-                Player::Player2 => state.settings.player2_key_map.map = scan_code,
-            },
-            ControlEntry::Strafe => match self.selected_player {
-                Player::Player1 => state.settings.player1_key_map.strafe = scan_code,
-                Player::Player2 => state.settings.player2_key_map.strafe = scan_code,
-            },
-            ControlEntry::MenuOk => match self.selected_player {
-                Player::Player1 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player1_key_map.menu_ok,
-                        &mut state.settings.player1_key_map.menu_back,
-                        scan_code,
-                    );
-                }
-                Player::Player2 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player2_key_map.menu_ok,
-                        &mut state.settings.player2_key_map.menu_back,
-                        scan_code,
-                    );
-                }
-            },
-            ControlEntry::MenuBack => match self.selected_player {
-                Player::Player1 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player1_key_map.menu_back,
-                        &mut state.settings.player1_key_map.menu_ok,
-                        scan_code,
-                    );
-                }
-                Player::Player2 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player2_key_map.menu_back,
-                        &mut state.settings.player2_key_map.menu_ok,
-                        scan_code,
-                    );
-                }
-            },
-        }
+        let (other_key_map, other_button_map) = match self.selected_player {
+            Player::Player1 => (&state.settings.player2_key_map, &state.settings.player2_controller_button_map),
+            Player::Player2 => (&state.settings.player1_key_map, &state.settings.player1_controller_button_map),
+        };
 
-        state.settings.save(ctx)?;
+        REBIND_ACTIONS.iter().any(|&action| {
+            other_key_map.get(action).contains(binding) || other_button_map.get(action).contains(binding)
+        })
+    }
 
-        let keymap = match self.selected_player {
-            Player::Player1 => &mut self.player1_key_map,
-            Player::Player2 => &mut self.player2_key_map,
+    fn add_binding(&mut self, state: &mut SharedGameState, binding: InputBinding, ctx: &Context) -> GameResult {
+        let control = match self.selected_control {
+            Some(control) => control,
+            None => return Ok(()),
         };
 
-        for (entry, value) in keymap.iter_mut() {
-            if *entry == self.selected_control.unwrap() {
-                *value = scan_code;
-            }
+        let map = self.selected_map_mut(state);
 
-            if did_swap_controls {
-                let map = match self.selected_player {
-                    Player::Player1 => &state.settings.player1_key_map,
-                    Player::Player2 => &state.settings.player2_key_map,
-                };
+        if let Some(paired) = paired_action(control) {
+            if map.get(paired).contains(&binding) {
+                map.get_mut(paired).remove(&binding);
 
-                match *entry {
-                    ControlEntry::Jump => *value = map.jump,
-                    ControlEntry::Shoot => *value = map.shoot,
-                    ControlEntry::MenuOk => *value = map.menu_ok,
-                    ControlEntry::MenuBack => *value = map.menu_back,
-                    _ => {}
+                if map.get(paired).0.is_empty() {
+                    let previous = std::mem::take(map.get_mut(control));
+                    *map.get_mut(paired) = previous;
                 }
             }
         }
 
-        Ok(())
+        map.get_mut(control).add(binding);
+
+        state.settings.save(ctx)
     }
 
-    fn set_controller_input(
-        &mut self,
-        state: &mut SharedGameState,
-        input_type: PlayerControllerInputType,
-        ctx: &Context,
-    ) -> GameResult {
-        if self.selected_control.is_none() {
-            return Ok(());
-        }
+    fn remove_binding(&mut self, state: &mut SharedGameState, idx: usize, ctx: &Context) -> GameResult {
+        let control = match self.selected_control {
+            Some(control) => control,
+            None => return Ok(()),
+        };
 
-        let mut did_swap_controls = false;
+        let bindings = self.selected_map_mut(state).get_mut(control);
 
-        match self.selected_control.unwrap() {
-            ControlEntry::Left => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.left = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.left = input_type,
-            },
-            ControlEntry::Up => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.up = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.up = input_type,
-            },
-            ControlEntry::Right => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.right = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.right = input_type,
-            },
-            ControlEntry::Down => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.down = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.down = input_type,
-            },
-            ControlEntry::PrevWeapon => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.prev_weapon = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.prev_weapon = input_type,
-            },
-            ControlEntry::NextWeapon => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.next_weapon = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.next_weapon = input_type,
-            },
-            ControlEntry::Jump => match self.selected_player {
-                Player::Player1 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player1_controller_button_map.jump,
-                        &mut state.settings.player1_controller_button_map.shoot,
-                        input_type,
-                    );
-                }
-                Player::Player2 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player2_controller_button_map.jump,
-                        &mut state.settings.player2_controller_button_map.shoot,
-                        input_type,
-                    );
-                }
-            },
-            ControlEntry::Shoot => match self.selected_player {
-                Player::Player1 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player1_controller_button_map.shoot,
-                        &mut state.settings.player1_controller_button_map.jump,
-                        input_type,
-                    );
-                }
-                Player::Player2 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player2_controller_button_map.shoot,
-                        &mut state.settings.player2_controller_button_map.jump,
-                        input_type,
-                    );
-                }
-            },
-            ControlEntry::Skip => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.skip = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.skip = input_type,
-            },
-            ControlEntry::Inventory => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.inventory = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.inventory = input_type,
-            },
-            ControlEntry::Map => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.map = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.map = input_type,
-            },
-            ControlEntry::Strafe => match self.selected_player {
-                Player::Player1 => state.settings.player1_controller_button_map.strafe = input_type,
-                Player::Player2 => state.settings.player2_controller_button_map.strafe = input_type,
-            },
-            ControlEntry::MenuOk => match self.selected_player {
-                Player::Player1 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player1_controller_button_map.menu_ok,
-                        &mut state.settings.player1_controller_button_map.menu_back,
-                        input_type,
-                    );
-                }
-                Player::Player2 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player2_controller_button_map.menu_ok,
-                        &mut state.settings.player2_controller_button_map.menu_back,
-                        input_type,
-                    );
-                }
-            },
-            ControlEntry::MenuBack => match self.selected_player {
-                Player::Player1 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player1_controller_button_map.menu_back,
-                        &mut state.settings.player1_controller_button_map.menu_ok,
-                        input_type,
-                    );
-                }
-                Player::Player2 => {
-                    did_swap_controls = self.swap_if_same(
-                        &mut state.settings.player2_controller_button_map.menu_back,
-                        &mut state.settings.player2_controller_button_map.menu_ok,
-                        input_type,
-                    );
-                }
-            },
+        // every action keeps at least one binding, so the menus can't become unusable
+        if bindings.0.len() <= 1 || idx >= bindings.0.len() {
+            state.sound_manager.play_sfx_on(12, MixerBus::UI);
+            return Ok(());
         }
 
-        state.settings.save(ctx)?;
+        bindings.0.remove(idx);
+
+        state.settings.save(ctx)
+    }
 
-        let button_map = match self.selected_player {
-            Player::Player1 => &mut self.player1_controller_button_map,
-            Player::Player2 => &mut self.player2_controller_button_map,
+    fn adjust_deadzone(
+        &mut self,
+        state: &mut SharedGameState,
+        idx: usize,
+        direction: i16,
+        ctx: &Context,
+    ) -> GameResult {
+        let control = match self.selected_control {
+            Some(control) => control,
+            None => return Ok(()),
         };
 
-        for (entry, value) in button_map.iter_mut() {
-            if *entry == self.selected_control.unwrap() {
-                *value = input_type;
-            }
+        let axis_sensitivity = match self.selected_player {
+            Player::Player1 => state.settings.player1_controller_axis_sensitivity,
+            Player::Player2 => state.settings.player2_controller_axis_sensitivity,
+        };
 
-            if did_swap_controls {
-                let map = match self.selected_player {
-                    Player::Player1 => &state.settings.player1_controller_button_map,
-                    Player::Player2 => &state.settings.player2_controller_button_map,
-                };
+        let binding = match self.selected_map_mut(state).get_mut(control).0.get_mut(idx) {
+            Some(binding) => binding,
+            None => return Ok(()),
+        };
 
-                match *entry {
-                    ControlEntry::Jump => *value = map.jump,
-                    ControlEntry::Shoot => *value = map.shoot,
-                    ControlEntry::MenuOk => *value = map.menu_ok,
-                    ControlEntry::MenuBack => *value = map.menu_back,
-                    _ => {}
-                }
+        // axis inputs without their own deadzone start from the controller's axis sensitivity
+        let (axis, axis_direction, deadzone) = match *binding {
+            InputBinding::Axis(axis, axis_direction, deadzone) => (axis, axis_direction, deadzone),
+            InputBinding::Gamepad(PlayerControllerInputType::AxisInput(axis, axis_direction)) => {
+                (axis, axis_direction, axis_sensitivity)
             }
-        }
+            _ => return Ok(()),
+        };
 
-        Ok(())
-    }
+        let deadzone = ((deadzone / DEADZONE_STEP).round() + direction as f64) * DEADZONE_STEP;
+        *binding = InputBinding::Axis(axis, axis_direction, deadzone.clamp(DEADZONE_STEP, 1.0 - DEADZONE_STEP));
 
-    fn swap_if_same<T: Eq + Copy>(&mut self, fst: &mut T, snd: &mut T, value: T) -> bool {
-        let mut swapped = false;
+        state.sound_manager.play_sfx_on(1, MixerBus::UI);
+        state.settings.save(ctx)
+    }
 
-        if *snd == value {
-            *snd = *fst;
-            swapped = true;
+    /// Turns the inputs held while rebinding into a binding, or `None` if it contains forbidden or occupied keys.
+    fn capture_binding(&self, state: &SharedGameState, inputs: Vec<InputBinding>) -> Option<InputBinding> {
+        if inputs.iter().any(|input| matches!(input, InputBinding::Key(key) if FORBIDDEN_SCANCODES.contains(key))) {
+            return None;
         }
 
-        *fst = value;
+        let binding = if inputs.len() == 1 {
+            match inputs.into_iter().next() {
+                Some(InputBinding::Gamepad(input)) => InputBinding::Gamepad(self.normalize_gamepad_input(input)),
+                Some(input) => input,
+                None => return None,
+            }
+        } else {
+            InputBinding::Chord(inputs)
+        };
+
+        if self.is_binding_occupied(state, &binding) {
+            return None;
+        }
 
-        swapped
+        Some(binding)
     }
 
     fn normalize_gamepad_input(&self, input: PlayerControllerInputType) -> PlayerControllerInputType {
@@ -928,6 +1009,13 @@ impl ControlsMenu {
                 MenuSelectionResult::Selected(MainMenuEntry::Rebind, _) => {
                     self.current = CurrentMenu::RebindMenu;
                 }
+                MenuSelectionResult::Selected(MainMenuEntry::InputLayer, _) => {
+                    self.update_input_layer_menu(state);
+                    self.current = CurrentMenu::InputLayerMenu;
+                }
+                MenuSelectionResult::Selected(MainMenuEntry::Mouse, _) => {
+                    self.current = CurrentMenu::MouseMenu;
+                }
                 MenuSelectionResult::Selected(MainMenuEntry::Rumble, toggle) => {
                     if let MenuEntry::Toggle(_, value) = toggle {
                         match self.selected_player {
@@ -1040,9 +1128,27 @@ impl ControlsMenu {
                 MenuSelectionResult::Selected(RebindMenuEntry::Control(control), _) => {
                     if !self.input_busy {
                         self.selected_control = Some(control);
-                        self.update_confirm_controls_menu(state);
-                        self.input_busy = true;
-                        self.current = CurrentMenu::ConfirmRebindMenu;
+                        self.bindings.selected = BindingsMenuEntry::default();
+                        self.update_bindings_menu(state, ctx);
+                        self.current = CurrentMenu::BindingsMenu;
+                    }
+                }
+                MenuSelectionResult::Selected(RebindMenuEntry::Import, _) => {
+                    match state.settings.import_binding_profile(ctx, self.selected_player.target()) {
+                        Ok(()) => {
+                            state.settings.save(ctx)?;
+                            self.update_rebind_menu(state, ctx);
+                        }
+                        Err(err) => {
+                            log::warn!("Failed to import binding profile: {}", err);
+                            state.sound_manager.play_sfx_on(12, MixerBus::UI);
+                        }
+                    }
+                }
+                MenuSelectionResult::Selected(RebindMenuEntry::Export, _) => {
+                    if let Err(err) = state.settings.export_binding_profile(ctx, self.selected_player.target()) {
+                        log::warn!("Failed to export binding profile: {}", err);
+                        state.sound_manager.play_sfx_on(12, MixerBus::UI);
                     }
                 }
                 MenuSelectionResult::Selected(RebindMenuEntry::Reset, _) => {
@@ -1051,108 +1157,103 @@ impl ControlsMenu {
                 }
                 _ => {}
             },
-            CurrentMenu::ConfirmRebindMenu => match self.confirm_rebind.tick(controller, state) {
-                _ => {
-                    let pressed_keys: Vec<_> = ctx.keyboard_context.pressed_keys().into_iter().collect();
-
-                    for key in pressed_keys.clone() {
-                        if *key == ScanCode::Escape {
-                            state.sound_manager.play_sfx(5);
-                            self.current = CurrentMenu::RebindMenu;
-                            return Ok(());
-                        }
+            CurrentMenu::BindingsMenu => match self.bindings.tick(controller, state) {
+                MenuSelectionResult::Selected(BindingsMenuEntry::Back, _) | MenuSelectionResult::Canceled => {
+                    if !self.input_busy {
+                        self.update_rebind_menu(state, ctx);
+                        self.current = CurrentMenu::RebindMenu;
                     }
+                }
+                MenuSelectionResult::Selected(BindingsMenuEntry::Add, _) => {
+                    if !self.input_busy {
+                        self.capture_target = CaptureTarget::Binding;
+                        self.captured_inputs.clear();
+                        self.update_confirm_controls_menu(state);
+                        self.input_busy = true;
+                        self.current = CurrentMenu::ConfirmRebindMenu;
+                    }
+                }
+                MenuSelectionResult::Selected(BindingsMenuEntry::Binding(idx), _) => {
+                    if !self.input_busy {
+                        self.remove_binding(state, idx, ctx)?;
+                        self.update_bindings_menu(state, ctx);
+                    }
+                }
+                MenuSelectionResult::Left(BindingsMenuEntry::Binding(idx), _, direction)
+                | MenuSelectionResult::Right(BindingsMenuEntry::Binding(idx), _, direction) => {
+                    self.adjust_deadzone(state, idx, direction, ctx)?;
+                    self.update_bindings_menu(state, ctx);
+                }
+                _ => {}
+            },
+            CurrentMenu::ConfirmRebindMenu => {
+                self.confirm_rebind.tick(controller, state);
+
+                let pressed_keys: Vec<ScanCode> = ctx.keyboard_context.pressed_keys().iter().copied().collect();
+                let mut held_inputs: Vec<InputBinding> =
+                    pressed_keys.iter().map(|key| InputBinding::Key(*key)).collect();
+                let mut cancel = pressed_keys.contains(&ScanCode::Escape);
+
+                // the keyboard map is also used without a gamepad, so only the gamepad map can mix in gamepad inputs
+                let gamepad_allowed = self.capture_target != CaptureTarget::Binding
+                    || self.selected_controller != ControllerType::Keyboard;
+
+                if let ControllerType::Gamepad(idx) = self.selected_player.controller_type(state) {
+                    let pressed_gamepad_buttons = ctx.gamepad_context.pressed_buttons(idx);
+                    cancel = cancel || pressed_gamepad_buttons.contains(&Button::Start);
+
+                    if gamepad_allowed {
+                        held_inputs.extend(
+                            pressed_gamepad_buttons
+                                .into_iter()
+                                .map(|button| InputBinding::Gamepad(PlayerControllerInputType::ButtonInput(button))),
+                        );
+                        held_inputs.extend(ctx.gamepad_context.active_axes(idx).into_iter().map(|(axis, value)| {
+                            let direction = AxisDirection::from_axis_data(axis, value);
+                            InputBinding::Gamepad(PlayerControllerInputType::AxisInput(axis, direction))
+                        }));
+                    }
+                }
 
-                    match self.selected_controller {
-                        ControllerType::Keyboard => {
-                            if pressed_keys.len() == 1 {
-                                if !self.input_busy {
-                                    self.input_busy = true;
-                                    self.rebind.non_interactive = true;
-
-                                    let key = **pressed_keys.first().unwrap();
-
-                                    if self.is_key_occupied(key)
-                                        || FORBIDDEN_SCANCODES.contains(&key)
-                                        || self.selected_controller != ControllerType::Keyboard
-                                    {
-                                        state.sound_manager.play_sfx(12);
-                                    } else {
-                                        self.set_key(state, key, ctx)?;
-                                        self.update_rebind_menu(state, ctx);
-                                        self.selected_control = None;
-                                        state.sound_manager.play_sfx(18);
-                                        self.current = CurrentMenu::RebindMenu;
-                                    }
-                                }
-                            }
-                        }
-                        ControllerType::Gamepad(idx) => {
-                            let pressed_gamepad_buttons: Vec<_> =
-                                ctx.gamepad_context.pressed_buttons(idx).into_iter().collect();
-
-                            for button in pressed_gamepad_buttons.clone() {
-                                if button == Button::Start {
-                                    state.sound_manager.play_sfx(5);
-                                    self.current = CurrentMenu::RebindMenu;
-                                    return Ok(());
-                                }
-                            }
-
-                            if pressed_gamepad_buttons.len() == 1 {
-                                if !self.input_busy {
-                                    self.input_busy = true;
-                                    self.rebind.non_interactive = true;
-
-                                    let button = *pressed_gamepad_buttons.first().unwrap();
-
-                                    if self.selected_player.controller_type(state) != self.selected_controller {
-                                        state.sound_manager.play_sfx(12);
-                                    } else {
-                                        let normalized_input = self
-                                            .normalize_gamepad_input(PlayerControllerInputType::ButtonInput(button));
-
-                                        self.set_controller_input(state, normalized_input, ctx)?;
-                                        self.update_rebind_menu(state, ctx);
-                                        self.selected_control = None;
-                                        state.sound_manager.play_sfx(18);
-                                        self.current = CurrentMenu::RebindMenu;
-                                    }
-                                }
-                            }
-
-                            let active_axes: Vec<_> = ctx.gamepad_context.active_axes(idx).into_iter().collect();
-
-                            if active_axes.len() == 1 {
-                                if !self.input_busy {
-                                    self.input_busy = true;
-                                    self.rebind.non_interactive = true;
-
-                                    if self.selected_player.controller_type(state) != self.selected_controller {
-                                        state.sound_manager.play_sfx(12);
-                                    } else {
-                                        let (axis, value) = *active_axes.first().unwrap();
-                                        let direction = AxisDirection::from_axis_data(axis, value);
-                                        let normalized_input = self.normalize_gamepad_input(
-                                            PlayerControllerInputType::AxisInput(axis, direction),
-                                        );
-
-                                        self.set_controller_input(state, normalized_input, ctx)?;
-                                        self.update_rebind_menu(state, ctx);
-                                        self.selected_control = None;
-                                        state.sound_manager.play_sfx(18);
-                                        self.current = CurrentMenu::RebindMenu;
-                                    }
-                                }
-                            }
+                if cancel {
+                    state.sound_manager.play_sfx_on(5, MixerBus::UI);
+                    self.captured_inputs.clear();
+                    self.current = self.capture_return_menu();
+                    return Ok(());
+                }
 
-                            if pressed_keys.is_empty() && pressed_gamepad_buttons.is_empty() && active_axes.is_empty() {
-                                self.input_busy = false;
-                            }
+                if self.input_busy && self.captured_inputs.is_empty() {
+                    // wait until the input used to open this menu is released
+                } else if !held_inputs.is_empty() {
+                    for input in held_inputs {
+                        if !self.captured_inputs.contains(&input) {
+                            self.captured_inputs.push(input);
+                        }
+                    }
+                } else if !self.captured_inputs.is_empty() {
+                    // everything got released, bind whatever was held
+                    let inputs = std::mem::take(&mut self.captured_inputs);
+
+                    match (self.capture_target, self.capture_binding(state, inputs)) {
+                        (CaptureTarget::Binding, Some(binding)) => {
+                            self.add_binding(state, binding, ctx)?;
+                            self.update_bindings_menu(state, ctx);
+                            state.sound_manager.play_sfx_on(18, MixerBus::UI);
+                            self.current = CurrentMenu::BindingsMenu;
+                        }
+                        (CaptureTarget::MacroTrigger, Some(trigger)) => {
+                            self.recording = Some(MacroRecording {
+                                trigger,
+                                recorder: MacroRecorder::new(),
+                                controller: self.selected_player.create_controller(state),
+                            });
+                            state.sound_manager.play_sfx_on(18, MixerBus::UI);
+                            self.current = CurrentMenu::RecordMacroMenu;
                         }
+                        (_, None) => state.sound_manager.play_sfx_on(12, MixerBus::UI),
                     }
                 }
-            },
+            }
             CurrentMenu::ConfirmResetMenu => match self.confirm_reset.tick(controller, state) {
                 MenuSelectionResult::Selected(ConfirmResetMenuEntry::Yes, _) => {
                     self.reset_controls(state, ctx)?;
@@ -1166,6 +1267,168 @@ impl ControlsMenu {
                 }
                 _ => {}
             },
+            CurrentMenu::InputLayerMenu => match self.input_layer.tick(controller, state) {
+                MenuSelectionResult::Selected(InputLayerMenuEntry::TurboRate, toggle)
+                | MenuSelectionResult::Right(InputLayerMenuEntry::TurboRate, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + 1) % TURBO_RATES.len();
+                        self.selected_player.input_layer_mut(state).turbo_rate = TURBO_RATES[*value];
+
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Left(InputLayerMenuEntry::TurboRate, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + TURBO_RATES.len() - 1) % TURBO_RATES.len();
+                        self.selected_player.input_layer_mut(state).turbo_rate = TURBO_RATES[*value];
+
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Selected(InputLayerMenuEntry::Turbo(action), toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        let layer = self.selected_player.input_layer_mut(state);
+
+                        if layer.turbo.contains(&action) {
+                            layer.turbo.retain(|&a| a != action);
+                        } else {
+                            layer.turbo.push(action);
+                        }
+
+                        *value = layer.turbo.contains(&action);
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Selected(InputLayerMenuEntry::ToggleShoot, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        let layer = self.selected_player.input_layer_mut(state);
+                        layer.toggle_shoot = !layer.toggle_shoot;
+
+                        *value = layer.toggle_shoot;
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Selected(InputLayerMenuEntry::ToggleStrafe, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        let layer = self.selected_player.input_layer_mut(state);
+                        layer.toggle_strafe = !layer.toggle_strafe;
+
+                        *value = layer.toggle_strafe;
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Selected(InputLayerMenuEntry::Macros, _) => {
+                    self.macros.selected = MacroMenuEntry::default();
+                    self.update_macro_menu(state);
+                    self.current = CurrentMenu::MacroMenu;
+                }
+                MenuSelectionResult::Selected(InputLayerMenuEntry::Back, _) | MenuSelectionResult::Canceled => {
+                    self.current = CurrentMenu::MainMenu;
+                }
+                _ => {}
+            },
+            CurrentMenu::MacroMenu => match self.macros.tick(controller, state) {
+                MenuSelectionResult::Selected(MacroMenuEntry::Macro(idx), _) => {
+                    if !self.input_busy {
+                        let macros = &mut self.selected_player.input_layer_mut(state).macros;
+                        if idx < macros.len() {
+                            macros.remove(idx);
+                        }
+
+                        state.settings.save(ctx)?;
+                        self.update_macro_menu(state);
+                    }
+                }
+                MenuSelectionResult::Selected(MacroMenuEntry::Record, _) => {
+                    if !self.input_busy {
+                        self.capture_target = CaptureTarget::MacroTrigger;
+                        self.captured_inputs.clear();
+                        self.update_confirm_controls_menu(state);
+                        self.input_busy = true;
+                        self.current = CurrentMenu::ConfirmRebindMenu;
+                    }
+                }
+                MenuSelectionResult::Selected(MacroMenuEntry::Back, _) | MenuSelectionResult::Canceled => {
+                    if !self.input_busy {
+                        self.update_input_layer_menu(state);
+                        self.current = CurrentMenu::InputLayerMenu;
+                    }
+                }
+                _ => {}
+            },
+            CurrentMenu::MouseMenu => match self.mouse.tick(controller, state) {
+                MenuSelectionResult::Selected(MouseMenuEntry::Aim, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.mouse.aim = !state.settings.mouse.aim;
+                        *value = state.settings.mouse.aim;
+
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Selected(MouseMenuEntry::ClickToFire, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.mouse.click_to_fire = !state.settings.mouse.click_to_fire;
+                        *value = state.settings.mouse.click_to_fire;
+
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Selected(MouseMenuEntry::WheelWeapons, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.mouse.wheel_weapons = !state.settings.mouse.wheel_weapons;
+                        *value = state.settings.mouse.wheel_weapons;
+
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Selected(MouseMenuEntry::MenuNavigation, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.mouse.menu_navigation = !state.settings.mouse.menu_navigation;
+                        *value = state.settings.mouse.menu_navigation;
+
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Selected(MouseMenuEntry::Back, _) | MenuSelectionResult::Canceled => {
+                    self.current = CurrentMenu::MainMenu;
+                }
+                _ => {}
+            },
+            CurrentMenu::RecordMacroMenu => {
+                self.record_macro.tick(controller, state);
+
+                if self.is_cancel_held(state, ctx) {
+                    state.sound_manager.play_sfx_on(5, MixerBus::UI);
+                    self.recording = None;
+                    self.input_busy = true;
+                    self.current = CurrentMenu::MacroMenu;
+                    return Ok(());
+                }
+
+                let finished = match self.recording.as_mut() {
+                    Some(recording) => {
+                        recording.controller.update(state, ctx)?;
+                        recording.controller.update_trigger();
+
+                        recording.recorder.record(held_inputs(recording.controller.as_ref()))
+                    }
+                    None => true,
+                };
+
+                if finished {
+                    if let Some(recording) = self.recording.take() {
+                        let input_macro =
+                            InputMacro { trigger: recording.trigger, frames: recording.recorder.finish() };
+                        self.selected_player.input_layer_mut(state).macros.push(input_macro);
+
+                        state.settings.save(ctx)?;
+                        state.sound_manager.play_sfx_on(18, MixerBus::UI);
+                    }
+
+                    self.update_macro_menu(state);
+                    self.current = CurrentMenu::MacroMenu;
+                }
+            }
         }
 
         if self.input_busy {
@@ -1196,8 +1459,13 @@ impl ControlsMenu {
             CurrentMenu::MainMenu => self.main.draw(state, ctx)?,
             CurrentMenu::SelectControllerMenu => self.select_controller.draw(state, ctx)?,
             CurrentMenu::RebindMenu => self.rebind.draw(state, ctx)?,
+            CurrentMenu::BindingsMenu => self.bindings.draw(state, ctx)?,
             CurrentMenu::ConfirmRebindMenu => self.confirm_rebind.draw(state, ctx)?,
             CurrentMenu::ConfirmResetMenu => self.confirm_reset.draw(state, ctx)?,
+            CurrentMenu::InputLayerMenu => self.input_layer.draw(state, ctx)?,
+            CurrentMenu::MacroMenu => self.macros.draw(state, ctx)?,
+            CurrentMenu::RecordMacroMenu => self.record_macro.draw(state, ctx)?,
+            CurrentMenu::MouseMenu => self.mouse.draw(state, ctx)?,
         }
 
         Ok(())
@@ -143,19 +143,22 @@ impl PlayerCountMenu {
                     let current_skin_spritesheet_name =
                         state.constants.player_skin_paths[state.player2_skin_location.texture_index as usize].as_str();
 
-                    if let Some(tex_size) = state.constants.tex_sizes.get(current_skin_spritesheet_name) {
+                    let next_spritesheet = match state.constants.tex_sizes.get(current_skin_spritesheet_name) {
                         // TODO: should probably have a way to figure out the height from the spritesheet ahead of time
-
-                        if state.player2_skin_location.offset * 2 * 16 >= tex_size.1 {
-                            state.player2_skin_location.offset = 0;
-
-                            if (state.player2_skin_location.texture_index as usize)
-                                == state.constants.player_skin_paths.len() - 1
-                            {
-                                state.player2_skin_location.texture_index = 0;
-                            } else {
-                                state.player2_skin_location.texture_index += 1;
-                            }
+                        Some(tex_size) => state.player2_skin_location.offset * 2 * 16 >= tex_size.1,
+                        // skin packs contain a single variant
+                        None => state.constants.player_skin_previews.contains_key(current_skin_spritesheet_name),
+                    };
+
+                    if next_spritesheet {
+                        state.player2_skin_location.offset = 0;
+
+                        if (state.player2_skin_location.texture_index as usize)
+                            == state.constants.player_skin_paths.len() - 1
+                        {
+                            state.player2_skin_location.texture_index = 0;
+                        } else {
+                            state.player2_skin_location.texture_index += 1;
                         }
                     }
                 }
@@ -9,6 +9,7 @@ use crate::game::shared_game_state::{GameDifficulty, MenuCharacter, SharedGameSt
 use crate::graphics::font::Font;
 use crate::input::combined_menu_controller::CombinedMenuController;
 use crate::menu::save_select_menu::MenuSaveInfo;
+use crate::sound::mixer::MixerBus;
 
 pub mod controls_menu;
 pub mod coop_menu;
@@ -18,6 +19,11 @@ pub mod settings_menu;
 
 const MENU_MIN_PADDING: f32 = 30.0;
 
+/// Checks for a touch or a mouse click within given bounds.
+fn clicked_in(controller: &CombinedMenuController, state: &mut SharedGameState, bounds: Rect) -> bool {
+    controller.mouse_clicked_in(bounds) || state.touch_controls.consume_click_in(bounds)
+}
+
 #[derive(Clone, Debug)]
 pub enum ControlMenuData {
     String(String),
@@ -632,12 +638,13 @@ impl<T: std::cmp::PartialEq + std::default::Default + Clone> Menu<T> {
                     let spritesheet_name =
                         state.constants.player_skin_paths[state.player2_skin_location.texture_index as usize].as_str();
 
+                    let preview = match state.constants.player_skin_previews.get(spritesheet_name) {
+                        Some(rect) => *rect,
+                        None => Rect::new_size(0, (state.player2_skin_location.offset).saturating_mul(2 * 16), 16, 16),
+                    };
+
                     let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, spritesheet_name)?;
-                    batch.add_rect(
-                        self.x as f32 + 88.0,
-                        y - 4.0,
-                        &Rect::new_size(0, (state.player2_skin_location.offset).saturating_mul(2 * 16), 16, 16),
-                    );
+                    batch.add_rect(self.x as f32 + 88.0, y - 4.0, &preview);
                     batch.draw(ctx)?;
                 }
                 MenuEntry::SaveData(save) | MenuEntry::SaveDataSingle(save) => {
@@ -694,16 +701,27 @@ impl<T: std::cmp::PartialEq + std::default::Default + Clone> Menu<T> {
                             )?;
                         }
 
+                        // Assist
+                        if save.assist != 0 {
+                            let text = state.loc.t("menus.save_menu.assist");
+                            let width = state.font.builder().compute_width(text);
+
+                            state.font.builder().position(right_edge - width - 64.0, y).draw(
+                                text,
+                                ctx,
+                                &state.constants,
+                                &mut state.texture_set,
+                            )?;
+                        }
+
                         // Weapons
                         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "ArmsImage")?;
 
                         for weapon_slot in 0..save.weapon_count {
                             let wtype = save.weapon_id[weapon_slot];
                             let pos_x = weapon_slot as f32 * 16.0 - (16 * save.weapon_count.saturating_sub(4)) as f32;
-                            let mut rect = Rect::new(0, 0, 0, 16);
                             if wtype != 0 {
-                                rect.left = wtype as u16 * 16;
-                                rect.right = rect.left + 16;
+                                let rect = state.constants.weapon.get_arms_icon_rect(wtype as u16);
                                 batch.add_rect(right_edge + pos_x - 60.0, y + 8.0, &rect);
                             }
                         }
@@ -791,12 +809,12 @@ impl<T: std::cmp::PartialEq + std::default::Default + Clone> Menu<T> {
         }
 
         if controller.trigger_back() {
-            state.sound_manager.play_sfx(5);
+            state.sound_manager.play_sfx_on(5, MixerBus::UI);
             return MenuSelectionResult::Canceled;
         }
 
         if (controller.trigger_up() || controller.trigger_down()) && !self.entries.is_empty() {
-            state.sound_manager.play_sfx(1);
+            state.sound_manager.play_sfx_on(1, MixerBus::UI);
 
             let mut selected = self.entries.iter().position(|(idx, _)| *idx == self.selected).ok_or(0).unwrap();
 
@@ -834,6 +852,11 @@ impl<T: std::cmp::PartialEq + std::default::Default + Clone> Menu<T> {
                 Rect::new_size(self.x - self.width as isize, y as isize, self.width as isize, entry.height() as isize);
             y += entry.height() as f32;
 
+            if self.selected != idx && entry.selectable() && controller.mouse_moved_in(entry_bounds) {
+                state.sound_manager.play_sfx_on(1, MixerBus::UI);
+                self.selected = idx.clone();
+            }
+
             match entry {
                 MenuEntry::Active(_)
                 | MenuEntry::Toggle(_, _)
@@ -843,47 +866,52 @@ impl<T: std::cmp::PartialEq + std::default::Default + Clone> Menu<T> {
                 | MenuEntry::NewSave
                 | MenuEntry::PlayerSkin
                     if (self.selected == idx && controller.trigger_ok())
-                        || state.touch_controls.consume_click_in(entry_bounds) =>
+                        || clicked_in(controller, state, entry_bounds) =>
                 {
-                    state.sound_manager.play_sfx(18);
+                    state.sound_manager.play_sfx_on(18, MixerBus::UI);
                     self.selected = idx.clone();
                     return MenuSelectionResult::Selected(idx, entry);
                 }
                 MenuEntry::Options(_, _, _) | MenuEntry::OptionsBar(_, _)
                     if (self.selected == idx && controller.trigger_left())
-                        || state.touch_controls.consume_click_in(left_entry_bounds) =>
+                        || clicked_in(controller, state, left_entry_bounds) =>
                 {
-                    state.sound_manager.play_sfx(1);
+                    state.sound_manager.play_sfx_on(1, MixerBus::UI);
                     self.selected = idx.clone();
                     return MenuSelectionResult::Left(self.selected.clone(), entry, -1);
                 }
                 MenuEntry::Options(_, _, _) | MenuEntry::OptionsBar(_, _)
                     if (self.selected == idx && controller.trigger_right())
-                        || state.touch_controls.consume_click_in(right_entry_bounds) =>
+                        || clicked_in(controller, state, right_entry_bounds) =>
                 {
-                    state.sound_manager.play_sfx(1);
+                    state.sound_manager.play_sfx_on(1, MixerBus::UI);
                     self.selected = idx.clone();
                     return MenuSelectionResult::Right(self.selected.clone(), entry, 1);
                 }
                 MenuEntry::DescriptiveOptions(_, _, _, _)
                     if (self.selected == idx && controller.trigger_left())
-                        || state.touch_controls.consume_click_in(left_entry_bounds) =>
+                        || clicked_in(controller, state, left_entry_bounds) =>
                 {
-                    state.sound_manager.play_sfx(1);
+                    state.sound_manager.play_sfx_on(1, MixerBus::UI);
                     return MenuSelectionResult::Left(self.selected.clone(), entry, -1);
                 }
                 MenuEntry::DescriptiveOptions(_, _, _, _) | MenuEntry::SaveData(_)
                     if (self.selected == idx && controller.trigger_right())
-                        || state.touch_controls.consume_click_in(right_entry_bounds) =>
+                        || clicked_in(controller, state, right_entry_bounds) =>
                 {
-                    state.sound_manager.play_sfx(1);
+                    state.sound_manager.play_sfx_on(1, MixerBus::UI);
+                    return MenuSelectionResult::Right(self.selected.clone(), entry, 1);
+                }
+                // no sound here, controls only react to left/right in some menus
+                MenuEntry::Control(_, _) if self.selected == idx && controller.trigger_left() => {
+                    return MenuSelectionResult::Left(self.selected.clone(), entry, -1);
+                }
+                MenuEntry::Control(_, _) if self.selected == idx && controller.trigger_right() => {
                     return MenuSelectionResult::Right(self.selected.clone(), entry, 1);
                 }
                 MenuEntry::Control(_, _) => {
-                    if self.selected == idx && controller.trigger_ok()
-                        || state.touch_controls.consume_click_in(entry_bounds)
-                    {
-                        state.sound_manager.play_sfx(18);
+                    if self.selected == idx && controller.trigger_ok() || clicked_in(controller, state, entry_bounds) {
+                        state.sound_manager.play_sfx_on(18, MixerBus::UI);
                         self.selected = idx.clone();
                         return MenuSelectionResult::Selected(idx, entry);
                     }
@@ -1,3 +1,4 @@
+use crate::components::dialogue_backlog::DialogueBacklog;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::graphics;
@@ -7,6 +8,7 @@ use crate::input::combined_menu_controller::CombinedMenuController;
 use crate::menu::MenuEntry;
 use crate::menu::{Menu, MenuSelectionResult};
 use crate::scene::title_scene::TitleScene;
+use crate::sound::mixer::MixerBus;
 
 use super::coop_menu::PlayerCountMenu;
 use super::settings_menu::SettingsMenu;
@@ -19,6 +21,7 @@ enum CurrentMenu {
     CoopMenu,
     SettingsMenu,
     ConfirmMenu,
+    Backlog,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -27,6 +30,7 @@ enum PauseMenuEntry {
     Retry,
     AddPlayer2,
     DropPlayer2,
+    Backlog,
     Settings,
     Title,
     Quit,
@@ -56,6 +60,7 @@ pub struct PauseMenu {
     current_menu: CurrentMenu,
     settings_menu: SettingsMenu,
     coop_menu: PlayerCountMenu,
+    backlog: DialogueBacklog,
     controller: CombinedMenuController,
     pause_menu: Menu<PauseMenuEntry>,
     confirm_menu: Menu<ConfirmMenuEntry>,
@@ -72,6 +77,7 @@ impl PauseMenu {
             current_menu: CurrentMenu::PauseMenu,
             settings_menu: SettingsMenu::new(),
             coop_menu: PlayerCountMenu::new(),
+            backlog: DialogueBacklog::new(),
             controller: CombinedMenuController::new(),
             pause_menu: main,
             confirm_menu: Menu::new(0, 0, 75, 0),
@@ -90,6 +96,8 @@ impl PauseMenu {
             .push_entry(PauseMenuEntry::Retry, MenuEntry::Active(state.loc.t("menus.pause_menu.retry").to_owned()));
         self.pause_menu.push_entry(PauseMenuEntry::AddPlayer2, MenuEntry::Hidden);
         self.pause_menu.push_entry(PauseMenuEntry::DropPlayer2, MenuEntry::Hidden);
+        self.pause_menu
+            .push_entry(PauseMenuEntry::Backlog, MenuEntry::Active(state.loc.t("menus.pause_menu.backlog").to_owned()));
         self.pause_menu.push_entry(
             PauseMenuEntry::Settings,
             MenuEntry::Active(state.loc.t("menus.pause_menu.options").to_owned()),
@@ -109,6 +117,7 @@ impl PauseMenu {
 
         self.settings_menu.init(state, ctx)?;
         self.coop_menu.init(state)?;
+        self.backlog.init(state);
 
         self.controller.update(state, ctx)?;
         self.controller.update_trigger();
@@ -165,7 +174,7 @@ impl PauseMenu {
 
     pub fn pause(&mut self, state: &mut SharedGameState) {
         self.is_paused = true;
-        state.sound_manager.play_sfx(5);
+        state.sound_manager.play_sfx_on(5, MixerBus::UI);
     }
 
     pub fn is_paused(&mut self) -> bool {
@@ -177,6 +186,7 @@ impl PauseMenu {
 
         self.controller.update(state, ctx)?;
         self.controller.update_trigger();
+        self.backlog.tick(state, ctx)?;
 
         // Shortcut for quick restart
         if ctx.keyboard_context.is_key_pressed(ScanCode::F2) {
@@ -218,6 +228,10 @@ impl PauseMenu {
                     state.player_count_modified_in_game = true;
                     self.should_update_coop_menu = true;
                 }
+                MenuSelectionResult::Selected(PauseMenuEntry::Backlog, _) => {
+                    self.backlog.open(state);
+                    self.current_menu = CurrentMenu::Backlog;
+                }
                 MenuSelectionResult::Selected(PauseMenuEntry::Settings, _) => {
                     self.current_menu = CurrentMenu::SettingsMenu;
                 }
@@ -262,6 +276,11 @@ impl PauseMenu {
                     ctx,
                 )?;
             }
+            CurrentMenu::Backlog => {
+                if !self.backlog.is_open() {
+                    self.current_menu = CurrentMenu::PauseMenu;
+                }
+            }
             CurrentMenu::ConfirmMenu => match self.confirm_menu.tick(&mut self.controller, state) {
                 MenuSelectionResult::Selected(ConfirmMenuEntry::Yes, _) => match self.pause_menu.selected {
                     PauseMenuEntry::Title => {
@@ -309,6 +328,9 @@ impl PauseMenu {
                 CurrentMenu::SettingsMenu => {
                     self.settings_menu.draw(state, ctx)?;
                 }
+                CurrentMenu::Backlog => {
+                    self.backlog.draw(state, ctx)?;
+                }
                 CurrentMenu::ConfirmMenu => {
                     graphics::set_clip_rect(ctx, Some(clip_rect))?;
                     self.confirm_menu.draw(state, ctx)?;
@@ -7,6 +7,7 @@ use crate::input::combined_menu_controller::CombinedMenuController;
 use crate::menu::coop_menu::PlayerCountMenu;
 use crate::menu::MenuEntry;
 use crate::menu::{Menu, MenuSelectionResult};
+use crate::sound::mixer::MixerBus;
 
 #[derive(Clone, Copy)]
 pub struct MenuSaveInfo {
@@ -16,11 +17,20 @@ pub struct MenuSaveInfo {
     pub weapon_count: usize,
     pub weapon_id: [u32; 8],
     pub difficulty: u8,
+    pub assist: u8,
 }
 
 impl Default for MenuSaveInfo {
     fn default() -> Self {
-        MenuSaveInfo { current_map: 0, max_life: 0, life: 0, weapon_count: 0, weapon_id: [0; 8], difficulty: 0 }
+        MenuSaveInfo {
+            current_map: 0,
+            max_life: 0,
+            life: 0,
+            weapon_count: 0,
+            weapon_id: [0; 8],
+            difficulty: 0,
+            assist: 0,
+        }
     }
 }
 
@@ -136,6 +146,7 @@ impl SaveSelectMenu {
                 save.weapon_count = loaded_save.weapon_data.iter().filter(|weapon| weapon.weapon_id != 0).count();
                 save.weapon_id = loaded_save.weapon_data.map(|weapon| weapon.weapon_id);
                 save.difficulty = loaded_save.difficulty;
+                save.assist = loaded_save.assist;
 
                 self.save_menu.push_entry(SaveMenuEntry::Load(iter), MenuEntry::SaveData(*save));
 
@@ -307,7 +318,7 @@ impl SaveSelectMenu {
                 MenuSelectionResult::Selected(DeleteConfirmMenuEntry::Yes, _) => {
                     match self.save_menu.selected {
                         SaveMenuEntry::Load(slot) => {
-                            state.sound_manager.play_sfx(17); // Player Death sfx
+                            state.sound_manager.play_sfx_on(17, MixerBus::UI); // Player Death sfx
                             filesystem::user_delete(ctx, state.get_save_filename(slot + 1).unwrap_or(String::new()))?;
                         }
                         _ => (),
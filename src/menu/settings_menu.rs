@@ -2,14 +2,16 @@ use itertools::Itertools;
 
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
-use crate::framework::graphics::VSyncMode;
+use crate::framework::graphics::{ColorblindFilter, UpscaleMode, VSyncMode};
 use crate::framework::{filesystem, graphics};
 use crate::game::shared_game_state::{CutsceneSkipMode, ScreenShakeIntensity, SharedGameState, TimingMode, WindowMode};
+use crate::game::speedrun::SpeedrunRoute;
 use crate::graphics::font::Font;
 use crate::input::combined_menu_controller::CombinedMenuController;
 use crate::menu::MenuEntry;
 use crate::menu::{Menu, MenuSelectionResult};
 use crate::scene::title_scene::TitleScene;
+use crate::sound::mixer::MixerBus;
 use crate::sound::InterpolationMode;
 use crate::util::browser;
 
@@ -26,6 +28,7 @@ enum CurrentMenu {
     SoundtrackMenu,
     LanguageMenu,
     BehaviorMenu,
+    AssistMenu,
     LinksMenu,
     AdvancedMenu,
     PortableMenu,
@@ -38,6 +41,7 @@ enum MainMenuEntry {
     Controls,
     Language,
     Behavior,
+    Assist,
     Links,
     Advanced,
     Back,
@@ -58,6 +62,11 @@ enum GraphicsMenuEntry {
     ScreenShake,
     MotionInterpolation,
     SubpixelScrolling,
+    Upscaling,
+    Scanlines,
+    CrtFilter,
+    ColorblindFilter,
+    CustomShader,
     OriginalTextures,
     SeasonalTextures,
     Renderer,
@@ -74,7 +83,11 @@ impl Default for GraphicsMenuEntry {
 enum SoundMenuEntry {
     MusicVolume,
     EffectsVolume,
+    AmbienceVolume,
+    UIVolume,
+    MusicDucking,
     BGMInterpolation,
+    PositionalSfx,
     Soundtrack,
     Back,
 }
@@ -118,6 +131,10 @@ enum BehaviorMenuEntry {
     CutsceneSkipMode,
     #[cfg(feature = "discord-rpc")]
     DiscordRPC,
+    SpeedrunTimer,
+    LiveSplit,
+    Minimap,
+    FogOfWar,
     Back,
 }
 
@@ -127,6 +144,23 @@ impl Default for BehaviorMenuEntry {
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum AssistMenuEntry {
+    DamageTaken,
+    InfiniteAir,
+    GameSpeed,
+    AutoFire,
+    PitProtection,
+    HighContrastText,
+    Back,
+}
+
+impl Default for AssistMenuEntry {
+    fn default() -> Self {
+        AssistMenuEntry::DamageTaken
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 enum LinksMenuEntry {
     Title,
@@ -180,10 +214,12 @@ pub struct SettingsMenu {
     soundtrack: Menu<SoundtrackMenuEntry>,
     language: Menu<LanguageMenuEntry>,
     behavior: Menu<BehaviorMenuEntry>,
+    assist: Menu<AssistMenuEntry>,
     links: Menu<LinksMenuEntry>,
     advanced: Menu<AdvancedMenuEntry>,
     portable: Menu<PortableMenuEntry>,
     controls_menu: ControlsMenu,
+    user_shaders: Vec<String>,
     pub on_title: bool,
 }
 
@@ -195,6 +231,9 @@ static GENERAL_LINK: &str = "https://discord.gg/cavestory";
 static MODDING_LINK: &str = "https://discord.gg/xRsWpz6";
 static GETPLUS_LINK: &str = "https://www.nicalis.com/games/cavestory+";
 
+static DAMAGE_TAKEN_OPTIONS: [u8; 4] = [100, 75, 50, 25];
+static GAME_SPEED_OPTIONS: [f64; 4] = [1.0, 0.9, 0.75, 0.5];
+
 impl SettingsMenu {
     pub fn new() -> SettingsMenu {
         let main = Menu::new(0, 0, 220, 0);
@@ -203,6 +242,7 @@ impl SettingsMenu {
         let soundtrack = Menu::new(0, 0, 260, 0);
         let language = Menu::new(0, 0, 120, 0);
         let behavior = Menu::new(0, 0, 220, 0);
+        let assist = Menu::new(0, 0, 220, 0);
         let links = Menu::new(0, 0, 220, 0);
         let advanced = Menu::new(0, 0, 220, 0);
         let portable = Menu::new(0, 0, 220, 0);
@@ -217,10 +257,12 @@ impl SettingsMenu {
             soundtrack,
             language,
             behavior,
+            assist,
             links,
             advanced,
             controls_menu,
             portable,
+            user_shaders: Vec::new(),
             on_title: false,
         }
     }
@@ -300,6 +342,79 @@ impl SettingsMenu {
             ),
         );
 
+        if graphics::supports_post_process(ctx) {
+            self.graphics.push_entry(
+                GraphicsMenuEntry::Upscaling,
+                MenuEntry::Options(
+                    state.loc.t("menus.options_menu.graphics_menu.upscaling.entry").to_owned(),
+                    state.settings.upscale_mode as usize,
+                    vec![
+                        state.loc.t("menus.options_menu.graphics_menu.upscaling.native").to_owned(),
+                        state.loc.t("menus.options_menu.graphics_menu.upscaling.integer").to_owned(),
+                        state.loc.t("menus.options_menu.graphics_menu.upscaling.sharp_bilinear").to_owned(),
+                    ],
+                ),
+            );
+            self.graphics.push_entry(
+                GraphicsMenuEntry::Scanlines,
+                MenuEntry::Toggle(
+                    state.loc.t("menus.options_menu.graphics_menu.scanlines").to_owned(),
+                    state.settings.scanlines,
+                ),
+            );
+            self.graphics.push_entry(
+                GraphicsMenuEntry::CrtFilter,
+                MenuEntry::Toggle(
+                    state.loc.t("menus.options_menu.graphics_menu.crt_filter").to_owned(),
+                    state.settings.crt_filter,
+                ),
+            );
+            self.graphics.push_entry(
+                GraphicsMenuEntry::ColorblindFilter,
+                MenuEntry::Options(
+                    state.loc.t("menus.options_menu.graphics_menu.colorblind_filter.entry").to_owned(),
+                    state.settings.colorblind_filter as usize,
+                    vec![
+                        state.loc.t("menus.options_menu.graphics_menu.colorblind_filter.off").to_owned(),
+                        state.loc.t("menus.options_menu.graphics_menu.colorblind_filter.protanopia").to_owned(),
+                        state.loc.t("menus.options_menu.graphics_menu.colorblind_filter.deuteranopia").to_owned(),
+                        state.loc.t("menus.options_menu.graphics_menu.colorblind_filter.tritanopia").to_owned(),
+                    ],
+                ),
+            );
+
+            self.user_shaders.clear();
+            if let Ok(dir) = filesystem::read_dir(ctx, "/shaders/postprocess/") {
+                for entry in dir {
+                    if entry.extension().map_or(false, |ext| ext == "glsl") {
+                        if let Some(name) = entry.file_stem() {
+                            self.user_shaders.push(name.to_string_lossy().to_string());
+                        }
+                    }
+                }
+            }
+            self.user_shaders.sort();
+
+            let mut shader_names = vec![state.loc.t("menus.options_menu.graphics_menu.custom_shader.none").to_owned()];
+            shader_names.extend(self.user_shaders.iter().cloned());
+
+            let current_shader = state
+                .settings
+                .user_shaders
+                .first()
+                .and_then(|name| self.user_shaders.iter().position(|s| s == name))
+                .map_or(0, |idx| idx + 1);
+
+            self.graphics.push_entry(
+                GraphicsMenuEntry::CustomShader,
+                MenuEntry::Options(
+                    state.loc.t("menus.options_menu.graphics_menu.custom_shader.entry").to_owned(),
+                    current_shader,
+                    shader_names,
+                ),
+            );
+        }
+
         // NS version uses two different maps, therefore we can't dynamically switch between graphics presets.
         if state.constants.supports_og_textures {
             if !state.constants.is_switch || self.on_title {
@@ -375,6 +490,9 @@ impl SettingsMenu {
             MenuEntry::Active(state.loc.t("menus.options_menu.behavior").to_owned()),
         );
 
+        self.main
+            .push_entry(MainMenuEntry::Assist, MenuEntry::Active(state.loc.t("menus.options_menu.assist").to_owned()));
+
         self.main
             .push_entry(MainMenuEntry::Links, MenuEntry::Active(state.loc.t("menus.options_menu.links").to_owned()));
 
@@ -476,6 +594,27 @@ impl SettingsMenu {
                 state.settings.sfx_volume,
             ),
         );
+        self.sound.push_entry(
+            SoundMenuEntry::AmbienceVolume,
+            MenuEntry::OptionsBar(
+                state.loc.t("menus.options_menu.sound_menu.ambience_volume").to_owned(),
+                state.settings.ambience_volume,
+            ),
+        );
+        self.sound.push_entry(
+            SoundMenuEntry::UIVolume,
+            MenuEntry::OptionsBar(
+                state.loc.t("menus.options_menu.sound_menu.ui_volume").to_owned(),
+                state.settings.ui_volume,
+            ),
+        );
+        self.sound.push_entry(
+            SoundMenuEntry::MusicDucking,
+            MenuEntry::OptionsBar(
+                state.loc.t("menus.options_menu.sound_menu.music_ducking").to_owned(),
+                state.settings.music_ducking,
+            ),
+        );
 
         self.sound.push_entry(
             SoundMenuEntry::BGMInterpolation,
@@ -498,6 +637,13 @@ impl SettingsMenu {
                 ],
             ),
         );
+        self.sound.push_entry(
+            SoundMenuEntry::PositionalSfx,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.sound_menu.positional_sfx").to_owned(),
+                state.settings.positional_sfx,
+            ),
+        );
         self.sound.push_entry(
             SoundMenuEntry::Soundtrack,
             MenuEntry::Active(
@@ -589,8 +735,92 @@ impl SettingsMenu {
             ),
         );
 
+        self.behavior.push_entry(
+            BehaviorMenuEntry::SpeedrunTimer,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.speedrun_timer").to_owned(),
+                state.settings.speedrun_timer,
+            ),
+        );
+
+        self.behavior.push_entry(
+            BehaviorMenuEntry::LiveSplit,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.livesplit").to_owned(),
+                state.settings.livesplit,
+            ),
+        );
+
+        self.behavior.push_entry(
+            BehaviorMenuEntry::Minimap,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.minimap").to_owned(),
+                state.settings.minimap,
+            ),
+        );
+
+        self.behavior.push_entry(
+            BehaviorMenuEntry::FogOfWar,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.fog_of_war").to_owned(),
+                state.settings.fog_of_war,
+            ),
+        );
+
         self.behavior.push_entry(BehaviorMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
 
+        self.assist.push_entry(
+            AssistMenuEntry::DamageTaken,
+            MenuEntry::Options(
+                state.loc.t("menus.options_menu.assist_menu.damage_taken").to_owned(),
+                DAMAGE_TAKEN_OPTIONS.iter().position(|&v| v == state.settings.assist.damage_taken).unwrap_or(0),
+                DAMAGE_TAKEN_OPTIONS.iter().map(|v| format!("{}%", v)).collect(),
+            ),
+        );
+
+        self.assist.push_entry(
+            AssistMenuEntry::InfiniteAir,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.assist_menu.infinite_air").to_owned(),
+                state.settings.assist.infinite_air,
+            ),
+        );
+
+        self.assist.push_entry(
+            AssistMenuEntry::GameSpeed,
+            MenuEntry::Options(
+                state.loc.t("menus.options_menu.assist_menu.game_speed").to_owned(),
+                GAME_SPEED_OPTIONS.iter().position(|&v| v == state.settings.speed).unwrap_or(0),
+                GAME_SPEED_OPTIONS.iter().map(|v| format!("{}%", (v * 100.0) as u32)).collect(),
+            ),
+        );
+
+        self.assist.push_entry(
+            AssistMenuEntry::AutoFire,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.assist_menu.auto_fire").to_owned(),
+                state.settings.assist.auto_fire,
+            ),
+        );
+
+        self.assist.push_entry(
+            AssistMenuEntry::PitProtection,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.assist_menu.pit_protection").to_owned(),
+                state.settings.assist.pit_protection,
+            ),
+        );
+
+        self.assist.push_entry(
+            AssistMenuEntry::HighContrastText,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.assist_menu.high_contrast_text").to_owned(),
+                state.settings.assist.high_contrast_text,
+            ),
+        );
+
+        self.assist.push_entry(AssistMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
+
         self.links.push_entry(LinksMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
 
         self.controls_menu.init(state, ctx)?;
@@ -631,6 +861,11 @@ impl SettingsMenu {
         self.behavior.x = ((state.canvas_size.0 - self.behavior.width as f32) / 2.0).floor() as isize;
         self.behavior.y = 30 + ((state.canvas_size.1 - self.behavior.height as f32) / 2.0).floor() as isize;
 
+        self.assist.update_width(state);
+        self.assist.update_height(state);
+        self.assist.x = ((state.canvas_size.0 - self.assist.width as f32) / 2.0).floor() as isize;
+        self.assist.y = 30 + ((state.canvas_size.1 - self.assist.height as f32) / 2.0).floor() as isize;
+
         self.links.update_width(state);
         self.links.update_height(state);
         self.links.x = ((state.canvas_size.0 - self.links.width as f32) / 2.0).floor() as isize;
@@ -673,6 +908,9 @@ impl SettingsMenu {
                 MenuSelectionResult::Selected(MainMenuEntry::Behavior, _) => {
                     self.current = CurrentMenu::BehaviorMenu;
                 }
+                MenuSelectionResult::Selected(MainMenuEntry::Assist, _) => {
+                    self.current = CurrentMenu::AssistMenu;
+                }
                 MenuSelectionResult::Selected(MainMenuEntry::Links, _) => {
                     self.current = CurrentMenu::LinksMenu;
                 }
@@ -737,6 +975,7 @@ impl SettingsMenu {
                 MenuSelectionResult::Selected(GraphicsMenuEntry::LightingEffects, toggle) => {
                     if let MenuEntry::Toggle(_, value) = toggle {
                         state.settings.shader_effects = !state.settings.shader_effects;
+                        state.apply_post_process(ctx)?;
                         let _ = state.settings.save(ctx);
 
                         *value = state.settings.shader_effects;
@@ -795,6 +1034,117 @@ impl SettingsMenu {
                         *value = state.settings.subpixel_coords;
                     }
                 }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::Upscaling, toggle)
+                | MenuSelectionResult::Right(GraphicsMenuEntry::Upscaling, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        let (new_mode, new_value) = match *value {
+                            0 => (UpscaleMode::Integer, 1),
+                            1 => (UpscaleMode::SharpBilinear, 2),
+                            _ => (UpscaleMode::Native, 0),
+                        };
+
+                        *value = new_value;
+                        state.settings.upscale_mode = new_mode;
+                        state.handle_resize(ctx)?;
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Left(GraphicsMenuEntry::Upscaling, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        let (new_mode, new_value) = match *value {
+                            0 => (UpscaleMode::SharpBilinear, 2),
+                            1 => (UpscaleMode::Native, 0),
+                            _ => (UpscaleMode::Integer, 1),
+                        };
+
+                        *value = new_value;
+                        state.settings.upscale_mode = new_mode;
+                        state.handle_resize(ctx)?;
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::Scanlines, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.scanlines = !state.settings.scanlines;
+                        state.apply_post_process(ctx)?;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.scanlines;
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::CrtFilter, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.crt_filter = !state.settings.crt_filter;
+                        state.apply_post_process(ctx)?;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.crt_filter;
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::ColorblindFilter, toggle)
+                | MenuSelectionResult::Right(GraphicsMenuEntry::ColorblindFilter, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        let (new_filter, new_value) = match *value {
+                            0 => (ColorblindFilter::Protanopia, 1),
+                            1 => (ColorblindFilter::Deuteranopia, 2),
+                            2 => (ColorblindFilter::Tritanopia, 3),
+                            _ => (ColorblindFilter::Off, 0),
+                        };
+
+                        *value = new_value;
+                        state.settings.colorblind_filter = new_filter;
+                        state.apply_post_process(ctx)?;
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Left(GraphicsMenuEntry::ColorblindFilter, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        let (new_filter, new_value) = match *value {
+                            0 => (ColorblindFilter::Tritanopia, 3),
+                            1 => (ColorblindFilter::Off, 0),
+                            2 => (ColorblindFilter::Protanopia, 1),
+                            _ => (ColorblindFilter::Deuteranopia, 2),
+                        };
+
+                        *value = new_value;
+                        state.settings.colorblind_filter = new_filter;
+                        state.apply_post_process(ctx)?;
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::CustomShader, toggle)
+                | MenuSelectionResult::Right(GraphicsMenuEntry::CustomShader, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        let count = self.user_shaders.len() + 1;
+                        *value = (*value + 1) % count;
+
+                        state.settings.user_shaders = match *value {
+                            0 => Vec::new(),
+                            idx => vec![self.user_shaders[idx - 1].clone()],
+                        };
+                        state.apply_post_process(ctx)?;
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Left(GraphicsMenuEntry::CustomShader, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        let count = self.user_shaders.len() + 1;
+                        *value = (*value + count - 1) % count;
+
+                        state.settings.user_shaders = match *value {
+                            0 => Vec::new(),
+                            idx => vec![self.user_shaders[idx - 1].clone()],
+                        };
+                        state.apply_post_process(ctx)?;
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
                 MenuSelectionResult::Selected(GraphicsMenuEntry::OriginalTextures, toggle) => {
                     if let MenuEntry::Toggle(_, value) = toggle {
                         state.settings.original_textures = !state.settings.original_textures;
@@ -843,6 +1193,36 @@ impl SettingsMenu {
                         let _ = state.settings.save(ctx);
                     }
                 }
+                MenuSelectionResult::Left(SoundMenuEntry::AmbienceVolume, vol, direction)
+                | MenuSelectionResult::Right(SoundMenuEntry::AmbienceVolume, vol, direction) => {
+                    if let MenuEntry::OptionsBar(_, value) = vol {
+                        *value = (*value * 10.0 + (direction as f32)).clamp(0.0, 10.0) / 10.0;
+                        state.settings.ambience_volume = *value;
+                        state.sound_manager.set_bus_volume(MixerBus::Ambience, *value);
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Left(SoundMenuEntry::UIVolume, vol, direction)
+                | MenuSelectionResult::Right(SoundMenuEntry::UIVolume, vol, direction) => {
+                    if let MenuEntry::OptionsBar(_, value) = vol {
+                        *value = (*value * 10.0 + (direction as f32)).clamp(0.0, 10.0) / 10.0;
+                        state.settings.ui_volume = *value;
+                        state.sound_manager.set_bus_volume(MixerBus::UI, *value);
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Left(SoundMenuEntry::MusicDucking, duck, direction)
+                | MenuSelectionResult::Right(SoundMenuEntry::MusicDucking, duck, direction) => {
+                    if let MenuEntry::OptionsBar(_, value) = duck {
+                        *value = (*value * 10.0 + (direction as f32)).clamp(0.0, 10.0) / 10.0;
+                        state.settings.music_ducking = *value;
+                        state.sound_manager.set_bus_duck_amount(MixerBus::Music, *value);
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
                 MenuSelectionResult::Selected(SoundMenuEntry::BGMInterpolation, toggle)
                 | MenuSelectionResult::Right(SoundMenuEntry::BGMInterpolation, toggle, _) => {
                     if let MenuEntry::DescriptiveOptions(_, value, _, _) = toggle {
@@ -876,6 +1256,15 @@ impl SettingsMenu {
                         let _ = state.settings.save(ctx);
                     }
                 }
+                MenuSelectionResult::Selected(SoundMenuEntry::PositionalSfx, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.positional_sfx = !state.settings.positional_sfx;
+                        state.sound_manager.set_positional_sfx(state.settings.positional_sfx);
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.positional_sfx;
+                    }
+                }
                 MenuSelectionResult::Selected(SoundMenuEntry::Soundtrack, _) => {
                     let mut active_soundtrack = SoundtrackMenuEntry::Soundtrack(0);
 
@@ -1021,11 +1410,120 @@ impl SettingsMenu {
                         }
                     }
                 }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::SpeedrunTimer, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.speedrun_timer = !state.settings.speedrun_timer;
+                        let _ = state.settings.save(ctx);
+
+                        if state.settings.speedrun_timer {
+                            SpeedrunRoute::create_default_file(ctx);
+                        }
+
+                        *value = state.settings.speedrun_timer;
+                    }
+                }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::LiveSplit, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.livesplit = !state.settings.livesplit;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.livesplit;
+                        state.speedrun.set_livesplit(state.settings.livesplit, &state.settings.livesplit_address);
+                    }
+                }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::Minimap, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.minimap = !state.settings.minimap;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.minimap;
+                    }
+                }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::FogOfWar, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.fog_of_war = !state.settings.fog_of_war;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.fog_of_war;
+                    }
+                }
                 MenuSelectionResult::Selected(BehaviorMenuEntry::Back, _) | MenuSelectionResult::Canceled => {
                     self.current = CurrentMenu::MainMenu;
                 }
                 _ => (),
             },
+            CurrentMenu::AssistMenu => match self.assist.tick(controller, state) {
+                MenuSelectionResult::Selected(AssistMenuEntry::DamageTaken, toggle)
+                | MenuSelectionResult::Right(AssistMenuEntry::DamageTaken, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + 1) % DAMAGE_TAKEN_OPTIONS.len();
+                        state.settings.assist.damage_taken = DAMAGE_TAKEN_OPTIONS[*value];
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Left(AssistMenuEntry::DamageTaken, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + DAMAGE_TAKEN_OPTIONS.len() - 1) % DAMAGE_TAKEN_OPTIONS.len();
+                        state.settings.assist.damage_taken = DAMAGE_TAKEN_OPTIONS[*value];
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Selected(AssistMenuEntry::InfiniteAir, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.assist.infinite_air = !state.settings.assist.infinite_air;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.assist.infinite_air;
+                    }
+                }
+                MenuSelectionResult::Selected(AssistMenuEntry::GameSpeed, toggle)
+                | MenuSelectionResult::Right(AssistMenuEntry::GameSpeed, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + 1) % GAME_SPEED_OPTIONS.len();
+                        state.set_speed(GAME_SPEED_OPTIONS[*value]);
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Left(AssistMenuEntry::GameSpeed, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + GAME_SPEED_OPTIONS.len() - 1) % GAME_SPEED_OPTIONS.len();
+                        state.set_speed(GAME_SPEED_OPTIONS[*value]);
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Selected(AssistMenuEntry::AutoFire, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.assist.auto_fire = !state.settings.assist.auto_fire;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.assist.auto_fire;
+                    }
+                }
+                MenuSelectionResult::Selected(AssistMenuEntry::PitProtection, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.assist.pit_protection = !state.settings.assist.pit_protection;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.assist.pit_protection;
+                    }
+                }
+                MenuSelectionResult::Selected(AssistMenuEntry::HighContrastText, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.assist.high_contrast_text = !state.settings.assist.high_contrast_text;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.assist.high_contrast_text;
+                    }
+                }
+                MenuSelectionResult::Selected(AssistMenuEntry::Back, _) | MenuSelectionResult::Canceled => {
+                    self.current = CurrentMenu::MainMenu;
+                }
+                _ => (),
+            },
             CurrentMenu::LinksMenu => match self.links.tick(controller, state) {
                 MenuSelectionResult::Selected(LinksMenuEntry::Link(url), _) => {
                     if let Err(e) = browser::open(&url) {
@@ -1083,6 +1581,7 @@ impl SettingsMenu {
             CurrentMenu::ControlsMenu => self.controls_menu.draw(state, ctx)?,
             CurrentMenu::LanguageMenu => self.language.draw(state, ctx)?,
             CurrentMenu::BehaviorMenu => self.behavior.draw(state, ctx)?,
+            CurrentMenu::AssistMenu => self.assist.draw(state, ctx)?,
             CurrentMenu::LinksMenu => self.links.draw(state, ctx)?,
             CurrentMenu::AdvancedMenu => self.advanced.draw(state, ctx)?,
             CurrentMenu::PortableMenu => self.portable.draw(state, ctx)?,
@@ -197,4 +197,12 @@ impl ModList {
             "NoName"
         }
     }
+
+    pub fn get_id_from_path(&self, mod_path: String) -> &str {
+        if let Some(mod_sel) = self.mods.iter().find(|x| x.path == mod_path) {
+            &mod_sel.id
+        } else {
+            "unknown"
+        }
+    }
 }
@@ -8,6 +8,7 @@ use crate::common::{interpolate_fix9_scale, Color, Direction, Rect};
 use crate::components::background::Background;
 use crate::components::boss_life_bar::BossLifeBar;
 use crate::components::credits::Credits;
+use crate::components::dialogue_backlog::DialogueBacklog;
 use crate::components::draw_common::Alignment;
 use crate::components::fade::Fade;
 use crate::components::falling_island::FallingIsland;
@@ -15,6 +16,7 @@ use crate::components::flash::Flash;
 use crate::components::hud::HUD;
 use crate::components::inventory::InventoryUI;
 use crate::components::map_system::MapSystem;
+use crate::components::minimap::Minimap;
 use crate::components::nikumaru::NikumaruCounter;
 use crate::components::replay::Replay;
 use crate::components::stage_select::StageSelect;
@@ -28,9 +30,11 @@ use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::graphics::{draw_rect, BlendMode, FilterMode};
 use crate::framework::keyboard::ScanCode;
+use crate::framework::mouse;
 use crate::framework::ui::Components;
 use crate::framework::{filesystem, gamepad, graphics};
 use crate::game::caret::CaretType;
+use crate::game::challenge::{ChallengeStatus, Leaderboard, LeaderboardEntry};
 use crate::game::frame::{Frame, UpdateTarget};
 use crate::game::inventory::{Inventory, TakeExperienceResult};
 use crate::game::map::WaterParams;
@@ -39,10 +43,12 @@ use crate::game::npc::list::NPCList;
 use crate::game::npc::{NPCLayer, NPC};
 use crate::game::physics::{PhysicalEntity, OFFSETS};
 use crate::game::player::{ControlMode, Player, TargetPlayer};
+use crate::game::run_timer::{format_millis, format_time};
 use crate::game::scripting::tsc::credit_script::CreditScriptVM;
 use crate::game::scripting::tsc::text_script::{ScriptMode, TextScriptExecutionState, TextScriptVM};
 use crate::game::settings::ControllerType;
 use crate::game::shared_game_state::{CutsceneSkipMode, PlayerCount, ReplayState, SharedGameState, TileSize};
+use crate::game::speedrun::SpeedrunStatus;
 use crate::game::stage::{BackgroundType, Stage, StageTexturePaths};
 use crate::game::weapon::bullet::BulletManager;
 use crate::game::weapon::{Weapon, WeaponType};
@@ -52,6 +58,7 @@ use crate::input::touch_controls::TouchControlType;
 use crate::menu::pause_menu::PauseMenu;
 use crate::scene::title_scene::TitleScene;
 use crate::scene::Scene;
+use crate::sound::spatial::SfxPositioning;
 use crate::util::rng::RNG;
 
 pub struct GameScene {
@@ -66,6 +73,7 @@ pub struct GameScene {
     pub falling_island: FallingIsland,
     pub inventory_ui: InventoryUI,
     pub map_system: MapSystem,
+    pub minimap: Minimap,
     pub hud_player1: HUD,
     pub hud_player2: HUD,
     pub nikumaru: NikumaruCounter,
@@ -86,6 +94,7 @@ pub struct GameScene {
     pub lighting_mode: LightingMode,
     pub intro_mode: bool,
     pub pause_menu: PauseMenu,
+    dialogue_backlog: DialogueBacklog,
     pub stage_textures: Rc<RefCell<StageTexturePaths>>,
     pub replay: Replay,
     map_name_counter: u16,
@@ -102,6 +111,8 @@ pub enum LightingMode {
 
 const P2_OFFSCREEN_TEXT: &'static str = "P2";
 const CUTSCENE_SKIP_WAIT: u16 = 50;
+/// Ticks the result of a challenge stays on screen before returning to title.
+const CHALLENGE_END_WAIT: u16 = 250;
 
 impl GameScene {
     pub fn new(state: &mut SharedGameState, ctx: &mut Context, id: usize) -> GameResult<Self> {
@@ -162,6 +173,7 @@ impl GameScene {
             falling_island: FallingIsland::new(),
             inventory_ui: InventoryUI::new(),
             map_system: MapSystem::new(),
+            minimap: Minimap::new(),
             hud_player1: HUD::new(Alignment::Left),
             hud_player2: HUD::new(Alignment::Right),
             nikumaru: NikumaruCounter::new(),
@@ -178,6 +190,7 @@ impl GameScene {
             lighting_mode: LightingMode::None,
             intro_mode: false,
             pause_menu: PauseMenu::new(),
+            dialogue_backlog: DialogueBacklog::new(),
             stage_textures,
             map_name_counter: 0,
             skip_counter: 0,
@@ -316,6 +329,21 @@ impl GameScene {
         Ok(())
     }
 
+    /// Draws a crosshair at the mouse cursor, the system cursor is hidden in fullscreen.
+    fn draw_crosshair(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let (x, y) = state.screen_to_canvas(ctx, mouse::position(ctx));
+        let (x, y) = ((x * state.scale) as isize, (y * state.scale) as isize);
+        let size = (3.0 * state.scale) as isize;
+        let width = state.scale.max(1.0) as isize;
+        let color = Color::from_rgba(255, 255, 255, 200);
+
+        draw_rect(ctx, Rect::new(x - size, y, x + size + width, y + width), color)?;
+        draw_rect(ctx, Rect::new(x, y - size, x + width, y), color)?;
+        draw_rect(ctx, Rect::new(x, y + width, x + width, y + size + width), color)?;
+
+        Ok(())
+    }
+
     fn draw_black_bars(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
         let (x, y) = self.frame.xy_interpolated(state.frame_time);
         let (x, y) = (x * state.scale, y * state.scale);
@@ -555,7 +583,7 @@ impl GameScene {
                             _ => ((150u8, 150u8, 150u8), 0.92),
                         };
 
-                        let (_, gun_off_y) = player.skin.get_gun_offset();
+                        let (_, gun_off_y) = player.skin.get_gun_offset(player.current_weapon);
 
                         self.draw_light_raycast(
                             state.tile_size,
@@ -1181,7 +1209,12 @@ impl GameScene {
                     } else {
                         if npc.shock < 14 {
                             if let Some(table_entry) = state.npc_table.get_entry(npc.npc_type) {
-                                state.sound_manager.play_sfx(table_entry.hurt_sound);
+                                state.sound_manager.play_sfx_at(
+                                    table_entry.hurt_sound,
+                                    npc.x,
+                                    npc.y,
+                                    SfxPositioning::DEFAULT,
+                                );
                             }
 
                             npc.shock = 16;
@@ -1277,7 +1310,12 @@ impl GameScene {
                             state.control_flags.set_interactions_disabled(true);
                             state.textscript_vm.start_script(npc.event_num);
                         } else {
-                            state.sound_manager.play_sfx(self.boss.death_sound[idx]);
+                            state.sound_manager.play_sfx_at(
+                                self.boss.death_sound[idx],
+                                npc.x,
+                                npc.y,
+                                SfxPositioning::PAN_ONLY,
+                            );
 
                             let destroy_count = 4usize * (2usize).pow((npc.size as u32).saturating_sub(1));
 
@@ -1296,7 +1334,12 @@ impl GameScene {
                             for _ in 0..3 {
                                 state.create_caret(bullet.x, bullet.y, CaretType::HurtParticles, Direction::Left);
                             }
-                            state.sound_manager.play_sfx(self.boss.hurt_sound[idx]);
+                            state.sound_manager.play_sfx_at(
+                                self.boss.hurt_sound[idx],
+                                npc.x,
+                                npc.y,
+                                SfxPositioning::PAN_ONLY,
+                            );
                         }
 
                         npc.shock = 8;
@@ -1325,6 +1368,8 @@ impl GameScene {
     }
 
     fn tick_world(&mut self, state: &mut SharedGameState) -> GameResult {
+        state.assist_used.0 |= state.get_assist().flags(state.settings.speed).0;
+
         self.nikumaru.tick(state, &self.player1)?;
         self.background.tick()?;
         self.hud_player1.visible = self.player1.cond.alive();
@@ -1334,14 +1379,14 @@ impl GameScene {
 
         self.player1.current_weapon = {
             if let Some(weapon) = self.inventory_player1.get_current_weapon_mut() {
-                weapon.wtype as u8
+                weapon.wtype.id()
             } else {
                 0
             }
         };
         self.player2.current_weapon = {
             if let Some(weapon) = self.inventory_player2.get_current_weapon_mut() {
-                weapon.wtype as u8
+                weapon.wtype.id()
             } else {
                 0
             }
@@ -1520,6 +1565,16 @@ impl GameScene {
         self.tilemap.tick()?;
 
         self.frame.update(state, &self.stage);
+        self.update_sfx_listener(state);
+
+        for (pos, player) in state.player_canvas_pos.iter_mut().zip([&self.player1, &self.player2]) {
+            *pos = ((player.x - self.frame.x) as f32 / 512.0, (player.y - self.frame.y) as f32 / 512.0);
+        }
+
+        if !self.intro_mode {
+            self.update_exploration(state);
+        }
+        self.minimap.tick();
 
         if state.control_flags.control_enabled() {
             self.hud_player1.tick(state, (&self.player1, &mut self.inventory_player1))?;
@@ -1551,6 +1606,199 @@ impl GameScene {
         Ok(())
     }
 
+    fn update_sfx_listener(&self, state: &mut SharedGameState) {
+        let half_width = state.canvas_size.0 as i32 * 0x200 / 2;
+
+        let points = if self.player2.cond.alive() && !self.player2.cond.hidden() {
+            [Some((self.player1.x, self.player1.y)), Some((self.player2.x, self.player2.y))]
+        } else {
+            let center_x = self.frame.x + half_width;
+            let center_y = self.frame.y + state.canvas_size.1 as i32 * 0x200 / 2;
+
+            [Some((center_x, center_y)), None]
+        };
+
+        state.sound_manager.set_listener(points, half_width);
+    }
+
+    /// Marks the parts of the stage that are on screen or close to the players as explored.
+    fn update_exploration(&self, state: &mut SharedGameState) {
+        const PLAYER_RADIUS: isize = 3;
+
+        let tile_size = self.stage.map.tile_size.as_int() * 0x200;
+        let explored = state.exploration.stage_mut(self.stage_id as u16, self.stage.map.width, self.stage.map.height);
+
+        let left = self.frame.x / tile_size;
+        let top = self.frame.y / tile_size;
+        let right = (self.frame.x + state.canvas_size.0 as i32 * 0x200) / tile_size + 1;
+        let bottom = (self.frame.y + state.canvas_size.1 as i32 * 0x200) / tile_size + 1;
+        explored.reveal(left as isize, top as isize, right as isize, bottom as isize);
+
+        for player in [&self.player1, &self.player2] {
+            if !player.cond.alive() || player.cond.hidden() {
+                continue;
+            }
+
+            let x = (player.x / tile_size) as isize;
+            let y = (player.y / tile_size) as isize;
+            explored.reveal(x - PLAYER_RADIUS, y - PLAYER_RADIUS, x + PLAYER_RADIUS + 1, y + PLAYER_RADIUS + 1);
+        }
+    }
+
+    fn tick_challenge(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let run = match &mut state.challenge {
+            Some(run) => run,
+            None => return Ok(()),
+        };
+
+        let prev_status = run.status;
+        let status =
+            run.tick(&state.game_flags, self.stage_id as u16, &self.boss, &self.player1, &mut self.inventory_player1);
+        let end_counter = run.end_counter;
+
+        if prev_status == ChallengeStatus::Running && status != ChallengeStatus::Running {
+            self.finish_challenge(state, ctx, status)?;
+        }
+
+        if end_counter >= CHALLENGE_END_WAIT {
+            state.next_scene = Some(Box::new(TitleScene::new()));
+        }
+
+        Ok(())
+    }
+
+    fn finish_challenge(
+        &mut self,
+        state: &mut SharedGameState,
+        ctx: &mut Context,
+        status: ChallengeStatus,
+    ) -> GameResult {
+        let mut new_record = false;
+
+        // watching a replay shouldn't put it on the leaderboard again
+        if status == ChallengeStatus::Finished && state.replay_state == ReplayState::Recording {
+            if let Some(run) = &state.challenge {
+                let tps = state.settings.timing_mode.get_tps() as u32;
+                let mut leaderboard = Leaderboard::load(ctx, &run.def.id);
+
+                new_record = leaderboard.insert(LeaderboardEntry::new(run, tps, state.assist_used.0)) == Some(0);
+                leaderboard.save(ctx, &run.def.id)?;
+            }
+        }
+
+        state.sound_manager.play_sfx(if status == ChallengeStatus::Finished { 27 } else { 17 });
+
+        if state.replay_state == ReplayState::Recording {
+            self.replay.stop_recording(state, ctx, new_record)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_challenge_timer(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let run = match &state.challenge {
+            Some(run) => run,
+            None => return Ok(()),
+        };
+
+        let tps = state.settings.timing_mode.get_tps() as u32;
+        let mut lines = vec![format_time(run.ticks, tps)];
+
+        if let Some(split) = run.splits.last() {
+            let name = state.stages.get(split.stage as usize).map_or("", |stage| stage.name.as_str());
+            let mut line = format!("{} {}", name, format_time(split.ticks, tps));
+
+            if let Some(delta) = run.split_delta() {
+                let sign = if delta > 0 { '+' } else { '-' };
+                line.push_str(&format!(" ({}{})", sign, format_time(delta.unsigned_abs() as u32, tps)));
+            }
+
+            lines.push(line);
+        }
+
+        match run.status {
+            ChallengeStatus::Running => {}
+            ChallengeStatus::Failed => lines.push(state.loc.t("game.challenge_failed").to_owned()),
+            ChallengeStatus::Finished => lines.push(state.loc.t("game.challenge_finished").to_owned()),
+        }
+
+        for (idx, line) in lines.iter().enumerate() {
+            state
+                .font
+                .builder()
+                .position(0.0, 8.0 + idx as f32 * state.font.line_height())
+                .center(state.canvas_size.0)
+                .shadow(true)
+                .draw(line, ctx, &state.constants, &mut state.texture_set)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_speedrun_splits(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        const VISIBLE_SPLITS: usize = 5;
+        const WIDTH: f32 = 120.0;
+
+        if state.speedrun.status == SpeedrunStatus::NotRunning {
+            return Ok(());
+        }
+
+        let timer = &state.speedrun;
+        let line_height = state.font.line_height();
+        let x = state.canvas_size.0 - WIDTH - 4.0;
+        let current = timer.splits.len();
+        let first =
+            current.saturating_sub(VISIBLE_SPLITS - 1).min(timer.route.splits.len().saturating_sub(VISIBLE_SPLITS));
+
+        let mut rows = Vec::new();
+        for (idx, split) in timer.route.splits.iter().enumerate().skip(first).take(VISIBLE_SPLITS) {
+            let (text, color) = if let Some(delta) = timer.pb_delta(idx) {
+                let sign = if delta > 0 { '+' } else { '-' };
+                let color = if timer.is_gold(idx) {
+                    (255, 216, 0, 255)
+                } else if delta > 0 {
+                    (255, 96, 96, 255)
+                } else {
+                    (96, 255, 96, 255)
+                };
+
+                (format!("{}{}", sign, format_millis(delta.unsigned_abs())), color)
+            } else if let Some(&time) = timer.splits.get(idx) {
+                (format_millis(time), (255, 255, 255, 255))
+            } else if let Some(&pb) = timer.records.pb.get(idx) {
+                (format_millis(pb), (160, 160, 160, 255))
+            } else {
+                ("-".to_owned(), (160, 160, 160, 255))
+            };
+
+            let name_color = if idx == current { (255, 255, 255, 255) } else { (160, 160, 160, 255) };
+            rows.push((split.name.clone(), name_color, text, color));
+        }
+
+        let color = if timer.status == SpeedrunStatus::Finished { (96, 96, 255, 255) } else { (255, 255, 255, 255) };
+        rows.push((String::new(), color, format_millis(timer.millis()), color));
+
+        for (idx, (name, name_color, text, color)) in rows.iter().enumerate() {
+            let y = 4.0 + idx as f32 * line_height;
+            let text_width = state.font.builder().compute_width(text);
+
+            state.font.builder().position(x, y).shadow(true).color(*name_color).draw(
+                name,
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+            state.font.builder().position(x + WIDTH - text_width, y).shadow(true).color(*color).draw(
+                text,
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+        }
+
+        Ok(())
+    }
+
     fn draw_debug_object(
         &self,
         entity: &dyn PhysicalEntity,
@@ -1641,7 +1889,8 @@ impl GameScene {
 
 impl Scene for GameScene {
     fn init(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
-        if state.mod_path.is_some() && state.replay_state == ReplayState::Recording {
+        let is_challenge = state.mod_path.is_some() || state.challenge.is_some();
+        if is_challenge && state.replay_state == ReplayState::Recording {
             self.replay.initialize_recording(state);
         }
         if state.player_count == PlayerCount::Two {
@@ -1650,12 +1899,22 @@ impl Scene for GameScene {
             self.drop_player2();
         }
 
-        if state.mod_path.is_some() {
+        self.player1.reset_safe_position();
+        self.player2.reset_safe_position();
+
+        if let Some(run) = &mut state.challenge {
+            run.enter_stage(self.stage_id as u16);
+        }
+        state.speedrun.scene_loaded();
+
+        if is_challenge {
             if let ReplayState::Playback(replay_kind) = state.replay_state {
                 self.replay.initialize_playback(state, ctx, replay_kind)?;
             }
         }
 
+        self.npc_list.set_max_capacity(state.constants.capacity.npc);
+        self.bullet_manager.max_bullets = state.constants.capacity.bullet;
         self.npc_list.set_rng_seed(state.game_rng.next());
         self.boss.init_rng(state.game_rng.next());
         state.textscript_vm.set_scene_script(self.stage.load_text_script(
@@ -1668,8 +1927,8 @@ impl Scene for GameScene {
         #[cfg(feature = "scripting-lua")]
         state.lua.set_game_scene(self as *mut _);
 
-        self.player1.controller = state.settings.create_player1_controller();
-        self.player2.controller = state.settings.create_player2_controller();
+        self.player1.controller = state.settings.create_player1_game_controller();
+        self.player2.controller = state.settings.create_player2_game_controller();
 
         let npcs = self.stage.load_npcs(&state.constants.base_paths, ctx)?;
         for npc_data in npcs.iter() {
@@ -1737,6 +1996,7 @@ impl Scene for GameScene {
         };
 
         self.pause_menu.init(state, ctx)?;
+        self.dialogue_backlog.init(state);
         self.whimsical_star.init(&self.player1);
 
         #[cfg(feature = "discord-rpc")]
@@ -1806,6 +2066,24 @@ impl Scene for GameScene {
             return Ok(());
         }
 
+        // the tick the backlog closes on is skipped like the one the pause menu closes on, otherwise the button
+        // that closed it would trigger a jump or advance the text box. player controllers are still updated above,
+        // so the button only counts as held on the next tick.
+        let backlog_was_open = self.dialogue_backlog.is_open();
+        self.dialogue_backlog.tick(state, ctx)?;
+        if backlog_was_open || self.dialogue_backlog.is_open() {
+            return Ok(());
+        }
+
+        if state.textscript_vm.flags.render()
+            && state.textscript_vm.mode == ScriptMode::Map
+            && !matches!(state.replay_state, ReplayState::Playback(_))
+            && self.player1.controller.trigger_map()
+        {
+            self.dialogue_backlog.open(state);
+            return Ok(());
+        }
+
         if state.replay_state == ReplayState::Recording {
             self.replay.tick(state, (ctx, &mut self.player1))?;
         }
@@ -1816,6 +2094,7 @@ impl Scene for GameScene {
             | TextScriptExecutionState::WaitInput(_, _, _)
             | TextScriptExecutionState::WaitStanding(_, _)
             | TextScriptExecutionState::WaitFade(_, _)
+            | TextScriptExecutionState::WaitScript(_, _)
             | TextScriptExecutionState::Msg(_, _, _, _)
             | TextScriptExecutionState::MsgNewLine(_, _, _, _, _)
             | TextScriptExecutionState::FallingIsland(_, _, _, _, _, _)
@@ -1849,7 +2128,10 @@ impl Scene for GameScene {
             }
         }
 
-        self.map_system.tick(state, ctx, &self.stage, [&self.player1, &self.player2])?;
+        self.tick_challenge(state, ctx)?;
+        state.speedrun.tick(ctx, &state.game_flags, self.stage_id as u16, &self.boss);
+
+        self.map_system.tick(state, ctx, &self.stage, self.stage_id, [&self.player1, &self.player2])?;
 
         match state.textscript_vm.mode {
             ScriptMode::Map | ScriptMode::Debug => {
@@ -1886,6 +2168,7 @@ impl Scene for GameScene {
         self.fade.tick(state, ())?;
         self.flash.tick(state, ())?;
         self.text_boxes.tick(state, ())?;
+        state.sound_manager.set_ducking(state.textscript_vm.flags.render());
 
         #[cfg(feature = "scripting-lua")]
         state.lua.scene_tick();
@@ -2047,6 +2330,11 @@ impl Scene for GameScene {
                 self.hud_player1.draw(state, ctx, &self.frame)?;
                 self.hud_player2.draw(state, ctx, &self.frame)?;
                 self.boss_life_bar.draw(state, ctx, &self.frame)?;
+                self.minimap.draw(state, ctx, &self.stage, self.stage_id, [&self.player1, &self.player2])?;
+
+                if state.settings.mouse.aim {
+                    self.draw_crosshair(state, ctx)?;
+                }
 
                 if self.player2.cond.alive() && !self.player2.cond.hidden() {
                     if self.player2.teleport_counter < state.settings.timing_mode.get_tps() as u16 * 3
@@ -2147,11 +2435,13 @@ impl Scene for GameScene {
             _ => {}
         }
 
-        self.map_system.draw(state, ctx, &self.stage, [&self.player1, &self.player2])?;
+        self.map_system.draw(state, ctx, &self.stage, self.stage_id, [&self.player1, &self.player2])?;
         self.fade.draw(state, ctx, &self.frame)?;
 
         if state.textscript_vm.mode == ScriptMode::Map || state.textscript_vm.mode == ScriptMode::Debug {
             self.nikumaru.draw(state, ctx, &self.frame)?;
+            self.draw_challenge_timer(state, ctx)?;
+            self.draw_speedrun_splits(state, ctx)?;
         }
 
         if (state.textscript_vm.mode == ScriptMode::Map || state.textscript_vm.mode == ScriptMode::Debug)
@@ -2184,31 +2474,34 @@ impl Scene for GameScene {
         self.text_boxes.draw(state, ctx, &self.frame)?;
 
         if self.skip_counter > 1 || state.tutorial_counter > 0 {
+            let gamepad_sprite_offset = match state.settings.player1_controller_type {
+                ControllerType::Keyboard => 1,
+                ControllerType::Gamepad(index) => ctx.gamepad_context.get_gamepad_sprite_offset(index as usize),
+            };
+
+            let skip_rect = state
+                .settings
+                .player1_controller_button_map
+                .skip
+                .first_gamepad_rect(gamepad_sprite_offset, &state.constants);
+
             let key = {
                 if state.settings.touch_controls {
                     ">>".to_owned()
                 } else {
-                    match state.settings.player1_controller_type {
-                        ControllerType::Keyboard => format!("{:?}", state.settings.player1_key_map.skip),
-                        ControllerType::Gamepad(_) => "=".to_owned(),
+                    match (state.settings.player1_controller_type, skip_rect) {
+                        (ControllerType::Keyboard, _) => state.settings.player1_key_map.skip.display_name(),
+                        (ControllerType::Gamepad(_), Some(_)) => "=".to_owned(),
+                        (ControllerType::Gamepad(_), None) => {
+                            state.settings.player1_controller_button_map.skip.display_name()
+                        }
                     }
                 }
             };
 
             let text = state.tt("game.cutscene_skip", &[("key", key.as_str())]);
 
-            let gamepad_sprite_offset = match state.settings.player1_controller_type {
-                ControllerType::Keyboard => 1,
-                ControllerType::Gamepad(index) => ctx.gamepad_context.get_gamepad_sprite_offset(index as usize),
-            };
-
-            let symbols = Symbols {
-                symbols: &[(
-                    '=',
-                    state.settings.player1_controller_button_map.skip.get_rect(gamepad_sprite_offset, &state.constants),
-                )],
-                texture: "buttons",
-            };
+            let symbols = Symbols { symbols: &[('=', skip_rect.unwrap_or(Rect::new(0, 0, 0, 0)))], texture: "buttons" };
 
             // let width = state.font.text_width_with_rects(text.chars(), &rect_map, &state.constants);
             let width = state.font.builder().with_symbols(Some(symbols)).compute_width(&text);
@@ -2290,6 +2583,7 @@ impl Scene for GameScene {
 
         self.replay.draw(state, ctx, &self.frame)?;
 
+        self.dialogue_backlog.draw(state, ctx)?;
         self.pause_menu.draw(state, ctx)?;
 
         //draw_number(state.canvas_size.0 - 8.0, 8.0, timer::fps(ctx) as usize, Alignment::Right, state, ctx)?;
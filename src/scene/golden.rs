@@ -0,0 +1,133 @@
+//! Golden image tests of the title menu, game scene with HUD and the pause menu.
+//!
+//! Scenes are drawn with the software renderer and compared pixel by pixel with the PNG images in
+//! `src/scene/goldens/`. The images can't be shipped with the repository since they're rendered from the original
+//! game data, so the test is ignored by default and has to be run explicitly with the `data` directory of
+//! Cave Story (freeware or Cave Story+) pointed by `DRS_DATA_DIR`:
+//!
+//! ```sh
+//! # render the images from the data on the first run, or after an intended change
+//! DRS_DATA_DIR=/path/to/data DRS_RECORD_GOLDEN_IMAGES=1 cargo test test_golden_images -- --ignored
+//! # compare against them afterwards
+//! DRS_DATA_DIR=/path/to/data cargo test test_golden_images -- --ignored
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use downcast::Downcast;
+use image::ImageFormat;
+
+use crate::data::builtin_fs::BuiltinFS;
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::filesystem::mount_vfs;
+use crate::framework::graphics;
+use crate::framework::render_software::SoftwareRenderer;
+use crate::framework::vfs::PhysicalFS;
+use crate::game::settings::Settings;
+use crate::game::shared_game_state::SharedGameState;
+use crate::scene::game_scene::GameScene;
+use crate::scene::title_scene::TitleScene;
+use crate::scene::Scene;
+use crate::util::rng::XorShift;
+
+const GOLDEN_SIZE: (u16, u16) = (320, 240);
+
+fn create_golden_state(ctx: &mut Context, data_dir: &str) -> GameResult<SharedGameState> {
+    // audio isn't initialized in headless mode, textures are loaded once it's turned off again
+    ctx.headless = true;
+    mount_vfs(ctx, Box::new(BuiltinFS::new()));
+    mount_vfs(ctx, Box::new(PhysicalFS::new(&PathBuf::from(data_dir), true)));
+
+    let mut state = SharedGameState::new(ctx)?;
+    ctx.headless = false;
+    ctx.renderer = Some(Box::new(SoftwareRenderer::new(GOLDEN_SIZE.0, GOLDEN_SIZE.1)));
+    ctx.screen_size = (GOLDEN_SIZE.0 as f32, GOLDEN_SIZE.1 as f32);
+
+    // the output must not depend on user settings or the current date
+    state.settings = Settings::default();
+    state.settings.seasonal_textures = false;
+    state.reload_resources(ctx)?;
+    state.handle_resize(ctx)?;
+
+    Ok(state)
+}
+
+fn run_scene(scene: &mut dyn Scene, ticks: u32, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+    for _ in 0..ticks {
+        scene.draw_tick(state)?;
+        scene.tick(state, ctx)?;
+    }
+
+    state.frame_time = 1.0;
+    graphics::prepare_draw(ctx)?;
+    graphics::clear(ctx, [0.0, 0.0, 0.0, 1.0].into());
+    scene.draw(state, ctx)?;
+
+    Ok(())
+}
+
+fn start_game_scene(state: &mut SharedGameState, ctx: &mut Context) -> GameResult<Box<dyn Scene>> {
+    state.start_new_game(ctx)?;
+    state.game_rng = XorShift::new(0);
+
+    let mut scene = state.next_scene.take().unwrap();
+    scene.init(state, ctx)?;
+
+    Ok(scene)
+}
+
+fn check_golden(name: &str, ctx: &mut Context) -> GameResult {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/scene/goldens").join(format!("{}.png", name));
+    let renderer = ctx.renderer.as_ref().unwrap().as_any().downcast_ref::<SoftwareRenderer>().unwrap();
+
+    if std::env::var("DRS_RECORD_GOLDEN_IMAGES").is_ok() {
+        fs::create_dir_all(path.parent().unwrap())?;
+        return renderer.write_png(fs::File::create(&path)?);
+    }
+
+    let expected = match fs::read(&path) {
+        Ok(data) => image::load_from_memory_with_format(&data, ImageFormat::Png)?.to_rgba8(),
+        Err(_) => panic!("{:?} is missing, record it with DRS_RECORD_GOLDEN_IMAGES=1", path),
+    };
+    let (width, height, pixels) = renderer.screen();
+
+    let matches = (expected.width(), expected.height()) == (width as u32, height as u32)
+        && expected.as_raw().as_slice() == pixels.as_slice();
+    if !matches {
+        let actual_path = std::env::temp_dir().join(format!("{}.actual.png", name));
+        renderer.write_png(fs::File::create(&actual_path)?)?;
+        panic!("{:?} doesn't match the rendered image, saved as {:?}", path, actual_path);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the game data pointed by DRS_DATA_DIR"]
+fn test_golden_images() -> GameResult {
+    let data_dir = match std::env::var("DRS_DATA_DIR") {
+        Ok(data_dir) => data_dir,
+        Err(_) => panic!("DRS_DATA_DIR has to point to the game data directory"),
+    };
+
+    let mut ctx = Context::new();
+    let mut state = create_golden_state(&mut ctx, &data_dir)?;
+
+    let mut title = TitleScene::new();
+    title.init(&mut state, &mut ctx)?;
+    run_scene(&mut title, 30, &mut state, &mut ctx)?;
+    check_golden("title_menu", &mut ctx)?;
+
+    let mut scene = start_game_scene(&mut state, &mut ctx)?;
+    run_scene(scene.as_mut(), 300, &mut state, &mut ctx)?;
+    check_golden("game_scene_hud", &mut ctx)?;
+
+    let game_scene = Downcast::<GameScene>::downcast_mut(scene.as_mut()).unwrap();
+    game_scene.pause_menu.pause(&mut state);
+    run_scene(game_scene, 10, &mut state, &mut ctx)?;
+    check_golden("pause_menu", &mut ctx)?;
+
+    Ok(())
+}
@@ -323,12 +323,14 @@ impl Scene for JukeboxScene {
                 .settings
                 .player1_controller_button_map
                 .prev_weapon
-                .get_rect(gamepad_sprite_offset, &state.constants);
+                .first_gamepad_rect(gamepad_sprite_offset, &state.constants)
+                .unwrap_or(Rect::new(0, 0, 0, 0));
             let next_rect = state
                 .settings
                 .player1_controller_button_map
                 .next_weapon
-                .get_rect(gamepad_sprite_offset, &state.constants);
+                .first_gamepad_rect(gamepad_sprite_offset, &state.constants)
+                .unwrap_or(Rect::new(0, 0, 0, 0));
 
             batch.add_rect(init_x, 15.0, &prev_rect);
             batch.add_rect(state.canvas_size.0 - init_x - next_rect.width() as f32, 15.0, &next_rect);
@@ -7,6 +7,8 @@ use crate::game::shared_game_state::SharedGameState;
 #[cfg(feature = "editor")]
 pub mod editor_scene;
 pub mod game_scene;
+#[cfg(test)]
+mod golden;
 pub mod jukebox_scene;
 pub mod loading_scene;
 pub mod no_data_scene;
@@ -5,8 +5,10 @@ use crate::components::nikumaru::NikumaruCounter;
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
+use crate::game::challenge::{ChallengeRun, Leaderboard};
 use crate::game::frame::Frame;
 use crate::game::map::Map;
+use crate::game::run_timer::format_time;
 use crate::game::shared_game_state::{
     GameDifficulty, MenuCharacter, ReplayKind, ReplayState, Season, SharedGameState, TileSize,
 };
@@ -53,6 +55,7 @@ impl Default for MainMenuEntry {
 pub enum ChallengesMenuEntry {
     Back,
     Challenge(usize),
+    TimeAttack(usize),
 }
 
 impl Default for ChallengesMenuEntry {
@@ -89,6 +92,7 @@ pub struct TitleScene {
     background: Background,
     frame: Frame,
     nikumaru_rec: NikumaruCounter,
+    leaderboard: Option<Leaderboard>,
     compact_jukebox: CompactJukebox,
     stage: Stage,
     textures: StageTexturePaths,
@@ -131,6 +135,7 @@ impl TitleScene {
             background: Background::new(),
             frame: Frame::new(),
             nikumaru_rec: NikumaruCounter::new(),
+            leaderboard: None,
             compact_jukebox: CompactJukebox::new(),
             stage: fake_stage,
             textures,
@@ -189,6 +194,67 @@ impl TitleScene {
         Ok(())
     }
 
+    /// Sets up the confirmation menu for currently selected mod or challenge.
+    fn open_confirm_menu(&mut self, title: String, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        self.confirm_menu.width = (state.font.builder().compute_width(&title).max(50.0) + 32.0) as u16;
+
+        self.confirm_menu.set_entry(ConfirmMenuEntry::Title, MenuEntry::Disabled(title));
+
+        if state.has_replay_data(ctx, ReplayKind::Best) {
+            self.confirm_menu.set_entry(
+                ConfirmMenuEntry::Replay(ReplayKind::Best),
+                MenuEntry::Active(state.loc.t("menus.challenge_menu.replay_best").to_owned()),
+            );
+            self.confirm_menu.set_entry(
+                ConfirmMenuEntry::DeleteReplay,
+                MenuEntry::Active(state.loc.t("menus.challenge_menu.delete_replay").to_owned()),
+            );
+        } else {
+            self.confirm_menu.set_entry(ConfirmMenuEntry::Replay(ReplayKind::Best), MenuEntry::Hidden);
+            self.confirm_menu.set_entry(ConfirmMenuEntry::DeleteReplay, MenuEntry::Hidden);
+        }
+
+        if state.has_replay_data(ctx, ReplayKind::Last) {
+            self.confirm_menu.set_entry(
+                ConfirmMenuEntry::Replay(ReplayKind::Last),
+                MenuEntry::Active(state.loc.t("menus.challenge_menu.replay_last").to_owned()),
+            );
+        } else {
+            self.confirm_menu.set_entry(ConfirmMenuEntry::Replay(ReplayKind::Last), MenuEntry::Hidden);
+        }
+
+        self.nikumaru_rec.load_counter(state, ctx)?;
+        self.current_menu = CurrentMenu::ChallengeConfirmMenu;
+
+        Ok(())
+    }
+
+    fn draw_leaderboard(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let leaderboard = match &self.leaderboard {
+            Some(leaderboard) if !leaderboard.entries.is_empty() => leaderboard,
+            _ => return Ok(()),
+        };
+
+        let mut y = (self.confirm_menu.y + self.confirm_menu.height as isize + 8) as f32;
+        let lines = leaderboard
+            .entries
+            .iter()
+            .take(5)
+            .enumerate()
+            .map(|(idx, entry)| {
+                let assist = if entry.assist != 0 { state.loc.t("menus.save_menu.assist") } else { "" };
+                format!("{}. {} {}", idx + 1, format_time(entry.ticks, entry.tps), assist)
+            })
+            .collect::<Vec<_>>();
+
+        for line in lines {
+            self.draw_text_centered(&line, y, state, ctx)?;
+            y += state.font.line_height();
+        }
+
+        Ok(())
+    }
+
     pub fn open_settings_menu(&mut self) -> GameResult {
         self.current_menu = CurrentMenu::OptionMenu;
         Ok(())
@@ -201,6 +267,10 @@ static COPYRIGHT_NICALIS: &str = "@2022 NICALIS INC."; // Nicalis font uses @ fo
 
 impl Scene for TitleScene {
     fn init(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        state.sound_manager.set_ducking(false);
+        state.speedrun.reset(ctx);
+        state.textscript_vm.history.clear();
+
         if !state.mod_path.is_none() {
             state.mod_path = None;
             state.reload_resources(ctx)?;
@@ -212,7 +282,7 @@ impl Scene for TitleScene {
         self.main_menu
             .push_entry(MainMenuEntry::Start, MenuEntry::Active(state.loc.t("menus.main_menu.start").to_owned()));
 
-        if !state.mod_list.mods.is_empty() {
+        if !state.mod_list.mods.is_empty() || !state.challenges.challenges.is_empty() {
             self.main_menu.push_entry(
                 MainMenuEntry::Challenges,
                 MenuEntry::Active(state.loc.t("menus.main_menu.challenges").to_owned()),
@@ -266,6 +336,15 @@ impl Scene for TitleScene {
                     .push_entry(ChallengesMenuEntry::Challenge(idx), MenuEntry::Disabled("???".to_owned()));
             }
         }
+        for (idx, challenge) in state.challenges.challenges.iter().enumerate() {
+            self.challenges_menu
+                .push_entry(ChallengesMenuEntry::TimeAttack(idx), MenuEntry::Active(challenge.name.clone()));
+
+            if mutate_selection {
+                selected = ChallengesMenuEntry::TimeAttack(idx);
+                mutate_selection = false;
+            }
+        }
         self.challenges_menu
             .push_entry(ChallengesMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
         self.challenges_menu.selected = selected;
@@ -288,6 +367,8 @@ impl Scene for TitleScene {
         self.update_menu_cursor(state, ctx)?;
 
         state.replay_state = ReplayState::None;
+        state.replay_assist = None;
+        state.challenge = None;
         state.textscript_vm.flags.set_cutscene_skip(false);
         state.difficulty = GameDifficulty::Normal;
 
@@ -396,41 +477,20 @@ impl Scene for TitleScene {
                             self.current_menu = CurrentMenu::SaveSelectMenu;
                         } else {
                             let mod_name = mod_info.name.clone();
-                            self.confirm_menu.width =
-                                (state.font.builder().compute_width(&mod_name).max(50.0) + 32.0) as u16;
-
-                            self.confirm_menu.set_entry(ConfirmMenuEntry::Title, MenuEntry::Disabled(mod_name));
-
-                            if state.has_replay_data(ctx, ReplayKind::Best) {
-                                self.confirm_menu.set_entry(
-                                    ConfirmMenuEntry::Replay(ReplayKind::Best),
-                                    MenuEntry::Active(state.loc.t("menus.challenge_menu.replay_best").to_owned()),
-                                );
-                                self.confirm_menu.set_entry(
-                                    ConfirmMenuEntry::DeleteReplay,
-                                    MenuEntry::Active(state.loc.t("menus.challenge_menu.delete_replay").to_owned()),
-                                );
-                            } else {
-                                self.confirm_menu
-                                    .set_entry(ConfirmMenuEntry::Replay(ReplayKind::Best), MenuEntry::Hidden);
-                                self.confirm_menu.set_entry(ConfirmMenuEntry::DeleteReplay, MenuEntry::Hidden);
-                            }
-
-                            if state.has_replay_data(ctx, ReplayKind::Last) {
-                                self.confirm_menu.set_entry(
-                                    ConfirmMenuEntry::Replay(ReplayKind::Last),
-                                    MenuEntry::Active(state.loc.t("menus.challenge_menu.replay_last").to_owned()),
-                                );
-                            } else {
-                                self.confirm_menu
-                                    .set_entry(ConfirmMenuEntry::Replay(ReplayKind::Last), MenuEntry::Hidden);
-                            }
-
-                            self.nikumaru_rec.load_counter(state, ctx)?;
-                            self.current_menu = CurrentMenu::ChallengeConfirmMenu;
+                            self.open_confirm_menu(mod_name, state, ctx)?;
                         }
                     }
                 }
+                MenuSelectionResult::Selected(ChallengesMenuEntry::TimeAttack(idx), _) => {
+                    if let Some(challenge) = state.challenges.get(idx) {
+                        let name = challenge.name.clone();
+
+                        state.mod_path = None;
+                        state.challenge = Some(ChallengeRun::new(idx, challenge.clone()));
+                        self.leaderboard = Some(Leaderboard::load(ctx, &challenge.id));
+                        self.open_confirm_menu(name, state, ctx)?;
+                    }
+                }
                 MenuSelectionResult::Selected(ChallengesMenuEntry::Back, _) | MenuSelectionResult::Canceled => {
                     state.mod_path = None;
                     self.nikumaru_rec.load_counter(state, ctx)?;
@@ -452,9 +512,13 @@ impl Scene for TitleScene {
                 }
                 MenuSelectionResult::Selected(ConfirmMenuEntry::DeleteReplay, _) => {
                     state.delete_replay_data(ctx, ReplayKind::Best)?;
+                    state.challenge = None;
+                    self.leaderboard = None;
                     self.current_menu = CurrentMenu::ChallengesMenu;
                 }
                 MenuSelectionResult::Selected(ConfirmMenuEntry::Back, _) | MenuSelectionResult::Canceled => {
+                    state.challenge = None;
+                    self.leaderboard = None;
                     self.current_menu = CurrentMenu::ChallengesMenu;
                 }
                 _ => (),
@@ -536,7 +600,10 @@ impl Scene for TitleScene {
         match self.current_menu {
             CurrentMenu::MainMenu => self.main_menu.draw(state, ctx)?,
             CurrentMenu::ChallengesMenu => self.challenges_menu.draw(state, ctx)?,
-            CurrentMenu::ChallengeConfirmMenu => self.confirm_menu.draw(state, ctx)?,
+            CurrentMenu::ChallengeConfirmMenu => {
+                self.confirm_menu.draw(state, ctx)?;
+                self.draw_leaderboard(state, ctx)?;
+            }
             CurrentMenu::OptionMenu => self.settings_menu.draw(state, ctx)?,
             CurrentMenu::SaveSelectMenu => self.save_select_menu.draw(state, ctx)?,
             CurrentMenu::PlayerCountMenu => self.coop_menu.draw(state, ctx)?,
@@ -0,0 +1,117 @@
+/// Named mixer buses, each having its own volume, mute and ducking parameters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MixerBus {
+    Music = 0,
+    Gameplay,
+    Ambience,
+    UI,
+}
+
+impl MixerBus {
+    pub const COUNT: usize = 4;
+    pub const ALL: [MixerBus; MixerBus::COUNT] =
+        [MixerBus::Music, MixerBus::Gameplay, MixerBus::Ambience, MixerBus::UI];
+
+    pub fn from_index(index: usize) -> Option<MixerBus> {
+        MixerBus::ALL.get(index).copied()
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct BusState {
+    volume: f32,
+    muted: bool,
+    /// How much the bus gets attenuated while ducking is active, 0.0 - not at all, 1.0 - silenced.
+    duck_amount: f32,
+    /// Current, smoothed attenuation caused by ducking.
+    duck_level: f32,
+}
+
+/// Mixer graph state owned by the audio thread.
+pub(in crate::sound) struct Mixer {
+    buses: [BusState; MixerBus::COUNT],
+    ducking: bool,
+}
+
+impl Mixer {
+    /// Amount by which the duck level changes per processed buffer (~10ms), so it takes ~0.2s to fully duck.
+    const DUCK_STEP: f32 = 0.05;
+
+    pub fn new() -> Mixer {
+        Mixer {
+            buses: [BusState { volume: 1.0, muted: false, duck_amount: 0.0, duck_level: 0.0 }; MixerBus::COUNT],
+            ducking: false,
+        }
+    }
+
+    pub fn set_volume(&mut self, bus: MixerBus, volume: f32) {
+        self.buses[bus as usize].volume = volume.max(0.0);
+    }
+
+    pub fn set_muted(&mut self, bus: MixerBus, muted: bool) {
+        self.buses[bus as usize].muted = muted;
+    }
+
+    pub fn set_duck_amount(&mut self, bus: MixerBus, amount: f32) {
+        self.buses[bus as usize].duck_amount = amount.clamp(0.0, 1.0);
+    }
+
+    pub fn set_ducking(&mut self, ducking: bool) {
+        self.ducking = ducking;
+    }
+
+    /// Moves the ducking levels towards their targets, should be called once per processed buffer.
+    pub fn tick(&mut self) {
+        for bus in &mut self.buses {
+            let target = if self.ducking { bus.duck_amount } else { 0.0 };
+
+            if bus.duck_level < target {
+                bus.duck_level = (bus.duck_level + Mixer::DUCK_STEP).min(target);
+            } else if bus.duck_level > target {
+                bus.duck_level = (bus.duck_level - Mixer::DUCK_STEP).max(target);
+            }
+        }
+    }
+
+    pub fn gain(&self, bus: MixerBus) -> f32 {
+        let bus = &self.buses[bus as usize];
+
+        if bus.muted {
+            0.0
+        } else {
+            bus.volume * (1.0 - bus.duck_level)
+        }
+    }
+
+    pub fn gains(&self) -> [f32; MixerBus::COUNT] {
+        MixerBus::ALL.map(|bus| self.gain(bus))
+    }
+}
+
+#[test]
+fn test_mixer_ducking() {
+    let mut mixer = Mixer::new();
+    mixer.set_volume(MixerBus::Music, 0.5);
+    mixer.set_duck_amount(MixerBus::Music, 0.5);
+
+    assert_eq!(mixer.gain(MixerBus::Music), 0.5);
+
+    mixer.set_ducking(true);
+    for _ in 0..20 {
+        mixer.tick();
+    }
+
+    assert_eq!(mixer.gain(MixerBus::Music), 0.25);
+    assert_eq!(mixer.gain(MixerBus::Gameplay), 1.0);
+
+    mixer.set_muted(MixerBus::Gameplay, true);
+    assert_eq!(mixer.gain(MixerBus::Gameplay), 0.0);
+
+    mixer.set_ducking(false);
+    for _ in 0..20 {
+        mixer.tick();
+    }
+
+    assert_eq!(mixer.gain(MixerBus::Music), 0.5);
+}
@@ -1,5 +1,6 @@
+use std::collections::VecDeque;
 use std::io;
-use std::io::{BufRead, BufReader, Lines};
+use std::io::{BufRead, BufReader, BufWriter, Lines};
 use std::str::FromStr;
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
@@ -20,16 +21,21 @@ use crate::game::settings::Settings;
 use crate::sound::ogg_playback::{OggPlaybackEngine, SavedOggPlaybackState};
 use crate::sound::org_playback::{OrgPlaybackEngine, SavedOrganyaPlaybackState};
 use crate::sound::organya::Song;
+use crate::sound::mixer::{Mixer, MixerBus};
 use crate::sound::pixtone::{PixToneParameters, PixTonePlayback};
+use crate::sound::spatial::{SfxListener, SfxPositioning};
+use crate::sound::wav::WavWriter;
 use crate::sound::wave_bank::SoundBank;
 
 mod fir;
+pub mod mixer;
 #[cfg(feature = "ogg-playback")]
 mod ogg_playback;
 mod org_playback;
 mod organya;
 pub mod pixtone;
 mod pixtone_sfx;
+pub mod spatial;
 mod stuff;
 mod wav;
 mod wave_bank;
@@ -42,6 +48,8 @@ pub struct SoundManager {
     no_audio: bool,
     load_failed: bool,
     stream: Option<cpal::Stream>,
+    listener: SfxListener,
+    ducking: bool,
 }
 
 enum SongFormat {
@@ -76,6 +84,8 @@ impl SoundManager {
                 no_audio: true,
                 load_failed: false,
                 stream: None,
+                listener: SfxListener::new(),
+                ducking: false,
             });
         }
 
@@ -96,6 +106,8 @@ impl SoundManager {
             no_audio: false,
             load_failed: false,
             stream: None,
+            listener: SfxListener::new(),
+            ducking: false,
         };
 
         let host = cpal::default_host();
@@ -153,7 +165,9 @@ impl SoundManager {
 
         let (tx, rx): (Sender<PlaybackMessage>, Receiver<PlaybackMessage>) = mpsc::channel();
         let soundbank = self.soundbank.take().unwrap();
+        let listener = self.listener;
         *self = SoundManager::bootstrap(&soundbank, tx, rx)?;
+        self.listener = listener;
 
         Ok(())
     }
@@ -186,26 +200,66 @@ impl SoundManager {
     }
 
     pub fn play_sfx(&mut self, id: u8) {
+        self.play_sfx_on(id, MixerBus::Gameplay);
+    }
+
+    /// Plays a sound effect routed through specified mixer bus.
+    pub fn play_sfx_on(&mut self, id: u8, bus: MixerBus) {
         if self.no_audio {
             return;
         }
 
-        self.send(PlaybackMessage::PlaySample(id)).unwrap();
+        self.send(PlaybackMessage::PlaySample(id, bus)).unwrap();
     }
 
-    pub fn loop_sfx(&self, id: u8) {
+    /// Plays a sound effect originating from specified position in the world.
+    ///
+    /// Falls back to [`SoundManager::play_sfx`] if positional sound effects are disabled in settings.
+    pub fn play_sfx_at(&mut self, id: u8, x: i32, y: i32, positioning: SfxPositioning) {
         if self.no_audio {
             return;
         }
 
-        self.tx.send(PlaybackMessage::LoopSample(id)).unwrap();
+        if !self.listener.enabled {
+            return self.play_sfx(id);
+        }
+
+        let (volume, pan) = self.listener.spatialize(x, y, positioning);
+        if volume <= 0.0 {
+            return;
+        }
+
+        self.send(PlaybackMessage::PlaySampleSpatial(id, volume, pan)).unwrap();
+    }
+
+    pub fn set_positional_sfx(&mut self, enabled: bool) {
+        self.listener.enabled = enabled;
+    }
+
+    pub fn positional_sfx(&self) -> bool {
+        self.listener.enabled
     }
 
-    pub fn loop_sfx_freq(&mut self, id: u8, freq: f32) {
+    /// Updates the positions positioned sound effects are heard from, should be called once per game tick.
+    pub fn set_listener(&mut self, points: [Option<(i32, i32)>; 2], half_width: i32) {
+        self.listener.points = points;
+        self.listener.half_width = half_width;
+    }
+
+    /// Loops a sound effect routed through specified mixer bus, until it's stopped with [`SoundManager::stop_sfx`].
+    pub fn loop_sfx(&self, id: u8, bus: MixerBus) {
+        if self.no_audio {
+            return;
+        }
+
+        self.tx.send(PlaybackMessage::LoopSample(id, bus)).unwrap();
+    }
+
+    pub fn loop_sfx_freq(&mut self, id: u8, freq: f32, bus: MixerBus) {
         if self.no_audio {
             return;
         }
-        self.send(PlaybackMessage::LoopSampleFreq(id, freq)).unwrap();
+        self.send(PlaybackMessage::LoopSampleFreq(id, freq, bus)).unwrap();
     }
 
     pub fn stop_sfx(&mut self, id: u8) {
@@ -223,17 +277,42 @@ impl SoundManager {
     }
 
     pub fn set_song_volume(&mut self, volume: f32) {
+        self.set_bus_volume(MixerBus::Music, volume);
+    }
+
+    pub fn set_sfx_volume(&mut self, volume: f32) {
+        self.set_bus_volume(MixerBus::Gameplay, volume);
+    }
+
+    pub fn set_bus_volume(&mut self, bus: MixerBus, volume: f32) {
         if self.no_audio {
             return;
         }
-        self.send(PlaybackMessage::SetSongVolume(volume.powf(3.0))).unwrap();
+        self.send(PlaybackMessage::SetBusVolume(bus, volume.powf(3.0))).unwrap();
     }
 
-    pub fn set_sfx_volume(&mut self, volume: f32) {
+    pub fn set_bus_muted(&mut self, bus: MixerBus, muted: bool) {
         if self.no_audio {
             return;
         }
-        self.send(PlaybackMessage::SetSampleVolume(volume.powf(3.0))).unwrap();
+        self.send(PlaybackMessage::SetBusMuted(bus, muted)).unwrap();
+    }
+
+    /// Sets how much the bus is attenuated while ducking is active (0.0 - not at all, 1.0 - silenced).
+    pub fn set_bus_duck_amount(&mut self, bus: MixerBus, amount: f32) {
+        if self.no_audio {
+            return;
+        }
+        self.send(PlaybackMessage::SetBusDuckAmount(bus, amount)).unwrap();
+    }
+
+    /// Enables or disables ducking, used to lower the music while text boxes are open.
+    pub fn set_ducking(&mut self, ducking: bool) {
+        if self.no_audio || self.ducking == ducking {
+            return;
+        }
+        self.ducking = ducking;
+        self.send(PlaybackMessage::SetDucking(ducking)).unwrap();
     }
 
     pub fn set_sfx_samples(&mut self, id: u8, data: Vec<i16>) {
@@ -438,6 +517,33 @@ impl SoundManager {
         Ok(())
     }
 
+    /// Starts writing the mixed audio output into specified file as a 16-bit stereo PCM WAV.
+    pub fn start_capture(&mut self, file: File) -> GameResult {
+        if self.no_audio {
+            return Ok(());
+        }
+
+        self.send(PlaybackMessage::StartCapture(file))
+    }
+
+    pub fn stop_capture(&mut self) -> GameResult {
+        if self.no_audio {
+            return Ok(());
+        }
+
+        self.send(PlaybackMessage::StopCapture)
+    }
+
+    /// Mixes the audio of a single tick into the capture, should be called once per captured frame.
+    /// While capturing, the output is advanced only by these calls, so audio stays in lockstep with the frames.
+    pub fn capture_tick(&mut self, tps: usize) -> GameResult {
+        if self.no_audio {
+            return Ok(());
+        }
+
+        self.send(PlaybackMessage::CaptureTick(tps))
+    }
+
     pub fn current_song(&self) -> usize {
         self.current_song_id
     }
@@ -555,19 +661,26 @@ pub(in crate::sound) enum PlaybackMessage {
     PlayOggSongSinglePart(Box<OggStreamReader<File>>),
     #[cfg(feature = "ogg-playback")]
     PlayOggSongMultiPart(Box<OggStreamReader<File>>, Box<OggStreamReader<File>>),
-    PlaySample(u8),
-    LoopSample(u8),
-    LoopSampleFreq(u8, f32),
+    PlaySample(u8, MixerBus),
+    PlaySampleSpatial(u8, f32, f32),
+    LoopSample(u8, MixerBus),
+    LoopSampleFreq(u8, f32, MixerBus),
     StopSample(u8),
     SetSpeed(f32),
-    SetSongVolume(f32),
-    SetSampleVolume(f32),
+    SetBusVolume(MixerBus, f32),
+    SetBusMuted(MixerBus, bool),
+    SetBusDuckAmount(MixerBus, f32),
+    SetDucking(bool),
     FadeoutSong,
     SaveState,
     RestoreState,
     SetSampleParams(u8, PixToneParameters),
     SetOrgInterpolation(InterpolationMode),
     SetSampleData(u8, Vec<i16>),
+    StartCapture(File),
+    StopCapture,
+    /// Renders audio for a single captured tick, played at given tick rate.
+    CaptureTick(usize),
 }
 
 #[derive(PartialEq, Eq)]
@@ -621,15 +734,19 @@ fn run<T>(
 
     let buf_size = sample_rate as usize * 10 / 1000;
     let mut bgm_buf = vec![0x8080; buf_size * 2];
-    let mut pxt_buf = vec![0x8000; buf_size];
+    let mut pxt_buf = vec![0x8000; buf_size * 2];
     let mut bgm_index = 0;
     let mut pxt_index = 0;
     let mut samples = 0;
-    let mut bgm_vol = 1.0_f32;
-    let mut bgm_vol_saved = 1.0_f32;
-    let mut sfx_vol = 1.0_f32;
+    let mut mixer = Mixer::new();
+    let mut bgm_fade = 1.0_f32;
     let mut bgm_fadeout = false;
-    pixtone.mix(&mut pxt_buf, sample_rate);
+    let mut wav_capture: Option<WavWriter<BufWriter<File>>> = None;
+    let mut capture_ticks = 0u64;
+    let mut capture_frames = 0u64;
+    let mut capture_monitor = VecDeque::new();
+    let mut mixed = Vec::with_capacity(buf_size * 2);
+    pixtone.mix(&mut pxt_buf, sample_rate, &mixer.gains());
 
     let err_fn = |err| eprintln!("an error occurred on stream: {}", err);
 
@@ -637,258 +754,326 @@ fn run<T>(
         &config,
         move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
             loop {
-                if bgm_fadeout && bgm_vol > 0.0 {
-                    bgm_vol -= 0.02;
-                }
+                // frames of audio to render for the capture before handling the rest of messages
+                let mut render_frames = None;
 
-                if bgm_vol < 0.0 {
-                    bgm_vol = 0.0;
-                }
+                loop {
+                    if bgm_fadeout && bgm_fade > 0.0 {
+                        bgm_fade -= 0.02;
+                    }
 
-                match rx.try_recv() {
-                    Ok(PlaybackMessage::PlayOrganyaSong(song)) => {
-                        if state == PlaybackState::Stopped {
-                            saved_state = PlaybackStateType::None;
-                        }
+                    if bgm_fade < 0.0 {
+                        bgm_fade = 0.0;
+                    }
 
-                        if bgm_fadeout {
-                            bgm_fadeout = false;
-                            bgm_vol = bgm_vol_saved;
-                        }
+                    match rx.try_recv() {
+                        Ok(PlaybackMessage::PlayOrganyaSong(song)) => {
+                            if state == PlaybackState::Stopped {
+                                saved_state = PlaybackStateType::None;
+                            }
 
-                        org_engine.start_song(*song, &bank);
+                            if bgm_fadeout {
+                                bgm_fadeout = false;
+                                bgm_fade = 1.0;
+                            }
 
-                        for i in &mut bgm_buf[0..samples] {
-                            *i = 0x8000
-                        }
-                        samples = org_engine.render_to(&mut bgm_buf);
-                        bgm_index = 0;
+                            org_engine.start_song(*song, &bank);
 
-                        state = PlaybackState::PlayingOrg;
-                    }
-                    #[cfg(feature = "ogg-playback")]
-                    Ok(PlaybackMessage::PlayOggSongSinglePart(data)) => {
-                        if state == PlaybackState::Stopped {
-                            saved_state = PlaybackStateType::None;
-                        }
+                            for i in &mut bgm_buf[0..samples] {
+                                *i = 0x8000
+                            }
+                            samples = org_engine.render_to(&mut bgm_buf);
+                            bgm_index = 0;
 
-                        if bgm_fadeout {
-                            bgm_fadeout = false;
-                            bgm_vol = bgm_vol_saved;
+                            state = PlaybackState::PlayingOrg;
                         }
+                        #[cfg(feature = "ogg-playback")]
+                        Ok(PlaybackMessage::PlayOggSongSinglePart(data)) => {
+                            if state == PlaybackState::Stopped {
+                                saved_state = PlaybackStateType::None;
+                            }
 
-                        ogg_engine.start_single(data);
+                            if bgm_fadeout {
+                                bgm_fadeout = false;
+                                bgm_fade = 1.0;
+                            }
 
-                        for i in &mut bgm_buf[0..samples] {
-                            *i = 0x8000
-                        }
-                        samples = ogg_engine.render_to(&mut bgm_buf);
-                        bgm_index = 0;
+                            ogg_engine.start_single(data);
 
-                        state = PlaybackState::PlayingOgg;
-                    }
-                    #[cfg(feature = "ogg-playback")]
-                    Ok(PlaybackMessage::PlayOggSongMultiPart(data_intro, data_loop)) => {
-                        if state == PlaybackState::Stopped {
-                            saved_state = PlaybackStateType::None;
-                        }
+                            for i in &mut bgm_buf[0..samples] {
+                                *i = 0x8000
+                            }
+                            samples = ogg_engine.render_to(&mut bgm_buf);
+                            bgm_index = 0;
 
-                        if bgm_fadeout {
-                            bgm_fadeout = false;
-                            bgm_vol = bgm_vol_saved;
+                            state = PlaybackState::PlayingOgg;
                         }
+                        #[cfg(feature = "ogg-playback")]
+                        Ok(PlaybackMessage::PlayOggSongMultiPart(data_intro, data_loop)) => {
+                            if state == PlaybackState::Stopped {
+                                saved_state = PlaybackStateType::None;
+                            }
 
-                        ogg_engine.start_multi(data_intro, data_loop);
+                            if bgm_fadeout {
+                                bgm_fadeout = false;
+                                bgm_fade = 1.0;
+                            }
 
-                        for i in &mut bgm_buf[0..samples] {
-                            *i = 0x8000
-                        }
-                        samples = ogg_engine.render_to(&mut bgm_buf);
-                        bgm_index = 0;
+                            ogg_engine.start_multi(data_intro, data_loop);
 
-                        state = PlaybackState::PlayingOgg;
-                    }
-                    Ok(PlaybackMessage::PlaySample(id)) => {
-                        pixtone.play_sfx(id);
-                    }
+                            for i in &mut bgm_buf[0..samples] {
+                                *i = 0x8000
+                            }
+                            samples = ogg_engine.render_to(&mut bgm_buf);
+                            bgm_index = 0;
 
-                    Ok(PlaybackMessage::LoopSample(id)) => {
-                        pixtone.loop_sfx(id);
-                    }
-                    Ok(PlaybackMessage::LoopSampleFreq(id, freq)) => {
-                        pixtone.loop_sfx_freq(id, freq);
-                    }
-                    Ok(PlaybackMessage::StopSample(id)) => {
-                        pixtone.stop_sfx(id);
-                    }
-                    Ok(PlaybackMessage::Stop) => {
-                        if state == PlaybackState::Stopped {
-                            saved_state = PlaybackStateType::None;
+                            state = PlaybackState::PlayingOgg;
                         }
-
-                        state = PlaybackState::Stopped;
-                    }
-                    Ok(PlaybackMessage::SetSpeed(new_speed)) => {
-                        assert!(new_speed > 0.0);
-                        speed = new_speed;
-                        #[cfg(feature = "ogg-playback")]
-                            ogg_engine.set_sample_rate((sample_rate / new_speed) as usize);
-                        org_engine.set_sample_rate((sample_rate / new_speed) as usize);
-                    }
-                    Ok(PlaybackMessage::SetSongVolume(new_volume)) => {
-                        assert!(bgm_vol >= 0.0);
-                        if bgm_fadeout {
-                            bgm_vol_saved = new_volume;
-                        } else {
-                            bgm_vol = new_volume;
+                        Ok(PlaybackMessage::PlaySample(id, bus)) => {
+                            pixtone.play_sfx(id, bus);
+                        }
+                        Ok(PlaybackMessage::PlaySampleSpatial(id, volume, pan)) => {
+                            pixtone.play_sfx_spatial(id, MixerBus::Gameplay, volume, pan);
                         }
-                    }
-                    Ok(PlaybackMessage::SetSampleVolume(new_volume)) => {
-                        assert!(sfx_vol >= 0.0);
-                        sfx_vol = new_volume;
-                    }
-                    Ok(PlaybackMessage::FadeoutSong) => {
-                        bgm_fadeout = true;
-                        bgm_vol_saved = bgm_vol;
-                    }
-                    Ok(PlaybackMessage::SaveState) => {
-                        saved_state = match state {
-                            PlaybackState::Stopped => PlaybackStateType::None,
-                            PlaybackState::PlayingOrg => PlaybackStateType::Organya(org_engine.get_state()),
-                            #[cfg(feature = "ogg-playback")]
-                            PlaybackState::PlayingOgg => PlaybackStateType::Ogg(ogg_engine.get_state()),
-                        };
-                    }
-                    Ok(PlaybackMessage::RestoreState) => {
-                        let saved_state_loc = std::mem::take(&mut saved_state);
 
-                        match saved_state_loc {
-                            PlaybackStateType::None => {
-                                state = PlaybackState::Stopped;
+                        Ok(PlaybackMessage::LoopSample(id, bus)) => {
+                            pixtone.loop_sfx(id, bus);
+                        }
+                        Ok(PlaybackMessage::LoopSampleFreq(id, freq, bus)) => {
+                            pixtone.loop_sfx_freq(id, freq, bus);
+                        }
+                        Ok(PlaybackMessage::StopSample(id)) => {
+                            pixtone.stop_sfx(id);
+                        }
+                        Ok(PlaybackMessage::Stop) => {
+                            if state == PlaybackState::Stopped {
+                                saved_state = PlaybackStateType::None;
                             }
-                            PlaybackStateType::Organya(playback_state) => {
-                                org_engine.set_state(playback_state, &bank);
 
-                                if state == PlaybackState::Stopped {
-                                    org_engine.rewind();
-                                }
+                            state = PlaybackState::Stopped;
+                        }
+                        Ok(PlaybackMessage::SetSpeed(new_speed)) => {
+                            assert!(new_speed > 0.0);
+                            speed = new_speed;
+                            #[cfg(feature = "ogg-playback")]
+                                ogg_engine.set_sample_rate((sample_rate / new_speed) as usize);
+                            org_engine.set_sample_rate((sample_rate / new_speed) as usize);
+                        }
+                        Ok(PlaybackMessage::SetBusVolume(bus, new_volume)) => {
+                            mixer.set_volume(bus, new_volume);
+                        }
+                        Ok(PlaybackMessage::SetBusMuted(bus, muted)) => {
+                            mixer.set_muted(bus, muted);
+                        }
+                        Ok(PlaybackMessage::SetBusDuckAmount(bus, amount)) => {
+                            mixer.set_duck_amount(bus, amount);
+                        }
+                        Ok(PlaybackMessage::SetDucking(ducking)) => {
+                            mixer.set_ducking(ducking);
+                        }
+                        Ok(PlaybackMessage::FadeoutSong) => {
+                            bgm_fadeout = true;
+                        }
+                        Ok(PlaybackMessage::SaveState) => {
+                            saved_state = match state {
+                                PlaybackState::Stopped => PlaybackStateType::None,
+                                PlaybackState::PlayingOrg => PlaybackStateType::Organya(org_engine.get_state()),
+                                #[cfg(feature = "ogg-playback")]
+                                PlaybackState::PlayingOgg => PlaybackStateType::Ogg(ogg_engine.get_state()),
+                            };
+                        }
+                        Ok(PlaybackMessage::RestoreState) => {
+                            let saved_state_loc = std::mem::take(&mut saved_state);
 
-                                for i in &mut bgm_buf[0..samples] {
-                                    *i = 0x8000
+                            match saved_state_loc {
+                                PlaybackStateType::None => {
+                                    state = PlaybackState::Stopped;
                                 }
-                                samples = org_engine.render_to(&mut bgm_buf);
-                                bgm_index = 0;
+                                PlaybackStateType::Organya(playback_state) => {
+                                    org_engine.set_state(playback_state, &bank);
 
-                                if bgm_fadeout {
-                                    bgm_fadeout = false;
-                                    bgm_vol = bgm_vol_saved;
-                                }
+                                    if state == PlaybackState::Stopped {
+                                        org_engine.rewind();
+                                    }
 
-                                state = PlaybackState::PlayingOrg;
-                            }
-                            #[cfg(feature = "ogg-playback")]
-                            PlaybackStateType::Ogg(playback_state) => {
-                                ogg_engine.set_state(playback_state);
+                                    for i in &mut bgm_buf[0..samples] {
+                                        *i = 0x8000
+                                    }
+                                    samples = org_engine.render_to(&mut bgm_buf);
+                                    bgm_index = 0;
 
-                                if state == PlaybackState::Stopped {
-                                    ogg_engine.rewind();
-                                }
+                                    if bgm_fadeout {
+                                        bgm_fadeout = false;
+                                        bgm_fade = 1.0;
+                                    }
 
-                                for i in &mut bgm_buf[0..samples] {
-                                    *i = 0x8000
+                                    state = PlaybackState::PlayingOrg;
                                 }
-                                samples = ogg_engine.render_to(&mut bgm_buf);
-                                bgm_index = 0;
-
-                                if bgm_fadeout {
-                                    bgm_fadeout = false;
-                                    bgm_vol = bgm_vol_saved;
+                                #[cfg(feature = "ogg-playback")]
+                                PlaybackStateType::Ogg(playback_state) => {
+                                    ogg_engine.set_state(playback_state);
+
+                                    if state == PlaybackState::Stopped {
+                                        ogg_engine.rewind();
+                                    }
+
+                                    for i in &mut bgm_buf[0..samples] {
+                                        *i = 0x8000
+                                    }
+                                    samples = ogg_engine.render_to(&mut bgm_buf);
+                                    bgm_index = 0;
+
+                                    if bgm_fadeout {
+                                        bgm_fadeout = false;
+                                        bgm_fade = 1.0;
+                                    }
+
+                                    state = PlaybackState::PlayingOgg;
                                 }
+                            }
+                        }
+                        Ok(PlaybackMessage::SetSampleParams(id, params)) => {
+                            pixtone.set_sample_parameters(id, params);
+                        }
+                        Ok(PlaybackMessage::SetOrgInterpolation(interpolation)) => {
+                            org_engine.interpolation = interpolation;
+                        }
+                        Ok(PlaybackMessage::SetSampleData(id, data)) => {
+                            pixtone.set_sample_data(id, data);
+                        }
+                        Ok(PlaybackMessage::StartCapture(file)) => {
+                            if let Some(writer) = wav_capture.take() {
+                                let _ = writer.finish();
+                            }
 
-                                state = PlaybackState::PlayingOgg;
+                            match WavWriter::new(BufWriter::new(file), 2, sample_rate as u32) {
+                                Ok(writer) => wav_capture = Some(writer),
+                                Err(err) => log::error!("Failed to start audio capture: {}", err),
                             }
+                            capture_ticks = 0;
+                            capture_frames = 0;
+                            capture_monitor.clear();
+                        }
+                        Ok(PlaybackMessage::StopCapture) => {
+                            if let Some(writer) = wav_capture.take() {
+                                if let Err(err) = writer.finish() {
+                                    log::error!("Failed to finish audio capture: {}", err);
+                                }
+                            }
+                            capture_monitor.clear();
+                        }
+                        Ok(PlaybackMessage::CaptureTick(tps)) => {
+                            if wav_capture.is_some() {
+                                // stays aligned with the frames even if sample rate isn't divisible by tps
+                                capture_ticks += 1;
+                                let total = capture_ticks * sample_rate as u64 / tps.max(1) as u64;
+                                render_frames = Some((total - capture_frames) as usize);
+                                capture_frames = total;
+                                break;
+                            }
+                        }
+                        Err(_) => {
+                            break;
                         }
-                    }
-                    Ok(PlaybackMessage::SetSampleParams(id, params)) => {
-                        pixtone.set_sample_parameters(id, params);
-                    }
-                    Ok(PlaybackMessage::SetOrgInterpolation(interpolation)) => {
-                        org_engine.interpolation = interpolation;
-                    }
-                    Ok(PlaybackMessage::SetSampleData(id, data)) => {
-                        pixtone.set_sample_data(id, data);
-                    }
-                    Err(_) => {
-                        break;
                     }
                 }
-            }
 
-            for frame in data.chunks_mut(channels) {
-                let (bgm_sample_l, bgm_sample_r): (u16, u16) = {
-                    if state == PlaybackState::Stopped {
-                        (0x8000, 0x8000)
-                    } else if bgm_index < samples {
-                        let samples = (bgm_buf[bgm_index], bgm_buf[bgm_index + 1]);
-                        bgm_index += 2;
-                        samples
-                    } else {
-                        for i in &mut bgm_buf[0..samples] {
-                            *i = 0x8000
-                        }
+                let count = match render_frames {
+                    Some(count) => count,
+                    // while capturing, the output is only advanced by capture ticks
+                    None if wav_capture.is_some() => 0,
+                    None => data.len() / channels,
+                };
 
-                        match state {
-                            PlaybackState::PlayingOrg => {
-                                samples = org_engine.render_to(&mut bgm_buf);
+                mixed.clear();
+                for _ in 0..count {
+                    let bgm_vol = mixer.gain(MixerBus::Music) * bgm_fade;
+                    let (bgm_sample_l, bgm_sample_r): (u16, u16) = {
+                        if state == PlaybackState::Stopped {
+                            (0x8000, 0x8000)
+                        } else if bgm_index < samples {
+                            let samples = (bgm_buf[bgm_index], bgm_buf[bgm_index + 1]);
+                            bgm_index += 2;
+                            samples
+                        } else {
+                            for i in &mut bgm_buf[0..samples] {
+                                *i = 0x8000
                             }
-                            #[cfg(feature = "ogg-playback")]
-                            PlaybackState::PlayingOgg => {
-                                samples = ogg_engine.render_to(&mut bgm_buf);
+
+                            match state {
+                                PlaybackState::PlayingOrg => {
+                                    samples = org_engine.render_to(&mut bgm_buf);
+                                }
+                                #[cfg(feature = "ogg-playback")]
+                                PlaybackState::PlayingOgg => {
+                                    samples = ogg_engine.render_to(&mut bgm_buf);
+                                }
+                                _ => unreachable!(),
                             }
-                            _ => unreachable!(),
+                            bgm_index = 2;
+                            (bgm_buf[0], bgm_buf[1])
                         }
-                        bgm_index = 2;
-                        (bgm_buf[0], bgm_buf[1])
-                    }
-                };
+                    };
 
-                let pxt_sample: u16 = pxt_buf[pxt_index];
+                    let (pxt_sample_l, pxt_sample_r): (u16, u16) = (pxt_buf[pxt_index], pxt_buf[pxt_index + 1]);
 
-                if pxt_index < (pxt_buf.len() - 1) {
-                    pxt_index += 1;
-                } else {
-                    pxt_index = 0;
-                    pxt_buf.fill(0x8000);
-                    pixtone.mix(&mut pxt_buf, sample_rate / speed);
-                }
+                    if pxt_index < (pxt_buf.len() - 2) {
+                        pxt_index += 2;
+                    } else {
+                        pxt_index = 0;
+                        pxt_buf.fill(0x8000);
+                        mixer.tick();
+                        pixtone.mix(&mut pxt_buf, sample_rate / speed, &mixer.gains());
+                    }
 
-                if frame.len() >= 2 {
                     let sample_l = clamp(
                         (((bgm_sample_l ^ 0x8000) as i16) as f32 * bgm_vol) as isize
-                            + (((pxt_sample ^ 0x8000) as i16) as f32 * sfx_vol) as isize,
+                            + ((pxt_sample_l ^ 0x8000) as i16) as isize,
                         -0x7fff,
                         0x7fff,
                     ) as u16
                         ^ 0x8000;
                     let sample_r = clamp(
                         (((bgm_sample_r ^ 0x8000) as i16) as f32 * bgm_vol) as isize
-                            + (((pxt_sample ^ 0x8000) as i16) as f32 * sfx_vol) as isize,
+                            + ((pxt_sample_r ^ 0x8000) as i16) as isize,
                         -0x7fff,
                         0x7fff,
                     ) as u16
                         ^ 0x8000;
 
+                    mixed.push((sample_l, sample_r));
+                }
+
+                if render_frames.is_none() {
+                    break;
+                }
+
+                if let Some(writer) = &mut wav_capture {
+                    for &(sample_l, sample_r) in mixed.iter() {
+                        let _ = writer.write_sample((sample_l ^ 0x8000) as i16);
+                        let _ = writer.write_sample((sample_r ^ 0x8000) as i16);
+                    }
+                }
+
+                // play the captured audio back as well, unless capturing runs faster than real time
+                if capture_monitor.len() < sample_rate as usize {
+                    capture_monitor.extend(mixed.iter().copied());
+                }
+            }
+
+            for (i, frame) in data.chunks_mut(channels).enumerate() {
+                let (sample_l, sample_r) = if wav_capture.is_some() {
+                    capture_monitor.pop_front().unwrap_or((0x8000, 0x8000))
+                } else {
+                    mixed.get(i).copied().unwrap_or((0x8000, 0x8000))
+                };
+
+                if frame.len() >= 2 {
                     frame[0] = T::from_sample(sample_l);
                     frame[1] = T::from_sample(sample_r);
                 } else {
-                    let sample = clamp(
-                        ((((bgm_sample_l ^ 0x8000) as i16) + ((bgm_sample_r ^ 0x8000) as i16)) as f32 * bgm_vol / 2.0)
-                            as isize
-                            + (((pxt_sample ^ 0x8000) as i16) as f32 * sfx_vol) as isize,
-                        -0x7fff,
-                        0x7fff,
-                    ) as u16
-                        ^ 0x8000;
+                    let mono = (((sample_l ^ 0x8000) as i16) as i32 + ((sample_r ^ 0x8000) as i16) as i32) / 2;
+                    let sample = mono as u16 ^ 0x8000;
 
                     frame[0] = T::from_sample(sample);
                 }
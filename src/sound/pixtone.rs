@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use lazy_static::lazy_static;
 use vec_mut_scan::VecMutScan;
 
+use crate::sound::mixer::MixerBus;
 use crate::sound::pixtone_sfx::DEFAULT_PIXTONE_TABLE;
 use crate::sound::stuff::cubic_interp;
 
@@ -183,6 +184,9 @@ pub struct PlaybackState {
     pos: f32,
     tag: u32,
     freq: f32,
+    volume: f32,
+    pan: f32,
+    bus: MixerBus,
 }
 
 pub struct PixTonePlayback {
@@ -218,38 +222,48 @@ impl PixTonePlayback {
         self.samples.insert(id, data);
     }
 
-    pub fn play_sfx(&mut self, id: u8) {
+    pub fn play_sfx(&mut self, id: u8, bus: MixerBus) {
+        self.play_sfx_spatial(id, bus, 1.0, 0.0);
+    }
+
+    pub fn play_sfx_spatial(&mut self, id: u8, bus: MixerBus, volume: f32, pan: f32) {
         for state in &mut self.playback_state {
             if state.id == id && state.tag == 0 {
                 state.pos = 0.0;
                 state.looping = false;
+                state.volume = volume;
+                state.pan = pan;
+                state.bus = bus;
                 return;
             }
         }
 
-        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: false, freq: 1.0 });
+        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: false, freq: 1.0, volume, pan, bus });
     }
 
-    pub fn loop_sfx(&mut self, id: u8) {
-        for state in &mut self.playback_state {
-            if state.id == id && state.tag == 0 {
-                state.looping = true;
-                return;
-            }
-        }
-
-        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: true, freq: 1.0 });
+    pub fn loop_sfx(&mut self, id: u8, bus: MixerBus) {
+        self.loop_sfx_freq(id, 1.0, bus);
     }
 
-    pub fn loop_sfx_freq(&mut self, id: u8, freq: f32) {
+    pub fn loop_sfx_freq(&mut self, id: u8, freq: f32, bus: MixerBus) {
         for state in &mut self.playback_state {
             if state.id == id && state.tag == 0 {
                 state.looping = true;
+                state.bus = bus;
                 return;
             }
         }
 
-        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: true, freq });
+        self.playback_state.push(PlaybackState {
+            id,
+            pos: 0.0,
+            tag: 0,
+            looping: true,
+            freq,
+            volume: 1.0,
+            pan: 0.0,
+            bus,
+        });
     }
 
     pub fn stop_sfx(&mut self, id: u8) {
@@ -259,10 +273,20 @@ impl PixTonePlayback {
     }
 
     pub fn play_concurrent(&mut self, id: u8, tag: u32) {
-        self.playback_state.push(PlaybackState { id, pos: 0.0, tag, looping: false, freq: 1.0 });
+        self.playback_state.push(PlaybackState {
+            id,
+            pos: 0.0,
+            tag,
+            looping: false,
+            freq: 1.0,
+            volume: 1.0,
+            pan: 0.0,
+            bus: MixerBus::Gameplay,
+        });
     }
 
-    pub fn mix(&mut self, dst: &mut [u16], sample_rate: f32) {
+    /// Mixes all playing samples into an interleaved stereo buffer, applying gains of their mixer buses.
+    pub fn mix(&mut self, dst: &mut [u16], sample_rate: f32, gains: &[f32; MixerBus::COUNT]) {
         let mut scan = VecMutScan::new(&mut self.playback_state);
         let delta = 22050.0 / sample_rate;
 
@@ -276,7 +300,11 @@ impl PixTonePlayback {
                     continue;
                 };
 
-                for result in dst.iter_mut() {
+                let volume = state.volume * gains[state.bus as usize];
+                let gain_l = volume * (1.0 - state.pan).min(1.0);
+                let gain_r = volume * (1.0 + state.pan).min(1.0);
+
+                for result in dst.chunks_exact_mut(2) {
                     if state.pos >= sample.len() as f32 {
                         if state.looping {
                             state.pos = 0.0;
@@ -294,8 +322,10 @@ impl PixTonePlayback {
 
                     let s = cubic_interp(s1, s2, s4, s3, state.pos.fract()) * 32768.0;
                     // let s = sample[pos] as f32;
-                    let sam = (*result ^ 0x8000) as i16;
-                    *result = sam.saturating_add(s as i16) as u16 ^ 0x8000;
+                    let sam_l = (result[0] ^ 0x8000) as i16;
+                    let sam_r = (result[1] ^ 0x8000) as i16;
+                    result[0] = sam_l.saturating_add((s * gain_l) as i16) as u16 ^ 0x8000;
+                    result[1] = sam_r.saturating_add((s * gain_r) as i16) as u16 ^ 0x8000;
 
                     state.pos += delta * state.freq;
                 }
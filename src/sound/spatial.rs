@@ -0,0 +1,86 @@
+/// Controls how a positioned sound effect is spatialized at a call site.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SfxPositioning {
+    /// Whether the sound should be panned between left and right channels.
+    pub pan: bool,
+    /// Whether the sound should get quieter with distance from the listener.
+    pub attenuate: bool,
+    /// Distance (in fix9 units) at which the sound becomes silent.
+    pub range: i32,
+}
+
+impl SfxPositioning {
+    /// Pans and attenuates the sound, fading out a bit further than one screen away.
+    pub const DEFAULT: SfxPositioning = SfxPositioning { pan: true, attenuate: true, range: 480 * 0x200 };
+    /// Only pans the sound, used for important sounds that must always be heard (boss hits, etc.)
+    pub const PAN_ONLY: SfxPositioning = SfxPositioning { pan: true, attenuate: false, range: 480 * 0x200 };
+}
+
+impl Default for SfxPositioning {
+    fn default() -> Self {
+        SfxPositioning::DEFAULT
+    }
+}
+
+/// Listener state used to compute volume and panning of positioned sound effects.
+///
+/// In single player the listener is the center of the camera, in co-op each alive player is a listener
+/// and the one closest to the sound source is used.
+#[derive(Debug, Copy, Clone)]
+pub struct SfxListener {
+    pub enabled: bool,
+    pub points: [Option<(i32, i32)>; 2],
+    /// Half of the screen width in fix9 units, used as the distance at which the sound is fully panned.
+    pub half_width: i32,
+}
+
+impl SfxListener {
+    pub const fn new() -> SfxListener {
+        SfxListener { enabled: false, points: [None, None], half_width: 160 * 0x200 }
+    }
+
+    /// Returns (volume, pan) pair for a sound played at specified position.
+    /// Pan ranges from -1.0 (left) to 1.0 (right).
+    pub fn spatialize(&self, x: i32, y: i32, positioning: SfxPositioning) -> (f32, f32) {
+        let nearest =
+            self.points.iter().flatten().min_by_key(|(lx, ly)| ((x - lx) as i64).pow(2) + ((y - ly) as i64).pow(2));
+
+        let (lx, ly) = match nearest {
+            Some(point) => *point,
+            None => return (1.0, 0.0),
+        };
+
+        let dx = (x - lx) as f32;
+        let dy = (y - ly) as f32;
+
+        let pan =
+            if positioning.pan && self.half_width > 0 { (dx / self.half_width as f32).clamp(-1.0, 1.0) } else { 0.0 };
+
+        let volume = if positioning.attenuate && positioning.range > 0 {
+            let distance = (dx * dx + dy * dy).sqrt();
+            (1.0 - distance / positioning.range as f32).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        (volume, pan)
+    }
+}
+
+#[test]
+fn test_sfx_spatialize() {
+    let mut listener = SfxListener::new();
+    listener.points[0] = Some((0, 0));
+
+    assert_eq!(listener.spatialize(0, 0, SfxPositioning::DEFAULT), (1.0, 0.0));
+    assert_eq!(listener.spatialize(320 * 0x200, 0, SfxPositioning::PAN_ONLY), (1.0, 1.0));
+    assert_eq!(listener.spatialize(-80 * 0x200, 0, SfxPositioning::PAN_ONLY), (1.0, -0.5));
+    assert_eq!(listener.spatialize(0, 960 * 0x200, SfxPositioning::DEFAULT), (0.0, 0.0));
+
+    // the closest listener wins in co-op
+    listener.points[1] = Some((240 * 0x200, 0));
+    assert_eq!(listener.spatialize(240 * 0x200, 0, SfxPositioning::DEFAULT), (1.0, 0.0));
+
+    listener.points = [None, None];
+    assert_eq!(listener.spatialize(1000, 1000, SfxPositioning::DEFAULT), (1.0, 0.0));
+}
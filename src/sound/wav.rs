@@ -2,7 +2,7 @@ use std::fmt;
 use std::io;
 use std::io::ErrorKind;
 
-use byteorder::{LE, ReadBytesExt};
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct RiffChunk {
@@ -119,3 +119,63 @@ impl WavSample {
         Ok(WavSample { format: WavFormat { channels, sample_rate: samples, bit_depth: bits }, data: buf })
     }
 }
+
+/// Writes 16-bit PCM WAV files, the RIFF header lengths are fixed up in [`WavWriter::finish`].
+pub struct WavWriter<W: io::Write + io::Seek> {
+    inner: W,
+    data_length: u32,
+}
+
+impl<W: io::Write + io::Seek> WavWriter<W> {
+    pub fn new(mut inner: W, channels: u16, sample_rate: u32) -> io::Result<WavWriter<W>> {
+        let block_align = channels * 2;
+
+        inner.write_all(b"RIFF")?;
+        inner.write_u32::<LE>(0)?;
+        inner.write_all(b"WAVE")?;
+        inner.write_all(b"fmt ")?;
+        inner.write_u32::<LE>(16)?;
+        inner.write_u16::<LE>(1)?;
+        inner.write_u16::<LE>(channels)?;
+        inner.write_u32::<LE>(sample_rate)?;
+        inner.write_u32::<LE>(sample_rate * block_align as u32)?;
+        inner.write_u16::<LE>(block_align)?;
+        inner.write_u16::<LE>(16)?;
+        inner.write_all(b"data")?;
+        inner.write_u32::<LE>(0)?;
+
+        Ok(WavWriter { inner, data_length: 0 })
+    }
+
+    pub fn write_sample(&mut self, sample: i16) -> io::Result<()> {
+        self.inner.write_i16::<LE>(sample)?;
+        self.data_length = self.data_length.saturating_add(2);
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.seek(io::SeekFrom::Start(4))?;
+        self.inner.write_u32::<LE>(self.data_length.saturating_add(36))?;
+        self.inner.seek(io::SeekFrom::Start(40))?;
+        self.inner.write_u32::<LE>(self.data_length)?;
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+}
+
+#[test]
+fn test_wav_roundtrip() {
+    let mut writer = WavWriter::new(io::Cursor::new(Vec::new()), 2, 44100).unwrap();
+    for sample in [0i16, 1000, -1000, i16::MAX] {
+        writer.write_sample(sample).unwrap();
+    }
+
+    let buf = writer.finish().unwrap().into_inner();
+    let wav = WavSample::read_from(io::Cursor::new(buf)).unwrap();
+
+    assert_eq!(wav.format, WavFormat { channels: 2, sample_rate: 44100, bit_depth: 16 });
+    assert_eq!(wav.data.len(), 8);
+    assert_eq!(i16::from_le_bytes([wav.data[2], wav.data[3]]), 1000);
+}